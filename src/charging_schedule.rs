@@ -0,0 +1,335 @@
+//! Version-neutral charging schedule arithmetic.
+//!
+//! 1.6 `ChargingSchedule` and 2.1 `ChargingScheduleType` share one time model: a period starts
+//! `startPeriod` seconds after the schedule start and runs until the next period or the
+//! schedule's `duration`; `Recurring` profiles restart their schedule every day or week and
+//! `Relative` ones start with the transaction. [`Schedule`] and [`StackedProfile`] expose that
+//! shape so [`crate::v16::composite`] and [`crate::v21::composite`] merge profiles with the
+//! same code.
+//!
+//! The query functions ([`effective_periods`], [`next_change`], [`max_energy`], ...) back
+//! `ChargingSchedule::limit_at` and friends in [`crate::v16::schedule`] and
+//! [`crate::v21::schedule`]. A relative schedule has no anchor of its own, so every query takes
+//! the instant it started (typically the transaction start), used only when `startSchedule` is
+//! absent. Periods are taken in `startPeriod` order whatever their wire order; of several
+//! periods with the same `startPeriod` the last one counts. The last period of a schedule
+//! without `duration` never ends.
+
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+
+const DAY_SECS: i64 = 86_400;
+
+/// Timing and periods of a charging schedule.
+pub trait Schedule {
+    type Period;
+
+    /// `startSchedule`, if any.
+    fn start_schedule(&self) -> Option<DateTime<Utc>>;
+
+    /// `duration` in seconds, if any.
+    fn duration(&self) -> Option<i32>;
+
+    /// `chargingSchedulePeriod`, in wire order.
+    fn periods(&self) -> &[Self::Period];
+
+    /// `startPeriod` of `period`, in seconds from the schedule start.
+    fn start_period(period: &Self::Period) -> i32;
+}
+
+/// `chargingProfileKind` (with `recurrencyKind`) of a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileKind {
+    Absolute,
+    Recurring { weekly: bool },
+    Relative,
+}
+
+impl ProfileKind {
+    /// Recurrence period in seconds; `None` unless `Recurring`.
+    #[must_use]
+    pub const fn recurrence_secs(self) -> Option<i64> {
+        match self {
+            Self::Recurring { weekly: true } => Some(7 * DAY_SECS),
+            Self::Recurring { weekly: false } => Some(DAY_SECS),
+            Self::Absolute | Self::Relative => None,
+        }
+    }
+}
+
+/// An installed profile as seen by the composite schedule calculation.
+pub(crate) trait StackedProfile {
+    type Purpose: PartialEq;
+    /// Order among valid profiles of one purpose; the greatest applies.
+    type Rank: Ord;
+
+    fn purpose(&self) -> &Self::Purpose;
+    fn rank(&self) -> Self::Rank;
+    fn kind(&self) -> ProfileKind;
+    fn valid_from(&self) -> Option<DateTime<Utc>>;
+    fn valid_to(&self) -> Option<DateTime<Utc>>;
+}
+
+/// Start of the schedule; `anchor` stands in for the transaction start of `Relative` profiles
+/// (and for a missing `startSchedule`).
+fn schedule_base<S: Schedule>(
+    kind: ProfileKind,
+    schedule: &S,
+    anchor: DateTime<Utc>,
+) -> DateTime<Utc> {
+    match kind {
+        ProfileKind::Relative => anchor,
+        ProfileKind::Absolute | ProfileKind::Recurring { .. } => {
+            schedule.start_schedule().unwrap_or(anchor)
+        }
+    }
+}
+
+/// Start of the occurrence covering `t` (recurring profiles repeat from their base).
+fn occurrence_start<S: Schedule>(
+    kind: ProfileKind,
+    schedule: &S,
+    anchor: DateTime<Utc>,
+    t: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let base = schedule_base(kind, schedule, anchor);
+    let Some(period) = kind.recurrence_secs() else {
+        return Some(base);
+    };
+    let elapsed = (t - base).num_seconds();
+    if elapsed < 0 {
+        return None;
+    }
+    Some(base + TimeDelta::seconds(elapsed - elapsed.rem_euclid(period)))
+}
+
+/// Period of `schedule` in effect at `t` under `profile`'s validity, kind and duration.
+pub(crate) fn period_in_effect<'s, P: StackedProfile, S: Schedule>(
+    profile: &P,
+    schedule: &'s S,
+    anchor: DateTime<Utc>,
+    t: DateTime<Utc>,
+) -> Option<&'s S::Period> {
+    let valid = profile.valid_from().is_none_or(|from| from <= t)
+        && profile.valid_to().is_none_or(|to| t < to);
+    if !valid {
+        return None;
+    }
+    let kind = profile.kind();
+    let offset = (t - occurrence_start(kind, schedule, anchor, t)?).num_seconds();
+    let mut end = schedule.duration().map_or(i64::MAX, i64::from);
+    if let Some(recurrence) = kind.recurrence_secs() {
+        end = end.min(recurrence);
+    }
+    if offset < 0 || offset >= end {
+        return None;
+    }
+    schedule
+        .periods()
+        .iter()
+        .filter(|p| i64::from(S::start_period(p)) <= offset)
+        .max_by_key(|p| S::start_period(p))
+}
+
+/// Instants in `(from, to)` at which `profile` with `schedule` may change its values.
+fn change_points<P: StackedProfile, S: Schedule>(
+    profile: &P,
+    schedule: Option<&S>,
+    anchor: DateTime<Utc>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    out: &mut Vec<DateTime<Utc>>,
+) {
+    out.extend(profile.valid_from());
+    out.extend(profile.valid_to());
+    let Some(schedule) = schedule else {
+        return;
+    };
+    let kind = profile.kind();
+    let base = schedule_base(kind, schedule, anchor);
+    let step = kind.recurrence_secs().map(TimeDelta::seconds);
+    let mut start = if step.is_some() {
+        occurrence_start(kind, schedule, anchor, from).unwrap_or(base)
+    } else {
+        base
+    };
+    loop {
+        out.extend(
+            schedule
+                .periods()
+                .iter()
+                .map(|p| start + TimeDelta::seconds(i64::from(S::start_period(p)))),
+        );
+        if let Some(d) = schedule.duration() {
+            out.push(start + TimeDelta::seconds(i64::from(d)));
+        }
+        match step {
+            Some(step) if start + step < to => start += step,
+            Some(step) => {
+                out.push(start + step);
+                break;
+            }
+            None => break,
+        }
+    }
+}
+
+/// `start` and every instant in `(start, end)` at which one of `candidates` may change, in
+/// ascending order without duplicates. `schedule` picks the schedule a profile uses.
+pub(crate) fn change_instants<'a, P: StackedProfile, S: Schedule + 'a>(
+    candidates: &[&'a P],
+    schedule: impl Fn(&'a P) -> Option<&'a S>,
+    anchor: DateTime<Utc>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let mut points = Vec::new();
+    for c in candidates {
+        change_points(*c, schedule(c), anchor, start, end, &mut points);
+    }
+    points.retain(|t| start < *t && *t < end);
+    points.push(start);
+    points.sort_unstable();
+    points.dedup();
+    points
+}
+
+/// Value of the highest-ranked profile of `purpose` for which `level` gives one.
+pub(crate) fn winner<'a, P: StackedProfile, L>(
+    candidates: &[&'a P],
+    purpose: &P::Purpose,
+    level: impl Fn(&'a P) -> Option<L>,
+) -> Option<L> {
+    candidates
+        .iter()
+        .filter(|c| c.purpose() == purpose)
+        .filter_map(|c| level(c).map(|l| (c.rank(), l)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, l)| l)
+}
+
+/// A schedule period resolved to absolute time: `[start, end)`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EffectivePeriod<'a, P> {
+    pub start: DateTime<Utc>,
+    /// `None` = open-ended.
+    pub end: Option<DateTime<Utc>>,
+    pub period: &'a P,
+}
+
+impl<P> Clone for EffectivePeriod<'_, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for EffectivePeriod<'_, P> {}
+
+impl<P> EffectivePeriod<'_, P> {
+    #[must_use]
+    pub fn contains(&self, t: DateTime<Utc>) -> bool {
+        self.start <= t && self.end.is_none_or(|end| t < end)
+    }
+}
+
+/// Instant `schedule` starts: `startSchedule`, else `relative_start`.
+#[must_use]
+pub fn anchor<S: Schedule>(schedule: &S, relative_start: DateTime<Utc>) -> DateTime<Utc> {
+    schedule.start_schedule().unwrap_or(relative_start)
+}
+
+/// End of `schedule`, if it has a `duration`.
+#[must_use]
+pub fn end<S: Schedule>(schedule: &S, relative_start: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    schedule
+        .duration()
+        .map(|d| anchor(schedule, relative_start) + TimeDelta::seconds(i64::from(d)))
+}
+
+/// Periods of `schedule` in time order; periods starting at or after its end are skipped.
+pub fn effective_periods<S: Schedule>(
+    schedule: &S,
+    relative_start: DateTime<Utc>,
+) -> impl Iterator<Item = EffectivePeriod<'_, S::Period>> + '_ {
+    let anchor = anchor(schedule, relative_start);
+    let end = end(schedule, relative_start);
+    let mut periods: Vec<&S::Period> = schedule.periods().iter().collect();
+    periods.sort_by_key(|p| S::start_period(p));
+    // Keep the last of equal starts: drop a period when the next one starts at the same time.
+    let mut periods = periods.into_iter().peekable();
+    let mut ordered = Vec::new();
+    while let Some(period) = periods.next() {
+        if periods
+            .peek()
+            .is_none_or(|next| S::start_period(next) != S::start_period(period))
+        {
+            ordered.push(period);
+        }
+    }
+    let at = move |p: &S::Period| anchor + TimeDelta::seconds(i64::from(S::start_period(p)));
+    let starts: Vec<DateTime<Utc>> = ordered.iter().map(|p| at(p)).collect();
+    ordered
+        .into_iter()
+        .enumerate()
+        .map(move |(i, period)| {
+            let next = starts.get(i + 1).copied();
+            let end = match (next, end) {
+                (Some(next), Some(end)) => Some(next.min(end)),
+                (next, end) => next.or(end),
+            };
+            EffectivePeriod {
+                start: at(period),
+                end,
+                period,
+            }
+        })
+        .take_while(move |p| end.is_none_or(|end| p.start < end))
+}
+
+/// Period of `schedule` in effect at `t`.
+#[must_use]
+pub fn period_at<S: Schedule>(
+    schedule: &S,
+    relative_start: DateTime<Utc>,
+    t: DateTime<Utc>,
+) -> Option<EffectivePeriod<'_, S::Period>> {
+    effective_periods(schedule, relative_start).find(|p| p.contains(t))
+}
+
+/// First instant after `t` at which the period in effect changes (a period start or the end
+/// of the schedule); `None` if nothing changes after `t`.
+#[must_use]
+pub fn next_change<S: Schedule>(
+    schedule: &S,
+    relative_start: DateTime<Utc>,
+    t: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    effective_periods(schedule, relative_start)
+        .flat_map(|p| [Some(p.start), p.end])
+        .flatten()
+        .find(|boundary| *boundary > t)
+}
+
+/// Most energy (Wh) `schedule` lets through between `from` and `to`, given the power (W) each
+/// period allows.
+#[must_use]
+pub fn max_energy<S: Schedule>(
+    schedule: &S,
+    relative_start: DateTime<Utc>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    watts: impl Fn(&S::Period) -> f64,
+) -> f64 {
+    effective_periods(schedule, relative_start)
+        .map(|p| {
+            let start = p.start.max(from);
+            let end = p.end.map_or(to, |end| end.min(to));
+            if end <= start {
+                return 0.0;
+            }
+            #[allow(clippy::cast_precision_loss)]
+            let hours = (end - start).num_milliseconds() as f64 / 3_600_000.0;
+            watts(p.period).max(0.0) * hours
+        })
+        .sum()
+}
@@ -0,0 +1,218 @@
+//! Station clock disciplined by CSMS timestamps.
+//!
+//! `BootNotification` and `Heartbeat` responses (1.6 `.conf` and 2.1 `*Response`) carry the
+//! CSMS `currentTime`. [`StationClock`] pairs each of them with readings of a
+//! [`MonotonicClock`] taken around the round trip and fits a line through the last samples:
+//! the intercept is the offset, the slope the drift of the local oscillator against the CSMS.
+//! [`StationClock::now`] then gives a corrected time without `chrono::Utc::now`, so firmware
+//! only needs a millisecond tick counter.
+//!
+//! A sample that disagrees with the fit by more than [`StationClock::step_threshold`] is
+//! treated as a clock step on either side and restarts the fit.
+
+use alloc::collections::VecDeque;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::datetime::DateTimeWrapper;
+
+/// Milliseconds since an arbitrary, fixed origin; never goes backwards.
+pub trait MonotonicClock {
+    fn millis(&self) -> u64;
+}
+
+impl<T: MonotonicClock + ?Sized> MonotonicClock for &T {
+    fn millis(&self) -> u64 {
+        (**self).millis()
+    }
+}
+
+/// One CSMS timestamp with the local round trip it arrived in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSample {
+    /// Monotonic reading when the request was sent.
+    pub sent_at: u64,
+    /// Monotonic reading when the response arrived.
+    pub received_at: u64,
+    pub csms_time: DateTime<Utc>,
+}
+
+impl ClockSample {
+    /// Round-trip midpoint, the best guess for when the CSMS stamped the response.
+    #[allow(clippy::cast_precision_loss)]
+    const fn midpoint(&self) -> f64 {
+        f64::midpoint(self.sent_at as f64, self.received_at as f64)
+    }
+
+    const fn round_trip(&self) -> u64 {
+        self.received_at.saturating_sub(self.sent_at)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fit {
+    origin: DateTime<Utc>,
+    origin_tick: f64,
+    mean_x: f64,
+    mean_y: f64,
+    slope: f64,
+}
+
+impl Fit {
+    /// Fitted CSMS time at monotonic `tick`, in ms after `origin`.
+    fn offset_ms(&self, tick: f64) -> f64 {
+        self.mean_y + self.slope * (tick - self.origin_tick - self.mean_x)
+    }
+}
+
+/// Offset and drift estimate of a monotonic clock against the CSMS clock.
+#[derive(Debug, Clone)]
+pub struct StationClock<C> {
+    clock: C,
+    samples: VecDeque<ClockSample>,
+    max_samples: usize,
+    step_threshold: TimeDelta,
+    fit: Option<Fit>,
+}
+
+impl<C: MonotonicClock> StationClock<C> {
+    /// Fit over the last 8 samples, restarting on a 2 s disagreement.
+    #[must_use]
+    pub fn new(clock: C) -> Self {
+        Self::with_window(clock, 8, TimeDelta::seconds(2))
+    }
+
+    #[must_use]
+    pub fn with_window(clock: C, max_samples: usize, step_threshold: TimeDelta) -> Self {
+        Self {
+            clock,
+            samples: VecDeque::new(),
+            max_samples: max_samples.max(1),
+            step_threshold,
+            fit: None,
+        }
+    }
+
+    /// Current monotonic reading; take it when sending the request later passed to
+    /// [`Self::sync`].
+    #[must_use]
+    pub fn tick(&self) -> u64 {
+        self.clock.millis()
+    }
+
+    #[must_use]
+    pub const fn step_threshold(&self) -> TimeDelta {
+        self.step_threshold
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &ClockSample> {
+        self.samples.iter()
+    }
+
+    #[must_use]
+    pub const fn is_synced(&self) -> bool {
+        self.fit.is_some()
+    }
+
+    /// Add the `currentTime` of a response to a request sent at monotonic `sent_at`; the
+    /// response is taken to arrive now.
+    ///
+    /// Returns how far the previous estimate was off at that instant, `None` for the first
+    /// sample. A step beyond [`Self::step_threshold`] drops the older samples, as does a
+    /// previous estimate out of `DateTime` range (which also returns `None`).
+    pub fn sync(&mut self, csms_time: DateTimeWrapper, sent_at: u64) -> Option<TimeDelta> {
+        let sample = ClockSample {
+            sent_at,
+            received_at: self.clock.millis().max(sent_at),
+            csms_time: csms_time.inner(),
+        };
+        let error = self.fit.map(|fit| {
+            fit_time(&fit, sample.midpoint()).map(|estimate| sample.csms_time - estimate)
+        });
+        if error.is_some_and(|error| error.is_none_or(|error| error.abs() > self.step_threshold)) {
+            self.samples.clear();
+        }
+        while self.samples.len() >= self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.refit();
+        error.flatten()
+    }
+
+    /// Corrected CSMS time now, once synced.
+    #[must_use]
+    pub fn now(&self) -> Option<DateTimeWrapper> {
+        self.at(self.clock.millis()).map(DateTimeWrapper::new)
+    }
+
+    /// Corrected CSMS time at monotonic `tick`; `None` before the first sync, or when that
+    /// time is out of `DateTime` range.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn at(&self, tick: u64) -> Option<DateTime<Utc>> {
+        self.fit.and_then(|fit| fit_time(&fit, tick as f64))
+    }
+
+    /// How far `local` (e.g. an RTC reading taken now) is ahead of the corrected time.
+    #[must_use]
+    pub fn offset_of(&self, local: DateTime<Utc>) -> Option<TimeDelta> {
+        self.now().map(|now| local - now.inner())
+    }
+
+    /// Rate error of the monotonic clock in parts per million; positive when it runs slow.
+    /// `0.0` until two samples span some time.
+    #[must_use]
+    pub fn drift_ppm(&self) -> f64 {
+        self.fit.map_or(0.0, |fit| (fit.slope - 1.0) * 1_000_000.0)
+    }
+
+    /// Half the round trip of the latest sample: the bound on its timing error.
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn uncertainty(&self) -> Option<TimeDelta> {
+        self.samples
+            .back()
+            .map(|sample| TimeDelta::milliseconds(sample.round_trip() as i64) / 2)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn refit(&mut self) {
+        let Some(first) = self.samples.front() else {
+            self.fit = None;
+            return;
+        };
+        let origin = first.csms_time;
+        let origin_tick = first.midpoint();
+        let points = || {
+            self.samples.iter().map(move |sample| {
+                let y = (sample.csms_time - origin).num_milliseconds() as f64;
+                (sample.midpoint() - origin_tick, y)
+            })
+        };
+        let n = self.samples.len() as f64;
+        let (sum_x, sum_y) = points().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+        let (mean_x, mean_y) = (sum_x / n, sum_y / n);
+        let (sxx, sxy) = points().fold((0.0, 0.0), |(sxx, sxy), (x, y)| {
+            let dx = x - mean_x;
+            (sxx + dx * dx, sxy + dx * (y - mean_y))
+        });
+        let slope = if sxx > 0.0 { sxy / sxx } else { 1.0 };
+        self.fit = Some(Fit {
+            origin,
+            origin_tick,
+            mean_x,
+            mean_y,
+            slope,
+        });
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn fit_time(fit: &Fit, tick: f64) -> Option<DateTime<Utc>> {
+    let offset = fit.offset_ms(tick);
+    if !offset.is_finite() {
+        return None;
+    }
+    fit.origin
+        .checked_add_signed(TimeDelta::try_milliseconds(offset as i64)?)
+}
@@ -0,0 +1,110 @@
+//! Station heartbeat scheduling.
+//!
+//! A station sends `Heartbeat` every `HeartbeatInterval` seconds (the `interval` of an
+//! accepted `BootNotification`, see [`crate::registration`]). Both 1.6 and 2.1 let it skip the
+//! heartbeat when another message went to the CSMS within the interval, because any traffic
+//! shows the connection is alive; [`HeartbeatScheduler`] does that when
+//! [`HeartbeatScheduler::suppress_on_traffic`] is set. Time comes from the caller, e.g.
+//! [`crate::clock::StationClock::now`] on firmware without a real-time clock.
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// When a `Heartbeat` is due, see [`HeartbeatScheduler::next_poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeartbeatDue {
+    /// None was sent yet.
+    Now,
+    At(DateTime<Utc>),
+}
+
+impl HeartbeatDue {
+    #[must_use]
+    pub fn is_due(self, now: DateTime<Utc>) -> bool {
+        match self {
+            Self::Now => true,
+            Self::At(due) => due <= now,
+        }
+    }
+}
+
+/// When the next `Heartbeat` is due.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeartbeatScheduler {
+    interval: u32,
+    suppress_on_traffic: bool,
+    last_heartbeat: Option<DateTime<Utc>>,
+    last_traffic: Option<DateTime<Utc>>,
+}
+
+impl HeartbeatScheduler {
+    /// `interval` in seconds; `0` disables heartbeats.
+    #[must_use]
+    pub const fn new(interval: u32, suppress_on_traffic: bool) -> Self {
+        Self {
+            interval,
+            suppress_on_traffic,
+            last_heartbeat: None,
+            last_traffic: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// New `HeartbeatInterval`, e.g. from `BootNotification` or a configuration change.
+    pub const fn set_interval(&mut self, interval: u32) {
+        self.interval = interval;
+    }
+
+    #[must_use]
+    pub const fn suppress_on_traffic(&self) -> bool {
+        self.suppress_on_traffic
+    }
+
+    pub const fn set_suppress_on_traffic(&mut self, suppress: bool) {
+        self.suppress_on_traffic = suppress;
+    }
+
+    /// Record a message other than `Heartbeat` sent to the CSMS.
+    pub fn on_message_sent(&mut self, now: DateTime<Utc>) {
+        self.last_traffic = self.last_traffic.max(Some(now));
+    }
+
+    /// Record a `Heartbeat` sent outside [`Self::poll`], e.g. after a `TriggerMessage`.
+    pub fn on_heartbeat_sent(&mut self, now: DateTime<Utc>) {
+        self.last_heartbeat = self.last_heartbeat.max(Some(now));
+        self.on_message_sent(now);
+    }
+
+    /// When the next heartbeat is due; `None` when disabled.
+    ///
+    /// The first one is due at once unless traffic already counts for it.
+    #[must_use]
+    pub fn next_poll(&self) -> Option<HeartbeatDue> {
+        if self.interval == 0 {
+            return None;
+        }
+        let last = if self.suppress_on_traffic {
+            self.last_traffic
+        } else {
+            self.last_heartbeat
+        };
+        Some(last.map_or(HeartbeatDue::Now, |last| {
+            HeartbeatDue::At(
+                last.checked_add_signed(TimeDelta::seconds(i64::from(self.interval)))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            )
+        }))
+    }
+
+    /// `true` when a `Heartbeat` must be sent now; it is then recorded as sent.
+    pub fn poll(&mut self, now: DateTime<Utc>) -> bool {
+        if !self.next_poll().is_some_and(|due| due.is_due(now)) {
+            return false;
+        }
+        self.on_heartbeat_sent(now);
+        true
+    }
+}
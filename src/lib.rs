@@ -152,9 +152,19 @@
 #![recursion_limit = "256"]
 
 extern crate alloc;
+pub mod charging_schedule;
+pub mod clock;
 pub mod datetime;
 pub mod errors;
+pub mod heartbeat;
 pub mod lenient_str_enum;
+pub mod load_balancing;
+pub mod metering;
+pub mod offline_queue;
+pub mod quantity;
+pub mod registration;
+pub mod registry;
+pub mod signed_meter;
 pub mod v16;
 pub mod v21;
 pub mod validate;
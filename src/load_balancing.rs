@@ -0,0 +1,313 @@
+//! Version-neutral site load balancing for a CSMS.
+//!
+//! [`plan`] splits a site power budget (constant or a time series) between active charging
+//! sessions according to a [`FairnessPolicy`], honouring each session's minimum and maximum
+//! power, remaining energy need and departure time. The result is a per-session power
+//! schedule in W; [`crate::v16::load_balancing`] and [`crate::v21::load_balancing`] turn it
+//! into `TxProfile` charging profiles.
+//!
+//! Planning is pure and deterministic: the same inputs always give the same schedule, ties
+//! are broken by session start and then input order, and no clock is read.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// Minimum current (A per phase) of IEC 61851-1 charging, used when the EV reports none.
+pub const DEFAULT_MIN_CURRENT: f64 = 6.0;
+
+/// Site-wide power budget in W.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SiteLimit {
+    Constant(f64),
+    /// `(from, watts)` steps in ascending time order; the first value also applies before
+    /// its instant.
+    Series(Vec<(DateTime<Utc>, f64)>),
+}
+
+impl SiteLimit {
+    /// Budget at `t`.
+    #[must_use]
+    pub fn at(&self, t: DateTime<Utc>) -> f64 {
+        match self {
+            Self::Constant(watts) => *watts,
+            Self::Series(steps) => steps
+                .iter()
+                .take_while(|(from, _)| *from <= t)
+                .last()
+                .or_else(|| steps.first())
+                .map_or(0.0, |(_, watts)| *watts),
+        }
+        .max(0.0)
+    }
+
+    fn change_points(&self) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        let steps: &[(DateTime<Utc>, f64)] = match self {
+            Self::Constant(_) => &[],
+            Self::Series(steps) => steps,
+        };
+        steps.iter().map(|(from, _)| *from)
+    }
+}
+
+/// How the budget is shared when it does not cover every session's maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FairnessPolicy {
+    /// Same power for everyone (water-filling up to each maximum).
+    #[default]
+    EqualShare,
+    /// Power proportional to each session's maximum.
+    ProportionalToMax,
+    /// Earlier sessions are served in full first.
+    FirstComeFirstServed,
+    /// Sessions leaving first are served in full first; sessions without a departure time
+    /// come last.
+    EarliestDepartureFirst,
+}
+
+/// One session to plan for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionDemand {
+    pub started: DateTime<Utc>,
+    /// Highest power (W) the connector and the EV accept.
+    pub max_power: f64,
+    /// Lowest power (W) the EV can charge at; below it the session is paused (0 W).
+    pub min_power: f64,
+    /// Energy still to deliver (Wh); `None` = until departure / the end of the horizon.
+    pub energy_needed: Option<f64>,
+    pub departure: Option<DateTime<Utc>>,
+}
+
+impl SessionDemand {
+    /// Demand limited only by `max_power`.
+    #[must_use]
+    pub const fn new(started: DateTime<Utc>, max_power: f64) -> Self {
+        Self {
+            started,
+            max_power,
+            min_power: 0.0,
+            energy_needed: None,
+            departure: None,
+        }
+    }
+}
+
+/// A power step of a session's plan: `power` W from `start_offset` seconds after the plan
+/// start until the next step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlannedPeriod {
+    pub start_offset: i32,
+    pub power: f64,
+}
+
+/// Planned schedule of one session (same index as the input).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SessionPlan {
+    pub periods: Vec<PlannedPeriod>,
+    /// Energy (Wh) the plan delivers within the horizon.
+    pub energy: f64,
+}
+
+impl SessionPlan {
+    /// Power at `offset` seconds after the plan start.
+    #[must_use]
+    pub fn power_at(&self, offset: i32) -> f64 {
+        self.periods
+            .iter()
+            .take_while(|p| p.start_offset <= offset)
+            .last()
+            .map_or(0.0, |p| p.power)
+    }
+}
+
+/// Seconds needed to deliver `wh` at `watts`, rounded up (saturating at `i64::MAX`).
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn seconds_to_deliver(wh: f64, watts: f64) -> i64 {
+    let secs = wh * 3600.0 / watts;
+    let whole = secs as i64;
+    if (whole as f64) < secs {
+        whole.saturating_add(1)
+    } else {
+        whole
+    }
+}
+
+/// Priority order (best first) used to serve sessions and to pick which one to pause.
+fn priority(sessions: &[SessionDemand], policy: FairnessPolicy) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..sessions.len()).collect();
+    order.sort_by_key(|&i| {
+        let departure = match policy {
+            FairnessPolicy::EarliestDepartureFirst => sessions[i].departure,
+            _ => None,
+        };
+        (departure.is_none(), departure, sessions[i].started, i)
+    });
+    order
+}
+
+/// Weighted water-filling of `budget` over `caps`.
+fn water_fill(budget: f64, caps: &[(usize, f64, f64)], out: &mut [f64]) {
+    let mut left = budget;
+    let mut open: Vec<(usize, f64, f64)> = caps
+        .iter()
+        .copied()
+        .filter(|(_, cap, weight)| *cap > 0.0 && *weight > 0.0)
+        .collect();
+    while left > 1e-9 && !open.is_empty() {
+        let total_weight: f64 = open.iter().map(|(_, _, w)| w).sum();
+        let per_weight = left / total_weight;
+        let (full, partial): (Vec<_>, Vec<_>) = open
+            .iter()
+            .partition(|(i, cap, w)| cap - out[*i] <= per_weight * w);
+        if full.is_empty() {
+            for (i, _, w) in &partial {
+                out[*i] += per_weight * w;
+            }
+            return;
+        }
+        for (i, cap, _) in &full {
+            left -= cap - out[*i];
+            out[*i] = *cap;
+        }
+        open = partial;
+    }
+}
+
+fn allocate(
+    budget: f64,
+    sessions: &[SessionDemand],
+    active: &[bool],
+    order: &[usize],
+    policy: FairnessPolicy,
+) -> Vec<f64> {
+    let mut eligible: Vec<bool> = active.to_vec();
+    loop {
+        let mut out = vec![0.0; sessions.len()];
+        match policy {
+            FairnessPolicy::EqualShare | FairnessPolicy::ProportionalToMax => {
+                let caps: Vec<(usize, f64, f64)> = order
+                    .iter()
+                    .filter(|&&i| eligible[i])
+                    .map(|&i| {
+                        let cap = sessions[i].max_power.max(0.0);
+                        let weight = if policy == FairnessPolicy::EqualShare {
+                            1.0
+                        } else {
+                            cap
+                        };
+                        (i, cap, weight)
+                    })
+                    .collect();
+                water_fill(budget, &caps, &mut out);
+            }
+            FairnessPolicy::FirstComeFirstServed | FairnessPolicy::EarliestDepartureFirst => {
+                let mut left = budget;
+                for &i in order.iter().filter(|&&i| eligible[i]) {
+                    let give = sessions[i].max_power.max(0.0).min(left);
+                    // Skip sessions that cannot run at what is left; later ones may.
+                    if give >= sessions[i].min_power {
+                        out[i] = give;
+                        left -= give;
+                    }
+                }
+            }
+        }
+        // Pause the lowest-priority session that ended up below its minimum and retry.
+        let starved = order
+            .iter()
+            .rev()
+            .find(|&&i| eligible[i] && out[i] < sessions[i].min_power);
+        match starved {
+            Some(&i) => eligible[i] = false,
+            None => return out,
+        }
+    }
+}
+
+/// Plan `sessions` over `horizon` seconds from `start`.
+///
+/// A session gets no power before it started, after its departure or once its
+/// `energy_needed` is delivered; power freed that way goes to the others from that instant
+/// on. The returned plans have the same order as `sessions`.
+#[must_use]
+pub fn plan(
+    site: &SiteLimit,
+    sessions: &[SessionDemand],
+    policy: FairnessPolicy,
+    start: DateTime<Utc>,
+    horizon: i32,
+) -> Vec<SessionPlan> {
+    let end = start
+        .checked_add_signed(TimeDelta::seconds(i64::from(horizon.max(0))))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let order = priority(sessions, policy);
+    let mut fixed: Vec<DateTime<Utc>> = site
+        .change_points()
+        .chain(sessions.iter().filter_map(|s| s.departure))
+        .chain(sessions.iter().map(|s| s.started))
+        .filter(|t| start < *t && *t < end)
+        .collect();
+    fixed.push(end);
+    fixed.sort_unstable();
+    fixed.dedup();
+
+    let mut remaining: Vec<Option<f64>> = sessions.iter().map(|s| s.energy_needed).collect();
+    let mut plans: Vec<SessionPlan> = vec![SessionPlan::default(); sessions.len()];
+    let mut t = start;
+    while t < end {
+        let active: Vec<bool> = sessions
+            .iter()
+            .zip(&remaining)
+            .map(|(s, left)| {
+                s.started <= t
+                    && s.departure.is_none_or(|d| t < d)
+                    && left.is_none_or(|wh| wh > 1e-9)
+            })
+            .collect();
+        let power = allocate(site.at(t), sessions, &active, &order, policy);
+        // The segment ends at the next fixed point or when a session's need is met.
+        let mut next = fixed.iter().copied().find(|p| *p > t).unwrap_or(end);
+        for (i, watts) in power.iter().enumerate() {
+            if let Some(wh) = remaining[i].filter(|_| *watts > 0.0) {
+                let met = TimeDelta::try_seconds(seconds_to_deliver(wh, *watts).max(1))
+                    .and_then(|d| t.checked_add_signed(d))
+                    .unwrap_or(end);
+                next = next.min(met);
+            }
+        }
+        let offset = i32::try_from((t - start).num_seconds()).unwrap_or(i32::MAX);
+        #[allow(clippy::cast_precision_loss)]
+        let hours = (next - t).num_seconds() as f64 / 3600.0;
+        for (i, watts) in power.into_iter().enumerate() {
+            let delivered = watts * hours;
+            if let Some(wh) = remaining[i].as_mut() {
+                *wh = (*wh - delivered).max(0.0);
+            }
+            let plan_i = &mut plans[i];
+            plan_i.energy += delivered;
+            if plan_i
+                .periods
+                .last()
+                .is_none_or(|p| p.power.to_bits() != watts.to_bits())
+            {
+                plan_i.periods.push(PlannedPeriod {
+                    start_offset: offset,
+                    power: watts,
+                });
+            }
+        }
+        t = next;
+    }
+    plans
+}
+
+/// Round `value` down to one decimal, so rounded limits never add up above the budget.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+pub(crate) fn floor_tenth(value: f64) -> f64 {
+    let scaled = value * 10.0;
+    let mut tenths = scaled as i64;
+    if (tenths as f64) > scaled {
+        tenths -= 1;
+    }
+    tenths as f64 / 10.0
+}
@@ -0,0 +1,270 @@
+//! Per-transaction meter value aggregation.
+//!
+//! A [`MeterAggregator`] collects the readings of one transaction, as version-neutral
+//! [`Reading`]s in Wh / W / %, fed from 1.6 `StartTransaction` / `MeterValues` /
+//! `StopTransaction` (see [`crate::v16::metering`]) or 2.1 `TransactionEvent` /
+//! `MeterValues` (see [`crate::v21::metering`]). [`MeterAggregator::summary`] then works
+//! out the energy delivered from the `Energy.Active.Import.Register` readings between
+//! `Transaction.Begin` and `Transaction.End`, along with power and state-of-charge figures,
+//! flagging anything that needed a judgement call as a [`MeterAnomaly`].
+//!
+//! Readings may arrive in any order (offline queues replay late); the summary sorts them
+//! by timestamp. Per-phase readings are summed when a reading has no phase-less total.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, Utc};
+
+use crate::quantity::Unit;
+
+/// What a reading measures, after unit normalization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Measure {
+    /// `Energy.Active.Import.Register`, Wh.
+    EnergyImportRegister,
+    /// `Power.Active.Import`, W.
+    PowerImport,
+    /// `SoC`, %.
+    StateOfCharge,
+}
+
+impl Measure {
+    /// Unit [`Reading`]s of this measure are in.
+    #[must_use]
+    pub const fn unit(self) -> Unit {
+        match self {
+            Self::EnergyImportRegister => Unit::Wh,
+            Self::PowerImport => Unit::W,
+            Self::StateOfCharge => Unit::Percent,
+        }
+    }
+}
+
+/// Reading context, as far as the aggregation cares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleContext {
+    TransactionBegin,
+    TransactionEnd,
+    #[default]
+    Other,
+}
+
+/// One normalized sampled value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reading {
+    pub timestamp: DateTime<Utc>,
+    pub measure: Measure,
+    pub context: SampleContext,
+    /// Phase index 1–3 for per-phase values; `None` for totals.
+    pub phase: Option<u8>,
+    pub value: f64,
+}
+
+/// Something the summary had to work around.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeterAnomaly {
+    /// No `Transaction.Begin` register reading; the earliest one was used.
+    MissingBegin,
+    /// No `Transaction.End` register reading; the latest one was used.
+    MissingEnd,
+    /// The register went down from the top 10 % of [`AggregatorConfig::rollover_at`]:
+    /// counted as a wrap-around.
+    Rollover {
+        at: DateTime<Utc>,
+        from: f64,
+        to: f64,
+    },
+    /// The register went down otherwise: counted as a meter reset to 0.
+    Reset {
+        at: DateTime<Utc>,
+        from: f64,
+        to: f64,
+    },
+    /// Energy between two readings implies more than
+    /// [`AggregatorConfig::max_plausible_power`].
+    ImplausibleJump {
+        at: DateTime<Utc>,
+        energy: f64,
+        seconds: i64,
+    },
+    /// A sampled value that could not be read (unparseable, signed-only or an unknown
+    /// unit); `value` is the raw value.
+    Unreadable { at: DateTime<Utc>, value: String },
+}
+
+/// Meter properties the summary relies on.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AggregatorConfig {
+    /// Register value (Wh) at which the meter wraps to 0, e.g. `1e9` for a register
+    /// counting up to 999 999.999 kWh. `None`: every decrease is a reset.
+    pub rollover_at: Option<f64>,
+    /// Highest power (W) the connector can deliver; larger implied jumps are flagged.
+    pub max_plausible_power: Option<f64>,
+}
+
+impl AggregatorConfig {
+    #[must_use]
+    pub const fn new(rollover_at: Option<f64>, max_plausible_power: Option<f64>) -> Self {
+        Self {
+            rollover_at,
+            max_plausible_power,
+        }
+    }
+}
+
+/// Energy, power and state of charge of one transaction.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MeterSummary {
+    /// Register reading (timestamp, Wh) the transaction started at.
+    pub start: Option<(DateTime<Utc>, f64)>,
+    /// Register reading (timestamp, Wh) the transaction ended at.
+    pub end: Option<(DateTime<Utc>, f64)>,
+    /// Energy delivered, Wh.
+    pub energy: f64,
+    /// Average power over `start..end`, W.
+    pub average_power: Option<f64>,
+    /// Highest `Power.Active.Import` reported, W.
+    pub max_power: Option<f64>,
+    pub soc_start: Option<f64>,
+    pub soc_end: Option<f64>,
+    pub anomalies: Vec<MeterAnomaly>,
+}
+
+/// Readings of one transaction; see the module docs.
+#[derive(Debug, Clone, Default)]
+pub struct MeterAggregator {
+    config: AggregatorConfig,
+    readings: Vec<Reading>,
+    anomalies: Vec<MeterAnomaly>,
+}
+
+impl MeterAggregator {
+    #[must_use]
+    pub const fn new(config: AggregatorConfig) -> Self {
+        Self {
+            config,
+            readings: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn readings(&self) -> &[Reading] {
+        &self.readings
+    }
+
+    pub fn push(&mut self, reading: Reading) {
+        self.readings.push(reading);
+    }
+
+    /// Record an anomaly found while reading a message (e.g. an unparseable value).
+    pub fn flag(&mut self, anomaly: MeterAnomaly) {
+        self.anomalies.push(anomaly);
+    }
+
+    /// Values of `measure` per timestamp (in time order): the phase-less value when there
+    /// is one, else the sum of the per-phase values. The context is `TransactionBegin` /
+    /// `TransactionEnd` if any value at that timestamp carries it.
+    fn series(&self, measure: Measure) -> Vec<(DateTime<Utc>, SampleContext, f64)> {
+        let mut readings: Vec<&Reading> = self
+            .readings
+            .iter()
+            .filter(|r| r.measure == measure)
+            .collect();
+        readings.sort_by_key(|r| r.timestamp);
+        let mut series = Vec::new();
+        for group in readings.chunk_by(|a, b| a.timestamp == b.timestamp) {
+            let total = group.iter().rev().find(|r| r.phase.is_none()).map_or_else(
+                || {
+                    // Last value per phase wins.
+                    (1..=3_u8)
+                        .filter_map(|ph| group.iter().rev().find(|r| r.phase == Some(ph)))
+                        .map(|r| r.value)
+                        .sum()
+                },
+                |r| r.value,
+            );
+            let context = group
+                .iter()
+                .map(|r| r.context)
+                .find(|c| *c != SampleContext::Other)
+                .unwrap_or_default();
+            series.push((group[0].timestamp, context, total));
+        }
+        series
+    }
+
+    /// Summarize everything pushed so far.
+    #[must_use]
+    pub fn summary(&self) -> MeterSummary {
+        let mut summary = MeterSummary {
+            anomalies: self.anomalies.clone(),
+            ..MeterSummary::default()
+        };
+        let register = self.series(Measure::EnergyImportRegister);
+        let begin = register
+            .iter()
+            .position(|(_, c, _)| *c == SampleContext::TransactionBegin);
+        let end = register
+            .iter()
+            .rposition(|(_, c, _)| *c == SampleContext::TransactionEnd);
+        if !register.is_empty() {
+            if begin.is_none() {
+                summary.anomalies.push(MeterAnomaly::MissingBegin);
+            }
+            if end.is_none() {
+                summary.anomalies.push(MeterAnomaly::MissingEnd);
+            }
+        }
+        let first = begin.unwrap_or(0);
+        let last = end.unwrap_or_else(|| register.len().saturating_sub(1));
+        if let Some(window) = register.get(first..=last) {
+            summary.start = window.first().map(|(t, _, v)| (*t, *v));
+            summary.end = window.last().map(|(t, _, v)| (*t, *v));
+            for pair in window.windows(2) {
+                let ((from_t, _, from), (at, _, to)) = (pair[0], pair[1]);
+                let delta = if to >= from {
+                    to - from
+                } else if let Some(wrap) = self.config.rollover_at.filter(|w| from >= w * 0.9) {
+                    summary
+                        .anomalies
+                        .push(MeterAnomaly::Rollover { at, from, to });
+                    wrap - from + to
+                } else {
+                    summary.anomalies.push(MeterAnomaly::Reset { at, from, to });
+                    to
+                };
+                let seconds = (at - from_t).num_seconds();
+                if let Some(max) = self.config.max_plausible_power {
+                    #[allow(clippy::cast_precision_loss)]
+                    let hours = seconds as f64 / 3600.0;
+                    if delta > max * hours + 1e-6 {
+                        summary.anomalies.push(MeterAnomaly::ImplausibleJump {
+                            at,
+                            energy: delta,
+                            seconds,
+                        });
+                    }
+                }
+                summary.energy += delta;
+            }
+        }
+        if let (Some((from, _)), Some((to, _))) = (summary.start, summary.end) {
+            let seconds = (to - from).num_seconds();
+            if seconds > 0 {
+                #[allow(clippy::cast_precision_loss)]
+                let hours = seconds as f64 / 3600.0;
+                summary.average_power = Some(summary.energy / hours);
+            }
+        }
+        summary.max_power = self
+            .series(Measure::PowerImport)
+            .iter()
+            .map(|(_, _, w)| *w)
+            .reduce(f64::max);
+        let soc = self.series(Measure::StateOfCharge);
+        summary.soc_start = soc.first().map(|(_, _, v)| *v);
+        summary.soc_end = soc.last().map(|(_, _, v)| *v);
+        summary
+    }
+}
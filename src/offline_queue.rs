@@ -0,0 +1,289 @@
+//! Version-neutral queue for transaction-related messages that must survive offline periods.
+//!
+//! OCPP requires a station to keep transaction-related messages while the connection is down
+//! and to deliver them later in the order they were generated. When the CSMS answers one with a
+//! CALLERROR (or not at all) the station retries it a limited number of times, waiting the
+//! retry interval multiplied by the number of attempts already made, and then discards it.
+//! Every other message is simply dropped while offline.
+//!
+//! [`OfflineQueue`] encodes those rules for any [`QueueMessage`]; [`crate::v16::offline_queue`]
+//! and [`crate::v21::offline_queue`] implement it for the respective `Call` types. Only one
+//! queued message is in flight at a time, so replay keeps the original order. The queue does
+//! no I/O and reads no clock; entries are serde-serializable so the caller can persist them in
+//! whatever store it has and restore them with [`OfflineQueue::from_entries`].
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A message the queue can hold.
+pub trait QueueMessage: Clone {
+    /// Whether the message is transaction-related and must be kept while offline.
+    fn is_transaction_message(&self) -> bool;
+
+    /// OCPP-J `messageId` used to correlate the response.
+    fn unique_id(&self) -> &str;
+
+    /// Flag a message generated while offline (2.1 `offline`). No-op by default.
+    fn mark_offline(&mut self) {}
+}
+
+/// How often and how quickly a failed message is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total number of submissions, the first one included. `0` behaves like `1`.
+    pub attempts: u32,
+    /// Base wait in seconds; the wait after `n` failed attempts is `n * interval`.
+    pub interval: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            interval: 60,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Earliest retry after `attempts` failed submissions; saturates at
+    /// [`DateTime::<Utc>::MAX_UTC`].
+    #[must_use]
+    pub fn retry_at(&self, attempts: u32, now: DateTime<Utc>) -> DateTime<Utc> {
+        i64::from(self.interval)
+            .checked_mul(i64::from(attempts))
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|wait| now.checked_add_signed(wait))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+/// One queued message with its delivery state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedMessage<M> {
+    pub message: M,
+    pub queued_at: DateTime<Utc>,
+    /// Submissions made so far.
+    pub attempts: u32,
+    /// Not submitted again before this instant.
+    pub not_before: Option<DateTime<Utc>>,
+}
+
+/// What [`OfflineQueue::push`] did with a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// Held in the queue; it is sent through [`OfflineQueue::next_message`].
+    Queued,
+    /// Not transaction-related and online: send it directly.
+    SendDirectly,
+    /// Not transaction-related and offline: discarded.
+    Dropped,
+}
+
+/// What [`OfflineQueue::on_error`] did with the failed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureOutcome<M> {
+    /// Submitted again from this instant.
+    Retry(DateTime<Utc>),
+    /// Attempts exhausted; the message left the queue.
+    Discarded(M),
+}
+
+/// Ordered store-and-forward queue of transaction-related messages.
+#[derive(Debug, Clone)]
+pub struct OfflineQueue<M> {
+    policy: RetryPolicy,
+    entries: VecDeque<QueuedMessage<M>>,
+    online: bool,
+    in_flight: bool,
+}
+
+impl<M: QueueMessage> OfflineQueue<M> {
+    /// Empty queue; starts offline until [`Self::set_online`].
+    #[must_use]
+    pub const fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            entries: VecDeque::new(),
+            online: false,
+            in_flight: false,
+        }
+    }
+
+    /// Restore persisted entries, oldest first. Nothing is considered in flight.
+    #[must_use]
+    pub fn from_entries(policy: RetryPolicy, entries: Vec<QueuedMessage<M>>) -> Self {
+        Self {
+            policy,
+            entries: entries.into(),
+            online: false,
+            in_flight: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Configuration changes apply to the next failure.
+    pub const fn set_policy(&mut self, policy: RetryPolicy) {
+        self.policy = policy;
+    }
+
+    /// Queued entries, oldest first, for persistence or inspection.
+    pub fn entries(&self) -> impl Iterator<Item = &QueuedMessage<M>> {
+        self.entries.iter()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[must_use]
+    pub const fn is_online(&self) -> bool {
+        self.online
+    }
+
+    /// `messageId` of the message awaiting a response, if any.
+    #[must_use]
+    pub fn in_flight(&self) -> Option<&str> {
+        if self.in_flight {
+            self.entries.front().map(|entry| entry.message.unique_id())
+        } else {
+            None
+        }
+    }
+
+    /// Record a connection change.
+    ///
+    /// When the connection drops while a message is in flight, that message goes back to the
+    /// head of the queue without counting the attempt, and its `messageId` is returned so the
+    /// caller can forget it in its pending-call map.
+    pub fn set_online(&mut self, online: bool) -> Option<String> {
+        self.online = online;
+        if online || !self.in_flight {
+            return None;
+        }
+        self.in_flight = false;
+        let entry = self.entries.front_mut()?;
+        entry.attempts = entry.attempts.saturating_sub(1);
+        Some(String::from(entry.message.unique_id()))
+    }
+
+    /// Offer a newly generated message.
+    ///
+    /// Transaction-related messages are always queued, behind anything already waiting, and
+    /// marked as offline when there is no connection.
+    pub fn push(&mut self, mut message: M, now: DateTime<Utc>) -> PushOutcome {
+        if !message.is_transaction_message() {
+            return if self.online {
+                PushOutcome::SendDirectly
+            } else {
+                PushOutcome::Dropped
+            };
+        }
+        if !self.online {
+            message.mark_offline();
+        }
+        self.entries.push_back(QueuedMessage {
+            message,
+            queued_at: now,
+            attempts: 0,
+            not_before: None,
+        });
+        PushOutcome::Queued
+    }
+
+    /// Earliest instant [`Self::next_message`] may return something, if it is waiting on time.
+    #[must_use]
+    pub fn next_poll(&self) -> Option<DateTime<Utc>> {
+        if !self.online || self.in_flight {
+            return None;
+        }
+        self.entries.front().and_then(|entry| entry.not_before)
+    }
+
+    /// The message to submit now, if online, idle and past any retry wait.
+    pub fn next_message(&mut self, now: DateTime<Utc>) -> Option<M> {
+        if !self.online || self.in_flight {
+            return None;
+        }
+        let entry = self.entries.front_mut()?;
+        if entry.not_before.is_some_and(|at| now < at) {
+            return None;
+        }
+        entry.attempts += 1;
+        entry.not_before = None;
+        self.in_flight = true;
+        Some(entry.message.clone())
+    }
+
+    /// CALLRESULT for `unique_id`: the in-flight message is delivered and leaves the queue.
+    ///
+    /// Returns `None` when `unique_id` is not the in-flight message.
+    pub fn on_result(&mut self, unique_id: &str) -> Option<M> {
+        if self.in_flight() != Some(unique_id) {
+            return None;
+        }
+        self.in_flight = false;
+        self.entries.pop_front().map(|entry| entry.message)
+    }
+
+    /// CALLERROR or response timeout for `unique_id`.
+    ///
+    /// Returns `None` when `unique_id` is not the in-flight message.
+    pub fn on_error(&mut self, unique_id: &str, now: DateTime<Utc>) -> Option<FailureOutcome<M>> {
+        if self.in_flight() != Some(unique_id) {
+            return None;
+        }
+        self.in_flight = false;
+        let policy = self.policy;
+        let entry = self.entries.front_mut()?;
+        if entry.attempts >= policy.attempts.max(1) {
+            return self
+                .entries
+                .pop_front()
+                .map(|dropped| FailureOutcome::Discarded(dropped.message));
+        }
+        let at = policy.retry_at(entry.attempts, now);
+        entry.not_before = Some(at);
+        Some(FailureOutcome::Retry(at))
+    }
+
+    /// Rewrite queued messages in place, oldest first; those for which `f` returns `false`
+    /// leave the queue and are returned.
+    ///
+    /// Removing the in-flight message abandons it: its response is then ignored.
+    pub fn retain_mut(&mut self, mut f: impl FnMut(&mut M) -> bool) -> Vec<M> {
+        let mut removed = Vec::new();
+        let mut kept = VecDeque::with_capacity(self.entries.len());
+        for (index, mut entry) in self.entries.drain(..).enumerate() {
+            if f(&mut entry.message) {
+                kept.push_back(entry);
+            } else {
+                if index == 0 {
+                    self.in_flight = false;
+                }
+                removed.push(entry.message);
+            }
+        }
+        self.entries = kept;
+        removed
+    }
+}
+
+impl<M: QueueMessage> Default for OfflineQueue<M> {
+    fn default() -> Self {
+        Self::new(RetryPolicy::default())
+    }
+}
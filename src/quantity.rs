@@ -0,0 +1,226 @@
+//! Unit normalization for sampled meter values.
+//!
+//! 1.6 sends `SampledValue.value` as a decimal string with a `UnitOfMeasure` enum; 2.1
+//! sends `SampledValueType.value` as a number with `UnitOfMeasureType { unit, multiplier }`.
+//! Both end up as a [`Quantity`] in a base unit ([`SiUnit`]): energy in Wh, power in W,
+//! temperature in K, and so on. The version-specific accessors live next to the types:
+//! `SampledValue::quantity` ([`crate::v16::sampled_value`]) and
+//! `SampledValueType::quantity` ([`crate::v21::sampled_value`]).
+
+use alloc::string::String;
+use core::fmt;
+
+/// Units a sampled value may be expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Wh,
+    KWh,
+    Varh,
+    Kvarh,
+    Vah,
+    Kvah,
+    W,
+    Kw,
+    Va,
+    Kva,
+    Var,
+    Kvar,
+    A,
+    V,
+    Celsius,
+    Fahrenheit,
+    K,
+    Percent,
+    Hz,
+    S,
+}
+
+/// Base unit of a [`Quantity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiUnit {
+    Wh,
+    Varh,
+    Vah,
+    W,
+    Var,
+    Va,
+    A,
+    V,
+    K,
+    Percent,
+    Hz,
+    S,
+    /// Dimensionless (e.g. `Power.Factor`, `RPM`).
+    One,
+}
+
+impl Unit {
+    /// Parse a unit as written on the wire (2.1 `UnitOfMeasureType.unit`, 1.6
+    /// `UnitOfMeasure`). Accepts the 1.6 `Celcius` misspelling.
+    #[must_use]
+    pub fn parse(unit: &str) -> Option<Self> {
+        Some(match unit {
+            "Wh" => Self::Wh,
+            "kWh" => Self::KWh,
+            "varh" => Self::Varh,
+            "kvarh" => Self::Kvarh,
+            "VAh" => Self::Vah,
+            "kVAh" => Self::Kvah,
+            "W" => Self::W,
+            "kW" => Self::Kw,
+            "VA" => Self::Va,
+            "kVA" => Self::Kva,
+            "var" => Self::Var,
+            "kvar" => Self::Kvar,
+            "A" => Self::A,
+            "V" => Self::V,
+            "Celsius" | "Celcius" => Self::Celsius,
+            "Fahrenheit" => Self::Fahrenheit,
+            "K" => Self::K,
+            "Percent" => Self::Percent,
+            "Hz" => Self::Hz,
+            "s" => Self::S,
+            _ => return None,
+        })
+    }
+
+    #[must_use]
+    pub const fn si_unit(self) -> SiUnit {
+        match self {
+            Self::Wh | Self::KWh => SiUnit::Wh,
+            Self::Varh | Self::Kvarh => SiUnit::Varh,
+            Self::Vah | Self::Kvah => SiUnit::Vah,
+            Self::W | Self::Kw => SiUnit::W,
+            Self::Va | Self::Kva => SiUnit::Va,
+            Self::Var | Self::Kvar => SiUnit::Var,
+            Self::A => SiUnit::A,
+            Self::V => SiUnit::V,
+            Self::Celsius | Self::Fahrenheit | Self::K => SiUnit::K,
+            Self::Percent => SiUnit::Percent,
+            Self::Hz => SiUnit::Hz,
+            Self::S => SiUnit::S,
+        }
+    }
+
+    /// `value` in this unit, expressed in [`Self::si_unit`].
+    #[must_use]
+    pub fn to_si(self, value: f64) -> f64 {
+        match self {
+            Self::KWh | Self::Kvarh | Self::Kvah | Self::Kw | Self::Kva | Self::Kvar => {
+                value * 1000.0
+            }
+            Self::Celsius => value + 273.15,
+            Self::Fahrenheit => (value - 32.0) * 5.0 / 9.0 + 273.15,
+            _ => value,
+        }
+    }
+
+    /// `value` in [`Self::si_unit`], expressed in this unit.
+    #[must_use]
+    pub fn from_si(self, value: f64) -> f64 {
+        match self {
+            Self::KWh | Self::Kvarh | Self::Kvah | Self::Kw | Self::Kva | Self::Kvar => {
+                value / 1000.0
+            }
+            Self::Celsius => value - 273.15,
+            Self::Fahrenheit => (value - 273.15) * 9.0 / 5.0 + 32.0,
+            _ => value,
+        }
+    }
+}
+
+/// A sampled value in its base unit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub si_unit: SiUnit,
+}
+
+impl Quantity {
+    /// `value × 10^multiplier` in `unit`, normalized.
+    #[must_use]
+    pub fn new(value: f64, unit: Unit, multiplier: i32) -> Self {
+        Self {
+            value: unit.to_si(value * pow10(multiplier)),
+            si_unit: unit.si_unit(),
+        }
+    }
+
+    /// A dimensionless value.
+    #[must_use]
+    pub const fn one(value: f64) -> Self {
+        Self {
+            value,
+            si_unit: SiUnit::One,
+        }
+    }
+
+    /// Value in `unit`; `None` if `unit` measures something else.
+    #[must_use]
+    pub fn to(&self, unit: Unit) -> Option<f64> {
+        (unit.si_unit() == self.si_unit).then(|| unit.from_si(self.value))
+    }
+}
+
+/// Why a sampled value has no [`Quantity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuantityError {
+    /// The value is signed meter data, not a number (1.6 `format = SignedData`, or a
+    /// recognizable signed payload such as OCMF).
+    Signed,
+    /// Not a decimal number.
+    NotANumber(String),
+    /// Unit unknown to [`Unit::parse`].
+    UnknownUnit(String),
+}
+
+impl fmt::Display for QuantityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signed => write!(f, "signed meter data"),
+            Self::NotANumber(value) => write!(f, "not a number: {value}"),
+            Self::UnknownUnit(unit) => write!(f, "unknown unit: {unit}"),
+        }
+    }
+}
+
+/// Whether a non-numeric 1.6 value looks like signed meter data sent without
+/// `format = SignedData`: an OCMF or XML (EDL / Alfen) payload, or a long base64 / hex
+/// blob.
+#[must_use]
+pub fn looks_signed(value: &str) -> bool {
+    let value = value.trim();
+    value.starts_with("OCMF|")
+        || value.starts_with('<')
+        || (value.len() >= 32
+            && value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=')))
+}
+
+/// Parse a 1.6 decimal value.
+///
+/// # Errors
+/// [`QuantityError::Signed`] for signed-looking payloads, else
+/// [`QuantityError::NotANumber`].
+pub fn parse_decimal(value: &str) -> Result<f64, QuantityError> {
+    let trimmed = value.trim();
+    match trimmed.parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ if looks_signed(trimmed) => Err(QuantityError::Signed),
+        _ => Err(QuantityError::NotANumber(value.into())),
+    }
+}
+
+/// Exponents beyond this already give `0.0` or infinity.
+const MAX_POW10: i32 = 400;
+
+/// `10^exponent`, without `powi` (not available in `core`).
+pub(crate) fn pow10(exponent: i32) -> f64 {
+    let exponent = exponent.clamp(-MAX_POW10, MAX_POW10);
+    let mut factor = 1.0;
+    for _ in 0..exponent.unsigned_abs() {
+        factor *= 10.0;
+    }
+    if exponent < 0 { 1.0 / factor } else { factor }
+}
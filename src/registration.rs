@@ -0,0 +1,193 @@
+//! Version-neutral station registration (`BootNotification`) state machine.
+//!
+//! A station sends `BootNotification` first and nothing else until the CSMS answers. The
+//! `interval` of the response means two things: after `Accepted` it is the heartbeat interval,
+//! after `Pending` / `Rejected` it is the wait before the next `BootNotification`. While
+//! `Pending` the station only sends what the CSMS asked for (`TriggerMessage`, reports); while
+//! `Rejected` it sends nothing and does not answer CSMS calls.
+//!
+//! [`Registration`] tracks that by action name; [`crate::v16::registration`] and
+//! [`crate::v21::registration`] map the version's messages onto it. No clock is read: callers
+//! pass `now` and drive retries through [`Registration::poll`] / [`Registration::next_poll`].
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// Action name of the boot message, identical in 1.6 and 2.1.
+pub const BOOT_NOTIFICATION: &str = "BootNotification";
+
+/// Where the station stands with the CSMS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationState {
+    /// No `BootNotification` answered yet.
+    Booting,
+    Pending,
+    Rejected,
+    Accepted,
+}
+
+/// Station-side settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistrationConfig {
+    /// Wait in seconds before retrying `BootNotification` when the response carries no
+    /// interval or the call failed.
+    pub retry_interval: u32,
+}
+
+impl Default for RegistrationConfig {
+    fn default() -> Self {
+        Self { retry_interval: 60 }
+    }
+}
+
+/// Registration state machine.
+#[derive(Debug, Clone)]
+pub struct Registration {
+    config: RegistrationConfig,
+    state: RegistrationState,
+    boot_in_flight: bool,
+    next_boot: Option<DateTime<Utc>>,
+    heartbeat_interval: Option<u32>,
+    granted: BTreeSet<String>,
+}
+
+impl Registration {
+    /// Freshly booted station; the first `BootNotification` is due at `now`.
+    #[must_use]
+    pub const fn new(config: RegistrationConfig, now: DateTime<Utc>) -> Self {
+        Self {
+            config,
+            state: RegistrationState::Booting,
+            boot_in_flight: false,
+            next_boot: Some(now),
+            heartbeat_interval: None,
+            granted: BTreeSet::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &RegistrationConfig {
+        &self.config
+    }
+
+    #[must_use]
+    pub const fn state(&self) -> RegistrationState {
+        self.state
+    }
+
+    #[must_use]
+    pub const fn is_accepted(&self) -> bool {
+        matches!(self.state, RegistrationState::Accepted)
+    }
+
+    /// Heartbeat interval in seconds from the last `Accepted` response with a positive interval.
+    #[must_use]
+    pub const fn heartbeat_interval(&self) -> Option<u32> {
+        self.heartbeat_interval
+    }
+
+    /// When the next `BootNotification` is due, unless one is awaiting its response.
+    #[must_use]
+    pub const fn next_poll(&self) -> Option<DateTime<Utc>> {
+        if self.boot_in_flight {
+            None
+        } else {
+            self.next_boot
+        }
+    }
+
+    /// `true` when a `BootNotification` must be sent now; it is then considered in flight.
+    pub fn poll(&mut self, now: DateTime<Utc>) -> bool {
+        if self.boot_in_flight || self.next_boot.is_none_or(|at| now < at) {
+            return false;
+        }
+        self.boot_in_flight = true;
+        self.next_boot = None;
+        true
+    }
+
+    /// `BootNotification` response. `interval` is in seconds; zero or negative falls back to
+    /// [`RegistrationConfig::retry_interval`] for retries and keeps the heartbeat interval.
+    pub fn on_boot_response(
+        &mut self,
+        state: RegistrationState,
+        interval: i32,
+        now: DateTime<Utc>,
+    ) {
+        self.boot_in_flight = false;
+        self.granted.clear();
+        let interval = u32::try_from(interval).ok().filter(|seconds| *seconds > 0);
+        match state {
+            RegistrationState::Accepted => {
+                self.next_boot = None;
+                if interval.is_some() {
+                    self.heartbeat_interval = interval;
+                }
+            }
+            RegistrationState::Booting
+            | RegistrationState::Pending
+            | RegistrationState::Rejected => {
+                self.next_boot = Some(self.retry_at(interval, now));
+            }
+        }
+        if state != RegistrationState::Booting {
+            self.state = state;
+        }
+    }
+
+    /// The `BootNotification` got a CALLERROR or no response; retried after
+    /// [`RegistrationConfig::retry_interval`].
+    pub fn on_boot_failed(&mut self, now: DateTime<Utc>) {
+        if self.boot_in_flight {
+            self.boot_in_flight = false;
+            self.next_boot = Some(self.retry_at(None, now));
+        }
+    }
+
+    /// `TriggerMessage(BootNotification)`: send one at `now`. `false` while `Rejected`.
+    pub fn trigger_boot(&mut self, now: DateTime<Utc>) -> bool {
+        if self.state == RegistrationState::Rejected {
+            return false;
+        }
+        if !self.boot_in_flight {
+            self.next_boot = Some(now);
+        }
+        true
+    }
+
+    /// Allow `action_name` while not accepted because the CSMS asked for it. Grants last until
+    /// the next `BootNotification` response or [`Self::revoke`].
+    pub fn grant(&mut self, action_name: impl Into<String>) {
+        self.granted.insert(action_name.into());
+    }
+
+    pub fn revoke(&mut self, action_name: &str) {
+        self.granted.remove(action_name);
+    }
+
+    /// Whether a station-initiated CALL of `action_name` may go out now.
+    ///
+    /// `BootNotification` is always allowed (send it through [`Self::poll`]); anything else
+    /// needs `Accepted`, or a grant while `Pending`.
+    #[must_use]
+    pub fn may_send(&self, action_name: &str) -> bool {
+        match self.state {
+            RegistrationState::Accepted => true,
+            _ if action_name == BOOT_NOTIFICATION => true,
+            RegistrationState::Pending => self.granted.contains(action_name),
+            RegistrationState::Booting | RegistrationState::Rejected => false,
+        }
+    }
+
+    /// Whether CSMS-initiated CALLs are answered at all (not while `Rejected`).
+    #[must_use]
+    pub fn answers_csms(&self) -> bool {
+        self.state != RegistrationState::Rejected
+    }
+
+    fn retry_at(&self, interval: Option<u32>, now: DateTime<Utc>) -> DateTime<Utc> {
+        let seconds = interval.unwrap_or(self.config.retry_interval);
+        now + TimeDelta::seconds(i64::from(seconds))
+    }
+}
@@ -0,0 +1,749 @@
+//! Transport-independent CSMS registry of charging stations.
+//!
+//! [`ChargerRegistry`] keeps one [`ChargerRecord`] per station identity: the negotiated
+//! protocol, boot information, registration status, last heartbeat, connector statuses, open
+//! transactions and the station's in-flight CSMS calls. The WebSocket layer reports connection
+//! changes ([`ChargerRegistry::connect`] / [`ChargerRegistry::disconnect`]) and hands over every
+//! parsed frame ([`ChargerRegistry::ingest_v16`] / [`ChargerRegistry::ingest_v21`]); the CSMS
+//! logic reports the responses it sends to station calls
+//! ([`ChargerRegistry::responded_v16`] / [`ChargerRegistry::responded_v21`]) because those
+//! decide registration and, in 1.6, transaction ids. CSMS calls go out through
+//! [`ChargerRegistry::send_v16`] / [`ChargerRegistry::send_v21`] so their CALLRESULTs resolve.
+//!
+//! A connection is stale when nothing arrived for [`RegistryConfig::missed_heartbeats`]
+//! heartbeat intervals; [`ChargerRegistry::poll`] detects that, and the next frame from the
+//! station makes it connected again. A disconnected station keeps its record but takes no
+//! frames, calls or responses ([`RegistryError::Disconnected`]) until it connects again.
+//! Every change is queued as a [`RegistryEvent`] drained with
+//! [`ChargerRegistry::take_events`]. No clock is read.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+use core::fmt;
+
+use crate::errors::Error;
+use crate::registration::RegistrationState;
+use crate::v16;
+use crate::v21;
+use crate::v21::version::NegotiatedVersion;
+
+/// OCPP-J protocol spoken on a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Ocpp16,
+    Ocpp2(NegotiatedVersion),
+}
+
+impl Protocol {
+    /// Parse a `Sec-WebSocket-Protocol` token.
+    #[must_use]
+    pub fn from_subprotocol(proto: &str) -> Option<Self> {
+        if proto == "ocpp1.6" {
+            Some(Self::Ocpp16)
+        } else {
+            NegotiatedVersion::from_subprotocol(proto).map(Self::Ocpp2)
+        }
+    }
+
+    #[must_use]
+    pub const fn as_subprotocol(self) -> &'static str {
+        match self {
+            Self::Ocpp16 => "ocpp1.6",
+            Self::Ocpp2(version) => version.as_subprotocol(),
+        }
+    }
+}
+
+/// Registry-wide settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistryConfig {
+    /// Heartbeat interval (s) assumed until a `BootNotification` response sets one.
+    pub default_heartbeat_interval: u32,
+    /// Silent heartbeat intervals before a connection counts as stale.
+    pub missed_heartbeats: u32,
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self {
+            default_heartbeat_interval: 300,
+            missed_heartbeats: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connected,
+    /// Connected, but nothing received for too long.
+    Stale,
+    Disconnected,
+}
+
+/// Station description from the last `BootNotification` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootInfo {
+    pub vendor: String,
+    pub model: String,
+    pub serial_number: Option<String>,
+    pub firmware_version: Option<String>,
+    /// 2.1 boot reason.
+    pub reason: Option<String>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Connector address. 1.6 has no EVSE level, so `evse_id` is `None` there and `connector_id`
+/// is the 1.6 `connectorId` (`0` for the whole charge point).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConnectorRef {
+    pub evse_id: Option<i32>,
+    pub connector_id: i32,
+}
+
+/// Last reported connector status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectorStatus {
+    /// Wire status name (1.6 `ChargePointStatus`, 2.1 `ConnectorStatusEnumType`).
+    pub status: String,
+    /// 1.6 `errorCode`.
+    pub error_code: Option<String>,
+    /// Station timestamp, when given.
+    pub timestamp: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Transaction the station has started and not yet ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveTransaction {
+    /// 1.6 ids are the decimal `transactionId`.
+    pub transaction_id: String,
+    pub connector: Option<ConnectorRef>,
+    pub id_token: Option<String>,
+    /// Station timestamp of the start.
+    pub started_at: DateTime<Utc>,
+    /// 2.1 `chargingState` from the latest event that carried one.
+    pub charging_state: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Change reported by [`ChargerRegistry::take_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryEvent {
+    Connected {
+        charger: String,
+    },
+    Disconnected {
+        charger: String,
+    },
+    Stale {
+        charger: String,
+    },
+    /// `BootNotification` request received.
+    Booted {
+        charger: String,
+    },
+    RegistrationChanged {
+        charger: String,
+        state: RegistrationState,
+    },
+    ConnectorStatusChanged {
+        charger: String,
+        connector: ConnectorRef,
+        status: String,
+    },
+    TransactionStarted {
+        charger: String,
+        transaction_id: String,
+    },
+    TransactionEnded {
+        charger: String,
+        transaction_id: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Pending {
+    V16(v16::pending::PendingCalls),
+    V21(v21::pending::PendingCalls),
+}
+
+impl Pending {
+    fn new(protocol: Protocol) -> Self {
+        match protocol {
+            Protocol::Ocpp16 => Self::V16(v16::pending::PendingCalls::new()),
+            Protocol::Ocpp2(_) => Self::V21(v21::pending::PendingCalls::new()),
+        }
+    }
+}
+
+/// Everything the CSMS knows about one station.
+#[derive(Debug, Clone)]
+pub struct ChargerRecord {
+    pub id: String,
+    pub protocol: Protocol,
+    pub connection: ConnectionState,
+    pub connected_at: DateTime<Utc>,
+    /// Last frame of any kind from the station.
+    pub last_seen: DateTime<Utc>,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    pub boot: Option<BootInfo>,
+    pub registration: RegistrationState,
+    /// From the last accepted `BootNotification` response.
+    pub heartbeat_interval: Option<u32>,
+    pub connectors: BTreeMap<ConnectorRef, ConnectorStatus>,
+    /// Keyed by transaction id.
+    pub transactions: BTreeMap<String, ActiveTransaction>,
+    pending: Pending,
+    /// 1.6 `StartTransaction.req` awaiting the CSMS response, by `messageId`.
+    starts: BTreeMap<String, v16::call::StartTransaction>,
+}
+
+impl ChargerRecord {
+    fn new(id: String, protocol: Protocol, now: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            protocol,
+            connection: ConnectionState::Connected,
+            connected_at: now,
+            last_seen: now,
+            last_heartbeat: None,
+            boot: None,
+            registration: RegistrationState::Booting,
+            heartbeat_interval: None,
+            connectors: BTreeMap::new(),
+            transactions: BTreeMap::new(),
+            pending: Pending::new(protocol),
+            starts: BTreeMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_connected(&self) -> bool {
+        !matches!(self.connection, ConnectionState::Disconnected)
+    }
+
+    /// CSMS calls awaiting a response.
+    #[must_use]
+    pub fn pending_calls(&self) -> usize {
+        match &self.pending {
+            Pending::V16(pending) => pending.len(),
+            Pending::V21(pending) => pending.len(),
+        }
+    }
+
+    /// When this station turns stale if it stays silent (saturating at
+    /// [`DateTime::<Utc>::MAX_UTC`]).
+    fn stale_at(&self, config: RegistryConfig) -> DateTime<Utc> {
+        let interval = self
+            .heartbeat_interval
+            .unwrap_or(config.default_heartbeat_interval);
+        i64::from(interval)
+            .checked_mul(i64::from(config.missed_heartbeats.max(1)))
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|silence| self.last_seen.checked_add_signed(silence))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Something arrived: a stale connection is alive again.
+    fn touch(&mut self, now: DateTime<Utc>) {
+        self.last_seen = self.last_seen.max(now);
+        if self.connection == ConnectionState::Stale {
+            self.connection = ConnectionState::Connected;
+        }
+    }
+
+    fn set_connector(
+        &mut self,
+        events: &mut Vec<RegistryEvent>,
+        connector: ConnectorRef,
+        status: ConnectorStatus,
+    ) {
+        let changed = self
+            .connectors
+            .get(&connector)
+            .is_none_or(|previous| previous.status != status.status);
+        if changed {
+            events.push(RegistryEvent::ConnectorStatusChanged {
+                charger: self.id.clone(),
+                connector,
+                status: status.status.clone(),
+            });
+        }
+        self.connectors.insert(connector, status);
+    }
+
+    fn start_transaction(&mut self, events: &mut Vec<RegistryEvent>, tx: ActiveTransaction) {
+        events.push(RegistryEvent::TransactionStarted {
+            charger: self.id.clone(),
+            transaction_id: tx.transaction_id.clone(),
+        });
+        self.transactions.insert(tx.transaction_id.clone(), tx);
+    }
+
+    fn end_transaction(&mut self, events: &mut Vec<RegistryEvent>, transaction_id: &str) {
+        if self.transactions.remove(transaction_id).is_some() {
+            events.push(RegistryEvent::TransactionEnded {
+                charger: self.id.clone(),
+                transaction_id: transaction_id.to_string(),
+            });
+        }
+    }
+
+    fn boot(&mut self, events: &mut Vec<RegistryEvent>, boot: BootInfo) {
+        self.boot = Some(boot);
+        self.registration = RegistrationState::Booting;
+        events.push(RegistryEvent::Booted {
+            charger: self.id.clone(),
+        });
+    }
+
+    fn registered(
+        &mut self,
+        events: &mut Vec<RegistryEvent>,
+        state: RegistrationState,
+        interval: i32,
+    ) {
+        if state == RegistrationState::Accepted {
+            if let Some(interval) = u32::try_from(interval).ok().filter(|s| *s > 0) {
+                self.heartbeat_interval = Some(interval);
+            }
+        }
+        if self.registration != state {
+            self.registration = state;
+            events.push(RegistryEvent::RegistrationChanged {
+                charger: self.id.clone(),
+                state,
+            });
+        }
+    }
+}
+
+/// Why the registry refused a frame, call or response.
+#[derive(Debug)]
+pub enum RegistryError {
+    /// No station with this identity in the registry.
+    UnknownCharger(String),
+    /// The station's negotiated protocol does not match the message version.
+    ProtocolMismatch(String),
+    /// The station's connection is closed.
+    Disconnected(String),
+    /// Serializing the call or resolving the CALLRESULT failed.
+    Ocpp(Error),
+}
+
+impl From<Error> for RegistryError {
+    fn from(error: Error) -> Self {
+        Self::Ocpp(error)
+    }
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCharger(id) => write!(f, "unknown charger: {id}"),
+            Self::ProtocolMismatch(id) => write!(f, "{id} speaks another protocol version"),
+            Self::Disconnected(id) => write!(f, "{id} is disconnected"),
+            Self::Ocpp(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl core::error::Error for RegistryError {}
+
+/// Per-station state for a CSMS.
+#[derive(Debug, Clone, Default)]
+pub struct ChargerRegistry {
+    config: RegistryConfig,
+    chargers: BTreeMap<String, ChargerRecord>,
+    events: Vec<RegistryEvent>,
+}
+
+impl ChargerRegistry {
+    #[must_use]
+    pub const fn new(config: RegistryConfig) -> Self {
+        Self {
+            config,
+            chargers: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &RegistryConfig {
+        &self.config
+    }
+
+    #[must_use]
+    pub fn charger(&self, id: &str) -> Option<&ChargerRecord> {
+        self.chargers.get(id)
+    }
+
+    pub fn chargers(&self) -> impl Iterator<Item = &ChargerRecord> {
+        self.chargers.values()
+    }
+
+    /// Forget a station entirely.
+    pub fn remove(&mut self, id: &str) -> Option<ChargerRecord> {
+        self.chargers.remove(id)
+    }
+
+    /// Changes since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<RegistryEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    /// WebSocket opened. A known station keeps its record; in-flight CSMS calls of the previous
+    /// connection are dropped.
+    pub fn connect(&mut self, id: &str, protocol: Protocol, now: DateTime<Utc>) {
+        let record = self
+            .chargers
+            .entry(id.to_string())
+            .or_insert_with(|| ChargerRecord::new(id.to_string(), protocol, now));
+        record.protocol = protocol;
+        record.connection = ConnectionState::Connected;
+        record.connected_at = now;
+        record.last_seen = now;
+        record.pending = Pending::new(protocol);
+        record.starts.clear();
+        self.events.push(RegistryEvent::Connected {
+            charger: id.to_string(),
+        });
+    }
+
+    /// WebSocket closed. Returns `false` for an unknown or already disconnected station.
+    pub fn disconnect(&mut self, id: &str) -> bool {
+        let Some(record) = self.chargers.get_mut(id) else {
+            return false;
+        };
+        if record.connection == ConnectionState::Disconnected {
+            return false;
+        }
+        record.connection = ConnectionState::Disconnected;
+        record.pending = Pending::new(record.protocol);
+        record.starts.clear();
+        self.events.push(RegistryEvent::Disconnected {
+            charger: id.to_string(),
+        });
+        true
+    }
+
+    /// When the next connected station turns stale if it stays silent.
+    #[must_use]
+    pub fn next_poll(&self) -> Option<DateTime<Utc>> {
+        self.chargers
+            .values()
+            .filter(|record| record.connection == ConnectionState::Connected)
+            .map(|record| record.stale_at(self.config))
+            .min()
+    }
+
+    /// Mark silent connections stale.
+    pub fn poll(&mut self, now: DateTime<Utc>) {
+        for record in self.chargers.values_mut() {
+            if record.connection == ConnectionState::Connected
+                && now >= record.stale_at(self.config)
+            {
+                record.connection = ConnectionState::Stale;
+                self.events.push(RegistryEvent::Stale {
+                    charger: record.id.clone(),
+                });
+            }
+        }
+    }
+
+    /// Record of `id` if it is connected (or stale) with a 1.6 / 2.x protocol per `ocpp16`.
+    fn record_mut<'a>(
+        chargers: &'a mut BTreeMap<String, ChargerRecord>,
+        id: &str,
+        ocpp16: bool,
+    ) -> Result<&'a mut ChargerRecord, RegistryError> {
+        let record = chargers
+            .get_mut(id)
+            .ok_or_else(|| RegistryError::UnknownCharger(id.to_string()))?;
+        if (record.protocol == Protocol::Ocpp16) != ocpp16 {
+            return Err(RegistryError::ProtocolMismatch(id.to_string()));
+        }
+        if record.connection == ConnectionState::Disconnected {
+            return Err(RegistryError::Disconnected(id.to_string()));
+        }
+        Ok(record)
+    }
+
+    /// Register a CSMS call to a 1.6 station and serialize it.
+    ///
+    /// # Errors
+    /// Unknown or disconnected station, protocol mismatch or serialization errors.
+    pub fn send_v16(&mut self, id: &str, call: v16::call::Call) -> Result<String, RegistryError> {
+        let record = Self::record_mut(&mut self.chargers, id, true)?;
+        match &mut record.pending {
+            Pending::V16(pending) => Ok(pending.send_call(call)?),
+            Pending::V21(_) => Err(RegistryError::ProtocolMismatch(id.to_string())),
+        }
+    }
+
+    /// Register a CSMS call to a 2.0.1 / 2.1 station and serialize it.
+    ///
+    /// # Errors
+    /// Unknown or disconnected station, protocol mismatch or serialization errors.
+    pub fn send_v21(&mut self, id: &str, call: v21::call::Call) -> Result<String, RegistryError> {
+        let record = Self::record_mut(&mut self.chargers, id, false)?;
+        match &mut record.pending {
+            Pending::V21(pending) => Ok(pending.send_call(call)?),
+            Pending::V16(_) => Err(RegistryError::ProtocolMismatch(id.to_string())),
+        }
+    }
+
+    /// Frame received from a 1.6 station. CALLRESULTs are resolved against the calls sent
+    /// through [`Self::send_v16`].
+    ///
+    /// # Errors
+    /// Unknown or disconnected station, protocol mismatch or CALLRESULT resolve errors.
+    pub fn ingest_v16(
+        &mut self,
+        id: &str,
+        message: v16::parse::Message,
+        now: DateTime<Utc>,
+    ) -> Result<v16::parse::TypedMessage, RegistryError> {
+        let record = Self::record_mut(&mut self.chargers, id, true)?;
+        record.touch(now);
+        let Pending::V16(pending) = &mut record.pending else {
+            return Err(RegistryError::ProtocolMismatch(id.to_string()));
+        };
+        match message {
+            v16::parse::Message::Call(call) => {
+                apply_v16_call(record, &mut self.events, &call, now);
+                Ok(v16::parse::TypedMessage::Call(call))
+            }
+            v16::parse::Message::CallResult(raw) => Ok(pending
+                .resolve(raw)
+                .map(v16::parse::TypedMessage::CallResult)?),
+            v16::parse::Message::CallError(error) => {
+                pending.take(&error.unique_id);
+                Ok(v16::parse::TypedMessage::CallError(error))
+            }
+        }
+    }
+
+    /// Frame received from a 2.0.1 / 2.1 station. CALLRESULTs are resolved against the calls
+    /// sent through [`Self::send_v21`].
+    ///
+    /// # Errors
+    /// Unknown or disconnected station, protocol mismatch or CALLRESULT resolve errors.
+    pub fn ingest_v21(
+        &mut self,
+        id: &str,
+        message: v21::parse::Message,
+        now: DateTime<Utc>,
+    ) -> Result<v21::parse::TypedMessage, RegistryError> {
+        let record = Self::record_mut(&mut self.chargers, id, false)?;
+        record.touch(now);
+        let Pending::V21(pending) = &mut record.pending else {
+            return Err(RegistryError::ProtocolMismatch(id.to_string()));
+        };
+        match message {
+            v21::parse::Message::Call(call) => {
+                apply_v21_call(record, &mut self.events, &call, now);
+                Ok(v21::parse::TypedMessage::Call(call))
+            }
+            v21::parse::Message::CallResult(raw) => Ok(pending
+                .resolve(raw)
+                .map(v21::parse::TypedMessage::CallResult)?),
+            v21::parse::Message::CallError(error) => {
+                pending.take(&error.unique_id);
+                Ok(v21::parse::TypedMessage::CallError(error))
+            }
+            v21::parse::Message::CallResultError(error) => {
+                pending.take(&error.unique_id);
+                Ok(v21::parse::TypedMessage::CallResultError(error))
+            }
+            v21::parse::Message::Send(send) => Ok(v21::parse::TypedMessage::Send(send)),
+        }
+    }
+
+    /// Response the CSMS sent to a 1.6 station call: `BootNotification.conf` sets the
+    /// registration, `StartTransaction.conf` opens the transaction under its id.
+    ///
+    /// # Errors
+    /// Unknown or disconnected station, or protocol mismatch.
+    pub fn responded_v16(
+        &mut self,
+        id: &str,
+        result: &v16::typed_call_result::TypedCallResult,
+        now: DateTime<Utc>,
+    ) -> Result<(), RegistryError> {
+        use v16::typed_call_result::TypedCallResult;
+
+        let record = Self::record_mut(&mut self.chargers, id, true)?;
+        let mut events = Vec::new();
+        match result {
+            TypedCallResult::BootNotification(conf) => record.registered(
+                &mut events,
+                v16::registration::registration_state(conf.payload.status),
+                conf.payload.interval,
+            ),
+            TypedCallResult::StartTransaction(conf) => {
+                if let Some(start) = record.starts.remove(&conf.unique_id) {
+                    let tx = ActiveTransaction {
+                        transaction_id: conf.payload.transaction_id.to_string(),
+                        connector: Some(v16_connector(start.connector_id)),
+                        id_token: Some(start.id_tag),
+                        started_at: start.timestamp.inner(),
+                        charging_state: None,
+                        updated_at: now,
+                    };
+                    record.start_transaction(&mut events, tx);
+                }
+            }
+            _ => {}
+        }
+        self.events.append(&mut events);
+        Ok(())
+    }
+
+    /// Response the CSMS sent to a 2.0.1 / 2.1 station call: `BootNotificationResponse` sets
+    /// the registration.
+    ///
+    /// # Errors
+    /// Unknown or disconnected station, or protocol mismatch.
+    pub fn responded_v21(
+        &mut self,
+        id: &str,
+        result: &v21::typed_call_result::TypedCallResult,
+    ) -> Result<(), RegistryError> {
+        let record = Self::record_mut(&mut self.chargers, id, false)?;
+        let mut events = Vec::new();
+        if let v21::typed_call_result::TypedCallResult::BootNotification(response) = result {
+            record.registered(
+                &mut events,
+                v21::registration::registration_state(&response.payload.status),
+                response.payload.interval,
+            );
+        }
+        self.events.append(&mut events);
+        Ok(())
+    }
+}
+
+fn v16_connector(connector_id: u32) -> ConnectorRef {
+    ConnectorRef {
+        evse_id: None,
+        connector_id: i32::try_from(connector_id).unwrap_or(i32::MAX),
+    }
+}
+
+fn apply_v16_call(
+    record: &mut ChargerRecord,
+    events: &mut Vec<RegistryEvent>,
+    call: &v16::call::Call,
+    now: DateTime<Utc>,
+) {
+    use v16::call::Action;
+
+    match &call.payload {
+        Action::BootNotification(boot) => record.boot(
+            events,
+            BootInfo {
+                vendor: boot.charge_point_vendor.clone(),
+                model: boot.charge_point_model.clone(),
+                serial_number: boot
+                    .charge_point_serial_number
+                    .clone()
+                    .or_else(|| boot.charge_box_serial_number.clone()),
+                firmware_version: boot.firmware_version.clone(),
+                reason: None,
+                received_at: now,
+            },
+        ),
+        Action::Heartbeat(_) => record.last_heartbeat = Some(now),
+        Action::StatusNotification(notification) => record.set_connector(
+            events,
+            v16_connector(notification.connector_id),
+            ConnectorStatus {
+                status: notification.status.as_str().to_string(),
+                error_code: Some(notification.error_code.as_str().to_string()),
+                timestamp: notification.timestamp.map(|t| t.inner()),
+                updated_at: now,
+            },
+        ),
+        Action::StartTransaction(start) => {
+            record.starts.insert(call.unique_id.clone(), start.clone());
+        }
+        Action::StopTransaction(stop) => {
+            record.end_transaction(events, &stop.transaction_id.to_string());
+        }
+        _ => {}
+    }
+}
+
+fn apply_v21_call(
+    record: &mut ChargerRecord,
+    events: &mut Vec<RegistryEvent>,
+    call: &v21::call::Call,
+    now: DateTime<Utc>,
+) {
+    use v21::call::Action;
+    use v21::messages::transaction_event::TransactionEventEnumType;
+
+    match &call.payload {
+        Action::BootNotification(boot) => record.boot(
+            events,
+            BootInfo {
+                vendor: boot.charging_station.vendor_name.clone(),
+                model: boot.charging_station.model.clone(),
+                serial_number: boot.charging_station.serial_number.clone(),
+                firmware_version: boot.charging_station.firmware_version.clone(),
+                reason: Some(boot.reason.as_str().to_string()),
+                received_at: now,
+            },
+        ),
+        Action::Heartbeat(_) => record.last_heartbeat = Some(now),
+        Action::StatusNotification(notification) => record.set_connector(
+            events,
+            ConnectorRef {
+                evse_id: Some(notification.evse_id),
+                connector_id: notification.connector_id,
+            },
+            ConnectorStatus {
+                status: notification.connector_status.as_str().to_string(),
+                error_code: None,
+                timestamp: Some(notification.timestamp.inner()),
+                updated_at: now,
+            },
+        ),
+        Action::TransactionEvent(event) => {
+            let info = &event.transaction_info;
+            if event.event_type == TransactionEventEnumType::Ended {
+                record.end_transaction(events, &info.transaction_id);
+                return;
+            }
+            let connector = event.evse.as_ref().map(|evse| ConnectorRef {
+                evse_id: Some(evse.id),
+                connector_id: evse.connector_id.unwrap_or(0),
+            });
+            let id_token = event.id_token.as_ref().map(|token| token.id_token.clone());
+            let charging_state = info
+                .charging_state
+                .as_ref()
+                .map(|state| state.as_str().to_string());
+            if let Some(tx) = record.transactions.get_mut(&info.transaction_id) {
+                tx.connector = connector.or(tx.connector);
+                tx.id_token = id_token.or_else(|| tx.id_token.take());
+                tx.charging_state = charging_state.or_else(|| tx.charging_state.take());
+                tx.updated_at = now;
+            } else {
+                let tx = ActiveTransaction {
+                    transaction_id: info.transaction_id.clone(),
+                    connector,
+                    id_token,
+                    started_at: event.timestamp.inner(),
+                    charging_state,
+                    updated_at: now,
+                };
+                record.start_transaction(events, tx);
+            }
+        }
+        _ => {}
+    }
+}
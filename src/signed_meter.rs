@@ -0,0 +1,442 @@
+//! Signed meter values (German Eichrecht): OCMF decoding and signature verification.
+//!
+//! Meters sign their readings; the station forwards the signed blob untouched, as a 1.6
+//! `SampledValue` with `format = SignedData` ([`crate::v16::sampled_value`]) or a 2.1
+//! `SignedMeterValueType` ([`crate::v21::sampled_value`]). [`Ocmf::decode`] accepts an
+//! OCMF string (`OCMF|{payload}|{signature}`) as sent or base64 encoded, and exposes the
+//! payload — identification, pagination, readings — and the signature.
+//!
+//! Verification goes through a [`SignatureVerifier`], so the crate stays free of
+//! cryptography by default; the `signed_meter_p256` feature provides [`P256Verifier`] for
+//! `ECDSA-secp256r1-SHA256`, the algorithm most meters use. The signed message is the
+//! payload exactly as transmitted (between the separators), never a re-serialization.
+//!
+//! Only OCMF is decoded. EDL (the SML-based format of EDL40 meters, 2.1
+//! `encodingMethod = "EDL"`) is out of scope and reported as
+//! [`SignedMeterError::UnsupportedEncoding`] so callers can hand it to a dedicated decoder.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, Utc};
+use core::fmt;
+use serde::Deserialize;
+
+use crate::quantity::{Quantity, Unit};
+
+/// `SA` when the signature omits it.
+pub const DEFAULT_SIGNATURE_ALGORITHM: &str = "ECDSA-secp256r1-SHA256";
+
+/// Why signed meter data could not be decoded or verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignedMeterError {
+    /// Not `OCMF|…|…`, neither as sent nor base64 decoded.
+    NotOcmf,
+    /// A 2.1 `encodingMethod` other than OCMF (e.g. EDL), which this module does not decode.
+    UnsupportedEncoding(String),
+    /// The payload or signature section is not the JSON OCMF specifies.
+    Json(String),
+    /// `SD`, or a public key, is not valid hex / base64.
+    Encoding,
+    /// The verifier does not implement this `SA`.
+    UnsupportedAlgorithm(String),
+    /// The public key is not a key for the algorithm.
+    InvalidKey,
+    /// `SD` is not a well-formed signature for the algorithm.
+    MalformedSignature,
+    /// The signature does not match the payload and key.
+    BadSignature,
+    /// No public key was given (2.1 `publicKey` absent).
+    MissingPublicKey,
+}
+
+impl fmt::Display for SignedMeterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotOcmf => write!(f, "not OCMF"),
+            Self::UnsupportedEncoding(encoding) => write!(f, "unsupported encoding: {encoding}"),
+            Self::Json(error) => write!(f, "invalid OCMF JSON: {error}"),
+            Self::Encoding => write!(f, "invalid hex / base64"),
+            Self::UnsupportedAlgorithm(algorithm) => {
+                write!(f, "unsupported signature algorithm: {algorithm}")
+            }
+            Self::InvalidKey => write!(f, "invalid public key"),
+            Self::MalformedSignature => write!(f, "malformed signature"),
+            Self::BadSignature => write!(f, "signature mismatch"),
+            Self::MissingPublicKey => write!(f, "missing public key"),
+        }
+    }
+}
+
+/// Checks a signature over a message; see [`P256Verifier`].
+pub trait SignatureVerifier {
+    /// Verify `signature` (as decoded from `SD`, DER for ECDSA) over `message` with
+    /// `public_key` (as decoded, DER `SubjectPublicKeyInfo` or a SEC1 point).
+    ///
+    /// # Errors
+    /// [`SignedMeterError::UnsupportedAlgorithm`] for an `algorithm` this verifier does not
+    /// implement, [`SignedMeterError::InvalidKey`] / [`SignedMeterError::MalformedSignature`]
+    /// for unusable input and [`SignedMeterError::BadSignature`] on mismatch.
+    fn verify(
+        &self,
+        algorithm: &str,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), SignedMeterError>;
+}
+
+/// OCMF pagination (`PG`): `T` transaction readings, `F` fiscal readings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pagination {
+    Transaction(u64),
+    Fiscal(u64),
+}
+
+/// One OCMF reading (`RD` entry).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OcmfReading {
+    /// Time with synchronization status, e.g. `2018-07-24T13:22:04,000+0200 S`.
+    #[serde(rename = "TM", default)]
+    pub time: Option<String>,
+    /// Transaction step: `B` begin, `C` charging, `X` exception, `E`/`L`/`R`/`A`/`P` end
+    /// variants, `S` suspended, `T` tariff change.
+    #[serde(rename = "TX", default)]
+    pub transaction: Option<String>,
+    #[serde(rename = "RV", default)]
+    pub value: Option<f64>,
+    /// OBIS code of the register, e.g. `01-00:B2.08.00*FF`.
+    #[serde(rename = "RI", default)]
+    pub register_id: Option<String>,
+    #[serde(rename = "RU", default)]
+    pub unit: Option<String>,
+    /// `AC` or `DC`.
+    #[serde(rename = "RT", default)]
+    pub current_type: Option<String>,
+    /// Cumulated loss (DC), in `RU`.
+    #[serde(rename = "CL", default)]
+    pub cumulated_loss: Option<f64>,
+    #[serde(rename = "EF", default)]
+    pub error_flags: Option<String>,
+    /// Meter status, `G` (good) when nothing is wrong.
+    #[serde(rename = "ST", default)]
+    pub status: Option<String>,
+}
+
+impl OcmfReading {
+    /// `TM` as UTC, ignoring the synchronization status suffix.
+    #[must_use]
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        let time = self.time.as_deref()?.split(' ').next()?.replace(',', ".");
+        DateTime::parse_from_str(&time, "%Y-%m-%dT%H:%M:%S%.f%z")
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    /// `RV` in `RU`, normalized; `None` when either is missing or the unit is unknown.
+    #[must_use]
+    pub fn quantity(&self) -> Option<Quantity> {
+        let unit = Unit::parse(self.unit.as_deref()?)?;
+        Some(Quantity::new(self.value?, unit, 0))
+    }
+
+    /// Whether the meter reported a good status (`ST = G`) and no error flags.
+    #[must_use]
+    pub fn is_good(&self) -> bool {
+        self.status.as_deref().is_none_or(|s| s == "G")
+            && self.error_flags.as_deref().is_none_or(str::is_empty)
+    }
+}
+
+/// OCMF payload section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OcmfPayload {
+    #[serde(rename = "FV", default)]
+    pub format_version: Option<String>,
+    #[serde(rename = "GI", default)]
+    pub gateway_identification: Option<String>,
+    #[serde(rename = "GS", default)]
+    pub gateway_serial: Option<String>,
+    #[serde(rename = "GV", default)]
+    pub gateway_version: Option<String>,
+    /// Raw `PG`; see [`Self::pagination`].
+    #[serde(rename = "PG", default)]
+    pub pagination: Option<String>,
+    #[serde(rename = "MV", default)]
+    pub meter_vendor: Option<String>,
+    #[serde(rename = "MM", default)]
+    pub meter_model: Option<String>,
+    #[serde(rename = "MS", default)]
+    pub meter_serial: Option<String>,
+    #[serde(rename = "MF", default)]
+    pub meter_firmware: Option<String>,
+    /// Whether the user was identified (`IS`).
+    #[serde(rename = "IS", default)]
+    pub identified: Option<bool>,
+    #[serde(rename = "IL", default)]
+    pub identification_level: Option<String>,
+    #[serde(rename = "IF", default)]
+    pub identification_flags: Vec<String>,
+    /// e.g. `ISO14443`, `ISO15118`, `PLMN`.
+    #[serde(rename = "IT", default)]
+    pub identification_type: Option<String>,
+    #[serde(rename = "ID", default)]
+    pub identification_data: Option<String>,
+    #[serde(rename = "TT", default)]
+    pub tariff_text: Option<String>,
+    #[serde(rename = "CT", default)]
+    pub charge_point_identification_type: Option<String>,
+    #[serde(rename = "CI", default)]
+    pub charge_point_identification: Option<String>,
+    #[serde(rename = "RD", default)]
+    pub readings: Vec<OcmfReading>,
+}
+
+impl OcmfPayload {
+    /// `PG` parsed; `None` if absent or malformed.
+    #[must_use]
+    pub fn pagination(&self) -> Option<Pagination> {
+        let pagination = self.pagination.as_deref()?;
+        let (kind, number) = pagination.split_at_checked(1)?;
+        let number = number.parse().ok()?;
+        match kind {
+            "T" => Some(Pagination::Transaction(number)),
+            "F" => Some(Pagination::Fiscal(number)),
+            _ => None,
+        }
+    }
+}
+
+/// OCMF signature section.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OcmfSignature {
+    /// `SA`; see [`Self::algorithm`].
+    #[serde(rename = "SA", default)]
+    pub algorithm: Option<String>,
+    /// `SE`: `hex` (default) or `base64`.
+    #[serde(rename = "SE", default)]
+    pub encoding: Option<String>,
+    /// `SM`: `application/x-der` (default).
+    #[serde(rename = "SM", default)]
+    pub mime_type: Option<String>,
+    #[serde(rename = "SD")]
+    pub data: String,
+}
+
+impl OcmfSignature {
+    /// `SA`, or [`DEFAULT_SIGNATURE_ALGORITHM`].
+    #[must_use]
+    pub fn algorithm(&self) -> &str {
+        self.algorithm
+            .as_deref()
+            .unwrap_or(DEFAULT_SIGNATURE_ALGORITHM)
+    }
+
+    /// `SD` decoded according to `SE`.
+    ///
+    /// # Errors
+    /// [`SignedMeterError::Encoding`] if `SD` is not valid in its encoding.
+    pub fn bytes(&self) -> Result<Vec<u8>, SignedMeterError> {
+        let decoded = match self.encoding.as_deref() {
+            Some(encoding) if encoding.eq_ignore_ascii_case("base64") => decode_base64(&self.data),
+            _ => decode_hex(&self.data),
+        };
+        decoded.ok_or(SignedMeterError::Encoding)
+    }
+}
+
+/// A decoded OCMF document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ocmf {
+    pub payload: OcmfPayload,
+    pub signature: OcmfSignature,
+    raw_payload: String,
+}
+
+impl Ocmf {
+    /// Decode `OCMF|{payload}|{signature}`, as sent or base64 encoded.
+    ///
+    /// # Errors
+    /// [`SignedMeterError::NotOcmf`] if `data` is neither, [`SignedMeterError::Json`] if
+    /// a section does not parse.
+    pub fn decode(data: &str) -> Result<Self, SignedMeterError> {
+        let data = data.trim();
+        if data.starts_with("OCMF|") {
+            return Self::parse(data);
+        }
+        let decoded = decode_base64(data)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or(SignedMeterError::NotOcmf)?;
+        Self::parse(decoded.trim())
+    }
+
+    fn parse(data: &str) -> Result<Self, SignedMeterError> {
+        let rest = data
+            .strip_prefix("OCMF|")
+            .ok_or(SignedMeterError::NotOcmf)?;
+        // The signature section holds no `|`; the payload may (e.g. in `TT`).
+        let (payload, signature) = rest.rsplit_once('|').ok_or(SignedMeterError::NotOcmf)?;
+        let json = |e: serde_json::Error| SignedMeterError::Json(alloc::format!("{e}"));
+        Ok(Self {
+            payload: serde_json::from_str(payload).map_err(json)?,
+            signature: serde_json::from_str(signature).map_err(json)?,
+            raw_payload: payload.into(),
+        })
+    }
+
+    /// The signed message: the payload section as transmitted.
+    #[must_use]
+    pub fn signed_message(&self) -> &[u8] {
+        self.raw_payload.as_bytes()
+    }
+
+    /// Verify the signature with `public_key` (as decoded, see [`decode_public_key`]).
+    ///
+    /// # Errors
+    /// See [`SignatureVerifier::verify`]; also [`SignedMeterError::Encoding`] for an
+    /// undecodable `SD`.
+    pub fn verify(
+        &self,
+        public_key: &[u8],
+        verifier: &impl SignatureVerifier,
+    ) -> Result<(), SignedMeterError> {
+        verifier.verify(
+            self.signature.algorithm(),
+            public_key,
+            self.signed_message(),
+            &self.signature.bytes()?,
+        )
+    }
+}
+
+/// How a public key is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEncoding {
+    /// As OCMF documents and meter displays show it.
+    Hex,
+    /// As the 2.1 `publicKey` field specifies.
+    Base64,
+}
+
+/// Decode a public key in a known `encoding`.
+///
+/// # Errors
+/// [`SignedMeterError::Encoding`] if `key` is not valid in `encoding`.
+pub fn decode_public_key_as(key: &str, encoding: KeyEncoding) -> Result<Vec<u8>, SignedMeterError> {
+    let key = key.trim();
+    match encoding {
+        KeyEncoding::Hex => decode_hex(key),
+        KeyEncoding::Base64 => decode_base64(key),
+    }
+    .ok_or(SignedMeterError::Encoding)
+}
+
+/// Decode a public key given as hex or base64, when the encoding is not known.
+///
+/// Hex is taken only when it decodes to something shaped like a key (a DER `SEQUENCE` or a
+/// SEC1 point), since short base64 strings may consist of hex digits only; use
+/// [`decode_public_key_as`] when the encoding is known.
+///
+/// # Errors
+/// [`SignedMeterError::Encoding`] if `key` is neither.
+pub fn decode_public_key(key: &str) -> Result<Vec<u8>, SignedMeterError> {
+    let key = key.trim();
+    let hex = decode_hex(key);
+    if hex
+        .as_ref()
+        .is_some_and(|bytes| matches!(bytes.first(), Some(0x30 | 0x02..=0x04)))
+    {
+        return hex.ok_or(SignedMeterError::Encoding);
+    }
+    decode_base64(key).or(hex).ok_or(SignedMeterError::Encoding)
+}
+
+/// Decode hex (either case); `None` if malformed.
+pub(crate) fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    const fn nibble(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+    let text = text.trim().as_bytes();
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    text.chunks_exact(2)
+        .map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
+
+/// Decode standard or URL-safe base64, padded or not, ignoring whitespace; `None` if
+/// malformed.
+pub(crate) fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    fn sextet(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some(u32::from(c - b'A')),
+            b'a'..=b'z' => Some(u32::from(c - b'a') + 26),
+            b'0'..=b'9' => Some(u32::from(c - b'0') + 52),
+            b'+' | b'-' => Some(62),
+            b'/' | b'_' => Some(63),
+            _ => None,
+        }
+    }
+    let symbols: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    let symbols = symbols
+        .strip_suffix(b"==")
+        .or_else(|| symbols.strip_suffix(b"="))
+        .unwrap_or(&symbols);
+    if symbols.is_empty() || symbols.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(symbols.len());
+    for chunk in symbols.chunks(4) {
+        let mut word = 0_u32;
+        for (i, symbol) in chunk.iter().enumerate() {
+            word |= sextet(*symbol)? << (18 - 6 * i);
+        }
+        let word = word.to_be_bytes();
+        bytes.extend_from_slice(&word[1..chunk.len()]);
+    }
+    Some(bytes)
+}
+
+/// `ECDSA-secp256r1-SHA256` verifier (feature `signed_meter_p256`).
+///
+/// Accepts the public key as DER `SubjectPublicKeyInfo` (as meters display it) or as a
+/// SEC1 point, and the signature as DER (`SM = application/x-der`) or raw `r ‖ s`.
+#[cfg(feature = "signed_meter_p256")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct P256Verifier;
+
+#[cfg(feature = "signed_meter_p256")]
+impl SignatureVerifier for P256Verifier {
+    fn verify(
+        &self,
+        algorithm: &str,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), SignedMeterError> {
+        use p256::ecdsa::signature::Verifier;
+        use p256::ecdsa::{Signature, VerifyingKey};
+
+        if algorithm != DEFAULT_SIGNATURE_ALGORITHM {
+            return Err(SignedMeterError::UnsupportedAlgorithm(algorithm.into()));
+        }
+        // A P-256 SubjectPublicKeyInfo ends in the BIT STRING `03 42 00` + the SEC1 point.
+        let point = match public_key.len().checked_sub(68) {
+            Some(at) if public_key[0] == 0x30 && public_key[at..at + 3] == [0x03, 0x42, 0x00] => {
+                &public_key[at + 3..]
+            }
+            _ => public_key,
+        };
+        let key = VerifyingKey::from_sec1_bytes(point).map_err(|_| SignedMeterError::InvalidKey)?;
+        let signature = Signature::from_der(signature)
+            .or_else(|_| Signature::from_slice(signature))
+            .map_err(|_| SignedMeterError::MalformedSignature)?;
+        key.verify(message, &signature)
+            .map_err(|_| SignedMeterError::BadSignature)
+    }
+}
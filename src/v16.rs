@@ -8,13 +8,22 @@
 pub mod call;
 pub mod call_error;
 pub mod call_result;
+pub mod charge_point;
+pub mod charging_profiles;
+pub mod composite;
 pub mod data_types;
 pub mod enums;
+pub mod load_balancing;
 pub mod log_helper;
+pub mod metering;
+pub mod offline_queue;
 pub mod parse;
 pub mod pending;
+pub mod registration;
 pub mod response_trait;
 pub mod rpc_error_code;
+pub mod sampled_value;
+pub mod schedule;
 pub mod typed_call_result;
 pub mod utils;
 
@@ -0,0 +1,741 @@
+//! Charge-point-side 1.6 core: connector status and transactions.
+//!
+//! [`ChargePoint`] is driven by hardware events ([`ChargePoint::plug_in`],
+//! [`ChargePoint::present_id_tag`], [`ChargePoint::fault`], …), by Central System requests
+//! (`RemoteStartTransaction`, `RemoteStopTransaction`, `ChangeAvailability`) and by the
+//! confirmations of its own requests. It queues the requests to send —
+//! `StatusNotification`, `StartTransaction`, `MeterValues`, `StopTransaction` — for the
+//! caller to wrap in a [`Call`](super::call::Call) ([`ChargePoint::take_requests`]).
+//!
+//! The status of a connector is derived from its state (fault, availability, cable,
+//! authorized tag, transaction, energy flow); a change the 1.6 status diagram does not allow
+//! ([`transition_allowed`]) is reported via `Available`. A new status is only reported once it has been
+//! stable for `MinimumStatusDuration`; call [`ChargePoint::poll`] at
+//! [`ChargePoint::next_poll`] for those, and for `ConnectionTimeOut`.
+//!
+//! Authorization (`Authorize.req`, local list, cache) is up to the caller:
+//! [`ChargePoint::present_id_tag`] takes a tag that was accepted.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::call::{
+    Action, ChangeAvailability, MeterValues, RemoteStartTransaction, RemoteStopTransaction,
+    StartTransaction, StatusNotification, StopTransaction,
+};
+use super::call_result;
+use super::charging_profiles::ConnectorState;
+use super::data_types::{DateTimeWrapper, MeterValue};
+use super::enums::{
+    AuthorizationStatus, AvailabilityStatus, AvailabilityType, ChargePointErrorCode,
+    ChargePointStatus, Reason, RemoteStartStopStatus,
+};
+
+/// Whether the 1.6 status diagram allows a connector to go from `from` to `to`. Connector 0
+/// only knows `Available`, `Unavailable` and `Faulted`.
+#[must_use]
+pub const fn transition_allowed(from: &ChargePointStatus, to: &ChargePointStatus) -> bool {
+    use ChargePointStatus::{
+        Available, Charging, Faulted, Finishing, Preparing, Reserved, SuspendedEV, SuspendedEVSE,
+        Unavailable,
+    };
+    match from {
+        Available => matches!(
+            to,
+            Preparing | Charging | SuspendedEV | SuspendedEVSE | Reserved | Unavailable | Faulted
+        ),
+        Preparing => matches!(
+            to,
+            Available | Charging | SuspendedEV | SuspendedEVSE | Finishing | Faulted
+        ),
+        Charging => matches!(
+            to,
+            Available | SuspendedEV | SuspendedEVSE | Finishing | Unavailable | Faulted
+        ),
+        SuspendedEV => matches!(
+            to,
+            Available | Charging | SuspendedEVSE | Finishing | Unavailable | Faulted
+        ),
+        SuspendedEVSE => matches!(
+            to,
+            Available | Charging | SuspendedEV | Finishing | Unavailable | Faulted
+        ),
+        Finishing => matches!(to, Available | Preparing | Unavailable | Faulted),
+        Reserved => matches!(to, Available | Preparing | Unavailable | Faulted),
+        Unavailable => matches!(
+            to,
+            Available | Preparing | Charging | SuspendedEV | SuspendedEVSE | Faulted
+        ),
+        Faulted => matches!(
+            to,
+            Available
+                | Preparing
+                | Charging
+                | SuspendedEV
+                | SuspendedEVSE
+                | Finishing
+                | Reserved
+                | Unavailable
+        ),
+        ChargePointStatus::Unknown(_) => false,
+    }
+}
+
+/// Configuration keys the core honours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChargePointConfig {
+    /// `MinimumStatusDuration`, seconds.
+    pub minimum_status_duration: u32,
+    /// `ConnectionTimeOut`, seconds: how long an authorized tag waits for the cable.
+    pub connection_timeout: u32,
+    /// `StopTransactionOnEVSideDisconnect`.
+    pub stop_transaction_on_ev_side_disconnect: bool,
+    /// `StopTransactionOnInvalidId`.
+    pub stop_transaction_on_invalid_id: bool,
+}
+
+impl Default for ChargePointConfig {
+    fn default() -> Self {
+        Self {
+            minimum_status_duration: 0,
+            connection_timeout: 60,
+            stop_transaction_on_ev_side_disconnect: true,
+            stop_transaction_on_invalid_id: true,
+        }
+    }
+}
+
+/// What the EV and the EVSE do during a transaction, as reported by the hardware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnergyFlow {
+    /// Contactor closed, the EV takes energy.
+    #[default]
+    Charging,
+    /// The EVSE offers energy, the EV does not take it.
+    SuspendedEv,
+    /// The EVSE does not offer energy (smart charging, local constraints, …).
+    SuspendedEvse,
+}
+
+/// A transaction on a connector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    /// From `StartTransaction.conf`; `None` until it arrives.
+    pub transaction_id: Option<i32>,
+    pub id_tag: String,
+    pub meter_start: u64,
+    pub started_at: DateTime<Utc>,
+    /// `StartTransaction.conf` did not accept the tag and `StopTransactionOnInvalidId` is
+    /// false: the transaction goes on without energy.
+    pub deauthorized: bool,
+    /// Stopped before `StartTransaction.conf`: the `StopTransaction.req` waits for the
+    /// `transactionId`.
+    stop: Option<StopTransaction>,
+}
+
+impl Transaction {
+    /// Whether the transaction is still running (not stopped awaiting its id).
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.stop.is_none()
+    }
+}
+
+/// State of one connector (or, for connector 0, of the charge point).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connector {
+    availability: AvailabilityType,
+    /// Availability to apply once the connector is no longer in use.
+    scheduled: Option<AvailabilityType>,
+    fault: Option<(ChargePointErrorCode, Option<String>)>,
+    plugged: bool,
+    energy: EnergyFlow,
+    /// Authorized tag waiting for the cable (or for the start), with when it was presented.
+    id_tag: Option<(String, DateTime<Utc>)>,
+    transaction: Option<Transaction>,
+    finishing: bool,
+    meter: u64,
+    status: ChargePointStatus,
+    since: DateTime<Utc>,
+    reported: Option<ChargePointStatus>,
+}
+
+impl Connector {
+    const fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            availability: AvailabilityType::Operative,
+            scheduled: None,
+            fault: None,
+            plugged: false,
+            energy: EnergyFlow::Charging,
+            id_tag: None,
+            transaction: None,
+            finishing: false,
+            meter: 0,
+            status: ChargePointStatus::Available,
+            since: now,
+            reported: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn status(&self) -> &ChargePointStatus {
+        &self.status
+    }
+
+    /// Last status sent in a `StatusNotification`.
+    #[must_use]
+    pub const fn reported_status(&self) -> Option<&ChargePointStatus> {
+        self.reported.as_ref()
+    }
+
+    #[must_use]
+    pub const fn availability(&self) -> &AvailabilityType {
+        &self.availability
+    }
+
+    /// Availability change waiting for the end of the current use.
+    #[must_use]
+    pub const fn scheduled_availability(&self) -> Option<&AvailabilityType> {
+        self.scheduled.as_ref()
+    }
+
+    #[must_use]
+    pub const fn is_plugged(&self) -> bool {
+        self.plugged
+    }
+
+    /// Energy register, Wh.
+    #[must_use]
+    pub const fn meter(&self) -> u64 {
+        self.meter
+    }
+
+    /// Running transaction.
+    #[must_use]
+    pub fn transaction(&self) -> Option<&Transaction> {
+        self.transaction.as_ref().filter(|tx| tx.is_active())
+    }
+
+    const fn is_operative(&self) -> bool {
+        matches!(self.availability, AvailabilityType::Operative)
+    }
+
+    /// In use for `ChangeAvailability`: a transaction, or a user getting ready for one.
+    fn in_use(&self) -> bool {
+        self.transaction().is_some() || self.id_tag.is_some() || self.plugged && !self.finishing
+    }
+
+    fn derived_status(&self) -> ChargePointStatus {
+        if self.fault.is_some() {
+            return ChargePointStatus::Faulted;
+        }
+        if let Some(tx) = self.transaction() {
+            return if tx.deauthorized {
+                ChargePointStatus::SuspendedEVSE
+            } else {
+                match self.energy {
+                    EnergyFlow::Charging => ChargePointStatus::Charging,
+                    EnergyFlow::SuspendedEv => ChargePointStatus::SuspendedEV,
+                    EnergyFlow::SuspendedEvse => ChargePointStatus::SuspendedEVSE,
+                }
+            };
+        }
+        if !self.is_operative() {
+            ChargePointStatus::Unavailable
+        } else if self.finishing {
+            ChargePointStatus::Finishing
+        } else if self.plugged || self.id_tag.is_some() {
+            ChargePointStatus::Preparing
+        } else {
+            ChargePointStatus::Available
+        }
+    }
+}
+
+/// The charge point: connector 0 and connectors `1..=n`.
+#[derive(Debug, Clone)]
+pub struct ChargePoint {
+    config: ChargePointConfig,
+    connectors: Vec<Connector>,
+    outbox: Vec<Action>,
+}
+
+impl ChargePoint {
+    /// A charge point with `connectors` connectors, all `Available`. Their first
+    /// `StatusNotification` is queued right away.
+    #[must_use]
+    pub fn new(connectors: u32, config: ChargePointConfig, now: DateTime<Utc>) -> Self {
+        let mut charge_point = Self {
+            config,
+            connectors: core::iter::repeat_with(|| Connector::new(now))
+                .take(usize::try_from(connectors).map_or(usize::MAX, |n| n.saturating_add(1)))
+                .collect(),
+            outbox: Vec::new(),
+        };
+        charge_point.poll(now);
+        charge_point
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &ChargePointConfig {
+        &self.config
+    }
+
+    pub const fn config_mut(&mut self) -> &mut ChargePointConfig {
+        &mut self.config
+    }
+
+    /// Number of connectors, connector 0 excluded.
+    #[must_use]
+    pub fn connector_count(&self) -> u32 {
+        u32::try_from(self.connectors.len() - 1).unwrap_or(u32::MAX)
+    }
+
+    #[must_use]
+    pub fn connector(&self, connector_id: u32) -> Option<&Connector> {
+        self.connectors.get(usize::try_from(connector_id).ok()?)
+    }
+
+    fn connector_mut(&mut self, connector_id: u32) -> Option<&mut Connector> {
+        self.connectors.get_mut(usize::try_from(connector_id).ok()?)
+    }
+
+    /// Connector of a running transaction.
+    #[must_use]
+    pub fn transaction_connector(&self, transaction_id: i32) -> Option<u32> {
+        (1..=self.connector_count()).find(|id| {
+            self.connector(*id)
+                .and_then(Connector::transaction)
+                .is_some_and(|tx| tx.transaction_id == Some(transaction_id))
+        })
+    }
+
+    /// Requests queued since the last call, in order.
+    pub fn take_requests(&mut self) -> Vec<Action> {
+        core::mem::take(&mut self.outbox)
+    }
+
+    /// When [`Self::poll`] has something to do: a status becoming reportable, or an
+    /// authorized tag timing out.
+    #[must_use]
+    pub fn next_poll(&self) -> Option<DateTime<Utc>> {
+        let min_duration = TimeDelta::seconds(i64::from(self.config.minimum_status_duration));
+        let timeout = TimeDelta::seconds(i64::from(self.config.connection_timeout));
+        self.connectors
+            .iter()
+            .flat_map(|c| {
+                let status = (c.reported.as_ref() != Some(&c.status)).then(|| {
+                    if c.reported.is_none() {
+                        c.since
+                    } else {
+                        c.since + min_duration
+                    }
+                });
+                let tag = c
+                    .id_tag
+                    .as_ref()
+                    .filter(|_| !c.plugged)
+                    .map(|(_, at)| *at + timeout);
+                [status, tag]
+            })
+            .flatten()
+            .min()
+    }
+
+    /// Expire authorized tags past `ConnectionTimeOut` and report the statuses stable for
+    /// `MinimumStatusDuration`.
+    pub fn poll(&mut self, now: DateTime<Utc>) {
+        let timeout = TimeDelta::seconds(i64::from(self.config.connection_timeout));
+        for id in 0..=self.connector_count() {
+            if let Some(connector) = self.connector_mut(id)
+                && !connector.plugged
+                && connector
+                    .id_tag
+                    .as_ref()
+                    .is_some_and(|(_, at)| now - *at >= timeout)
+            {
+                connector.id_tag = None;
+            }
+            self.refresh(id, now);
+        }
+    }
+
+    /// Recompute the status of `connector_id`, apply a scheduled availability change once
+    /// the connector is free, and queue its `StatusNotification` when due.
+    fn refresh(&mut self, connector_id: u32, now: DateTime<Utc>) {
+        let min_duration = TimeDelta::seconds(i64::from(self.config.minimum_status_duration));
+        let Some(connector) = self.connector_mut(connector_id) else {
+            return;
+        };
+        if !connector.in_use()
+            && let Some(availability) = connector.scheduled.take()
+        {
+            connector.availability = availability;
+        }
+        let status = connector.derived_status();
+        if status != connector.status {
+            connector.status = status;
+            connector.since = now;
+        }
+        let due = match &connector.reported {
+            None => true,
+            Some(reported) => {
+                *reported != connector.status && now - connector.since >= min_duration
+            }
+        };
+        if !due {
+            return;
+        }
+        // A change the status diagram does not allow (say, `Preparing` to `Unavailable` when
+        // a scheduled availability change applies) goes through `Available`.
+        let detour = connector
+            .reported
+            .as_ref()
+            .is_some_and(|reported| !transition_allowed(reported, &connector.status))
+            .then_some(ChargePointStatus::Available);
+        connector.reported = Some(connector.status.clone());
+        let (error_code, info) = connector
+            .fault
+            .clone()
+            .unwrap_or((ChargePointErrorCode::NoError, None));
+        let since = connector.since;
+        for sent in detour.into_iter().chain([connector.status.clone()]) {
+            let notification = StatusNotification {
+                connector_id,
+                error_code: error_code.clone(),
+                status: sent,
+                timestamp: Some(DateTimeWrapper::new(since)),
+                info: info.clone(),
+                vendor_id: None,
+                vendor_error_code: None,
+            };
+            self.outbox.push(Action::StatusNotification(notification));
+        }
+    }
+
+    /// Update the energy register of `connector_id` (Wh), used for `meterStart` and
+    /// `meterStop`.
+    pub fn set_meter(&mut self, connector_id: u32, meter_wh: u64) {
+        if let Some(connector) = self.connector_mut(connector_id) {
+            connector.meter = meter_wh;
+        }
+    }
+
+    /// Cable plugged into `connector_id`. Starts the transaction of an authorized tag.
+    pub fn plug_in(&mut self, connector_id: u32, now: DateTime<Utc>) {
+        let Some(connector) = self
+            .connector_mut(connector_id)
+            .filter(|_| connector_id > 0)
+        else {
+            return;
+        };
+        connector.plugged = true;
+        connector.finishing = false;
+        if connector.transaction().is_some() {
+            connector.energy = EnergyFlow::Charging;
+        }
+        self.try_start(connector_id, now);
+        self.refresh(connector_id, now);
+    }
+
+    /// Cable (or EV) gone from `connector_id`. Stops the transaction with
+    /// `EVDisconnected`, or suspends it when `StopTransactionOnEVSideDisconnect` is false.
+    pub fn unplug(&mut self, connector_id: u32, now: DateTime<Utc>) {
+        let stop = self.config.stop_transaction_on_ev_side_disconnect;
+        let Some(connector) = self
+            .connector_mut(connector_id)
+            .filter(|_| connector_id > 0)
+        else {
+            return;
+        };
+        connector.plugged = false;
+        connector.finishing = false;
+        if connector.transaction().is_some() {
+            if stop {
+                self.stop(connector_id, Reason::EVDisconnected, None, now);
+            } else {
+                connector.energy = EnergyFlow::SuspendedEv;
+            }
+        }
+        self.refresh(connector_id, now);
+    }
+
+    /// An accepted tag presented on `connector_id`: stops the running transaction when it
+    /// is the tag that started it, else prepares (or starts) a transaction. Returns whether
+    /// the tag was taken.
+    pub fn present_id_tag(&mut self, connector_id: u32, id_tag: &str, now: DateTime<Utc>) -> bool {
+        let Some(connector) = self.connector(connector_id).filter(|_| connector_id > 0) else {
+            return false;
+        };
+        if let Some(tx) = connector.transaction() {
+            if tx.id_tag != id_tag {
+                return false;
+            }
+            self.stop(connector_id, Reason::Local, Some(id_tag.into()), now);
+            self.refresh(connector_id, now);
+            return true;
+        }
+        self.authorize(connector_id, id_tag, now)
+    }
+
+    /// Take `id_tag` for the next transaction on `connector_id`, if it is free.
+    fn authorize(&mut self, connector_id: u32, id_tag: &str, now: DateTime<Utc>) -> bool {
+        let charge_point_ok =
+            self.connectors[0].is_operative() && self.connectors[0].fault.is_none();
+        let Some(connector) = self.connector_mut(connector_id) else {
+            return false;
+        };
+        if !charge_point_ok
+            || !connector.is_operative()
+            || connector.fault.is_some()
+            || connector.transaction.is_some()
+            || connector.id_tag.is_some()
+        {
+            return false;
+        }
+        connector.id_tag = Some((id_tag.into(), now));
+        connector.finishing = false;
+        self.try_start(connector_id, now);
+        self.refresh(connector_id, now);
+        true
+    }
+
+    fn try_start(&mut self, connector_id: u32, now: DateTime<Utc>) {
+        let Some(connector) = self.connector_mut(connector_id) else {
+            return;
+        };
+        if !connector.plugged || connector.transaction.is_some() {
+            return;
+        }
+        let Some((id_tag, _)) = connector.id_tag.take() else {
+            return;
+        };
+        connector.energy = EnergyFlow::Charging;
+        connector.transaction = Some(Transaction {
+            transaction_id: None,
+            id_tag: id_tag.clone(),
+            meter_start: connector.meter,
+            started_at: now,
+            deauthorized: false,
+            stop: None,
+        });
+        let request = StartTransaction {
+            connector_id,
+            id_tag,
+            meter_start: connector.meter,
+            timestamp: DateTimeWrapper::new(now),
+            reservation_id: None,
+        };
+        self.outbox.push(Action::StartTransaction(request));
+    }
+
+    /// Stop the running transaction of `connector_id`; no-op without one.
+    pub fn stop_transaction(&mut self, connector_id: u32, reason: Reason, now: DateTime<Utc>) {
+        if self
+            .connector(connector_id)
+            .and_then(Connector::transaction)
+            .is_some()
+        {
+            self.stop(connector_id, reason, None, now);
+            self.refresh(connector_id, now);
+        }
+    }
+
+    fn stop(
+        &mut self,
+        connector_id: u32,
+        reason: Reason,
+        id_tag: Option<String>,
+        now: DateTime<Utc>,
+    ) {
+        let Some(connector) = self.connector_mut(connector_id) else {
+            return;
+        };
+        let Some(tx) = connector.transaction.as_mut() else {
+            return;
+        };
+        let request = StopTransaction {
+            meter_stop: connector.meter,
+            timestamp: DateTimeWrapper::new(now),
+            transaction_id: tx.transaction_id.unwrap_or_default(),
+            reason: Some(reason),
+            id_tag,
+            transaction_data: None,
+        };
+        connector.finishing = connector.plugged;
+        if tx.transaction_id.is_some() {
+            connector.transaction = None;
+            self.outbox.push(Action::StopTransaction(request));
+        } else {
+            tx.stop = Some(request);
+        }
+    }
+
+    /// Energy flow of the running transaction on `connector_id` changed.
+    pub fn set_energy_flow(&mut self, connector_id: u32, flow: EnergyFlow, now: DateTime<Utc>) {
+        if let Some(connector) = self.connector_mut(connector_id) {
+            connector.energy = flow;
+            self.refresh(connector_id, now);
+        }
+    }
+
+    /// `connector_id` (0: the whole charge point) faulted. A running transaction is left to
+    /// the caller to stop.
+    pub fn fault(
+        &mut self,
+        connector_id: u32,
+        error_code: ChargePointErrorCode,
+        info: Option<String>,
+        now: DateTime<Utc>,
+    ) {
+        if let Some(connector) = self.connector_mut(connector_id) {
+            connector.fault = Some((error_code, info));
+            self.refresh(connector_id, now);
+        }
+    }
+
+    pub fn clear_fault(&mut self, connector_id: u32, now: DateTime<Utc>) {
+        if let Some(connector) = self.connector_mut(connector_id) {
+            connector.fault = None;
+            self.refresh(connector_id, now);
+        }
+    }
+
+    /// Queue `MeterValues.req` for `connector_id`, with the `transactionId` of its running
+    /// transaction when known.
+    pub fn meter_values(&mut self, connector_id: u32, meter_value: Vec<MeterValue>) {
+        let Some(connector) = self.connector(connector_id) else {
+            return;
+        };
+        let request = MeterValues {
+            connector_id,
+            meter_value,
+            transaction_id: connector.transaction().and_then(|tx| tx.transaction_id),
+        };
+        self.outbox.push(Action::MeterValues(request));
+    }
+
+    /// Handle the `StartTransaction.conf` of the transaction on `connector_id`.
+    pub fn on_start_transaction_conf(
+        &mut self,
+        connector_id: u32,
+        conf: &call_result::StartTransaction,
+        now: DateTime<Utc>,
+    ) {
+        let stop_on_invalid = self.config.stop_transaction_on_invalid_id;
+        let Some(connector) = self.connector_mut(connector_id) else {
+            return;
+        };
+        let Some(tx) = connector
+            .transaction
+            .as_mut()
+            .filter(|tx| tx.transaction_id.is_none())
+        else {
+            return;
+        };
+        tx.transaction_id = Some(conf.transaction_id);
+        if let Some(mut request) = tx.stop.take() {
+            request.transaction_id = conf.transaction_id;
+            connector.transaction = None;
+            self.outbox.push(Action::StopTransaction(request));
+        } else if conf.id_tag_info.status != AuthorizationStatus::Accepted {
+            if stop_on_invalid {
+                self.stop(connector_id, Reason::DeAuthorized, None, now);
+            } else {
+                tx.deauthorized = true;
+            }
+        } else {
+            // Accepted: charging goes on.
+        }
+        self.refresh(connector_id, now);
+    }
+
+    /// Handle a `RemoteStartTransaction.req`. Without `connectorId` the first free
+    /// connector is used. A `chargingProfile` is left to the caller.
+    pub fn remote_start_transaction(
+        &mut self,
+        request: &RemoteStartTransaction,
+        now: DateTime<Utc>,
+    ) -> call_result::RemoteStartTransaction {
+        let connector_id = request.connector_id.or_else(|| {
+            (1..=self.connector_count()).find(|id| {
+                self.connector(*id)
+                    .is_some_and(|c| c.status == ChargePointStatus::Available)
+            })
+        });
+        let accepted = connector_id
+            .filter(|id| *id > 0)
+            .is_some_and(|id| self.authorize(id, &request.id_tag, now));
+        call_result::RemoteStartTransaction {
+            status: if accepted {
+                RemoteStartStopStatus::Accepted
+            } else {
+                RemoteStartStopStatus::Rejected
+            },
+        }
+    }
+
+    /// Handle a `RemoteStopTransaction.req`.
+    pub fn remote_stop_transaction(
+        &mut self,
+        request: &RemoteStopTransaction,
+        now: DateTime<Utc>,
+    ) -> call_result::RemoteStopTransaction {
+        let connector_id = self.transaction_connector(request.transaction_id);
+        if let Some(connector_id) = connector_id {
+            self.stop_transaction(connector_id, Reason::Remote, now);
+        }
+        let status = if connector_id.is_some() {
+            RemoteStartStopStatus::Accepted
+        } else {
+            RemoteStartStopStatus::Rejected
+        };
+        call_result::RemoteStopTransaction { status }
+    }
+
+    /// Handle a `ChangeAvailability.req`. Connector 0 changes the charge point and every
+    /// connector. Connectors in use only change when they are free again (`Scheduled`).
+    pub fn change_availability(
+        &mut self,
+        request: &ChangeAvailability,
+        now: DateTime<Utc>,
+    ) -> call_result::ChangeAvailability {
+        let targets = if request.connector_id == 0 {
+            0..=self.connector_count()
+        } else if request.connector_id <= self.connector_count() {
+            request.connector_id..=request.connector_id
+        } else {
+            return call_result::ChangeAvailability {
+                status: AvailabilityStatus::Rejected,
+            };
+        };
+        let mut status = AvailabilityStatus::Accepted;
+        for id in targets {
+            let Some(connector) = self.connector_mut(id) else {
+                continue;
+            };
+            if id > 0 && connector.in_use() && connector.availability != request.availability_type {
+                connector.scheduled = Some(request.availability_type.clone());
+                status = AvailabilityStatus::Scheduled;
+            } else {
+                connector.scheduled = None;
+                connector.availability = request.availability_type.clone();
+            }
+            self.refresh(id, now);
+        }
+        call_result::ChangeAvailability { status }
+    }
+}
+
+impl ConnectorState for ChargePoint {
+    fn connector_exists(&self, connector_id: i32) -> bool {
+        u32::try_from(connector_id).is_ok_and(|id| id <= self.connector_count())
+    }
+
+    fn transaction_id(&self, connector_id: i32) -> Option<i32> {
+        let id = u32::try_from(connector_id).ok()?;
+        self.connector(id)?.transaction()?.transaction_id
+    }
+}
@@ -0,0 +1,211 @@
+//! Charge-point-side store of installed 1.6 charging profiles.
+//!
+//! [`ChargingProfileStore`] applies `SetChargingProfile` and `ClearChargingProfile` with the
+//! 1.6 installation rules: a profile with the same `chargingProfileId`, or with the same
+//! `stackLevel` and purpose on the same connector, is replaced; `ChargePointMaxProfile` is
+//! only accepted on connector 0 and `TxProfile` only on a connector with a (matching) active
+//! transaction; `ChargingScheduleMaxPeriods` and `MaxChargingProfilesInstalled` are enforced.
+//! 1.6 responses carry no reason; every refusal is `Rejected`.
+
+use alloc::vec::Vec;
+
+use super::call::{ClearChargingProfile, SetChargingProfile};
+use super::call_result;
+use super::composite::InstalledProfile;
+use super::data_types::ChargingProfile;
+use super::enums::{
+    ChargingProfileKindType, ChargingProfilePurposeType, ChargingProfileStatus,
+    ClearChargingProfileStatus,
+};
+
+/// What the store needs to know about the charge point's connectors.
+pub trait ConnectorState {
+    fn connector_exists(&self, connector_id: i32) -> bool;
+    /// `transactionId` of the transaction active on `connector_id`.
+    fn transaction_id(&self, connector_id: i32) -> Option<i32>;
+}
+
+/// `ChargingScheduleMaxPeriods` / `MaxChargingProfilesInstalled`. `None` = unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfileLimits {
+    pub max_schedule_periods: Option<usize>,
+    pub max_profiles_installed: Option<usize>,
+}
+
+impl ProfileLimits {
+    #[must_use]
+    pub const fn new(
+        max_schedule_periods: Option<usize>,
+        max_profiles_installed: Option<usize>,
+    ) -> Self {
+        Self {
+            max_schedule_periods,
+            max_profiles_installed,
+        }
+    }
+}
+
+/// Installed profiles of one charge point.
+#[derive(Debug, Clone, Default)]
+pub struct ChargingProfileStore {
+    limits: ProfileLimits,
+    profiles: Vec<InstalledProfile>,
+}
+
+fn valid_schedule(profile: &ChargingProfile, limits: &ProfileLimits) -> bool {
+    let schedule = &profile.charging_schedule;
+    let periods = &schedule.charging_schedule_period;
+    let kind_ok = match profile.charging_profile_kind {
+        ChargingProfileKindType::Recurring => {
+            profile.recurrency_kind.is_some() && schedule.start_schedule.is_some()
+        }
+        ChargingProfileKindType::Absolute => schedule.start_schedule.is_some(),
+        ChargingProfileKindType::Relative => true,
+    };
+    kind_ok
+        && periods.first().is_some_and(|p| p.start_period == 0)
+        && periods
+            .windows(2)
+            .all(|w| w[0].start_period < w[1].start_period)
+        && limits
+            .max_schedule_periods
+            .is_none_or(|max| periods.len() <= max)
+}
+
+impl ChargingProfileStore {
+    #[must_use]
+    pub const fn new(limits: ProfileLimits) -> Self {
+        Self {
+            limits,
+            profiles: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn limits(&self) -> &ProfileLimits {
+        &self.limits
+    }
+
+    /// Installed profiles, in installation order; pass to
+    /// [`composite_schedule`](super::composite::composite_schedule).
+    #[must_use]
+    pub fn profiles(&self) -> &[InstalledProfile] {
+        &self.profiles
+    }
+
+    #[must_use]
+    pub fn profile(&self, id: i32) -> Option<&InstalledProfile> {
+        self.profiles
+            .iter()
+            .find(|p| p.profile.charging_profile_id == id)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Handle a `SetChargingProfile.req`.
+    pub fn set_charging_profile(
+        &mut self,
+        request: &SetChargingProfile,
+        connectors: &impl ConnectorState,
+    ) -> call_result::SetChargingProfile {
+        let status = self.install(request, connectors);
+        call_result::SetChargingProfile { status }
+    }
+
+    fn install(
+        &mut self,
+        request: &SetChargingProfile,
+        connectors: &impl ConnectorState,
+    ) -> ChargingProfileStatus {
+        let Ok(connector_id) = i32::try_from(request.connector_id) else {
+            return ChargingProfileStatus::Rejected;
+        };
+        let profile = &request.cs_charging_profiles;
+        if connector_id != 0 && !connectors.connector_exists(connector_id) {
+            return ChargingProfileStatus::Rejected;
+        }
+        let placement_ok = match profile.charging_profile_purpose {
+            ChargingProfilePurposeType::ChargePointMaxProfile => connector_id == 0,
+            ChargingProfilePurposeType::TxDefaultProfile => true,
+            ChargingProfilePurposeType::TxProfile => {
+                connector_id != 0
+                    && connectors
+                        .transaction_id(connector_id)
+                        .is_some_and(|tx| profile.transaction_id.is_none_or(|id| id == tx))
+            }
+        };
+        if !placement_ok || !valid_schedule(profile, &self.limits) {
+            return ChargingProfileStatus::Rejected;
+        }
+        let replaces = |p: &InstalledProfile| {
+            p.profile.charging_profile_id == profile.charging_profile_id
+                || (p.connector_id == connector_id
+                    && p.profile.stack_level == profile.stack_level
+                    && p.profile.charging_profile_purpose == profile.charging_profile_purpose)
+        };
+        let kept = self.profiles.iter().filter(|p| !replaces(p)).count();
+        if self
+            .limits
+            .max_profiles_installed
+            .is_some_and(|max| kept >= max)
+        {
+            return ChargingProfileStatus::Rejected;
+        }
+        self.profiles.retain(|p| !replaces(p));
+        self.profiles.push(InstalledProfile {
+            connector_id,
+            profile: profile.clone(),
+        });
+        ChargingProfileStatus::Accepted
+    }
+
+    /// Handle a `ClearChargingProfile.req`. `id` takes precedence over the other criteria;
+    /// without any field every profile is cleared.
+    pub fn clear_charging_profile(
+        &mut self,
+        request: &ClearChargingProfile,
+    ) -> call_result::ClearChargingProfile {
+        let matches = |p: &InstalledProfile| {
+            if let Some(id) = request.id {
+                return p.profile.charging_profile_id == id;
+            }
+            request
+                .connector_id
+                .is_none_or(|c| i32::try_from(c).is_ok_and(|c| c == p.connector_id))
+                && request
+                    .charging_profile_purpose
+                    .as_ref()
+                    .is_none_or(|purpose| *purpose == p.profile.charging_profile_purpose)
+                && request
+                    .stack_level
+                    .is_none_or(|s| u32::try_from(s).is_ok_and(|s| s == p.profile.stack_level))
+        };
+        let before = self.profiles.len();
+        self.profiles.retain(|p| !matches(p));
+        let status = if self.profiles.len() < before {
+            ClearChargingProfileStatus::Accepted
+        } else {
+            ClearChargingProfileStatus::Unknown
+        };
+        call_result::ClearChargingProfile { status }
+    }
+
+    /// Remove the `TxProfile`s of a transaction that stopped on `connector_id`. Returns how
+    /// many were removed.
+    pub fn end_transaction(&mut self, connector_id: i32) -> usize {
+        let before = self.profiles.len();
+        self.profiles.retain(|p| {
+            !(p.connector_id == connector_id
+                && p.profile.charging_profile_purpose == ChargingProfilePurposeType::TxProfile)
+        });
+        before - self.profiles.len()
+    }
+}
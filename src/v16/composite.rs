@@ -0,0 +1,283 @@
+//! Composite schedule calculation for 1.6 charging profiles.
+//!
+//! [`composite_schedule`] merges the profiles installed on a charge point the way section 3.13
+//! of the 1.6 spec describes: per purpose the valid profile with the highest `stackLevel`
+//! applies, a `TxProfile` replaces the `TxDefaultProfile` while it applies, and the
+//! `ChargePointMaxProfile` (and the connector's hardware maximum) caps the result. Limits
+//! given in different units are compared in W using [`CompositeContext::voltage`] and the
+//! period's `numberPhases`.
+
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::data_types::{ChargingProfile, ChargingSchedule, ChargingSchedulePeriod};
+use super::enums::{
+    ChargingProfileKindType, ChargingProfilePurposeType, ChargingRateUnitType, RecurrencyKind,
+};
+use crate::charging_schedule::{self, ProfileKind, StackedProfile, period_in_effect};
+use crate::datetime::DateTimeWrapper;
+
+/// A profile as installed by `SetChargingProfile` (`connectorId` 0 is the whole charge point).
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledProfile {
+    pub connector_id: i32,
+    pub profile: ChargingProfile,
+}
+
+/// Transaction running on the connector the schedule is calculated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveTransaction {
+    /// `None` while the `StartTransaction.conf` is outstanding.
+    pub transaction_id: Option<i32>,
+    /// Start point of `Relative` profiles.
+    pub started: DateTime<Utc>,
+}
+
+/// Electrical parameters and transaction state needed to merge profiles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompositeContext {
+    pub transaction: Option<ActiveTransaction>,
+    /// Phase-to-neutral voltage used for A <-> W conversion.
+    pub voltage: f64,
+    /// Phases assumed when a period has no `numberPhases` (the spec default is 3).
+    pub phases: i32,
+    /// Connector hardware limit in A per phase; applies where no profile does.
+    pub max_current: f64,
+}
+
+impl Default for CompositeContext {
+    fn default() -> Self {
+        Self {
+            transaction: None,
+            voltage: 230.0,
+            phases: 3,
+            max_current: 32.0,
+        }
+    }
+}
+
+/// Convert a limit between A (per phase) and W for `phases` phases at `voltage`.
+#[must_use]
+pub fn convert_limit(
+    limit: f64,
+    from: &ChargingRateUnitType,
+    to: &ChargingRateUnitType,
+    phases: i32,
+    voltage: f64,
+) -> f64 {
+    let factor = voltage * f64::from(phases.max(1));
+    match (from, to) {
+        (ChargingRateUnitType::A, ChargingRateUnitType::W) => limit * factor,
+        (ChargingRateUnitType::W, ChargingRateUnitType::A) => limit / factor,
+        _ => limit,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limit {
+    watts: f64,
+    phases: i32,
+}
+
+impl StackedProfile for InstalledProfile {
+    type Purpose = ChargingProfilePurposeType;
+    /// Connector-specific beats connector 0 on equal stack levels.
+    type Rank = (u32, bool);
+
+    fn purpose(&self) -> &ChargingProfilePurposeType {
+        &self.profile.charging_profile_purpose
+    }
+
+    fn rank(&self) -> (u32, bool) {
+        (self.profile.stack_level, self.connector_id != 0)
+    }
+
+    fn kind(&self) -> ProfileKind {
+        match self.profile.charging_profile_kind {
+            ChargingProfileKindType::Absolute => ProfileKind::Absolute,
+            ChargingProfileKindType::Recurring => ProfileKind::Recurring {
+                weekly: self.profile.recurrency_kind == Some(RecurrencyKind::Weekly),
+            },
+            ChargingProfileKindType::Relative => ProfileKind::Relative,
+        }
+    }
+
+    fn valid_from(&self) -> Option<DateTime<Utc>> {
+        self.profile.valid_from.map(|from| from.inner())
+    }
+
+    fn valid_to(&self) -> Option<DateTime<Utc>> {
+        self.profile.valid_to.map(|to| to.inner())
+    }
+}
+
+fn profile_limit(
+    installed: &InstalledProfile,
+    anchor: DateTime<Utc>,
+    t: DateTime<Utc>,
+    ctx: &CompositeContext,
+) -> Option<Limit> {
+    let schedule = &installed.profile.charging_schedule;
+    let period = period_in_effect(installed, schedule, anchor, t)?;
+    let phases = period.number_phases.unwrap_or(ctx.phases);
+    Some(Limit {
+        watts: convert_limit(
+            f64::from(period.limit),
+            &schedule.charging_rate_unit,
+            &ChargingRateUnitType::W,
+            phases,
+            ctx.voltage,
+        ),
+        phases,
+    })
+}
+
+fn applicable<'a>(
+    profiles: &'a [InstalledProfile],
+    connector: i32,
+    ctx: &CompositeContext,
+) -> Vec<&'a InstalledProfile> {
+    profiles
+        .iter()
+        .filter(|p| match p.profile.charging_profile_purpose {
+            ChargingProfilePurposeType::ChargePointMaxProfile => p.connector_id == 0,
+            ChargingProfilePurposeType::TxDefaultProfile => {
+                connector > 0 && (p.connector_id == 0 || p.connector_id == connector)
+            }
+            ChargingProfilePurposeType::TxProfile => {
+                connector > 0
+                    && p.connector_id == connector
+                    && ctx.transaction.is_some_and(|tx| {
+                        p.profile.transaction_id.is_none()
+                            || p.profile.transaction_id == tx.transaction_id
+                    })
+            }
+        })
+        .collect()
+}
+
+/// Highest stack level with a limit at `t`.
+fn winner(
+    candidates: &[&InstalledProfile],
+    purpose: &ChargingProfilePurposeType,
+    anchor: DateTime<Utc>,
+    t: DateTime<Utc>,
+    ctx: &CompositeContext,
+) -> Option<Limit> {
+    charging_schedule::winner(candidates, purpose, |c| profile_limit(c, anchor, t, ctx))
+}
+
+fn limit_at(
+    candidates: &[&InstalledProfile],
+    anchor: DateTime<Utc>,
+    t: DateTime<Utc>,
+    ctx: &CompositeContext,
+) -> Limit {
+    let hardware = Limit {
+        watts: convert_limit(
+            ctx.max_current,
+            &ChargingRateUnitType::A,
+            &ChargingRateUnitType::W,
+            ctx.phases,
+            ctx.voltage,
+        ),
+        phases: ctx.phases,
+    };
+    let tx = winner(
+        candidates,
+        &ChargingProfilePurposeType::TxProfile,
+        anchor,
+        t,
+        ctx,
+    )
+    .or_else(|| {
+        winner(
+            candidates,
+            &ChargingProfilePurposeType::TxDefaultProfile,
+            anchor,
+            t,
+            ctx,
+        )
+    });
+    let max = winner(
+        candidates,
+        &ChargingProfilePurposeType::ChargePointMaxProfile,
+        anchor,
+        t,
+        ctx,
+    );
+    [tx, max]
+        .into_iter()
+        .flatten()
+        .fold(hardware, |acc, l| if l.watts < acc.watts { l } else { acc })
+}
+
+/// Round half away from zero to the one decimal the 1.6 schema allows.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn one_decimal(value: f64) -> f32 {
+    let scaled = value * 10.0;
+    let tenths = if scaled < 0.0 {
+        (scaled - 0.5) as i64
+    } else {
+        (scaled + 0.5) as i64
+    };
+    tenths as f32 / 10.0
+}
+
+/// Composite schedule of `connector` (0 = the whole charge point) for `duration` seconds
+/// from `start`, expressed in `unit`.
+///
+/// Only `ChargePointMaxProfile`s count for connector 0. `TxProfile`s apply while
+/// `ctx.transaction` is set and their `transactionId` (if any) matches. `Relative` profiles
+/// start at the transaction start, or at `start` when there is no transaction. Periods where
+/// no profile applies carry [`CompositeContext::max_current`].
+#[must_use]
+pub fn composite_schedule(
+    profiles: &[InstalledProfile],
+    connector: i32,
+    start: DateTime<Utc>,
+    duration: i32,
+    unit: ChargingRateUnitType,
+    ctx: &CompositeContext,
+) -> ChargingSchedule {
+    let end = start + TimeDelta::seconds(i64::from(duration.max(0)));
+    let anchor = ctx.transaction.map_or(start, |tx| tx.started);
+    let candidates = applicable(profiles, connector, ctx);
+    let points = charging_schedule::change_instants(
+        &candidates,
+        |c| Some(&c.profile.charging_schedule),
+        anchor,
+        start,
+        end,
+    );
+
+    let mut periods: Vec<ChargingSchedulePeriod> = Vec::new();
+    for t in points {
+        let limit = limit_at(&candidates, anchor, t, ctx);
+        let period = ChargingSchedulePeriod {
+            start_period: i32::try_from((t - start).num_seconds()).unwrap_or(i32::MAX),
+            limit: one_decimal(convert_limit(
+                limit.watts,
+                &ChargingRateUnitType::W,
+                &unit,
+                limit.phases,
+                ctx.voltage,
+            )),
+            number_phases: Some(limit.phases),
+        };
+        let same = periods.last().is_some_and(|p| {
+            p.limit.to_bits() == period.limit.to_bits() && p.number_phases == period.number_phases
+        });
+        if !same {
+            periods.push(period);
+        }
+    }
+
+    ChargingSchedule {
+        duration: Some(duration),
+        start_schedule: Some(DateTimeWrapper::new(start)),
+        charging_rate_unit: unit,
+        charging_schedule_period: periods,
+        min_charging_rate: None,
+    }
+}
@@ -0,0 +1,166 @@
+//! 1.6 front end of the CSMS load-balancing planner ([`crate::load_balancing`]).
+//!
+//! 1.6 has no EV charging needs message, so a [`PlannedTransaction`] is described by the
+//! connector's capability (per-phase current, phases, voltage) plus whatever the CSMS knows
+//! from elsewhere (remaining energy, departure). [`plan_tx_profiles`] returns one
+//! `SetChargingProfile.req` per transaction with an `Absolute` `TxProfile` starting at the
+//! plan start; limits are rounded down to the one decimal 1.6 allows.
+
+use alloc::vec::Vec;
+use chrono::{DateTime, Utc};
+
+use super::call::SetChargingProfile;
+use super::composite::convert_limit;
+use super::data_types::{
+    ChargingProfile, ChargingSchedule, ChargingSchedulePeriod, DateTimeWrapper,
+};
+use super::enums::{ChargingProfileKindType, ChargingProfilePurposeType, ChargingRateUnitType};
+pub use crate::load_balancing::DEFAULT_MIN_CURRENT;
+use crate::load_balancing::{FairnessPolicy, SessionDemand, SiteLimit, floor_tenth, plan};
+
+/// An active transaction to plan for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlannedTransaction {
+    pub transaction_id: i32,
+    pub connector_id: u32,
+    pub started: DateTime<Utc>,
+    pub phases: i32,
+    pub voltage: f64,
+    /// Connector capability, A per phase.
+    pub max_current: f64,
+    /// Below this (A per phase) the transaction is paused instead.
+    pub min_current: f64,
+    /// Energy still to deliver (Wh), if known.
+    pub energy_needed: Option<f64>,
+    pub departure: Option<DateTime<Utc>>,
+}
+
+impl PlannedTransaction {
+    /// Three phases at 230 V, minimum [`DEFAULT_MIN_CURRENT`], no energy need or departure.
+    #[must_use]
+    pub const fn new(
+        transaction_id: i32,
+        connector_id: u32,
+        started: DateTime<Utc>,
+        max_current: f64,
+    ) -> Self {
+        Self {
+            transaction_id,
+            connector_id,
+            started,
+            phases: 3,
+            voltage: 230.0,
+            max_current,
+            min_current: DEFAULT_MIN_CURRENT,
+            energy_needed: None,
+            departure: None,
+        }
+    }
+
+    /// Version-neutral demand of this transaction.
+    #[must_use]
+    pub fn demand(&self) -> SessionDemand {
+        let watts = |amps: f64| {
+            convert_limit(
+                amps,
+                &ChargingRateUnitType::A,
+                &ChargingRateUnitType::W,
+                self.phases,
+                self.voltage,
+            )
+        };
+        SessionDemand {
+            started: self.started,
+            max_power: watts(self.max_current),
+            min_power: watts(self.min_current),
+            energy_needed: self.energy_needed,
+            departure: self.departure,
+        }
+    }
+}
+
+/// Identity and unit of the emitted profiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileOptions {
+    /// `chargingProfileId` of the first transaction; the others follow in input order.
+    pub first_profile_id: i32,
+    pub stack_level: u32,
+    pub unit: ChargingRateUnitType,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        Self {
+            first_profile_id: 1,
+            stack_level: 0,
+            unit: ChargingRateUnitType::A,
+        }
+    }
+}
+
+/// Plan `transactions` under `site` for `horizon` seconds from `start` and build their
+/// `TxProfile`s (same order as `transactions`).
+#[must_use]
+pub fn plan_tx_profiles(
+    site: &SiteLimit,
+    transactions: &[PlannedTransaction],
+    policy: FairnessPolicy,
+    start: DateTime<Utc>,
+    horizon: i32,
+    options: &ProfileOptions,
+) -> Vec<SetChargingProfile> {
+    let demands: Vec<SessionDemand> = transactions
+        .iter()
+        .map(PlannedTransaction::demand)
+        .collect();
+    let plans = plan(site, &demands, policy, start, horizon);
+    transactions
+        .iter()
+        .zip(plans)
+        .zip(0..)
+        .map(|((tx, session), n)| {
+            let mut periods: Vec<ChargingSchedulePeriod> = Vec::new();
+            for planned in &session.periods {
+                let limit = floor_tenth(convert_limit(
+                    planned.power,
+                    &ChargingRateUnitType::W,
+                    &options.unit,
+                    tx.phases,
+                    tx.voltage,
+                ));
+                #[allow(clippy::cast_possible_truncation)]
+                let limit = limit as f32;
+                if periods
+                    .last()
+                    .is_none_or(|p| p.limit.to_bits() != limit.to_bits())
+                {
+                    periods.push(ChargingSchedulePeriod {
+                        start_period: planned.start_offset,
+                        limit,
+                        number_phases: Some(tx.phases),
+                    });
+                }
+            }
+            SetChargingProfile {
+                connector_id: tx.connector_id,
+                cs_charging_profiles: ChargingProfile {
+                    charging_profile_id: options.first_profile_id.saturating_add(n),
+                    transaction_id: Some(tx.transaction_id),
+                    stack_level: options.stack_level,
+                    charging_profile_purpose: ChargingProfilePurposeType::TxProfile,
+                    charging_profile_kind: ChargingProfileKindType::Absolute,
+                    recurrency_kind: None,
+                    valid_from: None,
+                    valid_to: None,
+                    charging_schedule: ChargingSchedule {
+                        duration: Some(horizon),
+                        start_schedule: Some(DateTimeWrapper::new(start)),
+                        charging_rate_unit: options.unit.clone(),
+                        charging_schedule_period: periods,
+                        min_charging_rate: None,
+                    },
+                },
+            }
+        })
+        .collect()
+}
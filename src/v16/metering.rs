@@ -0,0 +1,110 @@
+//! Feeding 1.6 messages into a [`MeterAggregator`].
+//!
+//! `StartTransaction.meterStart` and `StopTransaction.meterStop` (Wh) become the
+//! `Transaction.Begin` / `Transaction.End` register readings; `MeterValues` and
+//! `StopTransaction.transactionData` contribute their sampled values. A sampled value
+//! without measurand is `Energy.Active.Import.Register`; its [`SampledValue::quantity`] is
+//! taken in Wh, W or %.
+
+use super::call::{MeterValues, StartTransaction, StopTransaction};
+use super::data_types::{MeterValue, SampledValue};
+use super::enums::{Measurand, Phase, ReadingContext};
+use crate::datetime::DateTimeWrapper;
+use crate::metering::{Measure, MeterAggregator, MeterAnomaly, Reading, SampleContext};
+
+const fn measure(measurand: Option<&Measurand>) -> Option<Measure> {
+    match measurand {
+        None | Some(Measurand::EnergyActiveImportRegister) => Some(Measure::EnergyImportRegister),
+        Some(Measurand::PowerActiveImport) => Some(Measure::PowerImport),
+        Some(Measurand::SoC) => Some(Measure::StateOfCharge),
+        _ => None,
+    }
+}
+
+/// Phase index (1–3) of a per-phase value; `None` for values that do not add up to a
+/// total (neutral, line-to-line).
+const fn phase_index(phase: &Phase) -> Option<u8> {
+    match phase {
+        Phase::L1 | Phase::L1N => Some(1),
+        Phase::L2 | Phase::L2N => Some(2),
+        Phase::L3 | Phase::L3N => Some(3),
+        _ => None,
+    }
+}
+
+const fn context(context: Option<&ReadingContext>) -> SampleContext {
+    match context {
+        Some(ReadingContext::TransactionBegin) => SampleContext::TransactionBegin,
+        Some(ReadingContext::TransactionEnd) => SampleContext::TransactionEnd,
+        _ => SampleContext::Other,
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+const fn register(wh: u64) -> f64 {
+    wh as f64
+}
+
+impl MeterAggregator {
+    fn add_sampled_value(&mut self, timestamp: DateTimeWrapper, sample: &SampledValue) {
+        let Some(measure) = measure(sample.measurand.as_ref()) else {
+            return;
+        };
+        let phase = match sample.phase.as_ref().map(phase_index) {
+            None => None,
+            Some(Some(index)) => Some(index),
+            Some(None) => return,
+        };
+        let value = sample.quantity().ok().and_then(|q| q.to(measure.unit()));
+        match value {
+            Some(value) => self.push(Reading {
+                timestamp: timestamp.inner(),
+                measure,
+                context: context(sample.context.as_ref()),
+                phase,
+                value,
+            }),
+            None => self.flag(MeterAnomaly::Unreadable {
+                at: timestamp.inner(),
+                value: sample.value.clone(),
+            }),
+        }
+    }
+
+    fn add_meter_value(&mut self, meter_value: &MeterValue) {
+        for sample in &meter_value.sampled_value {
+            self.add_sampled_value(meter_value.timestamp, sample);
+        }
+    }
+
+    /// Take `meterStart` as the `Transaction.Begin` register reading.
+    pub fn add_start_transaction(&mut self, request: &StartTransaction) {
+        self.push(Reading {
+            timestamp: request.timestamp.inner(),
+            measure: Measure::EnergyImportRegister,
+            context: SampleContext::TransactionBegin,
+            phase: None,
+            value: register(request.meter_start),
+        });
+    }
+
+    pub fn add_meter_values(&mut self, request: &MeterValues) {
+        for meter_value in &request.meter_value {
+            self.add_meter_value(meter_value);
+        }
+    }
+
+    /// Take `transactionData` and `meterStop` (as the `Transaction.End` register reading).
+    pub fn add_stop_transaction(&mut self, request: &StopTransaction) {
+        for meter_value in request.transaction_data.iter().flatten() {
+            self.add_meter_value(meter_value);
+        }
+        self.push(Reading {
+            timestamp: request.timestamp.inner(),
+            measure: Measure::EnergyImportRegister,
+            context: SampleContext::TransactionEnd,
+            phase: None,
+            value: register(request.meter_stop),
+        });
+    }
+}
@@ -0,0 +1,189 @@
+//! 1.6 front end of the offline transaction queue ([`crate::offline_queue`]).
+//!
+//! `StartTransaction`, `StopTransaction` and `MeterValues` carrying a `transactionId` are
+//! transaction-related; everything else is dropped while offline. The retry policy comes from
+//! `TransactionMessageAttempts` / `TransactionMessageRetryInterval`. [`next_call`] and
+//! [`disconnected`] keep a [`PendingCalls`] map in step with the queue so replayed CALLRESULTs
+//! resolve after a reconnect.
+//!
+//! 1.6 transaction ids come from the CSMS in `StartTransaction.conf`, so a transaction started
+//! offline has none yet. [`TransactionIdMap`] hands out provisional ids for the queued
+//! `MeterValues` / `StopTransaction` and rewrites them once the real id arrives.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::call::{Action, Call};
+use super::call_result;
+use super::pending::PendingCalls;
+use crate::offline_queue::{OfflineQueue, QueueMessage, RetryPolicy};
+
+/// Queue of 1.6 CALLs.
+pub type TransactionQueue = OfflineQueue<Call>;
+
+impl QueueMessage for Call {
+    fn is_transaction_message(&self) -> bool {
+        match &self.payload {
+            Action::StartTransaction(_) | Action::StopTransaction(_) => true,
+            Action::MeterValues(values) => values.transaction_id.is_some(),
+            _ => false,
+        }
+    }
+
+    fn unique_id(&self) -> &str {
+        &self.unique_id
+    }
+}
+
+/// Policy from `TransactionMessageAttempts` and `TransactionMessageRetryInterval` (s).
+#[must_use]
+pub const fn retry_policy(attempts: u32, retry_interval: u32) -> RetryPolicy {
+    RetryPolicy {
+        attempts,
+        interval: retry_interval,
+    }
+}
+
+/// [`OfflineQueue::next_message`], registered in `pending`.
+pub fn next_call(
+    queue: &mut TransactionQueue,
+    pending: &mut PendingCalls,
+    now: DateTime<Utc>,
+) -> Option<Call> {
+    let call = queue.next_message(now)?;
+    pending.register_call(&call);
+    Some(call)
+}
+
+/// Connection lost: the in-flight call, if any, is requeued and forgotten in `pending`.
+pub fn disconnected(queue: &mut TransactionQueue, pending: &mut PendingCalls) -> Option<String> {
+    let unique_id = queue.set_online(false)?;
+    pending.take(&unique_id);
+    Some(unique_id)
+}
+
+/// State of a provisional transaction id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProvisionalId {
+    /// `StartTransaction.req` not answered yet.
+    Pending,
+    /// Id from `StartTransaction.conf`.
+    Assigned(i32),
+    /// `StartTransaction.req` was discarded; the CSMS never learns about the transaction.
+    Abandoned,
+}
+
+/// What [`TransactionIdMap::rewrite`] did with a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rewrite {
+    /// No provisional id in the payload.
+    Unchanged,
+    /// Provisional id replaced with the assigned one.
+    Rewritten,
+    /// Still waiting for `StartTransaction.conf`.
+    Pending,
+    /// Belongs to an abandoned transaction and cannot be sent.
+    Orphaned,
+}
+
+/// Provisional → CSMS transaction ids for transactions started without a `StartTransaction.conf`.
+///
+/// Provisional ids are negative (`-1`, `-2`, …) so they never clash with the positive ids a CSMS
+/// hands out. A `StartTransaction.conf` with a rejected `idTagInfo` still assigns the id: the
+/// transaction exists on the CSMS and is stopped under that id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionIdMap {
+    issued: i32,
+    /// `StartTransaction.req` `messageId` → provisional id.
+    starts: BTreeMap<String, i32>,
+    ids: BTreeMap<i32, ProvisionalId>,
+}
+
+impl TransactionIdMap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New provisional id for the transaction started by the `StartTransaction.req` with
+    /// `messageId` `start_unique_id`. After `-i32::MAX` ids start over at `-1`, skipping those
+    /// still tracked.
+    pub fn start(&mut self, start_unique_id: impl Into<String>) -> i32 {
+        let provisional = loop {
+            self.issued = if self.issued == i32::MAX {
+                1
+            } else {
+                self.issued + 1
+            };
+            if !self.ids.contains_key(&-self.issued) {
+                break -self.issued;
+            }
+        };
+        self.starts.insert(start_unique_id.into(), provisional);
+        self.ids.insert(provisional, ProvisionalId::Pending);
+        provisional
+    }
+
+    /// State of `provisional`, `None` if it is not a tracked provisional id.
+    #[must_use]
+    pub fn state(&self, provisional: i32) -> Option<ProvisionalId> {
+        self.ids.get(&provisional).copied()
+    }
+
+    /// Record the id from `StartTransaction.conf`; returns the provisional id it replaces.
+    pub fn on_start_transaction_conf(
+        &mut self,
+        start_unique_id: &str,
+        conf: &call_result::StartTransaction,
+    ) -> Option<i32> {
+        let provisional = self.starts.remove(start_unique_id)?;
+        self.ids
+            .insert(provisional, ProvisionalId::Assigned(conf.transaction_id));
+        Some(provisional)
+    }
+
+    /// The `StartTransaction.req` ran out of attempts; returns the abandoned provisional id.
+    pub fn on_start_discarded(&mut self, start_unique_id: &str) -> Option<i32> {
+        let provisional = self.starts.remove(start_unique_id)?;
+        self.ids.insert(provisional, ProvisionalId::Abandoned);
+        Some(provisional)
+    }
+
+    /// Drop the mapping once nothing refers to it any more; `id` may be the provisional or the
+    /// assigned id.
+    pub fn forget(&mut self, id: i32) {
+        self.ids.retain(|provisional, state| {
+            *provisional != id && *state != ProvisionalId::Assigned(id)
+        });
+    }
+
+    /// Replace a provisional `transactionId` in a `MeterValues` / `StopTransaction` payload.
+    pub fn rewrite(&self, call: &mut Call) -> Rewrite {
+        let transaction_id = match &mut call.payload {
+            Action::MeterValues(values) => match values.transaction_id.as_mut() {
+                Some(transaction_id) => transaction_id,
+                None => return Rewrite::Unchanged,
+            },
+            Action::StopTransaction(stop) => &mut stop.transaction_id,
+            _ => return Rewrite::Unchanged,
+        };
+        match self.ids.get(transaction_id) {
+            None => Rewrite::Unchanged,
+            Some(ProvisionalId::Pending) => Rewrite::Pending,
+            Some(ProvisionalId::Abandoned) => Rewrite::Orphaned,
+            Some(ProvisionalId::Assigned(assigned)) => {
+                *transaction_id = *assigned;
+                Rewrite::Rewritten
+            }
+        }
+    }
+
+    /// Rewrite every queued call; calls of abandoned transactions leave the queue and are
+    /// returned.
+    pub fn apply(&self, queue: &mut TransactionQueue) -> Vec<Call> {
+        queue.retain_mut(|call| self.rewrite(call) != Rewrite::Orphaned)
+    }
+}
@@ -0,0 +1,88 @@
+//! 1.6 front end of the registration state machine ([`crate::registration`]).
+//!
+//! While not accepted a 1.6 charge point only sends `BootNotification` and what a
+//! `TriggerMessage` / `ExtendedTriggerMessage` asked for; `RemoteStartTransaction` and
+//! `RemoteStopTransaction` are not processed before `Accepted`.
+
+use chrono::{DateTime, Utc};
+
+use super::call::Action;
+use super::call_result;
+use super::enums::{MessageTrigger, RegistrationStatus, TriggerMessageStatus};
+use crate::registration::{Registration, RegistrationState};
+
+#[must_use]
+pub const fn registration_state(status: RegistrationStatus) -> RegistrationState {
+    match status {
+        RegistrationStatus::Accepted => RegistrationState::Accepted,
+        RegistrationStatus::Pending => RegistrationState::Pending,
+        RegistrationStatus::Rejected => RegistrationState::Rejected,
+    }
+}
+
+/// Apply `BootNotification.conf`.
+pub fn on_boot_conf(
+    registration: &mut Registration,
+    conf: &call_result::BootNotification,
+    now: DateTime<Utc>,
+) {
+    registration.on_boot_response(registration_state(conf.status), conf.interval, now);
+}
+
+/// Whether the charge point may send this CALL now.
+#[must_use]
+pub fn may_send(registration: &Registration, action: &Action) -> bool {
+    registration.may_send(action.as_ref())
+}
+
+/// Whether a CALL from the Central System is processed in the current state.
+#[must_use]
+pub fn accepts_call(registration: &Registration, action: &Action) -> bool {
+    if registration.is_accepted() {
+        return true;
+    }
+    registration.answers_csms()
+        && !matches!(
+            action,
+            Action::RemoteStartTransaction(_) | Action::RemoteStopTransaction(_)
+        )
+}
+
+/// Action name a trigger asks for.
+#[must_use]
+pub const fn triggered_action(requested: &MessageTrigger) -> &'static str {
+    match requested {
+        MessageTrigger::BootNotification => "BootNotification",
+        MessageTrigger::DiagnosticsStatusNotification => "DiagnosticsStatusNotification",
+        MessageTrigger::FirmwareStatusNotification => "FirmwareStatusNotification",
+        MessageTrigger::Heartbeat => "Heartbeat",
+        MessageTrigger::MeterValues => "MeterValues",
+        MessageTrigger::StatusNotification => "StatusNotification",
+        MessageTrigger::LogStatusNotification => "LogStatusNotification",
+        MessageTrigger::SignChargePointCertificate => "SignCertificate",
+    }
+}
+
+/// Status for a `TriggerMessage` / `ExtendedTriggerMessage` with `requested`.
+///
+/// `BootNotification` is scheduled at `now`; other messages are granted while not accepted.
+pub fn on_trigger_message(
+    registration: &mut Registration,
+    requested: &MessageTrigger,
+    now: DateTime<Utc>,
+) -> TriggerMessageStatus {
+    if *requested == MessageTrigger::BootNotification {
+        return if registration.trigger_boot(now) {
+            TriggerMessageStatus::Accepted
+        } else {
+            TriggerMessageStatus::Rejected
+        };
+    }
+    if !registration.answers_csms() {
+        return TriggerMessageStatus::Rejected;
+    }
+    if !registration.is_accepted() {
+        registration.grant(triggered_action(requested));
+    }
+    TriggerMessageStatus::Accepted
+}
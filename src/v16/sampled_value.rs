@@ -0,0 +1,90 @@
+//! Typed access to 1.6 [`SampledValue`]s: the value as a normalized [`Quantity`] or as
+//! signed meter data, and the optional fields with their 1.6 defaults applied.
+
+use super::data_types::SampledValue;
+use super::enums::{Location, Measurand, ReadingContext, ValueFormat};
+use crate::quantity::{Quantity, QuantityError, Unit, parse_decimal};
+use crate::signed_meter::{Ocmf, SignedMeterError};
+
+/// Unit assumed when a sampled value of `measurand` carries none. 1.6 says Wh for every
+/// measurand; in practice stations omit the unit only where the measurand implies it.
+#[must_use]
+pub const fn default_unit(measurand: &Measurand) -> Option<Unit> {
+    Some(match measurand {
+        Measurand::EnergyReactiveExportRegister
+        | Measurand::EnergyReactiveImportRegister
+        | Measurand::EnergyReactiveExportInterval
+        | Measurand::EnergyReactiveImportInterval => Unit::Varh,
+        Measurand::PowerActiveExport | Measurand::PowerActiveImport | Measurand::PowerOffered => {
+            Unit::W
+        }
+        Measurand::PowerReactiveExport | Measurand::PowerReactiveImport => Unit::Var,
+        Measurand::CurrentExport | Measurand::CurrentImport | Measurand::CurrentOffered => Unit::A,
+        Measurand::Voltage => Unit::V,
+        Measurand::Frequency => Unit::Hz,
+        Measurand::Temperature => Unit::Celsius,
+        Measurand::SoC => Unit::Percent,
+        Measurand::PowerFactor | Measurand::Rpm => return None,
+        _ => Unit::Wh,
+    })
+}
+
+impl SampledValue {
+    /// `measurand`, defaulting to `Energy.Active.Import.Register`.
+    #[must_use]
+    pub fn measurand_or_default(&self) -> Measurand {
+        self.measurand.clone().unwrap_or_default()
+    }
+
+    /// `context`, defaulting to `Sample.Periodic`.
+    #[must_use]
+    pub fn context_or_default(&self) -> ReadingContext {
+        self.context.clone().unwrap_or_default()
+    }
+
+    /// `location`, defaulting to `Outlet`.
+    #[must_use]
+    pub fn location_or_default(&self) -> Location {
+        self.location.clone().unwrap_or_default()
+    }
+
+    /// Whether `value` holds signed meter data: `format = SignedData`, or a non-numeric
+    /// value that looks like a signed payload.
+    #[must_use]
+    pub fn is_signed(&self) -> bool {
+        self.format == Some(ValueFormat::SignedData)
+            || matches!(parse_decimal(&self.value), Err(QuantityError::Signed))
+    }
+
+    /// The value in its base unit; see [`default_unit`] when `unit` is absent.
+    ///
+    /// # Errors
+    /// [`QuantityError::Signed`] for signed data, [`QuantityError::NotANumber`] or
+    /// [`QuantityError::UnknownUnit`] otherwise.
+    pub fn quantity(&self) -> Result<Quantity, QuantityError> {
+        if self.format == Some(ValueFormat::SignedData) {
+            return Err(QuantityError::Signed);
+        }
+        let value = parse_decimal(&self.value)?;
+        let unit = match &self.unit {
+            Some(unit) => Some(
+                Unit::parse(unit.as_str())
+                    .ok_or_else(|| QuantityError::UnknownUnit(unit.as_str().into()))?,
+            ),
+            None => default_unit(&self.measurand_or_default()),
+        };
+        Ok(unit.map_or_else(
+            || Quantity::one(value),
+            |unit| Quantity::new(value, unit, 0),
+        ))
+    }
+
+    /// The signed value as OCMF (as sent or base64 encoded). 1.6 carries no public key;
+    /// it comes from the meter's display or the station's configuration.
+    ///
+    /// # Errors
+    /// See [`Ocmf::decode`].
+    pub fn ocmf(&self) -> Result<Ocmf, SignedMeterError> {
+        Ocmf::decode(&self.value)
+    }
+}
@@ -0,0 +1,105 @@
+//! Time queries on a 1.6 [`ChargingSchedule`].
+//!
+//! Thin wrappers over [`crate::charging_schedule`]; `relative_start` is the instant a schedule
+//! without `startSchedule` started. Limits are `f32` with one decimal, as on the 1.6 wire.
+
+use chrono::{DateTime, Utc};
+
+use super::composite::convert_limit;
+use super::data_types::{ChargingSchedule, ChargingSchedulePeriod};
+use super::enums::ChargingRateUnitType;
+use crate::charging_schedule::{self, Schedule};
+
+/// A 1.6 schedule period resolved to absolute time: `[start, end)`.
+pub type EffectivePeriod<'a> = charging_schedule::EffectivePeriod<'a, ChargingSchedulePeriod>;
+
+impl Schedule for ChargingSchedule {
+    type Period = ChargingSchedulePeriod;
+
+    fn start_schedule(&self) -> Option<DateTime<Utc>> {
+        self.start_schedule.map(|start| start.inner())
+    }
+
+    fn duration(&self) -> Option<i32> {
+        self.duration
+    }
+
+    fn periods(&self) -> &[ChargingSchedulePeriod] {
+        &self.charging_schedule_period
+    }
+
+    fn start_period(period: &ChargingSchedulePeriod) -> i32 {
+        period.start_period
+    }
+}
+
+impl ChargingSchedule {
+    /// Instant the schedule starts: `startSchedule`, else `relative_start`.
+    #[must_use]
+    pub fn anchor(&self, relative_start: DateTime<Utc>) -> DateTime<Utc> {
+        charging_schedule::anchor(self, relative_start)
+    }
+
+    /// End of the schedule, if it has a `duration`.
+    #[must_use]
+    pub fn end(&self, relative_start: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        charging_schedule::end(self, relative_start)
+    }
+
+    /// Periods in `startPeriod` order; periods starting at or after the end of the schedule
+    /// are skipped.
+    pub fn effective_periods(
+        &self,
+        relative_start: DateTime<Utc>,
+    ) -> impl Iterator<Item = EffectivePeriod<'_>> + '_ {
+        charging_schedule::effective_periods(self, relative_start)
+    }
+
+    /// Period in effect at `t`.
+    #[must_use]
+    pub fn period_at(
+        &self,
+        relative_start: DateTime<Utc>,
+        t: DateTime<Utc>,
+    ) -> Option<EffectivePeriod<'_>> {
+        charging_schedule::period_at(self, relative_start, t)
+    }
+
+    /// Limit (in `chargingRateUnit`) at `t`; `None` before the first period or after the
+    /// end of the schedule.
+    #[must_use]
+    pub fn limit_at(&self, relative_start: DateTime<Utc>, t: DateTime<Utc>) -> Option<f32> {
+        self.period_at(relative_start, t).map(|p| p.period.limit)
+    }
+
+    /// First instant after `t` at which the period in effect changes.
+    #[must_use]
+    pub fn next_change(
+        &self,
+        relative_start: DateTime<Utc>,
+        t: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        charging_schedule::next_change(self, relative_start, t)
+    }
+
+    /// Most energy (Wh) the schedule lets through between `from` and `to`. Limits in A are
+    /// converted with the period's `numberPhases` (3 when absent) at `voltage`.
+    #[must_use]
+    pub fn max_energy(
+        &self,
+        relative_start: DateTime<Utc>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        voltage: f64,
+    ) -> f64 {
+        charging_schedule::max_energy(self, relative_start, from, to, |period| {
+            convert_limit(
+                f64::from(period.limit),
+                &self.charging_rate_unit,
+                &ChargingRateUnitType::W,
+                period.number_phases.unwrap_or(3),
+                voltage,
+            )
+        })
+    }
+}
@@ -18,16 +18,34 @@ pub mod call;
 pub mod call_error;
 pub mod call_result;
 pub mod call_result_error;
+pub mod charging_profiles;
+pub mod composite;
 pub mod datatypes;
 pub mod enumerations;
 pub mod errata;
+pub mod evse;
+pub mod iso15118;
+pub mod load_balancing;
 pub mod log_helper;
 pub mod messages;
+pub mod metering;
+pub mod monitoring;
+pub mod offline_queue;
 pub mod parse;
 pub mod pending;
+pub mod price_schedule;
+pub mod reason_codes;
+pub mod reassembly;
+pub mod registration;
+pub mod report;
 pub mod response_trait;
 pub mod rpc_error_code;
+pub mod sampled_value;
+pub mod schedule;
 pub mod send;
+pub mod tariff;
+pub mod tariffs;
+pub mod transactions;
 pub mod typed_call_result;
 pub mod utils;
 pub mod version;
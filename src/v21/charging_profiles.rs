@@ -0,0 +1,420 @@
+//! Station-side store of installed charging profiles.
+//!
+//! [`ChargingProfileStore`] applies `SetChargingProfileRequest`, `ClearChargingProfileRequest`
+//! and `GetChargingProfilesRequest` with the installation rules of the smart-charging
+//! functional block:
+//!
+//! - a profile with the same `id` is replaced;
+//! - another profile with the same `stackLevel` and purpose on the same EVSE, with an
+//!   overlapping validity period, is rejected with `DuplicateProfile`;
+//! - a `TxProfile` needs the EVSE's active transaction (`TxNotFound`), and is rejected with
+//!   [`errata::REASON_INVALID_MESSAGE_SEQ`] while an ISO 15118 session has not yet sent
+//!   `NotifyEVChargingNeedsRequest`;
+//! - `ChargingScheduleMaxPeriods` and `MaxChargingProfilesInstalled` are enforced
+//!   (`TooManyElements`).
+//!
+//! The stored profiles feed [`composite_schedule`](super::composite::composite_schedule)
+//! directly.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::call::Action;
+use super::composite::InstalledProfile;
+use super::datatypes::{ChargingProfileType, DateTimeWrapper, StatusInfoType};
+use super::enumerations::{
+    ChargingProfileKindEnumType, ChargingProfilePurposeEnumType, ChargingProfileStatusEnumType,
+};
+use super::errata;
+use super::evse::EvseState;
+use super::messages::clear_charging_profile::{
+    ClearChargingProfileRequest, ClearChargingProfileResponse, ClearChargingProfileStatusEnumType,
+};
+use super::messages::get_charging_profiles::{
+    GetChargingProfileStatusEnumType, GetChargingProfilesRequest, GetChargingProfilesResponse,
+};
+use super::messages::report_charging_profiles::ReportChargingProfilesRequest;
+use super::messages::set_charging_profile::{
+    SetChargingProfileRequest, SetChargingProfileResponse,
+};
+use super::reason_codes::{self, status_info};
+use super::report::{MessageLimits, call_frame_len, chunk_by_limits};
+use crate::errors::Result;
+
+/// `chargingLimitSource` of profiles installed by the CSMS.
+pub const SOURCE_CSO: &str = "CSO";
+
+/// `ChargingScheduleMaxPeriods` / `MaxChargingProfilesInstalled` (`SmartChargingCtrlr`).
+/// `None` = unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfileLimits {
+    pub max_schedule_periods: Option<usize>,
+    pub max_profiles_installed: Option<usize>,
+}
+
+impl ProfileLimits {
+    #[must_use]
+    pub const fn new(
+        max_schedule_periods: Option<usize>,
+        max_profiles_installed: Option<usize>,
+    ) -> Self {
+        Self {
+            max_schedule_periods,
+            max_profiles_installed,
+        }
+    }
+}
+
+/// Installed profiles of one station.
+#[derive(Debug, Clone, Default)]
+pub struct ChargingProfileStore {
+    limits: ProfileLimits,
+    profiles: Vec<InstalledProfile>,
+    /// `chargingLimitSource` per profile id.
+    sources: BTreeMap<i32, String>,
+}
+
+fn rejected(reason: &str) -> SetChargingProfileResponse {
+    SetChargingProfileResponse {
+        status: ChargingProfileStatusEnumType::Rejected,
+        status_info: Some(status_info(reason)),
+        custom_data: None,
+    }
+}
+
+fn overlaps(a: &ChargingProfileType, b: &ChargingProfileType) -> bool {
+    let starts_before_end = |from: Option<DateTimeWrapper>, to: Option<DateTimeWrapper>| {
+        from.zip(to)
+            .is_none_or(|(from, to)| from.inner() < to.inner())
+    };
+    starts_before_end(a.valid_from, b.valid_to) && starts_before_end(b.valid_from, a.valid_to)
+}
+
+/// Schedule and kind checks; returns the reason code of the first problem.
+fn check_profile(profile: &ChargingProfileType, limits: &ProfileLimits) -> Option<&'static str> {
+    let recurring = profile.charging_profile_kind == ChargingProfileKindEnumType::Recurring;
+    if recurring && profile.recurrency_kind.is_none() {
+        return Some(reason_codes::INVALID_PROFILE);
+    }
+    if profile.charging_schedule.is_empty() {
+        return Some(reason_codes::INVALID_SCHEDULE);
+    }
+    for schedule in &profile.charging_schedule {
+        let periods = &schedule.charging_schedule_period;
+        let needs_start = matches!(
+            profile.charging_profile_kind,
+            ChargingProfileKindEnumType::Absolute | ChargingProfileKindEnumType::Recurring
+        );
+        if periods.first().is_none_or(|p| p.start_period != 0)
+            || periods
+                .windows(2)
+                .any(|w| w[0].start_period >= w[1].start_period)
+            || (needs_start && schedule.start_schedule.is_none())
+        {
+            return Some(reason_codes::INVALID_SCHEDULE);
+        }
+        if limits
+            .max_schedule_periods
+            .is_some_and(|max| periods.len() > max)
+        {
+            return Some(reason_codes::TOO_MANY_ELEMENTS);
+        }
+    }
+    None
+}
+
+impl ChargingProfileStore {
+    #[must_use]
+    pub fn new(limits: ProfileLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub const fn limits(&self) -> &ProfileLimits {
+        &self.limits
+    }
+
+    /// Installed profiles, in installation order; pass to
+    /// [`composite_schedule`](super::composite::composite_schedule).
+    #[must_use]
+    pub fn profiles(&self) -> &[InstalledProfile] {
+        &self.profiles
+    }
+
+    #[must_use]
+    pub fn profile(&self, id: i32) -> Option<&InstalledProfile> {
+        self.profiles.iter().find(|p| p.profile.id == id)
+    }
+
+    /// `chargingLimitSource` of profile `id`.
+    #[must_use]
+    pub fn source(&self, id: i32) -> Option<&str> {
+        self.sources.get(&id).map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Handle a `SetChargingProfileRequest` from the CSMS (source [`SOURCE_CSO`]).
+    ///
+    /// `ChargingStationExternalConstraints` cannot be set by the CSMS; use
+    /// [`Self::install_external`] for limits from an energy management system.
+    pub fn set_charging_profile(
+        &mut self,
+        request: &SetChargingProfileRequest,
+        evses: &impl EvseState,
+    ) -> SetChargingProfileResponse {
+        use ChargingProfilePurposeEnumType as P;
+        let evse_id = request.evse_id;
+        let profile = &request.charging_profile;
+        if evse_id != 0 && !evses.evse_exists(evse_id) {
+            return rejected(reason_codes::UNKNOWN_EVSE);
+        }
+        match profile.charging_profile_purpose {
+            P::ChargingStationExternalConstraints => {
+                return rejected(reason_codes::INVALID_PROFILE);
+            }
+            P::ChargingStationMaxProfile if evse_id != 0 => {
+                return rejected(reason_codes::INVALID_PROFILE);
+            }
+            P::TxProfile => {
+                if evse_id == 0 {
+                    return rejected(reason_codes::INVALID_PROFILE);
+                }
+                let active = evses.transaction_id(evse_id);
+                if active.is_none() || profile.transaction_id.as_deref() != active {
+                    return rejected(reason_codes::TX_NOT_FOUND);
+                }
+                if evses.awaiting_charging_needs(evse_id) {
+                    return rejected(errata::REASON_INVALID_MESSAGE_SEQ);
+                }
+            }
+            _ => {}
+        }
+        match self.install(evse_id, profile.clone(), SOURCE_CSO) {
+            Ok(()) => SetChargingProfileResponse {
+                status: ChargingProfileStatusEnumType::Accepted,
+                status_info: None,
+                custom_data: None,
+            },
+            Err(info) => SetChargingProfileResponse {
+                status: ChargingProfileStatusEnumType::Rejected,
+                status_info: Some(info),
+                custom_data: None,
+            },
+        }
+    }
+
+    /// Install a profile set locally (e.g. `ChargingStationExternalConstraints` from an EMS),
+    /// with the same duplicate, schedule and capacity checks.
+    ///
+    /// # Errors
+    /// The `StatusInfoType` a `SetChargingProfileResponse` would carry.
+    pub fn install_external(
+        &mut self,
+        evse_id: i32,
+        profile: ChargingProfileType,
+        source: &str,
+    ) -> core::result::Result<(), StatusInfoType> {
+        self.install(evse_id, profile, source)
+    }
+
+    fn install(
+        &mut self,
+        evse_id: i32,
+        profile: ChargingProfileType,
+        source: &str,
+    ) -> core::result::Result<(), StatusInfoType> {
+        if let Some(reason) = check_profile(&profile, &self.limits) {
+            return Err(status_info(reason));
+        }
+        let duplicate = self.profiles.iter().any(|p| {
+            p.profile.id != profile.id
+                && p.evse_id == evse_id
+                && p.profile.stack_level == profile.stack_level
+                && p.profile.charging_profile_purpose == profile.charging_profile_purpose
+                && overlaps(&p.profile, &profile)
+        });
+        if duplicate {
+            return Err(status_info(reason_codes::DUPLICATE_PROFILE));
+        }
+        let existing = self
+            .profiles
+            .iter()
+            .position(|p| p.profile.id == profile.id);
+        if existing.is_none()
+            && self
+                .limits
+                .max_profiles_installed
+                .is_some_and(|max| self.profiles.len() >= max)
+        {
+            return Err(status_info(reason_codes::TOO_MANY_ELEMENTS));
+        }
+        self.sources.insert(profile.id, source.into());
+        let installed = InstalledProfile { evse_id, profile };
+        match existing {
+            Some(i) => self.profiles[i] = installed,
+            None => self.profiles.push(installed),
+        }
+        Ok(())
+    }
+
+    /// Handle a `ClearChargingProfileRequest`. `chargingProfileId` takes precedence over the
+    /// criteria; `ChargingStationExternalConstraints` profiles are never cleared.
+    pub fn clear_charging_profile(
+        &mut self,
+        request: &ClearChargingProfileRequest,
+    ) -> ClearChargingProfileResponse {
+        let criteria = request.charging_profile_criteria.as_ref();
+        let matches = |p: &InstalledProfile| {
+            if p.profile.charging_profile_purpose
+                == ChargingProfilePurposeEnumType::ChargingStationExternalConstraints
+            {
+                return false;
+            }
+            if let Some(id) = request.charging_profile_id {
+                return p.profile.id == id;
+            }
+            criteria.is_none_or(|c| {
+                c.evse_id.is_none_or(|e| e == p.evse_id)
+                    && c.charging_profile_purpose
+                        .as_ref()
+                        .is_none_or(|purpose| *purpose == p.profile.charging_profile_purpose)
+                    && c.stack_level.is_none_or(|s| s == p.profile.stack_level)
+            })
+        };
+        let before = self.profiles.len();
+        let sources = &mut self.sources;
+        self.profiles.retain(|p| {
+            let clear = matches(p);
+            if clear {
+                sources.remove(&p.profile.id);
+            }
+            !clear
+        });
+        let status = if self.profiles.len() < before {
+            ClearChargingProfileStatusEnumType::Accepted
+        } else {
+            ClearChargingProfileStatusEnumType::Unknown
+        };
+        ClearChargingProfileResponse {
+            status,
+            status_info: None,
+            custom_data: None,
+        }
+    }
+
+    /// Remove the `TxProfile`s of a finished transaction. Returns how many were removed.
+    pub fn end_transaction(&mut self, transaction_id: &str) -> usize {
+        let before = self.profiles.len();
+        let sources = &mut self.sources;
+        self.profiles.retain(|p| {
+            let ended = p.profile.charging_profile_purpose
+                == ChargingProfilePurposeEnumType::TxProfile
+                && p.profile.transaction_id.as_deref() == Some(transaction_id);
+            if ended {
+                sources.remove(&p.profile.id);
+            }
+            !ended
+        });
+        before - self.profiles.len()
+    }
+
+    /// Profiles selected by a `GetChargingProfilesRequest` (`evseId` absent = all EVSEs).
+    #[must_use]
+    pub fn matching(&self, request: &GetChargingProfilesRequest) -> Vec<&InstalledProfile> {
+        let criterion = &request.charging_profile;
+        self.profiles
+            .iter()
+            .filter(|p| {
+                request.evse_id.is_none_or(|e| e == p.evse_id)
+                    && criterion
+                        .charging_profile_purpose
+                        .as_ref()
+                        .is_none_or(|purpose| *purpose == p.profile.charging_profile_purpose)
+                    && criterion
+                        .stack_level
+                        .is_none_or(|s| s == p.profile.stack_level)
+                    && criterion
+                        .charging_profile_id
+                        .as_ref()
+                        .is_none_or(|ids| ids.contains(&p.profile.id))
+                    && criterion
+                        .charging_limit_source
+                        .as_ref()
+                        .is_none_or(|sources| {
+                            self.source(p.profile.id)
+                                .is_some_and(|s| sources.iter().any(|x| x == s))
+                        })
+            })
+            .collect()
+    }
+
+    /// Answer a `GetChargingProfilesRequest`: the response plus the
+    /// `ReportChargingProfilesRequest`s to send after it, one group per EVSE and
+    /// `chargingLimitSource`, each split to `limits`; `tbc` is set on all but the last.
+    ///
+    /// # Errors
+    /// [`crate::errors::Error::ConstraintViolation`] if one profile alone exceeds
+    /// `bytes_per_message`; serialization errors while measuring.
+    pub fn get_charging_profiles(
+        &self,
+        request: &GetChargingProfilesRequest,
+        limits: &MessageLimits,
+    ) -> Result<(
+        GetChargingProfilesResponse,
+        Vec<ReportChargingProfilesRequest>,
+    )> {
+        let mut groups: BTreeMap<(i32, &str), Vec<ChargingProfileType>> = BTreeMap::new();
+        for p in self.matching(request) {
+            let source = self.source(p.profile.id).unwrap_or(SOURCE_CSO);
+            groups
+                .entry((p.evse_id, source))
+                .or_default()
+                .push(p.profile.clone());
+        }
+        let status = if groups.is_empty() {
+            GetChargingProfileStatusEnumType::NoProfiles
+        } else {
+            GetChargingProfileStatusEnumType::Accepted
+        };
+        let mut reports = Vec::new();
+        for ((evse_id, source), profiles) in groups {
+            let frame =
+                |charging_profile: Vec<ChargingProfileType>| ReportChargingProfilesRequest {
+                    request_id: request.request_id,
+                    charging_limit_source: source.into(),
+                    charging_profile,
+                    tbc: Some(true),
+                    evse_id,
+                    custom_data: None,
+                };
+            let chunks = chunk_by_limits(profiles, limits, "chargingProfile", |_| {
+                call_frame_len(Action::ReportChargingProfiles(frame(Vec::new())))
+            })?;
+            reports.extend(chunks.into_iter().map(frame));
+        }
+        if let Some(last) = reports.last_mut() {
+            last.tbc = None;
+        }
+        Ok((
+            GetChargingProfilesResponse {
+                status,
+                status_info: None,
+                custom_data: None,
+            },
+            reports,
+        ))
+    }
+}
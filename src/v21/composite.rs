@@ -0,0 +1,369 @@
+//! Composite schedule calculation for 2.1 charging profiles.
+//!
+//! [`composite_schedule`] merges installed profiles into the `CompositeScheduleType` of
+//! `GetCompositeScheduleResponse`. Per purpose the valid profile with the highest `stackLevel`
+//! applies (EVSE-specific before EVSE 0, then the higher profile `id`). The charging limit is
+//! the minimum of:
+//!
+//! - `ChargingStationExternalConstraints`, raised by `LocalGeneration` while both apply;
+//! - `ChargingStationMaxProfile`;
+//! - the transaction level: `PriorityCharging` (while [`CompositeContext::priority_charging`]),
+//!   else `TxProfile`, else `TxDefaultProfile`;
+//! - the EVSE's hardware maximum.
+//!
+//! Discharge limits combine the same way (the one closest to zero wins). Setpoints,
+//! `operationMode` and the other V2X fields come from the transaction-level period, with
+//! setpoints clamped into the composite limits. Schedules are evaluated in UTC; `useLocalTime`
+//! and `randomizedDelay` are not applied.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::charging_schedule::{self, ProfileKind, StackedProfile, period_in_effect};
+use crate::v21::datatypes::{
+    ChargingProfileType, ChargingSchedulePeriodType, ChargingScheduleType, DateTimeWrapper,
+};
+use crate::v21::enumerations::{
+    ChargingProfileKindEnumType, ChargingProfilePurposeEnumType, ChargingRateUnitEnumType,
+    RecurrencyKindEnumType,
+};
+use crate::v21::messages::get_composite_schedule::CompositeScheduleType;
+
+/// A profile as installed by `SetChargingProfileRequest` (`evseId` 0 is the whole station).
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledProfile {
+    pub evse_id: i32,
+    pub profile: ChargingProfileType,
+}
+
+/// Transaction running on the EVSE the schedule is calculated for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveTransaction {
+    pub transaction_id: String,
+    /// Start point of `Relative` profiles.
+    pub started: DateTime<Utc>,
+    /// `ChargingScheduleType.id` chosen by the EV (`NotifyEVChargingScheduleRequest`); the
+    /// first schedule of a profile is used otherwise.
+    pub selected_schedule: Option<i32>,
+}
+
+/// Electrical parameters and EVSE state needed to merge profiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeContext {
+    pub transaction: Option<ActiveTransaction>,
+    /// Whether priority charging is active on the EVSE (`UsePriorityChargingRequest`).
+    pub priority_charging: bool,
+    /// Phase-to-neutral voltage used for A <-> W conversion.
+    pub voltage: f64,
+    /// Phases assumed when a period has no `numberPhases`.
+    pub phases: i32,
+    /// EVSE hardware limit in A per phase; applies where no profile does.
+    pub max_current: f64,
+}
+
+impl Default for CompositeContext {
+    fn default() -> Self {
+        Self {
+            transaction: None,
+            priority_charging: false,
+            voltage: 230.0,
+            phases: 3,
+            max_current: 32.0,
+        }
+    }
+}
+
+/// Convert a limit between A (per phase) and W for `phases` phases at `voltage`. Use
+/// `phases = 1` for the per-phase `limit_L2` / `limit_L3` fields.
+#[must_use]
+pub fn convert_limit(
+    limit: f64,
+    from: &ChargingRateUnitEnumType,
+    to: &ChargingRateUnitEnumType,
+    phases: i32,
+    voltage: f64,
+) -> f64 {
+    let factor = voltage * f64::from(phases.max(1));
+    match (from, to) {
+        (ChargingRateUnitEnumType::A, ChargingRateUnitEnumType::W) => limit * factor,
+        (ChargingRateUnitEnumType::W, ChargingRateUnitEnumType::A) => limit / factor,
+        _ => limit,
+    }
+}
+
+/// One applicable period, with limits converted to W.
+#[derive(Debug, Clone)]
+struct Level<'a> {
+    period: &'a ChargingSchedulePeriodType,
+    unit: &'a ChargingRateUnitEnumType,
+    phases: i32,
+    limit: Option<f64>,
+    limit_l2: Option<f64>,
+    limit_l3: Option<f64>,
+    discharge: Option<f64>,
+}
+
+impl StackedProfile for InstalledProfile {
+    type Purpose = ChargingProfilePurposeEnumType;
+    /// Stack level, then EVSE-specific before EVSE 0, then the higher profile `id`.
+    type Rank = (i32, bool, i32);
+
+    fn purpose(&self) -> &ChargingProfilePurposeEnumType {
+        &self.profile.charging_profile_purpose
+    }
+
+    fn rank(&self) -> (i32, bool, i32) {
+        (self.profile.stack_level, self.evse_id != 0, self.profile.id)
+    }
+
+    fn kind(&self) -> ProfileKind {
+        match self.profile.charging_profile_kind {
+            ChargingProfileKindEnumType::Recurring => ProfileKind::Recurring {
+                weekly: self.profile.recurrency_kind == Some(RecurrencyKindEnumType::Weekly),
+            },
+            ChargingProfileKindEnumType::Relative => ProfileKind::Relative,
+            _ => ProfileKind::Absolute,
+        }
+    }
+
+    fn valid_from(&self) -> Option<DateTime<Utc>> {
+        self.profile.valid_from.map(|from| from.inner())
+    }
+
+    fn valid_to(&self) -> Option<DateTime<Utc>> {
+        self.profile.valid_to.map(|to| to.inner())
+    }
+}
+
+fn active_schedule<'a>(
+    profile: &'a ChargingProfileType,
+    ctx: &CompositeContext,
+) -> Option<&'a ChargingScheduleType> {
+    let selected = ctx.transaction.as_ref().and_then(|tx| tx.selected_schedule);
+    selected
+        .and_then(|id| profile.charging_schedule.iter().find(|s| s.id == id))
+        .or_else(|| profile.charging_schedule.first())
+}
+
+fn level_at<'a>(
+    installed: &'a InstalledProfile,
+    anchor: DateTime<Utc>,
+    t: DateTime<Utc>,
+    ctx: &CompositeContext,
+) -> Option<Level<'a>> {
+    let schedule = active_schedule(&installed.profile, ctx)?;
+    let period = period_in_effect(installed, schedule, anchor, t)?;
+    let unit = &schedule.charging_rate_unit;
+    let phases = period.number_phases.unwrap_or(ctx.phases);
+    let watts = |v: Option<f64>, n: i32| {
+        v.map(|v| convert_limit(v, unit, &ChargingRateUnitEnumType::W, n, ctx.voltage))
+    };
+    Some(Level {
+        period,
+        unit,
+        phases,
+        limit: watts(period.limit, phases),
+        limit_l2: watts(period.limit_l2, 1),
+        limit_l3: watts(period.limit_l3, 1),
+        discharge: watts(period.discharge_limit, phases),
+    })
+}
+
+fn applies(installed: &InstalledProfile, evse_id: i32, ctx: &CompositeContext) -> bool {
+    let station_or_evse = installed.evse_id == 0 || installed.evse_id == evse_id;
+    match installed.profile.charging_profile_purpose {
+        ChargingProfilePurposeEnumType::ChargingStationMaxProfile => installed.evse_id == 0,
+        ChargingProfilePurposeEnumType::ChargingStationExternalConstraints
+        | ChargingProfilePurposeEnumType::LocalGeneration => station_or_evse,
+        ChargingProfilePurposeEnumType::TxDefaultProfile => evse_id > 0 && station_or_evse,
+        ChargingProfilePurposeEnumType::PriorityCharging => {
+            evse_id > 0 && station_or_evse && ctx.priority_charging
+        }
+        ChargingProfilePurposeEnumType::TxProfile => {
+            evse_id > 0
+                && installed.evse_id == evse_id
+                && ctx.transaction.as_ref().is_some_and(|tx| {
+                    installed
+                        .profile
+                        .transaction_id
+                        .as_ref()
+                        .is_none_or(|id| *id == tx.transaction_id)
+                })
+        }
+    }
+}
+
+fn winner<'a>(
+    candidates: &[&'a InstalledProfile],
+    purpose: &ChargingProfilePurposeEnumType,
+    anchor: DateTime<Utc>,
+    t: DateTime<Utc>,
+    ctx: &CompositeContext,
+) -> Option<Level<'a>> {
+    charging_schedule::winner(candidates, purpose, |c| level_at(c, anchor, t, ctx))
+}
+
+fn min_opt(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Discharge limits are negative; the one closest to zero is the tightest.
+fn discharge_opt(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn period_at(
+    candidates: &[&InstalledProfile],
+    anchor: DateTime<Utc>,
+    t: DateTime<Utc>,
+    unit: &ChargingRateUnitEnumType,
+    ctx: &CompositeContext,
+) -> ChargingSchedulePeriodType {
+    use ChargingProfilePurposeEnumType as P;
+    let at = |purpose: &P| winner(candidates, purpose, anchor, t, ctx);
+
+    let mut external = at(&P::ChargingStationExternalConstraints);
+    if let (Some(ext), Some(local)) = (external.as_mut(), at(&P::LocalGeneration)) {
+        ext.limit = ext.limit.map(|e| e + local.limit.unwrap_or(0.0));
+    }
+    let tx = at(&P::PriorityCharging)
+        .or_else(|| at(&P::TxProfile))
+        .or_else(|| at(&P::TxDefaultProfile));
+    let levels = [external, at(&P::ChargingStationMaxProfile), tx.clone()];
+
+    let hardware = convert_limit(
+        ctx.max_current,
+        &ChargingRateUnitEnumType::A,
+        &ChargingRateUnitEnumType::W,
+        ctx.phases,
+        ctx.voltage,
+    );
+    let mut limit = hardware;
+    let mut phases = ctx.phases;
+    let (mut limit_l2, mut limit_l3, mut discharge) = (None, None, None);
+    let mut phase_to_use = None;
+    for level in levels.iter().flatten() {
+        if let Some(l) = level.limit.filter(|l| *l < limit) {
+            limit = l;
+            phases = level.phases;
+            phase_to_use = level.period.phase_to_use;
+        }
+        limit_l2 = min_opt(limit_l2, level.limit_l2);
+        limit_l3 = min_opt(limit_l3, level.limit_l3);
+        discharge = discharge_opt(discharge, level.discharge);
+    }
+
+    let back =
+        |w: f64, n: i32| convert_limit(w, &ChargingRateUnitEnumType::W, unit, n, ctx.voltage);
+    let mut period = tx.as_ref().map_or_else(empty_period, |level| {
+        let mut p = level.period.clone();
+        // Setpoints keep their sign (negative = discharge) and follow the unit change.
+        let convert = |v: Option<f64>, n: i32| {
+            v.map(|v| {
+                let w = convert_limit(v, level.unit, &ChargingRateUnitEnumType::W, n, ctx.voltage);
+                back(w.min(limit).max(discharge.unwrap_or(f64::MIN)), n)
+            })
+        };
+        p.setpoint = convert(p.setpoint, level.phases);
+        p.setpoint_l2 = convert(p.setpoint_l2, 1);
+        p.setpoint_l3 = convert(p.setpoint_l3, 1);
+        p
+    });
+    period.start_period = 0;
+    period.limit = Some(back(limit, phases));
+    period.limit_l2 = limit_l2.map(|w| back(w, 1));
+    period.limit_l3 = limit_l3.map(|w| back(w, 1));
+    period.discharge_limit = discharge.map(|w| back(w, phases));
+    period.discharge_limit_l2 = None;
+    period.discharge_limit_l3 = None;
+    period.number_phases = Some(phases);
+    period.phase_to_use = phase_to_use;
+    period
+}
+
+pub(crate) const fn empty_period() -> ChargingSchedulePeriodType {
+    ChargingSchedulePeriodType {
+        start_period: 0,
+        limit: None,
+        limit_l2: None,
+        limit_l3: None,
+        number_phases: None,
+        phase_to_use: None,
+        discharge_limit: None,
+        discharge_limit_l2: None,
+        discharge_limit_l3: None,
+        setpoint: None,
+        setpoint_l2: None,
+        setpoint_l3: None,
+        setpoint_reactive: None,
+        setpoint_reactive_l2: None,
+        setpoint_reactive_l3: None,
+        preconditioning_request: None,
+        evse_sleep: None,
+        v2x_baseline: None,
+        operation_mode: None,
+        v2x_freq_watt_curve: None,
+        v2x_signal_watt_curve: None,
+        custom_data: None,
+    }
+}
+
+/// Composite schedule of `evse_id` (0 = the whole station) for `duration` seconds from
+/// `start`, expressed in `unit`.
+///
+/// Only station-level purposes count for EVSE 0. `TxProfile`s apply while `ctx.transaction`
+/// is set and their `transactionId` (if any) matches. `Relative` profiles start at the
+/// transaction start, or at `start` when there is no transaction. Periods where no profile
+/// applies carry [`CompositeContext::max_current`].
+#[must_use]
+pub fn composite_schedule(
+    profiles: &[InstalledProfile],
+    evse_id: i32,
+    start: DateTime<Utc>,
+    duration: i32,
+    unit: ChargingRateUnitEnumType,
+    ctx: &CompositeContext,
+) -> CompositeScheduleType {
+    let end = start + TimeDelta::seconds(i64::from(duration.max(0)));
+    let anchor = ctx.transaction.as_ref().map_or(start, |tx| tx.started);
+    let candidates: Vec<&InstalledProfile> = profiles
+        .iter()
+        .filter(|p| applies(p, evse_id, ctx))
+        .collect();
+    let points = charging_schedule::change_instants(
+        &candidates,
+        |c| active_schedule(&c.profile, ctx),
+        anchor,
+        start,
+        end,
+    );
+
+    let mut periods: Vec<ChargingSchedulePeriodType> = Vec::new();
+    for t in points {
+        let mut period = period_at(&candidates, anchor, t, &unit, ctx);
+        if let Some(last) = periods.last() {
+            period.start_period = last.start_period;
+            if *last == period {
+                continue;
+            }
+        }
+        period.start_period = i32::try_from((t - start).num_seconds()).unwrap_or(i32::MAX);
+        periods.push(period);
+    }
+
+    CompositeScheduleType {
+        evse_id,
+        duration,
+        schedule_start: DateTimeWrapper::new(start),
+        charging_rate_unit: unit,
+        charging_schedule_period: periods,
+        custom_data: None,
+    }
+}
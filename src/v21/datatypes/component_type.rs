@@ -18,3 +18,11 @@ pub struct ComponentType {
     #[serde(default)]
     pub custom_data: Option<CustomDataType>,
 }
+
+/// Same component: name (case-insensitive), instance, EVSE and connector.
+pub(crate) fn same_component(a: &ComponentType, b: &ComponentType) -> bool {
+    a.name.eq_ignore_ascii_case(&b.name)
+        && a.instance == b.instance
+        && a.evse.as_ref().map(|e| (e.id, e.connector_id))
+            == b.evse.as_ref().map(|e| (e.id, e.connector_id))
+}
@@ -14,3 +14,8 @@ pub struct VariableType {
     #[serde(default)]
     pub custom_data: Option<CustomDataType>,
 }
+
+/// Same variable: name (case-insensitive) and instance.
+pub(crate) fn same_variable(a: &VariableType, b: &VariableType) -> bool {
+    a.name.eq_ignore_ascii_case(&b.name) && a.instance == b.instance
+}
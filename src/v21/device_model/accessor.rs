@@ -0,0 +1,252 @@
+//! Typed handles for well-known controller variables.
+//!
+//! A [`ControllerVariable<T>`] names one component / variable (/ instance) and knows its Rust
+//! type: it builds the `GetVariableDataType` / `SetVariableDataType` for it and parses
+//! `attributeValue`s. The standard controllers' handles live in [`super::controllers`].
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::time::Duration;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use super::store::{ComponentKey, DeviceModel, VariableKey};
+use crate::v21::datatypes::{ComponentType, DateTimeWrapper, VariableType};
+use crate::v21::enumerations::AttributeEnumType;
+use crate::v21::messages::get_variables::{
+    GetVariableDataType, GetVariableResultType, GetVariableStatusEnumType, GetVariablesResponse,
+};
+use crate::v21::messages::set_variables::SetVariableDataType;
+
+/// Conversion between an `attributeValue` string and a Rust value.
+pub trait VariableValue: Sized {
+    /// `None` when `value` is not valid for the type.
+    fn parse_value(value: &str) -> Option<Self>;
+    fn to_value(&self) -> String;
+}
+
+impl VariableValue for bool {
+    fn parse_value(value: &str) -> Option<Self> {
+        if value.eq_ignore_ascii_case("true") {
+            Some(true)
+        } else if value.eq_ignore_ascii_case("false") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+    fn to_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl VariableValue for i32 {
+    fn parse_value(value: &str) -> Option<Self> {
+        value.trim().parse().ok()
+    }
+    fn to_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl VariableValue for f64 {
+    fn parse_value(value: &str) -> Option<Self> {
+        value.trim().parse().ok().filter(|v: &Self| v.is_finite())
+    }
+    fn to_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl VariableValue for String {
+    fn parse_value(value: &str) -> Option<Self> {
+        Some(value.into())
+    }
+    fn to_value(&self) -> String {
+        self.clone()
+    }
+}
+
+/// Whole seconds (`integer` variables with unit `s`).
+impl VariableValue for Duration {
+    fn parse_value(value: &str) -> Option<Self> {
+        value.trim().parse().ok().map(Self::from_secs)
+    }
+    fn to_value(&self) -> String {
+        self.as_secs().to_string()
+    }
+}
+
+impl VariableValue for DateTimeWrapper {
+    fn parse_value(value: &str) -> Option<Self> {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|dt| Self::new(dt.with_timezone(&chrono::Utc)))
+    }
+    fn to_value(&self) -> String {
+        crate::datetime::date_time::serialize(self, serde_json::value::Serializer)
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default()
+    }
+}
+
+/// Comma-separated `MemberList` / `SequenceList`; items use their OCPP wire spelling.
+impl<T: Serialize + DeserializeOwned> VariableValue for Vec<T> {
+    fn parse_value(value: &str) -> Option<Self> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| serde_json::from_value(serde_json::Value::String(s.into())).ok())
+            .collect()
+    }
+    fn to_value(&self) -> String {
+        self.iter()
+            .filter_map(|item| match serde_json::to_value(item) {
+                Ok(serde_json::Value::String(s)) => Some(s),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Typed handle for one component variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ControllerVariable<T> {
+    pub component: &'static str,
+    pub variable: &'static str,
+    pub instance: Option<&'static str>,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T: VariableValue> ControllerVariable<T> {
+    #[must_use]
+    pub const fn new(
+        component: &'static str,
+        variable: &'static str,
+        instance: Option<&'static str>,
+    ) -> Self {
+        Self {
+            component,
+            variable,
+            instance,
+            value: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn component_type(&self) -> ComponentType {
+        ComponentType {
+            evse: None,
+            name: self.component.into(),
+            instance: None,
+            custom_data: None,
+        }
+    }
+
+    #[must_use]
+    pub fn variable_type(&self) -> VariableType {
+        VariableType {
+            name: self.variable.into(),
+            instance: self.instance.map(String::from),
+            custom_data: None,
+        }
+    }
+
+    /// Whether `component` / `variable` address this variable (names case-insensitive).
+    #[must_use]
+    pub fn matches(&self, component: &ComponentType, variable: &VariableType) -> bool {
+        component.name.eq_ignore_ascii_case(self.component)
+            && component.instance.is_none()
+            && variable.name.eq_ignore_ascii_case(self.variable)
+            && variable.instance.as_deref() == self.instance
+    }
+
+    /// `GetVariableDataType` for the `Actual` attribute.
+    #[must_use]
+    pub fn get(&self) -> GetVariableDataType {
+        GetVariableDataType {
+            attribute_type: None,
+            component: self.component_type(),
+            variable: self.variable_type(),
+            custom_data: None,
+        }
+    }
+
+    /// `SetVariableDataType` setting the `Actual` attribute to `value`.
+    #[must_use]
+    pub fn set(&self, value: &T) -> SetVariableDataType {
+        SetVariableDataType {
+            attribute_type: None,
+            attribute_value: value.to_value(),
+            component: self.component_type(),
+            variable: self.variable_type(),
+            custom_data: None,
+        }
+    }
+
+    #[must_use]
+    pub fn parse(&self, value: &str) -> Option<T> {
+        T::parse_value(value)
+    }
+
+    /// Value of an `Accepted` result for this variable's `Actual` attribute.
+    #[must_use]
+    pub fn from_result(&self, result: &GetVariableResultType) -> Option<T> {
+        let actual = matches!(
+            result.attribute_type,
+            None | Some(AttributeEnumType::Actual)
+        );
+        if result.attribute_status != GetVariableStatusEnumType::Accepted
+            || !actual
+            || !self.matches(&result.component, &result.variable)
+        {
+            return None;
+        }
+        result.attribute_value.as_deref().and_then(T::parse_value)
+    }
+
+    /// First usable result for this variable in a `GetVariablesResponse`.
+    #[must_use]
+    pub fn from_response(&self, response: &GetVariablesResponse) -> Option<T> {
+        response
+            .get_variable_result
+            .iter()
+            .find_map(|r| self.from_result(r))
+    }
+
+    fn keys(&self) -> (ComponentKey, VariableKey) {
+        let variable = VariableKey::new(self.variable);
+        (
+            ComponentKey::new(self.component),
+            match self.instance {
+                Some(i) => variable.with_instance(i),
+                None => variable,
+            },
+        )
+    }
+
+    /// Read the `Actual` value from a station-side [`DeviceModel`].
+    #[must_use]
+    pub fn read(&self, model: &DeviceModel) -> Option<T> {
+        let (component, variable) = self.keys();
+        model
+            .value(&component, &variable, &AttributeEnumType::Actual)
+            .and_then(T::parse_value)
+    }
+
+    /// Write the `Actual` value into a [`DeviceModel`] (bypasses mutability checks, like
+    /// [`DeviceModel::set_value`]). Returns `false` if the variable is not in the model.
+    pub fn write(&self, model: &mut DeviceModel, value: &T) -> bool {
+        let (component, variable) = self.keys();
+        model.set_value(
+            &component,
+            &variable,
+            &AttributeEnumType::Actual,
+            value.to_value(),
+        )
+    }
+}
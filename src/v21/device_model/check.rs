@@ -0,0 +1,212 @@
+//! Pre-send checks of `GetVariables` / `SetVariables` items against the catalog.
+//!
+//! [`VariableCatalog`] answers "does this variable exist on this component" from
+//! [`COMPONENT_VARIABLE_MATRIX`] (component rows, then `<generic>` rows) and checks
+//! `attributeValue`s with [`check_value`]. Vendor components and variables are added with
+//! [`VariableCatalog::add_rows`] / [`VariableCatalog::add_variable`] and take precedence over
+//! the standard tables.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::components::is_standard_component;
+use super::matrix::{COMPONENT_VARIABLE_MATRIX, ComponentVariableRow};
+use super::store::{
+    VariableEntry, VariableKey, catalog_row, check_value, entry_from_row, row_matches_instance,
+};
+use crate::v21::datatypes::{ComponentType, VariableType};
+use crate::v21::messages::get_variables::{GetVariableDataType, GetVariablesRequest};
+use crate::v21::messages::notify_report::DataEnumType;
+use crate::v21::messages::set_variables::{SetVariableDataType, SetVariablesRequest};
+
+/// Why an item does not match the catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogMismatch {
+    UnknownComponent,
+    /// The component exists but does not carry this variable (or instance).
+    UnknownVariable,
+    /// `attributeValue` does not fit the variable; `reason` is a
+    /// [`reason_codes`](crate::v21::reason_codes) constant.
+    InvalidValue {
+        data_type: DataEnumType,
+        reason: &'static str,
+    },
+    /// The unit is not one the catalog lists for the variable.
+    UnitMismatch {
+        allowed: Vec<String>,
+    },
+}
+
+/// A mismatch of request item `index`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogIssue {
+    pub index: usize,
+    pub mismatch: CatalogMismatch,
+}
+
+#[derive(Debug, Clone)]
+struct VendorVariable {
+    component: String,
+    entry: VariableEntry,
+}
+
+/// Standard catalog plus vendor extensions.
+#[derive(Debug, Clone, Default)]
+pub struct VariableCatalog {
+    rows: Vec<ComponentVariableRow>,
+    variables: Vec<VendorVariable>,
+}
+
+fn units(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+impl VariableCatalog {
+    /// Standard tables only.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add vendor matrix rows (same shape as [`COMPONENT_VARIABLE_MATRIX`]).
+    pub fn add_rows(&mut self, rows: &[ComponentVariableRow]) -> &mut Self {
+        self.rows.extend_from_slice(rows);
+        self
+    }
+
+    /// Add a vendor variable with full characteristics (limits, values list, …).
+    pub fn add_variable(
+        &mut self,
+        component: impl Into<String>,
+        entry: VariableEntry,
+    ) -> &mut Self {
+        self.variables.push(VendorVariable {
+            component: component.into(),
+            entry,
+        });
+        self
+    }
+
+    /// Whether the component is standard, in the matrix or added by a vendor.
+    #[must_use]
+    pub fn has_component(&self, name: &str) -> bool {
+        is_standard_component(name)
+            || COMPONENT_VARIABLE_MATRIX
+                .iter()
+                .chain(&self.rows)
+                .any(|r| r.component.eq_ignore_ascii_case(name))
+            || self
+                .variables
+                .iter()
+                .any(|v| v.component.eq_ignore_ascii_case(name))
+    }
+
+    fn vendor_row(&self, component: &str, variable: &VariableKey) -> Option<&ComponentVariableRow> {
+        let find = |comp: &str| {
+            self.rows.iter().find(|r| {
+                r.component.eq_ignore_ascii_case(comp)
+                    && r.variable.eq_ignore_ascii_case(&variable.name)
+                    && row_matches_instance(r, variable.instance.as_deref())
+            })
+        };
+        find(component).or_else(|| find("<generic>"))
+    }
+
+    /// Catalog description of `variable` on `component`, plus the allowed units.
+    ///
+    /// # Errors
+    /// [`CatalogMismatch::UnknownComponent`] / [`CatalogMismatch::UnknownVariable`].
+    pub fn lookup(
+        &self,
+        component: &ComponentType,
+        variable: &VariableType,
+    ) -> Result<(VariableEntry, Vec<String>), CatalogMismatch> {
+        let name = component.name.as_str();
+        let key = VariableKey::from(variable);
+        if let Some(v) = self
+            .variables
+            .iter()
+            .find(|v| v.component.eq_ignore_ascii_case(name) && v.entry.variable.matches(&key))
+        {
+            let allowed = v.entry.characteristics.unit.iter().cloned().collect();
+            return Ok((v.entry.clone(), allowed));
+        }
+        if !self.has_component(name) {
+            return Err(CatalogMismatch::UnknownComponent);
+        }
+        let row = self
+            .vendor_row(name, &key)
+            .or_else(|| catalog_row(name, &key.name, key.instance.as_deref()))
+            .ok_or(CatalogMismatch::UnknownVariable)?;
+        Ok((entry_from_row(row, key), units(row.unit)))
+    }
+
+    /// Check that the variable exists on the component.
+    ///
+    /// # Errors
+    /// See [`Self::lookup`].
+    pub fn check_get(&self, data: &GetVariableDataType) -> Result<(), CatalogMismatch> {
+        self.lookup(&data.component, &data.variable).map(|_| ())
+    }
+
+    /// Check existence and that `attributeValue` fits the variable's data type.
+    ///
+    /// # Errors
+    /// See [`Self::lookup`]; [`CatalogMismatch::InvalidValue`] when [`check_value`] fails.
+    pub fn check_set(&self, data: &SetVariableDataType) -> Result<(), CatalogMismatch> {
+        let (entry, _) = self.lookup(&data.component, &data.variable)?;
+        check_value(&entry.characteristics, &data.attribute_value).map_err(|reason| {
+            CatalogMismatch::InvalidValue {
+                data_type: entry.characteristics.data_type.clone(),
+                reason,
+            }
+        })
+    }
+
+    /// Check that `unit` is one the catalog lists for the variable (unitless variables accept
+    /// only an empty unit).
+    ///
+    /// # Errors
+    /// See [`Self::lookup`]; [`CatalogMismatch::UnitMismatch`].
+    pub fn check_unit(
+        &self,
+        component: &ComponentType,
+        variable: &VariableType,
+        unit: &str,
+    ) -> Result<(), CatalogMismatch> {
+        let (_, allowed) = self.lookup(component, variable)?;
+        let ok = if allowed.is_empty() {
+            unit.is_empty()
+        } else {
+            allowed.iter().any(|u| u == unit)
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(CatalogMismatch::UnitMismatch { allowed })
+        }
+    }
+
+    /// All mismatches of a `GetVariablesRequest`.
+    #[must_use]
+    pub fn check_get_variables(&self, request: &GetVariablesRequest) -> Vec<CatalogIssue> {
+        issues(request.get_variable_data.iter().map(|d| self.check_get(d)))
+    }
+
+    /// All mismatches of a `SetVariablesRequest`.
+    #[must_use]
+    pub fn check_set_variables(&self, request: &SetVariablesRequest) -> Vec<CatalogIssue> {
+        issues(request.set_variable_data.iter().map(|d| self.check_set(d)))
+    }
+}
+
+fn issues(results: impl Iterator<Item = Result<(), CatalogMismatch>>) -> Vec<CatalogIssue> {
+    results
+        .enumerate()
+        .filter_map(|(index, r)| r.err().map(|mismatch| CatalogIssue { index, mismatch }))
+        .collect()
+}
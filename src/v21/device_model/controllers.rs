@@ -0,0 +1,1013 @@
+//! AUTO-GENERATED by `tools/gen_device_model.py` — do not edit by hand.
+//!
+//! Typed [`ControllerVariable`] handles for the standard controllers, one module per
+//! controller. Types follow the catalog data type: `integer` with unit `s` is
+//! [`Duration`], `*Measurands` lists are `Vec<MeasurandEnumType>`.
+
+/// `ACDERCtrlr` variables.
+pub mod acder_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// `ModesSupported` (`MemberList`, required).
+    pub const MODES_SUPPORTED: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("ACDERCtrlr", "ModesSupported", None);
+}
+
+/// `AlignedDataCtrlr` variables.
+pub mod aligned_data_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use crate::v21::enumerations::MeasurandEnumType;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    /// `Available` (`boolean`).
+    pub const AVAILABLE: ControllerVariable<bool> =
+        ControllerVariable::new("AlignedDataCtrlr", "Available", None);
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("AlignedDataCtrlr", "Enabled", None);
+    /// `Interval` (`integer`, s, required).
+    pub const INTERVAL: ControllerVariable<Duration> =
+        ControllerVariable::new("AlignedDataCtrlr", "Interval", None);
+    /// `Measurands` (`MemberList`, required).
+    pub const MEASURANDS: ControllerVariable<Vec<MeasurandEnumType>> =
+        ControllerVariable::new("AlignedDataCtrlr", "Measurands", None);
+    /// `SendDuringIdle` (`boolean`).
+    pub const SEND_DURING_IDLE: ControllerVariable<bool> =
+        ControllerVariable::new("AlignedDataCtrlr", "SendDuringIdle", None);
+    /// `SignReadings` (`boolean`).
+    pub const SIGN_READINGS: ControllerVariable<bool> =
+        ControllerVariable::new("AlignedDataCtrlr", "SignReadings", None);
+    /// `SignUpdatedReadings` (`boolean`).
+    pub const SIGN_UPDATED_READINGS: ControllerVariable<bool> =
+        ControllerVariable::new("AlignedDataCtrlr", "SignUpdatedReadings", None);
+    /// `TxEndedInterval` (`integer`, s, required).
+    pub const TX_ENDED_INTERVAL: ControllerVariable<Duration> =
+        ControllerVariable::new("AlignedDataCtrlr", "TxEndedInterval", None);
+    /// `TxEndedMeasurands` (`MemberList`, required).
+    pub const TX_ENDED_MEASURANDS: ControllerVariable<Vec<MeasurandEnumType>> =
+        ControllerVariable::new("AlignedDataCtrlr", "TxEndedMeasurands", None);
+    /// `UpstreamInterval` (`integer`, s).
+    pub const UPSTREAM_INTERVAL: ControllerVariable<Duration> =
+        ControllerVariable::new("AlignedDataCtrlr", "UpstreamInterval", None);
+    /// `UpstreamMeasurands` (`MemberList`).
+    pub const UPSTREAM_MEASURANDS: ControllerVariable<Vec<MeasurandEnumType>> =
+        ControllerVariable::new("AlignedDataCtrlr", "UpstreamMeasurands", None);
+}
+
+/// `AuthCacheCtrlr` variables.
+pub mod auth_cache_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use core::time::Duration;
+
+    /// `Available` (`boolean`).
+    pub const AVAILABLE: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCacheCtrlr", "Available", None);
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCacheCtrlr", "Enabled", None);
+    /// `LifeTime` (`integer`, s).
+    pub const LIFE_TIME: ControllerVariable<Duration> =
+        ControllerVariable::new("AuthCacheCtrlr", "LifeTime", None);
+    /// `Policy` (`OptionList`).
+    pub const POLICY: ControllerVariable<String> =
+        ControllerVariable::new("AuthCacheCtrlr", "Policy", None);
+    /// `Storage` (`integer`, B).
+    pub const STORAGE: ControllerVariable<i32> =
+        ControllerVariable::new("AuthCacheCtrlr", "Storage", None);
+    /// `DisablePostAuthorize` (`boolean`).
+    pub const DISABLE_POST_AUTHORIZE: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCacheCtrlr", "DisablePostAuthorize", None);
+}
+
+/// `AuthCtrlr` variables.
+pub mod auth_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// `AdditionalInfoItemsPerMessage` (`integer`).
+    pub const ADDITIONAL_INFO_ITEMS_PER_MESSAGE: ControllerVariable<i32> =
+        ControllerVariable::new("AuthCtrlr", "AdditionalInfoItemsPerMessage", None);
+    /// `AuthorizeRemoteStart` (`boolean`, required).
+    pub const AUTHORIZE_REMOTE_START: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCtrlr", "AuthorizeRemoteStart", None);
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCtrlr", "Enabled", None);
+    /// `LocalAuthorizeOffline` (`boolean`, required).
+    pub const LOCAL_AUTHORIZE_OFFLINE: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCtrlr", "LocalAuthorizeOffline", None);
+    /// `LocalPreAuthorize` (`boolean`, required).
+    pub const LOCAL_PRE_AUTHORIZE: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCtrlr", "LocalPreAuthorize", None);
+    /// `MasterPassGroupId` (`string`).
+    pub const MASTER_PASS_GROUP_ID: ControllerVariable<String> =
+        ControllerVariable::new("AuthCtrlr", "MasterPassGroupId", None);
+    /// `OfflineTxForUnknownIdEnabled` (`boolean`).
+    pub const OFFLINE_TX_FOR_UNKNOWN_ID_ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCtrlr", "OfflineTxForUnknownIdEnabled", None);
+    /// `DisableRemoteAuthorization` (`boolean`).
+    pub const DISABLE_REMOTE_AUTHORIZATION: ControllerVariable<bool> =
+        ControllerVariable::new("AuthCtrlr", "DisableRemoteAuthorization", None);
+    /// `SupportedIdTokenType` (`MemberList`).
+    pub const SUPPORTED_ID_TOKEN_TYPE: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("AuthCtrlr", "SupportedIdTokenType", None);
+}
+
+/// `BatterySwapCtrlr` variables.
+pub mod battery_swap_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use core::time::Duration;
+
+    /// `TargetSoC` (`integer`, %).
+    pub const TARGET_SO_C: ControllerVariable<i32> =
+        ControllerVariable::new("BatterySwapCtrlr", "TargetSoC", None);
+    /// `MaxSoc` (`integer`, %).
+    pub const MAX_SOC: ControllerVariable<i32> =
+        ControllerVariable::new("BatterySwapCtrlr", "MaxSoc", None);
+    /// `IdToken` (`string`).
+    pub const ID_TOKEN: ControllerVariable<String> =
+        ControllerVariable::new("BatterySwapCtrlr", "IdToken", None);
+    /// `Timeout[In]` (`integer`, s).
+    pub const TIMEOUT_IN: ControllerVariable<Duration> =
+        ControllerVariable::new("BatterySwapCtrlr", "Timeout", Some("In"));
+    /// `Timeout[Out]` (`integer`, s).
+    pub const TIMEOUT_OUT: ControllerVariable<Duration> =
+        ControllerVariable::new("BatterySwapCtrlr", "Timeout", Some("Out"));
+}
+
+/// `CHAdeMOCtrlr` variables.
+pub mod chademo_ctrlr {
+    use super::super::accessor::ControllerVariable;
+
+    /// `SelftestActive` (`boolean`).
+    pub const SELFTEST_ACTIVE: ControllerVariable<bool> =
+        ControllerVariable::new("CHAdeMOCtrlr", "SelftestActive", None);
+    /// `CHAdeMOProtocolNumber` (`integer`).
+    pub const CH_ADE_MO_PROTOCOL_NUMBER: ControllerVariable<i32> =
+        ControllerVariable::new("CHAdeMOCtrlr", "CHAdeMOProtocolNumber", None);
+    /// `VehicleStatus` (`boolean`).
+    pub const VEHICLE_STATUS: ControllerVariable<bool> =
+        ControllerVariable::new("CHAdeMOCtrlr", "VehicleStatus", None);
+    /// `DynamicControl` (`boolean`).
+    pub const DYNAMIC_CONTROL: ControllerVariable<bool> =
+        ControllerVariable::new("CHAdeMOCtrlr", "DynamicControl", None);
+    /// `HighCurrentControl` (`boolean`).
+    pub const HIGH_CURRENT_CONTROL: ControllerVariable<bool> =
+        ControllerVariable::new("CHAdeMOCtrlr", "HighCurrentControl", None);
+    /// `HighVoltageControl` (`boolean`).
+    pub const HIGH_VOLTAGE_CONTROL: ControllerVariable<bool> =
+        ControllerVariable::new("CHAdeMOCtrlr", "HighVoltageControl", None);
+    /// `AutoManufacturerCode` (`integer`).
+    pub const AUTO_MANUFACTURER_CODE: ControllerVariable<i32> =
+        ControllerVariable::new("CHAdeMOCtrlr", "AutoManufacturerCode", None);
+}
+
+/// `ClockCtrlr` variables.
+pub mod clock_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use crate::v21::datatypes::DateTimeWrapper;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    /// `DateTime` (`dateTime`, required).
+    pub const DATE_TIME: ControllerVariable<DateTimeWrapper> =
+        ControllerVariable::new("ClockCtrlr", "DateTime", None);
+    /// `NextTimeOffsetTransitionDateTime` (`dateTime`).
+    pub const NEXT_TIME_OFFSET_TRANSITION_DATE_TIME: ControllerVariable<DateTimeWrapper> =
+        ControllerVariable::new("ClockCtrlr", "NextTimeOffsetTransitionDateTime", None);
+    /// `NtpServerUri` (`string`).
+    pub const NTP_SERVER_URI: ControllerVariable<String> =
+        ControllerVariable::new("ClockCtrlr", "NtpServerUri", None);
+    /// `NtpSource` (`OptionList`).
+    pub const NTP_SOURCE: ControllerVariable<String> =
+        ControllerVariable::new("ClockCtrlr", "NtpSource", None);
+    /// `TimeAdjustmentReportingThreshold` (`integer`, s).
+    pub const TIME_ADJUSTMENT_REPORTING_THRESHOLD: ControllerVariable<Duration> =
+        ControllerVariable::new("ClockCtrlr", "TimeAdjustmentReportingThreshold", None);
+    /// `TimeOffset` (`string`).
+    pub const TIME_OFFSET: ControllerVariable<String> =
+        ControllerVariable::new("ClockCtrlr", "TimeOffset", None);
+    /// `TimeSource` (`SequenceList`, required).
+    pub const TIME_SOURCE: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("ClockCtrlr", "TimeSource", None);
+    /// `TimeZone` (`string`).
+    pub const TIME_ZONE: ControllerVariable<String> =
+        ControllerVariable::new("ClockCtrlr", "TimeZone", None);
+}
+
+/// `CustomizationCtrlr` variables.
+pub mod customization_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// `CustomTriggers` (`MemberList`).
+    pub const CUSTOM_TRIGGERS: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("CustomizationCtrlr", "CustomTriggers", None);
+}
+
+/// `DCDERCtrlr` variables.
+pub mod dcder_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("DCDERCtrlr", "Enabled", None);
+    /// `MaxW` (`decimal`, W, required).
+    pub const MAX_W: ControllerVariable<f64> = ControllerVariable::new("DCDERCtrlr", "MaxW", None);
+    /// `OverExcitedW` (`decimal`, W, required).
+    pub const OVER_EXCITED_W: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "OverExcitedW", None);
+    /// `OverExcitedPF` (`decimal`, required).
+    pub const OVER_EXCITED_PF: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "OverExcitedPF", None);
+    /// `UnderExcitedW` (`decimal`, W, required).
+    pub const UNDER_EXCITED_W: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "UnderExcitedW", None);
+    /// `UnderExcitedPF` (`decimal`, required).
+    pub const UNDER_EXCITED_PF: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "UnderExcitedPF", None);
+    /// `MaxVA` (`decimal`, VA, required).
+    pub const MAX_VA: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "MaxVA", None);
+    /// `MaxVar` (`decimal`, Var, required).
+    pub const MAX_VAR: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "MaxVar", None);
+    /// `MaxVarNeg` (`decimal`, Var, required).
+    pub const MAX_VAR_NEG: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "MaxVarNeg", None);
+    /// `MaxChargeRateW` (`decimal`, W, required).
+    pub const MAX_CHARGE_RATE_W: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "MaxChargeRateW", None);
+    /// `MaxChargeRateVA` (`decimal`, VA, required).
+    pub const MAX_CHARGE_RATE_VA: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "MaxChargeRateVA", None);
+    /// `VNom` (`decimal`, V).
+    pub const V_NOM: ControllerVariable<f64> = ControllerVariable::new("DCDERCtrlr", "VNom", None);
+    /// `MaxV` (`decimal`, V).
+    pub const MAX_V: ControllerVariable<f64> = ControllerVariable::new("DCDERCtrlr", "MaxV", None);
+    /// `MinV` (`decimal`, V).
+    pub const MIN_V: ControllerVariable<f64> = ControllerVariable::new("DCDERCtrlr", "MinV", None);
+    /// `ModesSupported` (`MemberList`, required).
+    pub const MODES_SUPPORTED: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("DCDERCtrlr", "ModesSupported", None);
+    /// `InverterManufacturer` (`string`, required).
+    pub const INVERTER_MANUFACTURER: ControllerVariable<String> =
+        ControllerVariable::new("DCDERCtrlr", "InverterManufacturer", None);
+    /// `InverterModel` (`string`, required).
+    pub const INVERTER_MODEL: ControllerVariable<String> =
+        ControllerVariable::new("DCDERCtrlr", "InverterModel", None);
+    /// `InverterSerialNumber` (`string`).
+    pub const INVERTER_SERIAL_NUMBER: ControllerVariable<String> =
+        ControllerVariable::new("DCDERCtrlr", "InverterSerialNumber", None);
+    /// `InverterSwVersion` (`string`, required).
+    pub const INVERTER_SW_VERSION: ControllerVariable<String> =
+        ControllerVariable::new("DCDERCtrlr", "InverterSwVersion", None);
+    /// `InverterHwVersion` (`string`, required).
+    pub const INVERTER_HW_VERSION: ControllerVariable<String> =
+        ControllerVariable::new("DCDERCtrlr", "InverterHwVersion", None);
+    /// `IslandingDetectionMethod` (`OptionList`).
+    pub const ISLANDING_DETECTION_METHOD: ControllerVariable<String> =
+        ControllerVariable::new("DCDERCtrlr", "IslandingDetectionMethod", None);
+    /// `IslandingDetectionTripTime` (`decimal`, s).
+    pub const ISLANDING_DETECTION_TRIP_TIME: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "IslandingDetectionTripTime", None);
+    /// `ReactiveSusceptance` (`decimal`, s, required).
+    pub const REACTIVE_SUSCEPTANCE: ControllerVariable<f64> =
+        ControllerVariable::new("DCDERCtrlr", "ReactiveSusceptance", None);
+}
+
+/// `DeviceDataCtrlr` variables.
+pub mod device_data_ctrlr {
+    use super::super::accessor::ControllerVariable;
+
+    /// `BytesPerMessage[GetReport]` (`integer`, required).
+    pub const BYTES_PER_MESSAGE_GET_REPORT: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "BytesPerMessage", Some("GetReport"));
+    /// `BytesPerMessage[GetVariables]` (`integer`, required).
+    pub const BYTES_PER_MESSAGE_GET_VARIABLES: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "BytesPerMessage", Some("GetVariables"));
+    /// `BytesPerMessage[SetVariables]` (`integer`, required).
+    pub const BYTES_PER_MESSAGE_SET_VARIABLES: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "BytesPerMessage", Some("SetVariables"));
+    /// `ConfigurationValueSize` (`integer`).
+    pub const CONFIGURATION_VALUE_SIZE: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "ConfigurationValueSize", None);
+    /// `ItemsPerMessage[GetReport]` (`integer`, required).
+    pub const ITEMS_PER_MESSAGE_GET_REPORT: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "ItemsPerMessage", Some("GetReport"));
+    /// `ItemsPerMessage[GetVariables]` (`integer`, required).
+    pub const ITEMS_PER_MESSAGE_GET_VARIABLES: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "ItemsPerMessage", Some("GetVariables"));
+    /// `ItemsPerMessage[SetVariables]` (`integer`, required).
+    pub const ITEMS_PER_MESSAGE_SET_VARIABLES: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "ItemsPerMessage", Some("SetVariables"));
+    /// `ReportingValueSize` (`integer`).
+    pub const REPORTING_VALUE_SIZE: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "ReportingValueSize", None);
+    /// `ValueSize` (`integer`).
+    pub const VALUE_SIZE: ControllerVariable<i32> =
+        ControllerVariable::new("DeviceDataCtrlr", "ValueSize", None);
+}
+
+/// `DisplayMessageCtrlr` variables.
+pub mod display_message_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// `Available` (`boolean`).
+    pub const AVAILABLE: ControllerVariable<bool> =
+        ControllerVariable::new("DisplayMessageCtrlr", "Available", None);
+    /// `DisplayMessages` (`integer`, required).
+    pub const DISPLAY_MESSAGES: ControllerVariable<i32> =
+        ControllerVariable::new("DisplayMessageCtrlr", "DisplayMessages", None);
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("DisplayMessageCtrlr", "Enabled", None);
+    /// `SupportedStates` (`MemberList`, required).
+    pub const SUPPORTED_STATES: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("DisplayMessageCtrlr", "SupportedStates", None);
+    /// `SupportedFormats` (`MemberList`, required).
+    pub const SUPPORTED_FORMATS: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("DisplayMessageCtrlr", "SupportedFormats", None);
+    /// `SupportedPriorities` (`MemberList`, required).
+    pub const SUPPORTED_PRIORITIES: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("DisplayMessageCtrlr", "SupportedPriorities", None);
+    /// `Language` (`OptionList`, required).
+    pub const LANGUAGE: ControllerVariable<String> =
+        ControllerVariable::new("DisplayMessageCtrlr", "Language", None);
+}
+
+/// `ISO15118Ctrlr` variables.
+pub mod iso15118_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    /// `CentralContractValidationAllowed` (`boolean`).
+    pub const CENTRAL_CONTRACT_VALIDATION_ALLOWED: ControllerVariable<bool> =
+        ControllerVariable::new("ISO15118Ctrlr", "CentralContractValidationAllowed", None);
+    /// `ContractValidationOffline` (`boolean`, required).
+    pub const CONTRACT_VALIDATION_OFFLINE: ControllerVariable<bool> =
+        ControllerVariable::new("ISO15118Ctrlr", "ContractValidationOffline", None);
+    /// `SeccId` (`string`).
+    pub const SECC_ID: ControllerVariable<String> =
+        ControllerVariable::new("ISO15118Ctrlr", "SeccId", None);
+    /// `MaxScheduleEntries` (`integer`).
+    pub const MAX_SCHEDULE_ENTRIES: ControllerVariable<i32> =
+        ControllerVariable::new("ISO15118Ctrlr", "MaxScheduleEntries", None);
+    /// `RequestedEnergyTransferMode` (`OptionList`).
+    pub const REQUESTED_ENERGY_TRANSFER_MODE: ControllerVariable<String> =
+        ControllerVariable::new("ISO15118Ctrlr", "RequestedEnergyTransferMode", None);
+    /// `RequestMeteringReceipt` (`boolean`).
+    pub const REQUEST_METERING_RECEIPT: ControllerVariable<bool> =
+        ControllerVariable::new("ISO15118Ctrlr", "RequestMeteringReceipt", None);
+    /// `CountryName` (`string`).
+    pub const COUNTRY_NAME: ControllerVariable<String> =
+        ControllerVariable::new("ISO15118Ctrlr", "CountryName", None);
+    /// `OrganizationName` (`string`).
+    pub const ORGANIZATION_NAME: ControllerVariable<String> =
+        ControllerVariable::new("ISO15118Ctrlr", "OrganizationName", None);
+    /// `PnCEnabled` (`boolean`).
+    pub const PN_C_ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("ISO15118Ctrlr", "PnCEnabled", None);
+    /// `V2GCertificateInstallationEnabled` (`boolean`).
+    pub const V2_G_CERTIFICATE_INSTALLATION_ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("ISO15118Ctrlr", "V2GCertificateInstallationEnabled", None);
+    /// `ContractCertificateInstallationEnabled` (`boolean`).
+    pub const CONTRACT_CERTIFICATE_INSTALLATION_ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new(
+            "ISO15118Ctrlr",
+            "ContractCertificateInstallationEnabled",
+            None,
+        );
+    /// `CertificateStatusSource` (`MemberList`).
+    pub const CERTIFICATE_STATUS_SOURCE: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("ISO15118Ctrlr", "CertificateStatusSource", None);
+    /// `NotificationDelay` (`integer`, s).
+    pub const NOTIFICATION_DELAY: ControllerVariable<Duration> =
+        ControllerVariable::new("ISO15118Ctrlr", "NotificationDelay", None);
+    /// `ServiceRenegotiationSupport` (`boolean`).
+    pub const SERVICE_RENEGOTIATION_SUPPORT: ControllerVariable<bool> =
+        ControllerVariable::new("ISO15118Ctrlr", "ServiceRenegotiationSupport", None);
+    /// `SupportedProviders` (`string`).
+    pub const SUPPORTED_PROVIDERS: ControllerVariable<String> =
+        ControllerVariable::new("ISO15118Ctrlr", "SupportedProviders", None);
+    /// `MaxPriceElements` (`integer`).
+    pub const MAX_PRICE_ELEMENTS: ControllerVariable<i32> =
+        ControllerVariable::new("ISO15118Ctrlr", "MaxPriceElements", None);
+}
+
+/// `LocalAuthListCtrlr` variables.
+pub mod local_auth_list_ctrlr {
+    use super::super::accessor::ControllerVariable;
+
+    /// `Available` (`boolean`).
+    pub const AVAILABLE: ControllerVariable<bool> =
+        ControllerVariable::new("LocalAuthListCtrlr", "Available", None);
+    /// `BytesPerMessage` (`integer`, required).
+    pub const BYTES_PER_MESSAGE: ControllerVariable<i32> =
+        ControllerVariable::new("LocalAuthListCtrlr", "BytesPerMessage", None);
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("LocalAuthListCtrlr", "Enabled", None);
+    /// `Entries` (`integer`, required).
+    pub const ENTRIES: ControllerVariable<i32> =
+        ControllerVariable::new("LocalAuthListCtrlr", "Entries", None);
+    /// `ItemsPerMessage` (`integer`, required).
+    pub const ITEMS_PER_MESSAGE: ControllerVariable<i32> =
+        ControllerVariable::new("LocalAuthListCtrlr", "ItemsPerMessage", None);
+    /// `Storage` (`integer`, B).
+    pub const STORAGE: ControllerVariable<i32> =
+        ControllerVariable::new("LocalAuthListCtrlr", "Storage", None);
+    /// `DisablePostAuthorize` (`boolean`).
+    pub const DISABLE_POST_AUTHORIZE: ControllerVariable<bool> =
+        ControllerVariable::new("LocalAuthListCtrlr", "DisablePostAuthorize", None);
+    /// `SupportsExpiryDateTime` (`boolean`).
+    pub const SUPPORTS_EXPIRY_DATE_TIME: ControllerVariable<bool> =
+        ControllerVariable::new("LocalAuthListCtrlr", "SupportsExpiryDateTime", None);
+}
+
+/// `MonitoringCtrlr` variables.
+pub mod monitoring_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+
+    /// `Available` (`boolean`).
+    pub const AVAILABLE: ControllerVariable<bool> =
+        ControllerVariable::new("MonitoringCtrlr", "Available", None);
+    /// `BytesPerMessage[ClearVariableMonitoring]` (`integer`).
+    pub const BYTES_PER_MESSAGE_CLEAR_VARIABLE_MONITORING: ControllerVariable<i32> =
+        ControllerVariable::new(
+            "MonitoringCtrlr",
+            "BytesPerMessage",
+            Some("ClearVariableMonitoring"),
+        );
+    /// `BytesPerMessage[SetVariableMonitoring]` (`integer`, required).
+    pub const BYTES_PER_MESSAGE_SET_VARIABLE_MONITORING: ControllerVariable<i32> =
+        ControllerVariable::new(
+            "MonitoringCtrlr",
+            "BytesPerMessage",
+            Some("SetVariableMonitoring"),
+        );
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("MonitoringCtrlr", "Enabled", None);
+    /// `ItemsPerMessage[ClearVariableMonitoring]` (`integer`).
+    pub const ITEMS_PER_MESSAGE_CLEAR_VARIABLE_MONITORING: ControllerVariable<i32> =
+        ControllerVariable::new(
+            "MonitoringCtrlr",
+            "ItemsPerMessage",
+            Some("ClearVariableMonitoring"),
+        );
+    /// `ItemsPerMessage[SetVariableMonitoring]` (`integer`, required).
+    pub const ITEMS_PER_MESSAGE_SET_VARIABLE_MONITORING: ControllerVariable<i32> =
+        ControllerVariable::new(
+            "MonitoringCtrlr",
+            "ItemsPerMessage",
+            Some("SetVariableMonitoring"),
+        );
+    /// `OfflineQueuingSeverity` (`integer`).
+    pub const OFFLINE_QUEUING_SEVERITY: ControllerVariable<i32> =
+        ControllerVariable::new("MonitoringCtrlr", "OfflineQueuingSeverity", None);
+    /// `MonitoringBase` (`OptionList`).
+    pub const MONITORING_BASE: ControllerVariable<String> =
+        ControllerVariable::new("MonitoringCtrlr", "MonitoringBase", None);
+    /// `MonitoringLevel` (`integer`).
+    pub const MONITORING_LEVEL: ControllerVariable<i32> =
+        ControllerVariable::new("MonitoringCtrlr", "MonitoringLevel", None);
+    /// `ActiveMonitoringBase` (`OptionList`).
+    pub const ACTIVE_MONITORING_BASE: ControllerVariable<String> =
+        ControllerVariable::new("MonitoringCtrlr", "ActiveMonitoringBase", None);
+    /// `ActiveMonitoringLevel` (`integer`).
+    pub const ACTIVE_MONITORING_LEVEL: ControllerVariable<i32> =
+        ControllerVariable::new("MonitoringCtrlr", "ActiveMonitoringLevel", None);
+    /// `MaxPeriodicEventStreams` (`integer`).
+    pub const MAX_PERIODIC_EVENT_STREAMS: ControllerVariable<i32> =
+        ControllerVariable::new("MonitoringCtrlr", "MaxPeriodicEventStreams", None);
+}
+
+/// `OCPPCommCtrlr` variables.
+pub mod ocpp_comm_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use crate::v21::datatypes::DateTimeWrapper;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    /// `ActiveNetworkProfile` (`string`).
+    pub const ACTIVE_NETWORK_PROFILE: ControllerVariable<String> =
+        ControllerVariable::new("OCPPCommCtrlr", "ActiveNetworkProfile", None);
+    /// `FileTransferProtocols` (`MemberList`, required).
+    pub const FILE_TRANSFER_PROTOCOLS: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("OCPPCommCtrlr", "FileTransferProtocols", None);
+    /// `HeartbeatInterval` (`integer`, s).
+    pub const HEARTBEAT_INTERVAL: ControllerVariable<Duration> =
+        ControllerVariable::new("OCPPCommCtrlr", "HeartbeatInterval", None);
+    /// `MessageTimeout[Default]` (`integer`, s, required).
+    pub const MESSAGE_TIMEOUT_DEFAULT: ControllerVariable<Duration> =
+        ControllerVariable::new("OCPPCommCtrlr", "MessageTimeout", Some("Default"));
+    /// `MessageAttemptInterval[TransactionEvent]` (`integer`, s, required).
+    pub const MESSAGE_ATTEMPT_INTERVAL_TRANSACTION_EVENT: ControllerVariable<Duration> =
+        ControllerVariable::new(
+            "OCPPCommCtrlr",
+            "MessageAttemptInterval",
+            Some("TransactionEvent"),
+        );
+    /// `MessageAttempts[TransactionEvent]` (`integer`, required).
+    pub const MESSAGE_ATTEMPTS_TRANSACTION_EVENT: ControllerVariable<i32> =
+        ControllerVariable::new("OCPPCommCtrlr", "MessageAttempts", Some("TransactionEvent"));
+    /// `NetworkConfigurationPriority` (`string`, required).
+    pub const NETWORK_CONFIGURATION_PRIORITY: ControllerVariable<String> =
+        ControllerVariable::new("OCPPCommCtrlr", "NetworkConfigurationPriority", None);
+    /// `NetworkProfileConnectionAttempts` (`integer`, required).
+    pub const NETWORK_PROFILE_CONNECTION_ATTEMPTS: ControllerVariable<i32> =
+        ControllerVariable::new("OCPPCommCtrlr", "NetworkProfileConnectionAttempts", None);
+    /// `OfflineThreshold` (`integer`, s, required).
+    pub const OFFLINE_THRESHOLD: ControllerVariable<Duration> =
+        ControllerVariable::new("OCPPCommCtrlr", "OfflineThreshold", None);
+    /// `PublicKeyWithSignedMeterValue` (`OptionList`).
+    pub const PUBLIC_KEY_WITH_SIGNED_METER_VALUE: ControllerVariable<String> =
+        ControllerVariable::new("OCPPCommCtrlr", "PublicKeyWithSignedMeterValue", None);
+    /// `QueueAllMessages` (`boolean`).
+    pub const QUEUE_ALL_MESSAGES: ControllerVariable<bool> =
+        ControllerVariable::new("OCPPCommCtrlr", "QueueAllMessages", None);
+    /// `ResetRetries` (`integer`, required).
+    pub const RESET_RETRIES: ControllerVariable<i32> =
+        ControllerVariable::new("OCPPCommCtrlr", "ResetRetries", None);
+    /// `RetryBackOffRandomRange` (`integer`).
+    pub const RETRY_BACK_OFF_RANDOM_RANGE: ControllerVariable<i32> =
+        ControllerVariable::new("OCPPCommCtrlr", "RetryBackOffRandomRange", None);
+    /// `RetryBackOffRepeatTimes` (`integer`).
+    pub const RETRY_BACK_OFF_REPEAT_TIMES: ControllerVariable<i32> =
+        ControllerVariable::new("OCPPCommCtrlr", "RetryBackOffRepeatTimes", None);
+    /// `RetryBackOffWaitMinimum` (`integer`).
+    pub const RETRY_BACK_OFF_WAIT_MINIMUM: ControllerVariable<i32> =
+        ControllerVariable::new("OCPPCommCtrlr", "RetryBackOffWaitMinimum", None);
+    /// `UnlockOnEVSideDisconnect` (`boolean`, required).
+    pub const UNLOCK_ON_EV_SIDE_DISCONNECT: ControllerVariable<bool> =
+        ControllerVariable::new("OCPPCommCtrlr", "UnlockOnEVSideDisconnect", None);
+    /// `WebSocketPingInterval` (`integer`, s).
+    pub const WEB_SOCKET_PING_INTERVAL: ControllerVariable<Duration> =
+        ControllerVariable::new("OCPPCommCtrlr", "WebSocketPingInterval", None);
+    /// `FieldLength` (`integer`).
+    pub const FIELD_LENGTH: ControllerVariable<i32> =
+        ControllerVariable::new("OCPPCommCtrlr", "FieldLength", None);
+    /// `ExternalConfigChangeDate` (`DateTime`).
+    pub const EXTERNAL_CONFIG_CHANGE_DATE: ControllerVariable<DateTimeWrapper> =
+        ControllerVariable::new("OCPPCommCtrlr", "ExternalConfigChangeDate", None);
+}
+
+/// `PaymentCtrlr` variables.
+pub mod payment_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// `Enabled` (`boolean`, required).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("PaymentCtrlr", "Enabled", None);
+    /// `Problem` (`boolean`, required).
+    pub const PROBLEM: ControllerVariable<bool> =
+        ControllerVariable::new("PaymentCtrlr", "Problem", None);
+    /// `AuthorizeDirectPayment` (`boolean`, required).
+    pub const AUTHORIZE_DIRECT_PAYMENT: ControllerVariable<bool> =
+        ControllerVariable::new("PaymentCtrlr", "AuthorizeDirectPayment", None);
+    /// `AuthorizationAmount` (`decimal`, required).
+    pub const AUTHORIZATION_AMOUNT: ControllerVariable<f64> =
+        ControllerVariable::new("PaymentCtrlr", "AuthorizationAmount", None);
+    /// `IncrementalAuthorizationAmount` (`decimal`).
+    pub const INCREMENTAL_AUTHORIZATION_AMOUNT: ControllerVariable<f64> =
+        ControllerVariable::new("PaymentCtrlr", "IncrementalAuthorizationAmount", None);
+    /// `IncrementalAuthorizationThreshold` (`decimal`).
+    pub const INCREMENTAL_AUTHORIZATION_THRESHOLD: ControllerVariable<f64> =
+        ControllerVariable::new("PaymentCtrlr", "IncrementalAuthorizationThreshold", None);
+    /// `PaymentDetails` (`MemberList`, required).
+    pub const PAYMENT_DETAILS: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("PaymentCtrlr", "PaymentDetails", None);
+    /// `SettlementByCSMS` (`boolean`, required).
+    pub const SETTLEMENT_BY_CSMS: ControllerVariable<bool> =
+        ControllerVariable::new("PaymentCtrlr", "SettlementByCSMS", None);
+    /// `ReceiptServerUrl` (`string`, required).
+    pub const RECEIPT_SERVER_URL: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "ReceiptServerUrl", None);
+    /// `ReceiptByCSMS` (`boolean`, required).
+    pub const RECEIPT_BY_CSMS: ControllerVariable<bool> =
+        ControllerVariable::new("PaymentCtrlr", "ReceiptByCSMS", None);
+    /// `Merchant[Id]` (`string`, required).
+    pub const MERCHANT_ID: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "Merchant", Some("Id"));
+    /// `Merchant[TaxId]` (`string`, required).
+    pub const MERCHANT_TAX_ID: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "Merchant", Some("TaxId"));
+    /// `Merchant[Name]` (`string`, required).
+    pub const MERCHANT_NAME: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "Merchant", Some("Name"));
+    /// `Merchant[Address]` (`string`, required).
+    pub const MERCHANT_ADDRESS: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "Merchant", Some("Address"));
+    /// `Merchant[City]` (`string`, required).
+    pub const MERCHANT_CITY: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "Merchant", Some("City"));
+    /// `TerminalID` (`string`, required).
+    pub const TERMINAL_ID: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "TerminalID", None);
+    /// `PaymentServiceProvider` (`string`, required).
+    pub const PAYMENT_SERVICE_PROVIDER: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "PaymentServiceProvider", None);
+    /// `VendorName` (`string`, required).
+    pub const VENDOR_NAME: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "VendorName", None);
+    /// `Model` (`string`, required).
+    pub const MODEL: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "Model", None);
+    /// `SerialNumber` (`string`, required).
+    pub const SERIAL_NUMBER: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "SerialNumber", None);
+    /// `FirmwareVersion` (`string`, required).
+    pub const FIRMWARE_VERSION: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "FirmwareVersion", None);
+    /// `IMSI` (`string`, required).
+    pub const IMSI: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "IMSI", None);
+    /// `ICCID` (`string`, required).
+    pub const ICCID: ControllerVariable<String> =
+        ControllerVariable::new("PaymentCtrlr", "ICCID", None);
+    /// `Connected` (`boolean`, required).
+    pub const CONNECTED: ControllerVariable<bool> =
+        ControllerVariable::new("PaymentCtrlr", "Connected", None);
+}
+
+/// `ReservationCtrlr` variables.
+pub mod reservation_ctrlr {
+    use super::super::accessor::ControllerVariable;
+
+    /// `Available` (`boolean`).
+    pub const AVAILABLE: ControllerVariable<bool> =
+        ControllerVariable::new("ReservationCtrlr", "Available", None);
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("ReservationCtrlr", "Enabled", None);
+    /// `NonEvseSpecific` (`boolean`).
+    pub const NON_EVSE_SPECIFIC: ControllerVariable<bool> =
+        ControllerVariable::new("ReservationCtrlr", "NonEvseSpecific", None);
+}
+
+/// `SampledDataCtrlr` variables.
+pub mod sampled_data_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use crate::v21::enumerations::MeasurandEnumType;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    /// `Available` (`boolean`).
+    pub const AVAILABLE: ControllerVariable<bool> =
+        ControllerVariable::new("SampledDataCtrlr", "Available", None);
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("SampledDataCtrlr", "Enabled", None);
+    /// `SignReadings` (`boolean`).
+    pub const SIGN_READINGS: ControllerVariable<bool> =
+        ControllerVariable::new("SampledDataCtrlr", "SignReadings", None);
+    /// `SignStartedReadings` (`boolean`).
+    pub const SIGN_STARTED_READINGS: ControllerVariable<bool> =
+        ControllerVariable::new("SampledDataCtrlr", "SignStartedReadings", None);
+    /// `SignUpdatedReadings` (`boolean`).
+    pub const SIGN_UPDATED_READINGS: ControllerVariable<bool> =
+        ControllerVariable::new("SampledDataCtrlr", "SignUpdatedReadings", None);
+    /// `TxEndedInterval` (`integer`, s, required).
+    pub const TX_ENDED_INTERVAL: ControllerVariable<Duration> =
+        ControllerVariable::new("SampledDataCtrlr", "TxEndedInterval", None);
+    /// `TxEndedMeasurands` (`MemberList`, required).
+    pub const TX_ENDED_MEASURANDS: ControllerVariable<Vec<MeasurandEnumType>> =
+        ControllerVariable::new("SampledDataCtrlr", "TxEndedMeasurands", None);
+    /// `TxStartedMeasurands` (`MemberList`, required).
+    pub const TX_STARTED_MEASURANDS: ControllerVariable<Vec<MeasurandEnumType>> =
+        ControllerVariable::new("SampledDataCtrlr", "TxStartedMeasurands", None);
+    /// `TxUpdatedInterval` (`integer`, s, required).
+    pub const TX_UPDATED_INTERVAL: ControllerVariable<Duration> =
+        ControllerVariable::new("SampledDataCtrlr", "TxUpdatedInterval", None);
+    /// `TxUpdatedMeasurands` (`MemberList`, required).
+    pub const TX_UPDATED_MEASURANDS: ControllerVariable<Vec<MeasurandEnumType>> =
+        ControllerVariable::new("SampledDataCtrlr", "TxUpdatedMeasurands", None);
+    /// `RegisterValuesWithoutPhases` (`boolean`).
+    pub const REGISTER_VALUES_WITHOUT_PHASES: ControllerVariable<bool> =
+        ControllerVariable::new("SampledDataCtrlr", "RegisterValuesWithoutPhases", None);
+    /// `UpstreamInterval` (`integer`, s).
+    pub const UPSTREAM_INTERVAL: ControllerVariable<Duration> =
+        ControllerVariable::new("SampledDataCtrlr", "UpstreamInterval", None);
+    /// `UpstreamMeasurands` (`MemberList`).
+    pub const UPSTREAM_MEASURANDS: ControllerVariable<Vec<MeasurandEnumType>> =
+        ControllerVariable::new("SampledDataCtrlr", "UpstreamMeasurands", None);
+}
+
+/// `SecurityCtrlr` variables.
+pub mod security_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use core::time::Duration;
+
+    /// `AllowSecurityProfileDowngrade` (`boolean`).
+    pub const ALLOW_SECURITY_PROFILE_DOWNGRADE: ControllerVariable<bool> =
+        ControllerVariable::new("SecurityCtrlr", "AllowSecurityProfileDowngrade", None);
+    /// `AdditionalRootCertificateCheck` (`boolean`).
+    pub const ADDITIONAL_ROOT_CERTIFICATE_CHECK: ControllerVariable<bool> =
+        ControllerVariable::new("SecurityCtrlr", "AdditionalRootCertificateCheck", None);
+    /// `BasicAuthPassword` (`passwordString`).
+    pub const BASIC_AUTH_PASSWORD: ControllerVariable<String> =
+        ControllerVariable::new("SecurityCtrlr", "BasicAuthPassword", None);
+    /// `CertificateEntries` (`integer`, required).
+    pub const CERTIFICATE_ENTRIES: ControllerVariable<i32> =
+        ControllerVariable::new("SecurityCtrlr", "CertificateEntries", None);
+    /// `CertSigningRepeatTimes` (`integer`).
+    pub const CERT_SIGNING_REPEAT_TIMES: ControllerVariable<i32> =
+        ControllerVariable::new("SecurityCtrlr", "CertSigningRepeatTimes", None);
+    /// `CertSigningWaitMinimum` (`integer`, s).
+    pub const CERT_SIGNING_WAIT_MINIMUM: ControllerVariable<Duration> =
+        ControllerVariable::new("SecurityCtrlr", "CertSigningWaitMinimum", None);
+    /// `Identity` (`identifierString`).
+    pub const IDENTITY: ControllerVariable<String> =
+        ControllerVariable::new("SecurityCtrlr", "Identity", None);
+    /// `MaxCertificateChainSize` (`integer`).
+    pub const MAX_CERTIFICATE_CHAIN_SIZE: ControllerVariable<i32> =
+        ControllerVariable::new("SecurityCtrlr", "MaxCertificateChainSize", None);
+    /// `OrganizationName` (`string`, required).
+    pub const ORGANIZATION_NAME: ControllerVariable<String> =
+        ControllerVariable::new("SecurityCtrlr", "OrganizationName", None);
+    /// `SecurityProfile` (`integer`, required).
+    pub const SECURITY_PROFILE: ControllerVariable<i32> =
+        ControllerVariable::new("SecurityCtrlr", "SecurityProfile", None);
+}
+
+/// `SmartChargingCtrlr` variables.
+pub mod smart_charging_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// `ACPhaseSwitchingSupported` (`boolean`).
+    pub const AC_PHASE_SWITCHING_SUPPORTED: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "ACPhaseSwitchingSupported", None);
+    /// `Available` (`boolean`).
+    pub const AVAILABLE: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "Available", None);
+    /// `Enabled` (`boolean`).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "Enabled", None);
+    /// `Entries[ChargingProfiles]` (`integer`, required).
+    pub const ENTRIES_CHARGING_PROFILES: ControllerVariable<i32> =
+        ControllerVariable::new("SmartChargingCtrlr", "Entries", Some("ChargingProfiles"));
+    /// `ExternalControlSignalsEnabled` (`boolean`).
+    pub const EXTERNAL_CONTROL_SIGNALS_ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "ExternalControlSignalsEnabled", None);
+    /// `LimitChangeSignificance` (`decimal`, Percent, required).
+    pub const LIMIT_CHANGE_SIGNIFICANCE: ControllerVariable<f64> =
+        ControllerVariable::new("SmartChargingCtrlr", "LimitChangeSignificance", None);
+    /// `NotifyChargingLimitWithSchedules` (`boolean`).
+    pub const NOTIFY_CHARGING_LIMIT_WITH_SCHEDULES: ControllerVariable<bool> =
+        ControllerVariable::new(
+            "SmartChargingCtrlr",
+            "NotifyChargingLimitWithSchedules",
+            None,
+        );
+    /// `PeriodsPerSchedule` (`integer`, required).
+    pub const PERIODS_PER_SCHEDULE: ControllerVariable<i32> =
+        ControllerVariable::new("SmartChargingCtrlr", "PeriodsPerSchedule", None);
+    /// `Phases3to1` (`boolean`).
+    pub const PHASES3TO1: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "Phases3to1", None);
+    /// `ProfileStackLevel` (`integer`, required).
+    pub const PROFILE_STACK_LEVEL: ControllerVariable<i32> =
+        ControllerVariable::new("SmartChargingCtrlr", "ProfileStackLevel", None);
+    /// `RateUnit` (`MemberList`, required).
+    pub const RATE_UNIT: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("SmartChargingCtrlr", "RateUnit", None);
+    /// `ExternalConstraintsProfileDisallowed` (`boolean`).
+    pub const EXTERNAL_CONSTRAINTS_PROFILE_DISALLOWED: ControllerVariable<bool> =
+        ControllerVariable::new(
+            "SmartChargingCtrlr",
+            "ExternalConstraintsProfileDisallowed",
+            None,
+        );
+    /// `ChargingProfilePersistence[TxProfile]` (`boolean`).
+    pub const CHARGING_PROFILE_PERSISTENCE_TX_PROFILE: ControllerVariable<bool> =
+        ControllerVariable::new(
+            "SmartChargingCtrlr",
+            "ChargingProfilePersistence",
+            Some("TxProfile"),
+        );
+    /// `ChargingProfilePersistence[LocalGeneration]` (`boolean`).
+    pub const CHARGING_PROFILE_PERSISTENCE_LOCAL_GENERATION: ControllerVariable<bool> =
+        ControllerVariable::new(
+            "SmartChargingCtrlr",
+            "ChargingProfilePersistence",
+            Some("LocalGeneration"),
+        );
+    /// `ChargingProfilePersistence[ChargingStationExternalConstraints]` (`boolean`).
+    pub const CHARGING_PROFILE_PERSISTENCE_CHARGING_STATION_EXTERNAL_CONSTRAINTS:
+        ControllerVariable<bool> = ControllerVariable::new(
+        "SmartChargingCtrlr",
+        "ChargingProfilePersistence",
+        Some("ChargingStationExternalConstraints"),
+    );
+    /// `SetpointPriority` (`OptionList`).
+    pub const SETPOINT_PRIORITY: ControllerVariable<String> =
+        ControllerVariable::new("SmartChargingCtrlr", "SetpointPriority", None);
+    /// `MaxExternalConstraintsId` (`integer`).
+    pub const MAX_EXTERNAL_CONSTRAINTS_ID: ControllerVariable<i32> =
+        ControllerVariable::new("SmartChargingCtrlr", "MaxExternalConstraintsId", None);
+    /// `SupportedAdditionalPurposes` (`MemberList`).
+    pub const SUPPORTED_ADDITIONAL_PURPOSES: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("SmartChargingCtrlr", "SupportedAdditionalPurposes", None);
+    /// `SupportsDynamicProfiles` (`boolean`).
+    pub const SUPPORTS_DYNAMIC_PROFILES: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "SupportsDynamicProfiles", None);
+    /// `SupportsMaxOfflineDuration` (`boolean`).
+    pub const SUPPORTS_MAX_OFFLINE_DURATION: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "SupportsMaxOfflineDuration", None);
+    /// `SupportsUseLocalTime` (`boolean`).
+    pub const SUPPORTS_USE_LOCAL_TIME: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "SupportsUseLocalTime", None);
+    /// `SupportsRandomizedDelay` (`boolean`).
+    pub const SUPPORTS_RANDOMIZED_DELAY: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "SupportsRandomizedDelay", None);
+    /// `SupportsLimitAtSoC` (`boolean`).
+    pub const SUPPORTS_LIMIT_AT_SO_C: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "SupportsLimitAtSoC", None);
+    /// `SupportsEvseSleep` (`boolean`).
+    pub const SUPPORTS_EVSE_SLEEP: ControllerVariable<bool> =
+        ControllerVariable::new("SmartChargingCtrlr", "SupportsEvseSleep", None);
+}
+
+/// `TariffCostCtrlr` variables.
+pub mod tariff_cost_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use core::time::Duration;
+
+    /// `Available[Tariff]` (`boolean`).
+    pub const AVAILABLE_TARIFF: ControllerVariable<bool> =
+        ControllerVariable::new("TariffCostCtrlr", "Available", Some("Tariff"));
+    /// `Available[Cost]` (`boolean`).
+    pub const AVAILABLE_COST: ControllerVariable<bool> =
+        ControllerVariable::new("TariffCostCtrlr", "Available", Some("Cost"));
+    /// `Currency` (`string`, required).
+    pub const CURRENCY: ControllerVariable<String> =
+        ControllerVariable::new("TariffCostCtrlr", "Currency", None);
+    /// `Enabled[Tariff]` (`boolean`).
+    pub const ENABLED_TARIFF: ControllerVariable<bool> =
+        ControllerVariable::new("TariffCostCtrlr", "Enabled", Some("Tariff"));
+    /// `Enabled[Cost]` (`boolean`).
+    pub const ENABLED_COST: ControllerVariable<bool> =
+        ControllerVariable::new("TariffCostCtrlr", "Enabled", Some("Cost"));
+    /// `Enabled[RunningCost]` (`boolean`).
+    pub const ENABLED_RUNNING_COST: ControllerVariable<bool> =
+        ControllerVariable::new("TariffCostCtrlr", "Enabled", Some("RunningCost"));
+    /// `Interval[Tariff]` (`integer`, s).
+    pub const INTERVAL_TARIFF: ControllerVariable<Duration> =
+        ControllerVariable::new("TariffCostCtrlr", "Interval", Some("Tariff"));
+    /// `Interval[Cost]` (`integer`, s).
+    pub const INTERVAL_COST: ControllerVariable<Duration> =
+        ControllerVariable::new("TariffCostCtrlr", "Interval", Some("Cost"));
+    /// `MaxElements[Tariff]` (`integer`).
+    pub const MAX_ELEMENTS_TARIFF: ControllerVariable<i32> =
+        ControllerVariable::new("TariffCostCtrlr", "MaxElements", Some("Tariff"));
+    /// `ConditionsSupported[Tariff]` (`boolean`).
+    pub const CONDITIONS_SUPPORTED_TARIFF: ControllerVariable<bool> =
+        ControllerVariable::new("TariffCostCtrlr", "ConditionsSupported", Some("Tariff"));
+    /// `HandleFailedTariff[Tariff]` (`OptionList`).
+    pub const HANDLE_FAILED_TARIFF_TARIFF: ControllerVariable<String> =
+        ControllerVariable::new("TariffCostCtrlr", "HandleFailedTariff", Some("Tariff"));
+}
+
+/// `TxCtrlr` variables.
+pub mod tx_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    /// `ChargingTime` (`decimal`, s).
+    pub const CHARGING_TIME: ControllerVariable<f64> =
+        ControllerVariable::new("TxCtrlr", "ChargingTime", None);
+    /// `EVConnectionTimeOut` (`integer`, s, required).
+    pub const EV_CONNECTION_TIME_OUT: ControllerVariable<Duration> =
+        ControllerVariable::new("TxCtrlr", "EVConnectionTimeOut", None);
+    /// `MaxEnergyOnInvalidId` (`integer`, Wh).
+    pub const MAX_ENERGY_ON_INVALID_ID: ControllerVariable<i32> =
+        ControllerVariable::new("TxCtrlr", "MaxEnergyOnInvalidId", None);
+    /// `StopTxOnEVSideDisconnect` (`boolean`, required).
+    pub const STOP_TX_ON_EV_SIDE_DISCONNECT: ControllerVariable<bool> =
+        ControllerVariable::new("TxCtrlr", "StopTxOnEVSideDisconnect", None);
+    /// `StopTxOnInvalidId` (`boolean`, required).
+    pub const STOP_TX_ON_INVALID_ID: ControllerVariable<bool> =
+        ControllerVariable::new("TxCtrlr", "StopTxOnInvalidId", None);
+    /// `TxBeforeAcceptedEnabled` (`boolean`).
+    pub const TX_BEFORE_ACCEPTED_ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("TxCtrlr", "TxBeforeAcceptedEnabled", None);
+    /// `TxStartPoint` (`MemberList`, required).
+    pub const TX_START_POINT: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("TxCtrlr", "TxStartPoint", None);
+    /// `TxStopPoint` (`MemberList`, required).
+    pub const TX_STOP_POINT: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("TxCtrlr", "TxStopPoint", None);
+    /// `ResumptionTimeout` (`integer`, s).
+    pub const RESUMPTION_TIMEOUT: ControllerVariable<Duration> =
+        ControllerVariable::new("TxCtrlr", "ResumptionTimeout", None);
+    /// `AllowEnergyTransferResumption` (`boolean`).
+    pub const ALLOW_ENERGY_TRANSFER_RESUMPTION: ControllerVariable<bool> =
+        ControllerVariable::new("TxCtrlr", "AllowEnergyTransferResumption", None);
+    /// `SupportedLimits` (`MemberList`).
+    pub const SUPPORTED_LIMITS: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("TxCtrlr", "SupportedLimits", None);
+}
+
+/// `V2XChargingCtrlr` variables.
+pub mod v2_x_charging_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// `Enabled` (`boolean`, required).
+    pub const ENABLED: ControllerVariable<bool> =
+        ControllerVariable::new("V2XChargingCtrlr", "Enabled", None);
+    /// `SupportedEnergyTransferModes` (`MemberList`, required).
+    pub const SUPPORTED_ENERGY_TRANSFER_MODES: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("V2XChargingCtrlr", "SupportedEnergyTransferModes", None);
+    /// `SupportedOperationModes` (`MemberList`, required).
+    pub const SUPPORTED_OPERATION_MODES: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("V2XChargingCtrlr", "SupportedOperationModes", None);
+    /// `LocalFrequencyUpdateThreshold` (`boolean`, mHz).
+    pub const LOCAL_FREQUENCY_UPDATE_THRESHOLD: ControllerVariable<bool> =
+        ControllerVariable::new("V2XChargingCtrlr", "LocalFrequencyUpdateThreshold", None);
+    /// `LocalLoadBalancing[UpperThreshold]` (`decimal`, W).
+    pub const LOCAL_LOAD_BALANCING_UPPER_THRESHOLD: ControllerVariable<f64> =
+        ControllerVariable::new(
+            "V2XChargingCtrlr",
+            "LocalLoadBalancing",
+            Some("UpperThreshold"),
+        );
+    /// `LocalLoadBalancing[LowerThreshold]` (`decimal`, W).
+    pub const LOCAL_LOAD_BALANCING_LOWER_THRESHOLD: ControllerVariable<f64> =
+        ControllerVariable::new(
+            "V2XChargingCtrlr",
+            "LocalLoadBalancing",
+            Some("LowerThreshold"),
+        );
+    /// `LocalLoadBalancing[UpperOffset]` (`decimal`, W).
+    pub const LOCAL_LOAD_BALANCING_UPPER_OFFSET: ControllerVariable<f64> = ControllerVariable::new(
+        "V2XChargingCtrlr",
+        "LocalLoadBalancing",
+        Some("UpperOffset"),
+    );
+    /// `LocalLoadBalancing[LowerOffset]` (`decimal`, W).
+    pub const LOCAL_LOAD_BALANCING_LOWER_OFFSET: ControllerVariable<f64> = ControllerVariable::new(
+        "V2XChargingCtrlr",
+        "LocalLoadBalancing",
+        Some("LowerOffset"),
+    );
+}
+
+/// `WebPaymentsCtrlr` variables.
+pub mod web_payments_ctrlr {
+    use super::super::accessor::ControllerVariable;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    /// `URLTemplate` (`string`, required).
+    pub const URL_TEMPLATE: ControllerVariable<String> =
+        ControllerVariable::new("WebPaymentsCtrlr", "URLTemplate", None);
+    /// `URLParameters` (`MemberList`).
+    pub const URL_PARAMETERS: ControllerVariable<Vec<String>> =
+        ControllerVariable::new("WebPaymentsCtrlr", "URLParameters", None);
+    /// `TOTPVersion` (`string`, required).
+    pub const TOTP_VERSION: ControllerVariable<String> =
+        ControllerVariable::new("WebPaymentsCtrlr", "TOTPVersion", None);
+    /// `ChargingStationId` (`string`).
+    pub const CHARGING_STATION_ID: ControllerVariable<String> =
+        ControllerVariable::new("WebPaymentsCtrlr", "ChargingStationId", None);
+    /// `ValidityTime` (`integer`, s, required).
+    pub const VALIDITY_TIME: ControllerVariable<Duration> =
+        ControllerVariable::new("WebPaymentsCtrlr", "ValidityTime", None);
+    /// `SharedSecret` (`string`, required).
+    pub const SHARED_SECRET: ControllerVariable<String> =
+        ControllerVariable::new("WebPaymentsCtrlr", "SharedSecret", None);
+    /// `Length` (`integer`, m, required).
+    pub const LENGTH: ControllerVariable<i32> =
+        ControllerVariable::new("WebPaymentsCtrlr", "Length", None);
+    /// `QRCodeQuality` (`OptionList`).
+    pub const QR_CODE_QUALITY: ControllerVariable<String> =
+        ControllerVariable::new("WebPaymentsCtrlr", "QRCodeQuality", None);
+}
@@ -1,11 +1,24 @@
 //! OCPP 2.1 standardized device-model catalogs (feature `device_model_catalog`).
 //!
 //! Lookup tables of common component and variable names. Matching is case-insensitive.
+//! [`store::DeviceModel`] is a runtime store seeded from these tables;
+//! [`check::VariableCatalog`] checks `GetVariables` / `SetVariables` items against them;
+//! [`controllers`] holds typed [`accessor::ControllerVariable`] handles for the standard
+//! controllers.
 
+pub mod accessor;
+pub mod check;
 pub mod components;
+pub mod controllers;
 pub mod matrix;
+pub mod store;
 pub mod variables;
 
+pub use accessor::{ControllerVariable, VariableValue};
+pub use check::{CatalogIssue, CatalogMismatch, VariableCatalog};
 pub use components::{STANDARD_COMPONENTS, is_standard_component};
 pub use matrix::{COMPONENT_VARIABLE_MATRIX, ComponentVariableRow, rows_for_component};
+pub use store::{
+    ComponentEntry, ComponentKey, DeviceModel, VariableAttribute, VariableEntry, VariableKey,
+};
 pub use variables::{STANDARD_VARIABLES, VARIABLE_META, VariableMeta, is_standard_variable};
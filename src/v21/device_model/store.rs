@@ -0,0 +1,933 @@
+//! Runtime OCPP 2.1 device model (feature `device_model_catalog`).
+//!
+//! [`DeviceModel`] holds the components, variables and attributes of one charging station and
+//! answers `GetVariables` / `SetVariables` with the per-item statuses required by the spec.
+//! Variables can be seeded from [`COMPONENT_VARIABLE_MATRIX`] / [`VARIABLE_META`].
+//! Base reports come from [`DeviceModel::base_report_data`]; chunk them into
+//! `NotifyReportRequest`s with [`crate::v21::report::build_notify_reports`].
+//!
+//! Component and variable **names** match case-insensitively; instances and EVSE / connector
+//! ids match exactly (an absent EVSE addresses the station-level component).
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::components::STANDARD_COMPONENTS;
+use super::matrix::{COMPONENT_VARIABLE_MATRIX, ComponentVariableRow};
+use super::variables::VARIABLE_META;
+use crate::v21::datatypes::{ComponentType, EVSEType, StatusInfoType, VariableType};
+use crate::v21::enumerations::AttributeEnumType;
+use crate::v21::messages::get_base_report::ReportBaseEnumType;
+use crate::v21::messages::get_variables::{
+    GetVariableDataType, GetVariableResultType, GetVariableStatusEnumType, GetVariablesRequest,
+    GetVariablesResponse,
+};
+use crate::v21::messages::notify_report::{
+    DataEnumType, MutabilityEnumType, ReportDataType, VariableAttributeType,
+    VariableCharacteristicsType,
+};
+use crate::v21::messages::set_variables::{
+    SetVariableDataType, SetVariableResultType, SetVariableStatusEnumType, SetVariablesRequest,
+    SetVariablesResponse,
+};
+use crate::v21::reason_codes;
+use crate::v21::report::MessageLimits;
+
+/// Variables included in a `SummaryInventory` base report.
+pub const SUMMARY_VARIABLES: &[&str] = &["AvailabilityState", "Available", "Problem"];
+
+/// Address of a component: name, optional instance and optional EVSE / connector.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ComponentKey {
+    pub name: String,
+    pub instance: Option<String>,
+    pub evse_id: Option<i32>,
+    pub connector_id: Option<i32>,
+}
+
+impl ComponentKey {
+    /// Station-level component without instance.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn with_evse(mut self, evse_id: i32) -> Self {
+        self.evse_id = Some(evse_id);
+        self.connector_id = None;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_connector(mut self, evse_id: i32, connector_id: i32) -> Self {
+        self.evse_id = Some(evse_id);
+        self.connector_id = Some(connector_id);
+        self
+    }
+
+    /// Case-insensitive name match; instance and EVSE / connector must be equal.
+    #[must_use]
+    pub fn matches(&self, other: &Self) -> bool {
+        self.name.eq_ignore_ascii_case(&other.name)
+            && self.instance == other.instance
+            && self.evse_id == other.evse_id
+            && self.connector_id == other.connector_id
+    }
+
+    /// Wire representation (no `customData`).
+    #[must_use]
+    pub fn to_component_type(&self) -> ComponentType {
+        ComponentType {
+            evse: self.evse_id.map(|id| EVSEType {
+                id,
+                connector_id: self.connector_id,
+                custom_data: None,
+            }),
+            name: self.name.clone(),
+            instance: self.instance.clone(),
+            custom_data: None,
+        }
+    }
+}
+
+impl From<&ComponentType> for ComponentKey {
+    fn from(c: &ComponentType) -> Self {
+        Self {
+            name: c.name.clone(),
+            instance: c.instance.clone(),
+            evse_id: c.evse.as_ref().map(|e| e.id),
+            connector_id: c.evse.as_ref().and_then(|e| e.connector_id),
+        }
+    }
+}
+
+/// Address of a variable within a component: name and optional instance.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VariableKey {
+    pub name: String,
+    pub instance: Option<String>,
+}
+
+impl VariableKey {
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            instance: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Case-insensitive name match; instance must be equal.
+    #[must_use]
+    pub fn matches(&self, other: &Self) -> bool {
+        self.name.eq_ignore_ascii_case(&other.name) && self.instance == other.instance
+    }
+
+    /// Wire representation (no `customData`).
+    #[must_use]
+    pub fn to_variable_type(&self) -> VariableType {
+        VariableType {
+            name: self.name.clone(),
+            instance: self.instance.clone(),
+            custom_data: None,
+        }
+    }
+}
+
+impl From<&VariableType> for VariableKey {
+    fn from(v: &VariableType) -> Self {
+        Self {
+            name: v.name.clone(),
+            instance: v.instance.clone(),
+        }
+    }
+}
+
+/// One attribute (`Actual`, `Target`, `MinSet`, `MaxSet`) of a variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableAttribute {
+    pub attribute_type: AttributeEnumType,
+    pub value: Option<String>,
+    pub mutability: MutabilityEnumType,
+    pub persistent: bool,
+    pub constant: bool,
+}
+
+impl VariableAttribute {
+    /// `ReadWrite`, non-persistent, non-constant attribute without a value.
+    #[must_use]
+    pub const fn new(attribute_type: AttributeEnumType) -> Self {
+        Self {
+            attribute_type,
+            value: None,
+            mutability: MutabilityEnumType::ReadWrite,
+            persistent: false,
+            constant: false,
+        }
+    }
+
+    /// `NotifyReport` wire representation.
+    #[must_use]
+    pub fn to_wire(&self) -> VariableAttributeType {
+        VariableAttributeType {
+            type_: Some(self.attribute_type.clone()),
+            value: self.value.clone(),
+            mutability: Some(self.mutability.clone()),
+            persistent: Some(self.persistent),
+            constant: Some(self.constant),
+            custom_data: None,
+        }
+    }
+}
+
+/// A variable with its characteristics and attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableEntry {
+    pub variable: VariableKey,
+    pub characteristics: VariableCharacteristicsType,
+    pub attributes: Vec<VariableAttribute>,
+    /// Accepted `SetVariables` writes answer `RebootRequired` instead of `Accepted`.
+    pub reboot_required: bool,
+}
+
+impl VariableEntry {
+    /// Variable with a single `ReadWrite` `Actual` attribute and no value.
+    #[must_use]
+    pub fn new(variable: VariableKey, data_type: DataEnumType) -> Self {
+        Self {
+            variable,
+            characteristics: VariableCharacteristicsType {
+                unit: None,
+                data_type,
+                min_limit: None,
+                max_limit: None,
+                max_elements: None,
+                values_list: None,
+                supports_monitoring: false,
+                custom_data: None,
+            },
+            attributes: alloc::vec![VariableAttribute::new(AttributeEnumType::Actual)],
+            reboot_required: false,
+        }
+    }
+
+    #[must_use]
+    pub fn attribute(&self, attribute_type: &AttributeEnumType) -> Option<&VariableAttribute> {
+        self.attributes
+            .iter()
+            .find(|a| &a.attribute_type == attribute_type)
+    }
+
+    pub fn attribute_mut(
+        &mut self,
+        attribute_type: &AttributeEnumType,
+    ) -> Option<&mut VariableAttribute> {
+        self.attributes
+            .iter_mut()
+            .find(|a| &a.attribute_type == attribute_type)
+    }
+
+    /// Value of the `Actual` attribute.
+    #[must_use]
+    pub fn actual(&self) -> Option<&str> {
+        self.attribute(&AttributeEnumType::Actual)
+            .and_then(|a| a.value.as_deref())
+    }
+
+    /// Add an attribute, replacing any existing attribute of the same type.
+    #[must_use]
+    pub fn with_attribute(mut self, attribute: VariableAttribute) -> Self {
+        self.attributes
+            .retain(|a| a.attribute_type != attribute.attribute_type);
+        self.attributes.push(attribute);
+        self
+    }
+
+    /// Set the `Actual` value.
+    #[must_use]
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if let Some(a) = self.attribute_mut(&AttributeEnumType::Actual) {
+            a.value = Some(value);
+        } else {
+            let mut a = VariableAttribute::new(AttributeEnumType::Actual);
+            a.value = Some(value);
+            self.attributes.push(a);
+        }
+        self
+    }
+
+    /// Apply `mutability` to every attribute.
+    #[must_use]
+    pub fn with_mutability(mut self, mutability: &MutabilityEnumType) -> Self {
+        for a in &mut self.attributes {
+            a.mutability = mutability.clone();
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.characteristics.unit = Some(unit.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn with_limits(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.characteristics.min_limit = min;
+        self.characteristics.max_limit = max;
+        self
+    }
+
+    /// Comma-separated allowed values for `OptionList` / `MemberList` / `SequenceList`.
+    #[must_use]
+    pub fn with_values_list(mut self, values_list: impl Into<String>) -> Self {
+        self.characteristics.values_list = Some(values_list.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_elements(mut self, max_elements: i32) -> Self {
+        self.characteristics.max_elements = Some(max_elements);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_monitoring(mut self, supports_monitoring: bool) -> Self {
+        self.characteristics.supports_monitoring = supports_monitoring;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_reboot_required(mut self) -> Self {
+        self.reboot_required = true;
+        self
+    }
+
+    /// At least one attribute is writable (not `ReadOnly`, not constant).
+    #[must_use]
+    pub fn is_configurable(&self) -> bool {
+        self.attributes
+            .iter()
+            .any(|a| !a.constant && a.mutability != MutabilityEnumType::ReadOnly)
+    }
+
+    /// `NotifyReport` item for this variable on `component`.
+    ///
+    /// Values of `WriteOnly` attributes are never reported.
+    #[must_use]
+    pub fn to_report_data(&self, component: &ComponentKey) -> ReportDataType {
+        ReportDataType {
+            component: component.to_component_type(),
+            variable: self.variable.to_variable_type(),
+            variable_attribute: self
+                .attributes
+                .iter()
+                .map(|a| {
+                    let mut wire = a.to_wire();
+                    if a.mutability == MutabilityEnumType::WriteOnly {
+                        wire.value = None;
+                    }
+                    wire
+                })
+                .collect(),
+            variable_characteristics: Some(self.characteristics.clone()),
+            custom_data: None,
+        }
+    }
+}
+
+/// A component and its variables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentEntry {
+    pub component: ComponentKey,
+    pub variables: Vec<VariableEntry>,
+}
+
+impl ComponentEntry {
+    #[must_use]
+    pub const fn new(component: ComponentKey) -> Self {
+        Self {
+            component,
+            variables: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn variable(&self, variable: &VariableKey) -> Option<&VariableEntry> {
+        self.variables.iter().find(|v| v.variable.matches(variable))
+    }
+
+    pub fn variable_mut(&mut self, variable: &VariableKey) -> Option<&mut VariableEntry> {
+        self.variables
+            .iter_mut()
+            .find(|v| v.variable.matches(variable))
+    }
+}
+
+/// Why a component/variable lookup failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LookupError {
+    UnknownComponent,
+    UnknownVariable,
+}
+
+/// In-memory device model of one charging station.
+///
+/// Insertion order is preserved (reports list components and variables in that order).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceModel {
+    components: Vec<ComponentEntry>,
+}
+
+impl DeviceModel {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn components(&self) -> impl Iterator<Item = &ComponentEntry> {
+        self.components.iter()
+    }
+
+    #[must_use]
+    pub fn component(&self, component: &ComponentKey) -> Option<&ComponentEntry> {
+        self.components
+            .iter()
+            .find(|c| c.component.matches(component))
+    }
+
+    pub fn component_mut(&mut self, component: &ComponentKey) -> Option<&mut ComponentEntry> {
+        self.components
+            .iter_mut()
+            .find(|c| c.component.matches(component))
+    }
+
+    /// Get or insert a component.
+    pub fn add_component(&mut self, component: ComponentKey) -> &mut ComponentEntry {
+        let idx = if let Some(idx) = self
+            .components
+            .iter()
+            .position(|c| c.component.matches(&component))
+        {
+            idx
+        } else {
+            self.components.push(ComponentEntry::new(component));
+            self.components.len() - 1
+        };
+        &mut self.components[idx]
+    }
+
+    /// Insert (or replace) a variable on a component, creating the component if needed.
+    pub fn add_variable(
+        &mut self,
+        component: ComponentKey,
+        variable: VariableEntry,
+    ) -> &mut VariableEntry {
+        let entry = self.add_component(component);
+        let idx = if let Some(idx) = entry
+            .variables
+            .iter()
+            .position(|v| v.variable.matches(&variable.variable))
+        {
+            entry.variables[idx] = variable;
+            idx
+        } else {
+            entry.variables.push(variable);
+            entry.variables.len() - 1
+        };
+        &mut entry.variables[idx]
+    }
+
+    /// Insert a catalog variable (see [`catalog_variable`]). Returns `None` when the
+    /// variable is not in the catalog.
+    pub fn add_standard_variable(
+        &mut self,
+        component: ComponentKey,
+        variable: VariableKey,
+    ) -> Option<&mut VariableEntry> {
+        let entry = catalog_variable(&component.name, variable)?;
+        Some(self.add_variable(component, entry))
+    }
+
+    /// Insert every **required** catalog variable of `component` that is not present yet.
+    ///
+    /// Rows whose instance is a template (`<language>`, …) are skipped. Returns the number of
+    /// variables inserted.
+    pub fn seed_required(&mut self, component: &ComponentKey) -> usize {
+        let mut inserted = 0;
+        for row in COMPONENT_VARIABLE_MATRIX
+            .iter()
+            .filter(|r| r.required && r.component.eq_ignore_ascii_case(&component.name))
+        {
+            if is_template_instance(row.instance) {
+                continue;
+            }
+            let key = VariableKey {
+                name: row.variable.to_string(),
+                instance: (!row.instance.is_empty()).then(|| row.instance.to_string()),
+            };
+            let exists = self
+                .component(component)
+                .and_then(|c| c.variable(&key))
+                .is_some();
+            if !exists {
+                self.add_variable(component.clone(), entry_from_row(row, key));
+                inserted += 1;
+            }
+        }
+        inserted
+    }
+
+    /// [`Self::seed_required`] for every standard `*Ctrlr` component at station level.
+    pub fn seed_required_controllers(&mut self) -> usize {
+        STANDARD_COMPONENTS
+            .iter()
+            .filter(|c| c.ends_with("Ctrlr"))
+            .map(|c| self.seed_required(&ComponentKey::new(*c)))
+            .sum()
+    }
+
+    #[must_use]
+    pub fn variable(
+        &self,
+        component: &ComponentKey,
+        variable: &VariableKey,
+    ) -> Option<&VariableEntry> {
+        self.component(component)?.variable(variable)
+    }
+
+    pub fn variable_mut(
+        &mut self,
+        component: &ComponentKey,
+        variable: &VariableKey,
+    ) -> Option<&mut VariableEntry> {
+        self.component_mut(component)?.variable_mut(variable)
+    }
+
+    /// Current value of an attribute.
+    #[must_use]
+    pub fn value(
+        &self,
+        component: &ComponentKey,
+        variable: &VariableKey,
+        attribute_type: &AttributeEnumType,
+    ) -> Option<&str> {
+        self.variable(component, variable)?
+            .attribute(attribute_type)?
+            .value
+            .as_deref()
+    }
+
+    /// Station-side write of an attribute value (e.g. firmware updating `AvailabilityState`).
+    ///
+    /// Bypasses mutability and value checks. Returns `false` if the attribute does not exist.
+    pub fn set_value(
+        &mut self,
+        component: &ComponentKey,
+        variable: &VariableKey,
+        attribute_type: &AttributeEnumType,
+        value: impl Into<String>,
+    ) -> bool {
+        match self
+            .variable_mut(component, variable)
+            .and_then(|v| v.attribute_mut(attribute_type))
+        {
+            Some(a) => {
+                a.value = Some(value.into());
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn find(
+        &self,
+        component: &ComponentKey,
+        variable: &VariableKey,
+    ) -> core::result::Result<(usize, usize), LookupError> {
+        let ci = self
+            .components
+            .iter()
+            .position(|c| c.component.matches(component))
+            .ok_or(LookupError::UnknownComponent)?;
+        let vi = self.components[ci]
+            .variables
+            .iter()
+            .position(|v| v.variable.matches(variable))
+            .ok_or(LookupError::UnknownVariable)?;
+        Ok((ci, vi))
+    }
+
+    /// Answer one `GetVariableDataType` item.
+    #[must_use]
+    pub fn get_variable(&self, data: &GetVariableDataType) -> GetVariableResultType {
+        let attribute_type = data
+            .attribute_type
+            .clone()
+            .unwrap_or(AttributeEnumType::Actual);
+        let (attribute_status, attribute_status_info, attribute_value) = match self.find(
+            &ComponentKey::from(&data.component),
+            &VariableKey::from(&data.variable),
+        ) {
+            Err(LookupError::UnknownComponent) => {
+                (GetVariableStatusEnumType::UnknownComponent, None, None)
+            }
+            Err(LookupError::UnknownVariable) => {
+                (GetVariableStatusEnumType::UnknownVariable, None, None)
+            }
+            Ok((ci, vi)) => match self.components[ci].variables[vi].attribute(&attribute_type) {
+                None => (
+                    GetVariableStatusEnumType::NotSupportedAttributeType,
+                    None,
+                    None,
+                ),
+                Some(a) if a.mutability == MutabilityEnumType::WriteOnly => (
+                    GetVariableStatusEnumType::Rejected,
+                    Some(reason_codes::status_info(reason_codes::WRITE_ONLY)),
+                    None,
+                ),
+                Some(a) => (GetVariableStatusEnumType::Accepted, None, a.value.clone()),
+            },
+        };
+        GetVariableResultType {
+            attribute_status,
+            attribute_status_info,
+            attribute_type: data.attribute_type.clone(),
+            attribute_value,
+            component: data.component.clone(),
+            variable: data.variable.clone(),
+            custom_data: None,
+        }
+    }
+
+    /// Answer a `GetVariablesRequest`; one result per requested item, in order.
+    #[must_use]
+    pub fn get_variables(&self, request: &GetVariablesRequest) -> GetVariablesResponse {
+        GetVariablesResponse {
+            get_variable_result: request
+                .get_variable_data
+                .iter()
+                .map(|d| self.get_variable(d))
+                .collect(),
+            custom_data: None,
+        }
+    }
+
+    /// Apply one `SetVariableDataType` item.
+    ///
+    /// Rejects writes to `ReadOnly` / constant attributes and values that fail
+    /// [`check_value`]. Accepted writes to variables flagged
+    /// [`VariableEntry::reboot_required`] answer `RebootRequired`.
+    pub fn set_variable(&mut self, data: &SetVariableDataType) -> SetVariableResultType {
+        let (attribute_status, attribute_status_info) = self.apply_set(data);
+        SetVariableResultType {
+            attribute_type: data.attribute_type.clone(),
+            attribute_status,
+            attribute_status_info,
+            component: data.component.clone(),
+            variable: data.variable.clone(),
+            custom_data: None,
+        }
+    }
+
+    fn apply_set(
+        &mut self,
+        data: &SetVariableDataType,
+    ) -> (SetVariableStatusEnumType, Option<StatusInfoType>) {
+        let attribute_type = data
+            .attribute_type
+            .clone()
+            .unwrap_or(AttributeEnumType::Actual);
+        let (ci, vi) = match self.find(
+            &ComponentKey::from(&data.component),
+            &VariableKey::from(&data.variable),
+        ) {
+            Ok(found) => found,
+            Err(LookupError::UnknownComponent) => {
+                return (SetVariableStatusEnumType::UnknownComponent, None);
+            }
+            Err(LookupError::UnknownVariable) => {
+                return (SetVariableStatusEnumType::UnknownVariable, None);
+            }
+        };
+        let VariableEntry {
+            characteristics,
+            attributes,
+            reboot_required,
+            ..
+        } = &mut self.components[ci].variables[vi];
+        let Some(attribute) = attributes
+            .iter_mut()
+            .find(|a| a.attribute_type == attribute_type)
+        else {
+            return (SetVariableStatusEnumType::NotSupportedAttributeType, None);
+        };
+        if attribute.constant || attribute.mutability == MutabilityEnumType::ReadOnly {
+            return (
+                SetVariableStatusEnumType::Rejected,
+                Some(reason_codes::status_info(reason_codes::READ_ONLY)),
+            );
+        }
+        if let Err(reason) = check_value(characteristics, &data.attribute_value) {
+            return (
+                SetVariableStatusEnumType::Rejected,
+                Some(reason_codes::status_info(reason)),
+            );
+        }
+        attribute.value = Some(data.attribute_value.clone());
+        if *reboot_required {
+            (SetVariableStatusEnumType::RebootRequired, None)
+        } else {
+            (SetVariableStatusEnumType::Accepted, None)
+        }
+    }
+
+    /// Apply a `SetVariablesRequest`; one result per requested item, in order.
+    pub fn set_variables(&mut self, request: &SetVariablesRequest) -> SetVariablesResponse {
+        SetVariablesResponse {
+            set_variable_result: request
+                .set_variable_data
+                .iter()
+                .map(|d| self.set_variable(d))
+                .collect(),
+            custom_data: None,
+        }
+    }
+
+    /// Every variable as a `ReportDataType` (`FullInventory`), in insertion order.
+    #[must_use]
+    pub fn report_data(&self) -> Vec<ReportDataType> {
+        self.report_data_where(|_| true)
+    }
+
+    /// Report items for a `GetBaseReport`.
+    ///
+    /// * `FullInventory` — every variable
+    /// * `ConfigurationInventory` — variables with at least one writable, non-constant attribute
+    /// * `SummaryInventory` — availability and problem variables ([`SUMMARY_VARIABLES`])
+    #[must_use]
+    pub fn base_report_data(&self, report_base: &ReportBaseEnumType) -> Vec<ReportDataType> {
+        match report_base {
+            ReportBaseEnumType::FullInventory => self.report_data(),
+            ReportBaseEnumType::ConfigurationInventory => {
+                self.report_data_where(VariableEntry::is_configurable)
+            }
+            ReportBaseEnumType::SummaryInventory => self.report_data_where(|v| {
+                SUMMARY_VARIABLES
+                    .iter()
+                    .any(|n| n.eq_ignore_ascii_case(&v.variable.name))
+            }),
+        }
+    }
+
+    fn report_data_where(&self, keep: impl Fn(&VariableEntry) -> bool) -> Vec<ReportDataType> {
+        self.components
+            .iter()
+            .flat_map(|c| {
+                c.variables
+                    .iter()
+                    .filter(|v| keep(v))
+                    .map(|v| v.to_report_data(&c.component))
+            })
+            .collect()
+    }
+
+    /// `DeviceDataCtrlr.ItemsPerMessage` / `BytesPerMessage` for `instance`
+    /// (`"GetReport"`, `"GetVariables"`, `"SetVariables"`). Missing or unparsable values are
+    /// treated as unlimited.
+    #[must_use]
+    pub fn message_limits(&self, instance: &str) -> MessageLimits {
+        let read = |name: &str| {
+            self.value(
+                &ComponentKey::new("DeviceDataCtrlr"),
+                &VariableKey::new(name).with_instance(instance),
+                &AttributeEnumType::Actual,
+            )
+            .and_then(|v| v.parse::<usize>().ok())
+        };
+        MessageLimits::new(read("ItemsPerMessage"), read("BytesPerMessage"))
+    }
+}
+
+/// Map a catalog `data_type` string (`decimal`, `DateTime`, `passwordString`, …) to
+/// [`DataEnumType`]. Unknown or empty spellings map to `String`.
+#[must_use]
+pub fn data_type_from_catalog(data_type: &str) -> DataEnumType {
+    const TABLE: &[(&str, DataEnumType)] = &[
+        ("decimal", DataEnumType::Decimal),
+        ("integer", DataEnumType::Integer),
+        ("boolean", DataEnumType::Boolean),
+        ("dateTime", DataEnumType::DateTime),
+        ("OptionList", DataEnumType::OptionList),
+        ("MemberList", DataEnumType::MemberList),
+        ("SequenceList", DataEnumType::SequenceList),
+    ];
+    TABLE
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(data_type))
+        .map_or(DataEnumType::String, |(_, t)| t.clone())
+}
+
+/// Catalog instance column holds a placeholder (`<language>`, `1, 2 .. 20`) rather than a
+/// concrete instance name.
+pub(crate) fn is_template_instance(instance: &str) -> bool {
+    instance.contains('<') || instance.contains(',')
+}
+
+/// Whether a matrix row describes `instance` of its variable.
+pub(crate) fn row_matches_instance(row: &ComponentVariableRow, instance: Option<&str>) -> bool {
+    instance.map_or(row.instance.is_empty(), |i| {
+        is_template_instance(row.instance) || row.instance.eq_ignore_ascii_case(i)
+    })
+}
+
+/// Catalog row for `variable` on `component`, falling back to `<generic>` rows.
+pub(crate) fn catalog_row(
+    component: &str,
+    variable: &str,
+    instance: Option<&str>,
+) -> Option<&'static ComponentVariableRow> {
+    let find = |comp: &str| {
+        COMPONENT_VARIABLE_MATRIX.iter().find(|r| {
+            r.component.eq_ignore_ascii_case(comp)
+                && r.variable.eq_ignore_ascii_case(variable)
+                && row_matches_instance(r, instance)
+        })
+    };
+    find(component).or_else(|| find("<generic>"))
+}
+
+pub(crate) fn entry_from_row(row: &ComponentVariableRow, variable: VariableKey) -> VariableEntry {
+    entry_from_meta(row.data_type, row.unit, variable)
+}
+
+fn entry_from_meta(data_type: &str, unit: &str, variable: VariableKey) -> VariableEntry {
+    let entry = VariableEntry::new(variable, data_type_from_catalog(data_type));
+    // Catalog units may list alternatives ("W, kW"); the first is the canonical one.
+    match unit.split(',').next().map(str::trim) {
+        Some(u) if !u.is_empty() => entry.with_unit(u),
+        _ => entry,
+    }
+}
+
+/// Build a [`VariableEntry`] for a catalog variable.
+///
+/// Looks up `component`'s matrix rows, then `<generic>` rows, then [`VARIABLE_META`] (any
+/// component). Returns `None` when the variable name is not in the catalog.
+#[must_use]
+pub fn catalog_variable(component: &str, variable: VariableKey) -> Option<VariableEntry> {
+    if let Some(row) = catalog_row(component, &variable.name, variable.instance.as_deref()) {
+        return Some(entry_from_row(row, variable));
+    }
+    let meta = VARIABLE_META
+        .iter()
+        .find(|m| m.name.eq_ignore_ascii_case(&variable.name))?;
+    Some(entry_from_meta(meta.data_type, meta.unit, variable))
+}
+
+fn list_items(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn check_range(
+    characteristics: &VariableCharacteristicsType,
+    value: f64,
+) -> core::result::Result<(), &'static str> {
+    if characteristics.min_limit.is_some_and(|min| value < min)
+        || characteristics.max_limit.is_some_and(|max| value > max)
+    {
+        return Err(reason_codes::VALUE_OUT_OF_RANGE);
+    }
+    Ok(())
+}
+
+/// Check `value` against a variable's data type, limits and values list.
+///
+/// For `string` variables `maxLimit` bounds the length; for list types `maxElements` bounds
+/// the member count.
+///
+/// # Errors
+/// Returns the [`reason_codes`] constant describing the first mismatch.
+#[allow(clippy::cast_precision_loss)]
+pub fn check_value(
+    characteristics: &VariableCharacteristicsType,
+    value: &str,
+) -> core::result::Result<(), &'static str> {
+    let allowed = characteristics.values_list.as_deref();
+    let in_list = |item: &str| allowed.is_none_or(|l| list_items(l).any(|a| a == item));
+    match characteristics.data_type {
+        DataEnumType::Integer => {
+            let n: i64 = value.parse().map_err(|_| reason_codes::INVALID_VALUE)?;
+            check_range(characteristics, n as f64)
+        }
+        DataEnumType::Decimal => {
+            let n: f64 = value.parse().map_err(|_| reason_codes::INVALID_VALUE)?;
+            if !n.is_finite() {
+                return Err(reason_codes::INVALID_VALUE);
+            }
+            check_range(characteristics, n)
+        }
+        DataEnumType::Boolean => {
+            if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+                Ok(())
+            } else {
+                Err(reason_codes::INVALID_VALUE)
+            }
+        }
+        DataEnumType::DateTime => chrono::DateTime::parse_from_rfc3339(value)
+            .map(|_| ())
+            .map_err(|_| reason_codes::INVALID_VALUE),
+        DataEnumType::String => {
+            let len = value.chars().count() as f64;
+            if characteristics.max_limit.is_some_and(|max| len > max) {
+                return Err(reason_codes::VALUE_OUT_OF_RANGE);
+            }
+            Ok(())
+        }
+        DataEnumType::OptionList => {
+            if in_list(value) {
+                Ok(())
+            } else {
+                Err(reason_codes::INVALID_VALUE)
+            }
+        }
+        DataEnumType::MemberList | DataEnumType::SequenceList => {
+            let items: Vec<&str> = list_items(value).collect();
+            if !items.iter().all(|i| in_list(i)) {
+                return Err(reason_codes::INVALID_VALUE);
+            }
+            if characteristics.data_type == DataEnumType::MemberList
+                && items
+                    .iter()
+                    .enumerate()
+                    .any(|(i, a)| items[..i].contains(a))
+            {
+                return Err(reason_codes::INVALID_VALUE);
+            }
+            if characteristics
+                .max_elements
+                .is_some_and(|max| usize::try_from(max).is_ok_and(|max| items.len() > max))
+            {
+                return Err(reason_codes::TOO_MANY_ELEMENTS);
+            }
+            Ok(())
+        }
+    }
+}
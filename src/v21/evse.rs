@@ -0,0 +1,13 @@
+//! The station's EVSEs as seen by the station-side stores ([`super::charging_profiles`],
+//! [`super::tariffs`]).
+
+/// What a store needs to know about the station's EVSEs.
+pub trait EvseState {
+    fn evse_exists(&self, evse_id: i32) -> bool;
+    /// `transactionId` of the transaction active on `evse_id`.
+    fn transaction_id(&self, evse_id: i32) -> Option<&str>;
+    /// An ISO 15118 session on `evse_id` has not yet sent `NotifyEVChargingNeedsRequest`.
+    fn awaiting_charging_needs(&self, _evse_id: i32) -> bool {
+        false
+    }
+}
@@ -0,0 +1,419 @@
+//! ISO 15118 schedule negotiation around `NotifyEVChargingNeeds` /
+//! `NotifyEVChargingSchedule`.
+//!
+//! [`EvNeeds::from_request`] reads the EV's limits from either parameter set: ISO 15118-2
+//! (`acChargingParameters` / `dcChargingParameters`) or ISO 15118-20
+//! (`v2xChargingParameters`, which wins when present). [`offer_profile`] turns those needs
+//! and a CSMS budget into a `TxProfile` the station can offer the EV, and
+//! [`verify_ev_schedule`] checks the schedule the EV picked against that offer.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::composite::{convert_limit, empty_period};
+use super::datatypes::{
+    ChargingProfileType, ChargingSchedulePeriodType, ChargingScheduleType, DateTimeWrapper,
+};
+use super::enumerations::{
+    ChargingProfileKindEnumType, ChargingProfilePurposeEnumType, ChargingRateUnitEnumType,
+};
+use super::messages::notify_ev_charging_needs::NotifyEVChargingNeedsRequest;
+use super::messages::notify_ev_charging_schedule::NotifyEVChargingScheduleRequest;
+use crate::load_balancing::{FairnessPolicy, SessionDemand, SiteLimit, floor_tenth, plan};
+
+/// Which ISO 15118 parameter set the needs came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterSet {
+    /// ISO 15118-2: AC or DC charging parameters.
+    Iso2,
+    /// ISO 15118-20: V2X charging parameters.
+    Iso20,
+}
+
+/// EV charging needs in W / Wh, whatever the parameter set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvNeeds {
+    pub parameter_set: ParameterSet,
+    pub max_power: f64,
+    pub min_power: f64,
+    /// Energy the EV asks for (Wh): `energyAmount` (-2) or `evTargetEnergyRequest` (-20).
+    pub energy_request: Option<f64>,
+    /// Energy the EV needs at least (Wh, -20 only).
+    pub min_energy_request: Option<f64>,
+    /// Highest discharge power (W, -20 only), as a positive number.
+    pub max_discharge_power: Option<f64>,
+    pub departure: Option<DateTime<Utc>>,
+    pub max_schedule_tuples: Option<usize>,
+}
+
+fn watts(amps: f64, phases: i32, voltage: f64) -> f64 {
+    convert_limit(
+        amps,
+        &ChargingRateUnitEnumType::A,
+        &ChargingRateUnitEnumType::W,
+        phases,
+        voltage,
+    )
+}
+
+impl EvNeeds {
+    /// Needs of `request`; AC currents are converted with `phases` at `voltage`. `None`
+    /// when the EV sent neither AC, DC nor V2X parameters.
+    #[must_use]
+    pub fn from_request(
+        request: &NotifyEVChargingNeedsRequest,
+        phases: i32,
+        voltage: f64,
+    ) -> Option<Self> {
+        let needs = &request.charging_needs;
+        let mut result = Self {
+            parameter_set: ParameterSet::Iso2,
+            max_power: 0.0,
+            min_power: 0.0,
+            energy_request: None,
+            min_energy_request: None,
+            max_discharge_power: None,
+            departure: needs.departure_time.map(|d| d.inner()),
+            max_schedule_tuples: request
+                .max_schedule_tuples
+                .and_then(|n| usize::try_from(n).ok()),
+        };
+        if let Some(v2x) = &needs.v2x_charging_parameters {
+            let current = |amps: Option<f64>| amps.map(|a| watts(a, phases, voltage));
+            result.parameter_set = ParameterSet::Iso20;
+            result.max_power = v2x
+                .max_charge_power
+                .or_else(|| current(v2x.max_charge_current))?;
+            result.min_power = v2x
+                .min_charge_power
+                .or_else(|| current(v2x.min_charge_current))
+                .unwrap_or(0.0);
+            result.energy_request = v2x.ev_target_energy_request.or(v2x.ev_max_energy_request);
+            result.min_energy_request = v2x.ev_min_energy_request;
+            result.max_discharge_power = v2x
+                .max_discharge_power
+                .or_else(|| current(v2x.max_discharge_current))
+                .map(f64::abs);
+        } else if let Some(dc) = &needs.dc_charging_parameters {
+            result.max_power = dc
+                .ev_max_power
+                .unwrap_or(dc.ev_max_current * dc.ev_max_voltage);
+            result.energy_request = dc.energy_amount;
+        } else if let Some(ac) = &needs.ac_charging_parameters {
+            result.max_power = watts(ac.ev_max_current, phases, voltage);
+            result.min_power = watts(ac.ev_min_current, phases, voltage);
+            result.energy_request = Some(ac.energy_amount);
+        } else {
+            return None;
+        }
+        Some(result)
+    }
+}
+
+/// Identity and shape of the offered profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfferOptions {
+    /// Profile `id`; also used as the schedule `id`.
+    pub profile_id: i32,
+    pub stack_level: i32,
+    pub transaction_id: String,
+    pub unit: ChargingRateUnitEnumType,
+    pub phases: i32,
+    pub voltage: f64,
+    /// Schedule length when the EV gave no departure time, in seconds.
+    pub default_duration: i32,
+    /// Offer the EV's discharge power as `dischargeLimit` (-20 only).
+    pub allow_discharge: bool,
+}
+
+impl OfferOptions {
+    /// W, three phases at 230 V, 24 h without a departure time, no discharging.
+    #[must_use]
+    pub fn new(profile_id: i32, transaction_id: impl Into<String>) -> Self {
+        Self {
+            profile_id,
+            stack_level: 0,
+            transaction_id: transaction_id.into(),
+            unit: ChargingRateUnitEnumType::W,
+            phases: 3,
+            voltage: 230.0,
+            default_duration: 86_400,
+            allow_discharge: false,
+        }
+    }
+}
+
+/// Profile to offer and what it allows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfferedProfile {
+    pub profile: ChargingProfileType,
+    /// Most energy (Wh) the profile lets through before departure.
+    pub deliverable_energy: f64,
+    /// How much of the EV's energy request (Wh) the budget cannot cover.
+    pub shortfall: f64,
+}
+
+/// Offer the EV the most the budget allows from `now` until its departure.
+///
+/// Per period the budget is capped at the EV's maximum, or 0 where that is below its
+/// minimum. With
+/// `maxScheduleTuples` set, the tail beyond it is folded into one period at its lowest
+/// limit so the offer stays within the budget.
+#[must_use]
+pub fn offer_profile(
+    needs: &EvNeeds,
+    budget: &SiteLimit,
+    now: DateTime<Utc>,
+    options: &OfferOptions,
+) -> OfferedProfile {
+    let departure = needs
+        .departure
+        .filter(|d| *d > now)
+        .unwrap_or_else(|| now + TimeDelta::seconds(i64::from(options.default_duration)));
+    let duration = i32::try_from((departure - now).num_seconds()).unwrap_or(i32::MAX);
+    let demand = SessionDemand {
+        started: now,
+        max_power: needs.max_power,
+        min_power: needs.min_power,
+        energy_needed: None,
+        departure: None,
+    };
+    let planned = plan(budget, &[demand], FairnessPolicy::EqualShare, now, duration);
+    let mut steps: Vec<(i32, f64)> = planned
+        .first()
+        .map(|p| {
+            p.periods
+                .iter()
+                .map(|p| (p.start_offset, p.power))
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(max) = needs.max_schedule_tuples.filter(|max| *max > 0) {
+        if steps.len() > max {
+            let floor = steps[max - 1..]
+                .iter()
+                .map(|(_, w)| *w)
+                .fold(f64::INFINITY, f64::min);
+            steps.truncate(max);
+            steps[max - 1].1 = floor;
+        }
+    }
+
+    let mut deliverable_energy = 0.0;
+    let mut periods: Vec<ChargingSchedulePeriodType> = Vec::new();
+    for (i, (start, power)) in steps.iter().enumerate() {
+        let end = steps.get(i + 1).map_or(duration, |(next, _)| *next);
+        let hours = f64::from(end - start) / 3600.0;
+        deliverable_energy += power * hours;
+        let limit = floor_tenth(convert_limit(
+            *power,
+            &ChargingRateUnitEnumType::W,
+            &options.unit,
+            options.phases,
+            options.voltage,
+        ));
+        let discharge_limit = needs
+            .max_discharge_power
+            .filter(|_| options.allow_discharge)
+            .map(|w| {
+                -floor_tenth(convert_limit(
+                    w,
+                    &ChargingRateUnitEnumType::W,
+                    &options.unit,
+                    options.phases,
+                    options.voltage,
+                ))
+            });
+        periods.push(ChargingSchedulePeriodType {
+            start_period: *start,
+            limit: Some(limit),
+            number_phases: Some(options.phases),
+            discharge_limit,
+            ..empty_period()
+        });
+    }
+    let shortfall = needs
+        .energy_request
+        .map_or(0.0, |wh| (wh - deliverable_energy).max(0.0));
+
+    let profile = ChargingProfileType {
+        id: options.profile_id,
+        stack_level: options.stack_level,
+        charging_profile_purpose: ChargingProfilePurposeEnumType::TxProfile,
+        charging_profile_kind: ChargingProfileKindEnumType::Absolute,
+        recurrency_kind: None,
+        valid_from: None,
+        valid_to: Some(DateTimeWrapper::new(departure)),
+        transaction_id: Some(options.transaction_id.clone()),
+        max_offline_duration: None,
+        charging_schedule: vec![ChargingScheduleType {
+            id: options.profile_id,
+            limit_at_so_c: None,
+            start_schedule: Some(DateTimeWrapper::new(now)),
+            duration: Some(duration),
+            charging_rate_unit: options.unit.clone(),
+            min_charging_rate: None,
+            power_tolerance: None,
+            signature_id: None,
+            digest_value: None,
+            use_local_time: None,
+            charging_schedule_period: periods,
+            randomized_delay: None,
+            sales_tariff: None,
+            absolute_price_schedule: None,
+            price_level_schedule: None,
+            custom_data: None,
+        }],
+        invalid_after_offline_duration: None,
+        dyn_update_interval: None,
+        dyn_update_time: None,
+        price_schedule_signature: None,
+        custom_data: None,
+    };
+    OfferedProfile {
+        profile,
+        deliverable_energy,
+        shortfall,
+    }
+}
+
+/// Why an EV-proposed schedule does not fit the offered profile. Limits are in the EV
+/// schedule's `chargingRateUnit`; `start_period` identifies the EV's period.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleViolation {
+    /// `selectedChargingScheduleId` is not a schedule of the offered profile.
+    UnknownSchedule { schedule_id: i32 },
+    /// More periods than the EV was allowed to send.
+    TooManyPeriods { max: usize, actual: usize },
+    /// Charging at `at` where the offer allows nothing (before it starts or after it ends).
+    OutsideOffer {
+        start_period: i32,
+        at: DateTime<Utc>,
+    },
+    /// Charging above the offered limit from `at`.
+    ExceedsLimit {
+        start_period: i32,
+        at: DateTime<Utc>,
+        offered: f64,
+        requested: f64,
+    },
+    /// Discharging beyond the offered `dischargeLimit` (0 when none was offered) from `at`.
+    ExceedsDischargeLimit {
+        start_period: i32,
+        at: DateTime<Utc>,
+        offered: f64,
+        requested: f64,
+    },
+}
+
+fn to_watts(limit: f64, unit: &ChargingRateUnitEnumType, phases: i32, voltage: f64) -> f64 {
+    convert_limit(limit, unit, &ChargingRateUnitEnumType::W, phases, voltage)
+}
+
+fn from_watts(watts: f64, unit: &ChargingRateUnitEnumType, phases: i32, voltage: f64) -> f64 {
+    convert_limit(watts, &ChargingRateUnitEnumType::W, unit, phases, voltage)
+}
+
+/// Check the schedule the EV chose (`NotifyEVChargingScheduleRequest`) against `offered`.
+///
+/// The EV schedule is anchored at its `startSchedule`, else at `timeBase`; limits in A are
+/// compared in W using each period's `numberPhases` (`phases` when absent) at `voltage`.
+/// Returns every violation found, or nothing when the schedule fits.
+#[must_use]
+pub fn verify_ev_schedule(
+    offered: &ChargingProfileType,
+    proposal: &NotifyEVChargingScheduleRequest,
+    max_schedule_tuples: Option<usize>,
+    phases: i32,
+    voltage: f64,
+) -> Vec<ScheduleViolation> {
+    let mut violations = Vec::new();
+    let ev = &proposal.charging_schedule;
+    let time_base = proposal.time_base.inner();
+    let selected = proposal.selected_charging_schedule_id;
+    let Some(offer) = offered
+        .charging_schedule
+        .iter()
+        .find(|s| selected.is_none_or(|id| s.id == id))
+    else {
+        violations.push(ScheduleViolation::UnknownSchedule {
+            schedule_id: selected.unwrap_or_default(),
+        });
+        return violations;
+    };
+    let actual = ev.charging_schedule_period.len();
+    if let Some(max) = max_schedule_tuples.filter(|max| actual > *max) {
+        violations.push(ScheduleViolation::TooManyPeriods { max, actual });
+    }
+
+    let offer_points: Vec<DateTime<Utc>> = offer
+        .effective_periods(time_base)
+        .flat_map(|p| [Some(p.start), p.end])
+        .flatten()
+        .collect();
+    for period in ev.effective_periods(time_base) {
+        let ev_phases = period.period.number_phases.unwrap_or(phases);
+        let charge = period.period.limit.unwrap_or(0.0);
+        let discharge = period
+            .period
+            .discharge_limit
+            .unwrap_or(0.0)
+            .min(charge.min(0.0));
+        // Check at the EV period start and at every offer change inside it.
+        let mut instants = vec![period.start];
+        instants.extend(
+            offer_points
+                .iter()
+                .copied()
+                .filter(|t| *t > period.start && period.end.is_none_or(|end| *t < end)),
+        );
+        let start_period = period.period.start_period;
+        for at in instants {
+            let Some(offered_period) = offer.period_at(time_base, at) else {
+                if charge != 0.0 || discharge != 0.0 {
+                    violations.push(ScheduleViolation::OutsideOffer { start_period, at });
+                    break;
+                }
+                continue;
+            };
+            let offer_phases = offered_period.period.number_phases.unwrap_or(phases);
+            let offer_unit = &offer.charging_rate_unit;
+            let offered_watts = offered_period
+                .period
+                .limit
+                .map(|l| to_watts(l, offer_unit, offer_phases, voltage));
+            let offered_discharge = offered_period
+                .period
+                .discharge_limit
+                .map_or(0.0, |l| to_watts(l, offer_unit, offer_phases, voltage));
+            let charge_watts = to_watts(charge, &ev.charging_rate_unit, ev_phases, voltage);
+            if let Some(limit) = offered_watts.filter(|o| charge_watts > o + 1e-6) {
+                violations.push(ScheduleViolation::ExceedsLimit {
+                    start_period,
+                    at,
+                    offered: from_watts(limit, &ev.charging_rate_unit, ev_phases, voltage),
+                    requested: charge,
+                });
+                break;
+            }
+            let discharge_watts = to_watts(discharge, &ev.charging_rate_unit, ev_phases, voltage);
+            if discharge_watts < offered_discharge - 1e-6 {
+                violations.push(ScheduleViolation::ExceedsDischargeLimit {
+                    start_period,
+                    at,
+                    offered: from_watts(
+                        offered_discharge,
+                        &ev.charging_rate_unit,
+                        ev_phases,
+                        voltage,
+                    ),
+                    requested: discharge,
+                });
+                break;
+            }
+        }
+    }
+    violations
+}
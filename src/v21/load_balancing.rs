@@ -0,0 +1,232 @@
+//! 2.1 front end of the CSMS load-balancing planner ([`crate::load_balancing`]).
+//!
+//! A [`PlannedTransaction`] starts from the EVSE's capability and is narrowed by the EV's
+//! `NotifyEVChargingNeedsRequest` ([`PlannedTransaction::apply_charging_needs`]): maximum
+//! and minimum power, requested energy and departure time. [`plan_tx_profiles`] returns one
+//! `SetChargingProfileRequest` per transaction with an `Absolute` `TxProfile` starting at the
+//! plan start.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use chrono::{DateTime, Utc};
+
+use super::composite::{convert_limit, empty_period};
+use super::datatypes::{
+    ChargingProfileType, ChargingSchedulePeriodType, ChargingScheduleType, DateTimeWrapper,
+};
+use super::enumerations::{
+    ChargingProfileKindEnumType, ChargingProfilePurposeEnumType, ChargingRateUnitEnumType,
+};
+use super::messages::notify_ev_charging_needs::ChargingNeedsType;
+use super::messages::set_charging_profile::SetChargingProfileRequest;
+pub use crate::load_balancing::DEFAULT_MIN_CURRENT;
+use crate::load_balancing::{FairnessPolicy, SessionDemand, SiteLimit, floor_tenth, plan};
+
+fn watts(amps: f64, phases: i32, voltage: f64) -> f64 {
+    convert_limit(
+        amps,
+        &ChargingRateUnitEnumType::A,
+        &ChargingRateUnitEnumType::W,
+        phases,
+        voltage,
+    )
+}
+
+/// An active transaction to plan for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedTransaction {
+    pub transaction_id: String,
+    pub evse_id: i32,
+    pub started: DateTime<Utc>,
+    pub phases: i32,
+    pub voltage: f64,
+    /// EVSE capability, A per phase.
+    pub max_current: f64,
+    /// Below this (A per phase) the transaction is paused instead.
+    pub min_current: f64,
+    /// EV limits in W, from its charging needs.
+    pub ev_max_power: Option<f64>,
+    pub ev_min_power: Option<f64>,
+    /// Energy still to deliver (Wh), if known.
+    pub energy_needed: Option<f64>,
+    pub departure: Option<DateTime<Utc>>,
+}
+
+impl PlannedTransaction {
+    /// Three phases at 230 V, minimum [`DEFAULT_MIN_CURRENT`], no EV needs yet.
+    #[must_use]
+    pub fn new(
+        transaction_id: impl Into<String>,
+        evse_id: i32,
+        started: DateTime<Utc>,
+        max_current: f64,
+    ) -> Self {
+        Self {
+            transaction_id: transaction_id.into(),
+            evse_id,
+            started,
+            phases: 3,
+            voltage: 230.0,
+            max_current,
+            min_current: DEFAULT_MIN_CURRENT,
+            ev_max_power: None,
+            ev_min_power: None,
+            energy_needed: None,
+            departure: None,
+        }
+    }
+
+    /// Take the EV's limits, energy request and departure time from its charging needs.
+    /// V2X parameters win over DC, DC over AC; fields the EV did not send are left as they
+    /// were.
+    pub fn apply_charging_needs(&mut self, needs: &ChargingNeedsType) {
+        if let Some(ac) = &needs.ac_charging_parameters {
+            self.ev_max_power = Some(watts(ac.ev_max_current, self.phases, self.voltage));
+            self.ev_min_power = Some(watts(ac.ev_min_current, self.phases, self.voltage));
+            self.energy_needed = Some(ac.energy_amount);
+        }
+        if let Some(dc) = &needs.dc_charging_parameters {
+            self.ev_max_power = Some(
+                dc.ev_max_power
+                    .unwrap_or_else(|| watts(dc.ev_max_current, 1, dc.ev_max_voltage)),
+            );
+            self.energy_needed = dc.energy_amount.or(self.energy_needed);
+        }
+        if let Some(v2x) = &needs.v2x_charging_parameters {
+            let from_current =
+                |amps: Option<f64>| amps.map(|a| watts(a, self.phases, self.voltage));
+            self.ev_max_power = v2x
+                .max_charge_power
+                .or_else(|| from_current(v2x.max_charge_current))
+                .or(self.ev_max_power);
+            self.ev_min_power = v2x
+                .min_charge_power
+                .or_else(|| from_current(v2x.min_charge_current))
+                .or(self.ev_min_power);
+            self.energy_needed = v2x
+                .ev_target_energy_request
+                .or(v2x.ev_min_energy_request)
+                .or(self.energy_needed);
+        }
+        if let Some(departure) = needs.departure_time {
+            self.departure = Some(departure.inner());
+        }
+    }
+
+    /// Version-neutral demand of this transaction: the tighter of the EVSE and EV limits.
+    #[must_use]
+    pub fn demand(&self) -> SessionDemand {
+        let max = watts(self.max_current, self.phases, self.voltage);
+        let min = watts(self.min_current, self.phases, self.voltage);
+        SessionDemand {
+            started: self.started,
+            max_power: self.ev_max_power.map_or(max, |ev| ev.min(max)),
+            min_power: self.ev_min_power.map_or(min, |ev| ev.max(min)),
+            energy_needed: self.energy_needed,
+            departure: self.departure,
+        }
+    }
+}
+
+/// Identity and unit of the emitted profiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileOptions {
+    /// Profile `id` of the first transaction; the others follow in input order. Also used
+    /// as the schedule `id`.
+    pub first_profile_id: i32,
+    pub stack_level: i32,
+    pub unit: ChargingRateUnitEnumType,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        Self {
+            first_profile_id: 1,
+            stack_level: 0,
+            unit: ChargingRateUnitEnumType::A,
+        }
+    }
+}
+
+/// Plan `transactions` under `site` for `horizon` seconds from `start` and build their
+/// `TxProfile`s (same order as `transactions`).
+#[must_use]
+pub fn plan_tx_profiles(
+    site: &SiteLimit,
+    transactions: &[PlannedTransaction],
+    policy: FairnessPolicy,
+    start: DateTime<Utc>,
+    horizon: i32,
+    options: &ProfileOptions,
+) -> Vec<SetChargingProfileRequest> {
+    let demands: Vec<SessionDemand> = transactions
+        .iter()
+        .map(PlannedTransaction::demand)
+        .collect();
+    let plans = plan(site, &demands, policy, start, horizon);
+    transactions
+        .iter()
+        .zip(plans)
+        .zip(0..)
+        .map(|((tx, session), n)| {
+            let mut periods: Vec<ChargingSchedulePeriodType> = Vec::new();
+            for planned in &session.periods {
+                let limit = floor_tenth(convert_limit(
+                    planned.power,
+                    &ChargingRateUnitEnumType::W,
+                    &options.unit,
+                    tx.phases,
+                    tx.voltage,
+                ));
+                if periods.last().is_none_or(|p| p.limit != Some(limit)) {
+                    periods.push(ChargingSchedulePeriodType {
+                        start_period: planned.start_offset,
+                        limit: Some(limit),
+                        number_phases: Some(tx.phases),
+                        ..empty_period()
+                    });
+                }
+            }
+            let id = options.first_profile_id.saturating_add(n);
+            SetChargingProfileRequest {
+                evse_id: tx.evse_id,
+                charging_profile: ChargingProfileType {
+                    id,
+                    stack_level: options.stack_level,
+                    charging_profile_purpose: ChargingProfilePurposeEnumType::TxProfile,
+                    charging_profile_kind: ChargingProfileKindEnumType::Absolute,
+                    recurrency_kind: None,
+                    valid_from: None,
+                    valid_to: None,
+                    transaction_id: Some(tx.transaction_id.clone()),
+                    max_offline_duration: None,
+                    charging_schedule: vec![ChargingScheduleType {
+                        id,
+                        limit_at_so_c: None,
+                        start_schedule: Some(DateTimeWrapper::new(start)),
+                        duration: Some(horizon),
+                        charging_rate_unit: options.unit.clone(),
+                        min_charging_rate: None,
+                        power_tolerance: None,
+                        signature_id: None,
+                        digest_value: None,
+                        use_local_time: None,
+                        charging_schedule_period: periods,
+                        randomized_delay: None,
+                        sales_tariff: None,
+                        absolute_price_schedule: None,
+                        price_level_schedule: None,
+                        custom_data: None,
+                    }],
+                    invalid_after_offline_duration: None,
+                    dyn_update_interval: None,
+                    dyn_update_time: None,
+                    price_schedule_signature: None,
+                    custom_data: None,
+                },
+                custom_data: None,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,86 @@
+//! Feeding 2.1 messages into a [`MeterAggregator`].
+//!
+//! `TransactionEventRequest.meterValue` and `MeterValuesRequest` contribute their sampled
+//! values. A sampled value without measurand is `Energy.Active.Import.Register`; its
+//! [`SampledValueType::quantity`] is taken in Wh, W or %.
+
+use super::datatypes::{MeterValueType, SampledValueType};
+use super::enumerations::{MeasurandEnumType, PhaseEnumType, ReadingContextEnumType};
+use super::messages::meter_values::MeterValuesRequest;
+use super::messages::transaction_event::TransactionEventRequest;
+use crate::datetime::DateTimeWrapper;
+use crate::metering::{Measure, MeterAggregator, MeterAnomaly, Reading, SampleContext};
+
+const fn measure(measurand: Option<&MeasurandEnumType>) -> Option<Measure> {
+    match measurand {
+        None | Some(MeasurandEnumType::EnergyActiveImportRegister) => {
+            Some(Measure::EnergyImportRegister)
+        }
+        Some(MeasurandEnumType::PowerActiveImport) => Some(Measure::PowerImport),
+        Some(MeasurandEnumType::SoC) => Some(Measure::StateOfCharge),
+        _ => None,
+    }
+}
+
+/// Phase index (1–3) of a per-phase value; `None` for values that do not add up to a
+/// total (neutral, line-to-line).
+const fn phase_index(phase: &PhaseEnumType) -> Option<u8> {
+    match phase {
+        PhaseEnumType::L1 | PhaseEnumType::L1N => Some(1),
+        PhaseEnumType::L2 | PhaseEnumType::L2N => Some(2),
+        PhaseEnumType::L3 | PhaseEnumType::L3N => Some(3),
+        _ => None,
+    }
+}
+
+const fn context(context: Option<&ReadingContextEnumType>) -> SampleContext {
+    match context {
+        Some(ReadingContextEnumType::TransactionBegin) => SampleContext::TransactionBegin,
+        Some(ReadingContextEnumType::TransactionEnd) => SampleContext::TransactionEnd,
+        _ => SampleContext::Other,
+    }
+}
+
+impl MeterAggregator {
+    fn add_sampled_value_type(&mut self, timestamp: DateTimeWrapper, sample: &SampledValueType) {
+        let Some(measure) = measure(sample.measurand.as_ref()) else {
+            return;
+        };
+        let phase = match sample.phase.as_ref().map(phase_index) {
+            None => None,
+            Some(Some(index)) => Some(index),
+            Some(None) => return,
+        };
+        match sample.quantity().ok().and_then(|q| q.to(measure.unit())) {
+            Some(value) => self.push(Reading {
+                timestamp: timestamp.inner(),
+                measure,
+                context: context(sample.context.as_ref()),
+                phase,
+                value,
+            }),
+            None => self.flag(MeterAnomaly::Unreadable {
+                at: timestamp.inner(),
+                value: alloc::format!("{}", sample.value),
+            }),
+        }
+    }
+
+    fn add_meter_value_type(&mut self, meter_value: &MeterValueType) {
+        for sample in &meter_value.sampled_value {
+            self.add_sampled_value_type(meter_value.timestamp, sample);
+        }
+    }
+
+    pub fn add_transaction_event(&mut self, request: &TransactionEventRequest) {
+        for meter_value in request.meter_value.iter().flatten() {
+            self.add_meter_value_type(meter_value);
+        }
+    }
+
+    pub fn add_meter_values_request(&mut self, request: &MeterValuesRequest) {
+        for meter_value in &request.meter_value {
+            self.add_meter_value_type(meter_value);
+        }
+    }
+}
@@ -0,0 +1,764 @@
+//! Station-side variable monitoring (functional block N).
+//!
+//! [`MonitoringEngine`] stores monitors, answers `SetVariableMonitoring`,
+//! `ClearVariableMonitoring`, `SetMonitoringBase` and `SetMonitoringLevel`, and turns value
+//! updates ([`MonitoringEngine::update_value`], [`MonitoringEngine::update_target`]) and time
+//! ([`MonitoringEngine::tick`]) into `EventDataType`s for `NotifyEventRequest`.
+//!
+//! Event semantics:
+//! * `UpperThreshold` / `LowerThreshold` / `TargetDelta` / `TargetDeltaRelative` raise an
+//!   `Alerting` event when the condition starts and a `cleared = true` event (with `cause` set
+//!   to the raising event's id) when it ends.
+//! * `Delta` raises a `Delta` event when a numeric value moved more than the monitor value from
+//!   the last reported one, or on any change of a non-numeric value.
+//! * `Periodic` / `PeriodicClockAligned` raise `Periodic` events with the last known value.
+//!
+//! Events of monitors whose severity is above the monitoring level are not returned, but their
+//! state is still tracked. No clock is read: callers pass `now`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Timelike, Utc};
+
+use super::call::Action;
+use super::datatypes::component_type::same_component;
+use super::datatypes::variable_type::same_variable;
+use super::datatypes::{ComponentType, DateTimeWrapper, VariableType};
+use super::enumerations::{
+    EventNotificationEnumType, GenericDeviceModelStatusEnumType, GenericStatusEnumType,
+    MonitorEnumType,
+};
+use super::messages::clear_variable_monitoring::{
+    ClearMonitoringResultType, ClearMonitoringStatusEnumType, ClearVariableMonitoringRequest,
+    ClearVariableMonitoringResponse,
+};
+use super::messages::notify_event::{EventDataType, EventTriggerEnumType, NotifyEventRequest};
+use super::messages::notify_monitoring_report::{MonitoringDataType, VariableMonitoringType};
+use super::messages::set_monitoring_base::{
+    MonitoringBaseEnumType, SetMonitoringBaseRequest, SetMonitoringBaseResponse,
+};
+use super::messages::set_monitoring_level::{
+    SetMonitoringLevelRequest, SetMonitoringLevelResponse,
+};
+use super::messages::set_variable_monitoring::{
+    SetMonitoringDataType, SetMonitoringResultType, SetMonitoringStatusEnumType,
+    SetVariableMonitoringRequest, SetVariableMonitoringResponse,
+};
+use super::reason_codes;
+use super::report::{MessageLimits, call_frame_len, chunk_by_limits};
+use crate::errors::{Error, Result};
+
+/// Least severe monitoring level (report everything).
+pub const MAX_SEVERITY: i32 = 9;
+
+/// Longest `Periodic` / `PeriodicClockAligned` interval accepted, in seconds (366 days).
+pub const MAX_PERIODIC_INTERVAL: f64 = 31_622_400.0;
+
+/// Where a monitor comes from; decides clearing rules and `eventNotificationType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorOrigin {
+    /// Built in; cannot be changed, cleared or disabled.
+    HardWired,
+    /// Installed by the manufacturer; `factory_default` ones stay active under
+    /// `MonitoringBase = FactoryDefault`.
+    Preconfigured { factory_default: bool },
+    /// Installed through `SetVariableMonitoring`.
+    Custom,
+}
+
+impl MonitorOrigin {
+    #[must_use]
+    pub const fn notification_type(self) -> EventNotificationEnumType {
+        match self {
+            Self::HardWired => EventNotificationEnumType::HardWiredMonitor,
+            Self::Preconfigured { .. } => EventNotificationEnumType::PreconfiguredMonitor,
+            Self::Custom => EventNotificationEnumType::CustomMonitor,
+        }
+    }
+}
+
+/// One installed monitor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    pub id: i32,
+    pub component: ComponentType,
+    pub variable: VariableType,
+    pub type_: MonitorEnumType,
+    pub value: f64,
+    /// 0 (Danger) ..= 9 (Debug).
+    pub severity: i32,
+    /// Only active while a transaction is ongoing.
+    pub transaction: bool,
+    pub origin: MonitorOrigin,
+}
+
+impl Monitor {
+    /// Wire form for `NotifyMonitoringReport`.
+    #[must_use]
+    pub fn to_variable_monitoring(&self) -> VariableMonitoringType {
+        VariableMonitoringType {
+            id: self.id,
+            transaction: self.transaction,
+            value: self.value,
+            type_: self.type_.clone(),
+            severity: self.severity,
+            event_notification_type: self.origin.notification_type(),
+            custom_data: None,
+        }
+    }
+}
+
+/// Result of resolving a monitored component / variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableLookup {
+    Found,
+    UnknownComponent,
+    UnknownVariable,
+}
+
+/// The station's component / variable inventory, as seen by the monitoring engine.
+pub trait MonitoredVariables {
+    fn lookup(&self, component: &ComponentType, variable: &VariableType) -> VariableLookup;
+}
+
+#[cfg(feature = "device_model_catalog")]
+impl MonitoredVariables for super::device_model::DeviceModel {
+    fn lookup(&self, component: &ComponentType, variable: &VariableType) -> VariableLookup {
+        use super::device_model::{ComponentKey, VariableKey};
+        let Some(entry) = self.component(&ComponentKey::from(component)) else {
+            return VariableLookup::UnknownComponent;
+        };
+        if entry.variable(&VariableKey::from(variable)).is_some() {
+            VariableLookup::Found
+        } else {
+            VariableLookup::UnknownVariable
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct MonitorState {
+    /// Event id of the raised (not yet cleared) alert.
+    alert: Option<i32>,
+    /// Last reported value for `Delta`.
+    reference: Option<String>,
+    next_due: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    monitor: Monitor,
+    state: MonitorState,
+}
+
+#[derive(Debug, Clone)]
+struct VariableValues {
+    component: ComponentType,
+    variable: VariableType,
+    actual: Option<String>,
+    target: Option<String>,
+}
+
+/// Monitor store and evaluator.
+#[derive(Debug, Clone)]
+pub struct MonitoringEngine {
+    slots: Vec<Slot>,
+    values: Vec<VariableValues>,
+    level: i32,
+    base: MonitoringBaseEnumType,
+    transaction_id: Option<String>,
+    next_event_id: i32,
+}
+
+impl Default for MonitoringEngine {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            values: Vec::new(),
+            level: MAX_SEVERITY,
+            base: MonitoringBaseEnumType::All,
+            transaction_id: None,
+            next_event_id: 1,
+        }
+    }
+}
+
+fn numeric(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn interval(value: f64) -> TimeDelta {
+    TimeDelta::seconds(value.max(1.0).min(MAX_PERIODIC_INTERVAL) as i64)
+}
+
+/// First clock-aligned instant (multiples of `step` since midnight UTC) after `now`.
+fn next_aligned(now: DateTime<Utc>, step: TimeDelta) -> DateTime<Utc> {
+    let step_secs = step.num_seconds().max(1);
+    let since_midnight = i64::from(now.num_seconds_from_midnight());
+    let midnight = now
+        - TimeDelta::seconds(since_midnight)
+        - TimeDelta::nanoseconds(i64::from(now.nanosecond()));
+    let slots = since_midnight.div_euclid(step_secs) + 1;
+    let next = slots
+        .checked_mul(step_secs)
+        .and_then(TimeDelta::try_seconds)
+        .and_then(|d| midnight.checked_add_signed(d))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let next_midnight = midnight
+        .checked_add_signed(TimeDelta::days(1))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    next.min(next_midnight)
+}
+
+impl MonitoringEngine {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current `SetMonitoringLevel` severity.
+    #[must_use]
+    pub const fn level(&self) -> i32 {
+        self.level
+    }
+
+    #[must_use]
+    pub const fn base(&self) -> &MonitoringBaseEnumType {
+        &self.base
+    }
+
+    pub fn monitors(&self) -> impl Iterator<Item = &Monitor> {
+        self.slots.iter().map(|s| &s.monitor)
+    }
+
+    #[must_use]
+    pub fn monitor(&self, id: i32) -> Option<&Monitor> {
+        self.monitors().find(|m| m.id == id)
+    }
+
+    /// Whether `monitor` is evaluated under the current monitoring base and transaction state.
+    #[must_use]
+    pub const fn is_active(&self, monitor: &Monitor) -> bool {
+        let by_base = match monitor.origin {
+            MonitorOrigin::HardWired | MonitorOrigin::Custom => true,
+            MonitorOrigin::Preconfigured { factory_default } => match self.base {
+                MonitoringBaseEnumType::All => true,
+                MonitoringBaseEnumType::FactoryDefault => factory_default,
+                MonitoringBaseEnumType::HardWiredOnly => false,
+            },
+        };
+        by_base && (!monitor.transaction || self.transaction_id.is_some())
+    }
+
+    /// Install a monitor directly (hard-wired / preconfigured ones at startup). Replaces a
+    /// monitor with the same id.
+    pub fn install(&mut self, monitor: Monitor, now: DateTime<Utc>) {
+        let state = self.initial_state(&monitor, now);
+        let slot = Slot { monitor, state };
+        if let Some(existing) = self
+            .slots
+            .iter_mut()
+            .find(|s| s.monitor.id == slot.monitor.id)
+        {
+            *existing = slot;
+        } else {
+            self.slots.push(slot);
+        }
+    }
+
+    /// Ongoing transaction (activates `transaction` monitors, fills `transactionId`).
+    pub fn set_transaction(&mut self, transaction_id: Option<String>) {
+        self.transaction_id = transaction_id;
+    }
+
+    fn initial_state(&self, monitor: &Monitor, now: DateTime<Utc>) -> MonitorState {
+        let step = interval(monitor.value);
+        MonitorState {
+            alert: None,
+            reference: self
+                .values_of(&monitor.component, &monitor.variable)
+                .and_then(|v| v.actual.clone()),
+            next_due: match monitor.type_ {
+                MonitorEnumType::Periodic => Some(
+                    now.checked_add_signed(step)
+                        .unwrap_or(DateTime::<Utc>::MAX_UTC),
+                ),
+                MonitorEnumType::PeriodicClockAligned => Some(next_aligned(now, step)),
+                _ => None,
+            },
+        }
+    }
+
+    fn values_of(
+        &self,
+        component: &ComponentType,
+        variable: &VariableType,
+    ) -> Option<&VariableValues> {
+        self.values.iter().find(|v| {
+            same_component(&v.component, component) && same_variable(&v.variable, variable)
+        })
+    }
+
+    fn check_set(
+        &self,
+        data: &SetMonitoringDataType,
+        variables: &impl MonitoredVariables,
+    ) -> core::result::Result<(), (SetMonitoringStatusEnumType, &'static str)> {
+        use SetMonitoringStatusEnumType as S;
+        if !(0..=MAX_SEVERITY).contains(&data.severity) {
+            return Err((S::Rejected, reason_codes::VALUE_OUT_OF_RANGE));
+        }
+        if let Some(id) = data.id {
+            match self.monitor(id) {
+                None => return Err((S::Rejected, reason_codes::INVALID_VALUE)),
+                Some(m) if m.origin == MonitorOrigin::HardWired => {
+                    return Err((S::Rejected, reason_codes::READ_ONLY));
+                }
+                Some(_) => {}
+            }
+        }
+        match variables.lookup(&data.component, &data.variable) {
+            VariableLookup::Found => {}
+            VariableLookup::UnknownComponent => {
+                return Err((S::UnknownComponent, reason_codes::UNSPECIFIED));
+            }
+            VariableLookup::UnknownVariable => {
+                return Err((S::UnknownVariable, reason_codes::UNSPECIFIED));
+            }
+        }
+        let periodic = matches!(
+            data.type_,
+            MonitorEnumType::Periodic | MonitorEnumType::PeriodicClockAligned
+        );
+        if !data.value.is_finite()
+            || (periodic && !(1.0..=MAX_PERIODIC_INTERVAL).contains(&data.value))
+            || (!periodic
+                && !matches!(
+                    data.type_,
+                    MonitorEnumType::UpperThreshold | MonitorEnumType::LowerThreshold
+                )
+                && data.value < 0.0)
+        {
+            return Err((S::Rejected, reason_codes::VALUE_OUT_OF_RANGE));
+        }
+        let duplicate = self.slots.iter().any(|s| {
+            Some(s.monitor.id) != data.id
+                && s.monitor.type_ == data.type_
+                && s.monitor.severity == data.severity
+                && same_component(&s.monitor.component, &data.component)
+                && same_variable(&s.monitor.variable, &data.variable)
+        });
+        if duplicate {
+            return Err((S::Duplicate, reason_codes::UNSPECIFIED));
+        }
+        Ok(())
+    }
+
+    /// Install / replace custom monitors. New monitors get the next free id. Monitors are
+    /// evaluated from the next value update or tick.
+    pub fn set_variable_monitoring(
+        &mut self,
+        request: &SetVariableMonitoringRequest,
+        variables: &impl MonitoredVariables,
+        now: DateTime<Utc>,
+    ) -> SetVariableMonitoringResponse {
+        let set_monitoring_result = request
+            .set_monitoring_data
+            .iter()
+            .map(|data| {
+                let (status, status_info, id) =
+                    if let Err((status, reason)) = self.check_set(data, variables) {
+                        (status, Some(reason_codes::status_info(reason)), data.id)
+                    } else {
+                        let id = data.id.unwrap_or_else(|| {
+                            self.slots
+                                .iter()
+                                .map(|s| s.monitor.id)
+                                .max()
+                                .unwrap_or(0)
+                                .saturating_add(1)
+                        });
+                        let origin = self.monitor(id).map_or(MonitorOrigin::Custom, |m| m.origin);
+                        self.install(
+                            Monitor {
+                                id,
+                                component: data.component.clone(),
+                                variable: data.variable.clone(),
+                                type_: data.type_.clone(),
+                                value: data.value,
+                                severity: data.severity,
+                                transaction: data.transaction.unwrap_or(false),
+                                origin,
+                            },
+                            now,
+                        );
+                        (SetMonitoringStatusEnumType::Accepted, None, Some(id))
+                    };
+                SetMonitoringResultType {
+                    id,
+                    status_info,
+                    status,
+                    type_: data.type_.clone(),
+                    component: data.component.clone(),
+                    variable: data.variable.clone(),
+                    severity: data.severity,
+                    custom_data: None,
+                }
+            })
+            .collect();
+        SetVariableMonitoringResponse {
+            set_monitoring_result,
+            custom_data: None,
+        }
+    }
+
+    /// Remove monitors by id. Hard-wired monitors are `Rejected`.
+    pub fn clear_variable_monitoring(
+        &mut self,
+        request: &ClearVariableMonitoringRequest,
+    ) -> ClearVariableMonitoringResponse {
+        let clear_monitoring_result = request
+            .id
+            .iter()
+            .map(|&id| {
+                let (status, status_info) = match self.monitor(id).map(|m| m.origin) {
+                    None => (ClearMonitoringStatusEnumType::NotFound, None),
+                    Some(MonitorOrigin::HardWired) => (
+                        ClearMonitoringStatusEnumType::Rejected,
+                        Some(reason_codes::status_info(reason_codes::READ_ONLY)),
+                    ),
+                    Some(_) => {
+                        self.slots.retain(|s| s.monitor.id != id);
+                        (ClearMonitoringStatusEnumType::Accepted, None)
+                    }
+                };
+                ClearMonitoringResultType {
+                    status,
+                    id,
+                    status_info,
+                    custom_data: None,
+                }
+            })
+            .collect();
+        ClearVariableMonitoringResponse {
+            clear_monitoring_result,
+            custom_data: None,
+        }
+    }
+
+    /// `FactoryDefault` and `HardWiredOnly` remove all custom monitors; preconfigured monitors
+    /// are (de)activated per [`Self::is_active`].
+    pub fn set_monitoring_base(
+        &mut self,
+        request: &SetMonitoringBaseRequest,
+    ) -> SetMonitoringBaseResponse {
+        self.base = request.monitoring_base.clone();
+        if self.base != MonitoringBaseEnumType::All {
+            self.slots
+                .retain(|s| s.monitor.origin != MonitorOrigin::Custom);
+        }
+        SetMonitoringBaseResponse {
+            status: GenericDeviceModelStatusEnumType::Accepted,
+            status_info: None,
+            custom_data: None,
+        }
+    }
+
+    /// Only events with `severity <= level` are reported. Levels outside `0..=9` are rejected.
+    pub fn set_monitoring_level(
+        &mut self,
+        request: &SetMonitoringLevelRequest,
+    ) -> SetMonitoringLevelResponse {
+        if (0..=MAX_SEVERITY).contains(&request.severity) {
+            self.level = request.severity;
+            SetMonitoringLevelResponse {
+                status: GenericStatusEnumType::Accepted,
+                status_info: None,
+                custom_data: None,
+            }
+        } else {
+            SetMonitoringLevelResponse {
+                status: GenericStatusEnumType::Rejected,
+                status_info: Some(reason_codes::status_info(reason_codes::VALUE_OUT_OF_RANGE)),
+                custom_data: None,
+            }
+        }
+    }
+
+    /// Installed monitors grouped per component / variable, for `NotifyMonitoringReport`.
+    #[must_use]
+    pub fn monitoring_data(&self) -> Vec<MonitoringDataType> {
+        let mut data: Vec<MonitoringDataType> = Vec::new();
+        for slot in &self.slots {
+            let m = &slot.monitor;
+            if let Some(entry) = data.iter_mut().find(|d| {
+                same_component(&d.component, &m.component)
+                    && same_variable(&d.variable, &m.variable)
+            }) {
+                entry.variable_monitoring.push(m.to_variable_monitoring());
+            } else {
+                data.push(MonitoringDataType {
+                    component: m.component.clone(),
+                    variable: m.variable.clone(),
+                    variable_monitoring: alloc::vec![m.to_variable_monitoring()],
+                    custom_data: None,
+                });
+            }
+        }
+        data
+    }
+
+    fn store(
+        &mut self,
+        component: &ComponentType,
+        variable: &VariableType,
+        update: impl FnOnce(&mut VariableValues),
+    ) {
+        if let Some(v) = self.values.iter_mut().find(|v| {
+            same_component(&v.component, component) && same_variable(&v.variable, variable)
+        }) {
+            update(v);
+        } else {
+            let mut v = VariableValues {
+                component: component.clone(),
+                variable: variable.clone(),
+                actual: None,
+                target: None,
+            };
+            update(&mut v);
+            self.values.push(v);
+        }
+    }
+
+    /// New `Actual` value of a variable; returns the events to report.
+    pub fn update_value(
+        &mut self,
+        component: &ComponentType,
+        variable: &VariableType,
+        value: &str,
+        now: DateTime<Utc>,
+    ) -> Vec<EventDataType> {
+        self.store(component, variable, |v| v.actual = Some(value.into()));
+        self.evaluate(component, variable, now)
+    }
+
+    /// New `Target` value of a variable (for `TargetDelta` / `TargetDeltaRelative`).
+    pub fn update_target(
+        &mut self,
+        component: &ComponentType,
+        variable: &VariableType,
+        target: &str,
+        now: DateTime<Utc>,
+    ) -> Vec<EventDataType> {
+        self.store(component, variable, |v| v.target = Some(target.into()));
+        self.evaluate(component, variable, now)
+    }
+
+    fn take_event_id(&mut self) -> i32 {
+        let id = self.next_event_id;
+        self.next_event_id = id.checked_add(1).unwrap_or(1);
+        id
+    }
+
+    fn event(
+        &mut self,
+        monitor: &Monitor,
+        trigger: EventTriggerEnumType,
+        actual_value: String,
+        now: DateTime<Utc>,
+    ) -> EventDataType {
+        EventDataType {
+            event_id: self.take_event_id(),
+            timestamp: DateTimeWrapper::new(now),
+            trigger,
+            cause: None,
+            actual_value,
+            tech_code: None,
+            tech_info: None,
+            cleared: None,
+            transaction_id: if monitor.transaction {
+                self.transaction_id.clone()
+            } else {
+                None
+            },
+            component: monitor.component.clone(),
+            variable_monitoring_id: Some(monitor.id),
+            event_notification_type: monitor.origin.notification_type(),
+            variable: monitor.variable.clone(),
+            severity: Some(monitor.severity),
+            custom_data: None,
+        }
+    }
+
+    fn evaluate(
+        &mut self,
+        component: &ComponentType,
+        variable: &VariableType,
+        now: DateTime<Utc>,
+    ) -> Vec<EventDataType> {
+        let Some(values) = self.values_of(component, variable).cloned() else {
+            return Vec::new();
+        };
+        let Some(actual) = values.actual else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        for i in 0..self.slots.len() {
+            let Some(slot) = self.slots.get(i) else { break };
+            let monitor = slot.monitor.clone();
+            if !same_component(&monitor.component, component)
+                || !same_variable(&monitor.variable, variable)
+                || !self.is_active(&monitor)
+            {
+                continue;
+            }
+            let mut state = slot.state.clone();
+            let a = numeric(&actual);
+            let t = values.target.as_deref().and_then(numeric);
+            let violated = match monitor.type_ {
+                MonitorEnumType::UpperThreshold => a.map(|a| a > monitor.value),
+                MonitorEnumType::LowerThreshold => a.map(|a| a < monitor.value),
+                MonitorEnumType::TargetDelta => {
+                    a.zip(t).map(|(a, t)| (a - t).abs() > monitor.value)
+                }
+                MonitorEnumType::TargetDeltaRelative => a
+                    .zip(t)
+                    .map(|(a, t)| (a - t).abs() > monitor.value * t.abs()),
+                MonitorEnumType::Delta => {
+                    let changed = match (state.reference.as_deref(), a) {
+                        (None, _) => false,
+                        (Some(r), Some(a)) => {
+                            numeric(r).is_none_or(|r| (a - r).abs() > monitor.value)
+                        }
+                        (Some(r), None) => r != actual,
+                    };
+                    if state.reference.is_none() {
+                        state.reference = Some(actual.clone());
+                    }
+                    if changed {
+                        state.reference = Some(actual.clone());
+                        events.push(self.event(
+                            &monitor,
+                            EventTriggerEnumType::Delta,
+                            actual.clone(),
+                            now,
+                        ));
+                    }
+                    None
+                }
+                MonitorEnumType::Periodic | MonitorEnumType::PeriodicClockAligned => None,
+            };
+            match (violated, state.alert) {
+                (Some(true), None) => {
+                    let event = self.event(
+                        &monitor,
+                        EventTriggerEnumType::Alerting,
+                        actual.clone(),
+                        now,
+                    );
+                    state.alert = Some(event.event_id);
+                    events.push(event);
+                }
+                (Some(false), Some(raised)) => {
+                    let mut event = self.event(
+                        &monitor,
+                        EventTriggerEnumType::Alerting,
+                        actual.clone(),
+                        now,
+                    );
+                    event.cause = Some(raised);
+                    event.cleared = Some(true);
+                    state.alert = None;
+                    events.push(event);
+                }
+                _ => {}
+            }
+            if let Some(stored) = self.slots.get_mut(i) {
+                stored.state = state;
+            }
+        }
+        self.reportable(events)
+    }
+
+    /// Fire due `Periodic` / `PeriodicClockAligned` monitors. Missed periods collapse into one
+    /// event; monitors of variables without a known value only reschedule.
+    pub fn tick(&mut self, now: DateTime<Utc>) -> Vec<EventDataType> {
+        let mut events = Vec::new();
+        for i in 0..self.slots.len() {
+            let Some(slot) = self.slots.get(i) else { break };
+            let Some(due) = slot.state.next_due else {
+                continue;
+            };
+            if due > now {
+                continue;
+            }
+            let monitor = slot.monitor.clone();
+            let step = interval(monitor.value);
+            let next = if monitor.type_ == MonitorEnumType::PeriodicClockAligned {
+                next_aligned(now, step)
+            } else {
+                let missed = (now - due)
+                    .num_seconds()
+                    .div_euclid(step.num_seconds().max(1))
+                    + 1;
+                missed
+                    .checked_mul(step.num_seconds())
+                    .and_then(TimeDelta::try_seconds)
+                    .and_then(|d| due.checked_add_signed(d))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC)
+            };
+            if let Some(stored) = self.slots.get_mut(i) {
+                stored.state.next_due = Some(next);
+            }
+            if !self.is_active(&monitor) {
+                continue;
+            }
+            let actual = self
+                .values_of(&monitor.component, &monitor.variable)
+                .and_then(|v| v.actual.clone());
+            if let Some(actual) = actual {
+                events.push(self.event(&monitor, EventTriggerEnumType::Periodic, actual, now));
+            }
+        }
+        self.reportable(events)
+    }
+
+    fn reportable(&self, events: Vec<EventDataType>) -> Vec<EventDataType> {
+        events
+            .into_iter()
+            .filter(|e| e.severity.is_none_or(|s| s <= self.level))
+            .collect()
+    }
+}
+
+/// Split events into `NotifyEventRequest`s (`seqNo` from 0, `tbc` on all but the last),
+/// honouring `ItemsPerMessage` / `BytesPerMessage`.
+///
+/// # Errors
+/// * [`Error::ConstraintViolation`] if a single event does not fit in `bytes_per_message`
+/// * serialization errors while measuring
+pub fn build_notify_events(
+    generated_at: DateTimeWrapper,
+    events: impl IntoIterator<Item = EventDataType>,
+    limits: &MessageLimits,
+) -> Result<Vec<NotifyEventRequest>> {
+    let frame = |seq_no: usize, event_data: Vec<EventDataType>, tbc: Option<bool>| {
+        Ok(NotifyEventRequest {
+            generated_at,
+            tbc,
+            seq_no: i32::try_from(seq_no).map_err(|_| Error::from("seqNo overflow"))?,
+            event_data,
+            custom_data: None,
+        })
+    };
+    let chunks = chunk_by_limits(events, limits, "eventData", |seq_no| {
+        call_frame_len(Action::NotifyEvent(frame(seq_no, Vec::new(), Some(true))?))
+    })?;
+    let last = chunks.len().saturating_sub(1);
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(seq_no, chunk)| frame(seq_no, chunk, (seq_no < last).then_some(true)))
+        .collect()
+}
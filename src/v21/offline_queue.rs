@@ -0,0 +1,61 @@
+//! 2.1 front end of the offline transaction queue ([`crate::offline_queue`]).
+//!
+//! Only `TransactionEventRequest` is transaction-related; events queued while offline get
+//! `offline: true`. The retry policy comes from `OCPPCommCtrlr.MessageAttempts[TransactionEvent]`
+//! and `OCPPCommCtrlr.MessageAttemptInterval[TransactionEvent]`. [`next_call`] and
+//! [`disconnected`] keep a [`PendingCalls`] map in step with the queue so replayed CALLRESULTs
+//! resolve after a reconnect.
+
+use alloc::string::String;
+use chrono::{DateTime, Utc};
+
+use super::call::{Action, Call};
+use super::pending::PendingCalls;
+use crate::offline_queue::{OfflineQueue, QueueMessage, RetryPolicy};
+
+/// Queue of 2.1 CALLs.
+pub type TransactionQueue = OfflineQueue<Call>;
+
+impl QueueMessage for Call {
+    fn is_transaction_message(&self) -> bool {
+        matches!(self.payload, Action::TransactionEvent(_))
+    }
+
+    fn unique_id(&self) -> &str {
+        &self.unique_id
+    }
+
+    fn mark_offline(&mut self) {
+        if let Action::TransactionEvent(event) = &mut self.payload {
+            event.offline = Some(true);
+        }
+    }
+}
+
+/// Policy from `MessageAttempts[TransactionEvent]` and `MessageAttemptInterval[TransactionEvent]`
+/// (s).
+#[must_use]
+pub const fn retry_policy(attempts: u32, attempt_interval: u32) -> RetryPolicy {
+    RetryPolicy {
+        attempts,
+        interval: attempt_interval,
+    }
+}
+
+/// [`OfflineQueue::next_message`], registered in `pending`.
+pub fn next_call(
+    queue: &mut TransactionQueue,
+    pending: &mut PendingCalls,
+    now: DateTime<Utc>,
+) -> Option<Call> {
+    let call = queue.next_message(now)?;
+    pending.register_call(&call);
+    Some(call)
+}
+
+/// Connection lost: the in-flight call, if any, is requeued and forgotten in `pending`.
+pub fn disconnected(queue: &mut TransactionQueue, pending: &mut PendingCalls) -> Option<String> {
+    let unique_id = queue.set_online(false)?;
+    pending.take(&unique_id);
+    Some(unique_id)
+}
@@ -0,0 +1,681 @@
+//! ISO 15118-20 absolute price schedules: pricing a [`Timeline`] under an
+//! [`AbsolutePriceScheduleType`], and deriving one from a [`TariffType`].
+//!
+//! Price rule stacks follow each other from `timeAnchor`, each lasting `duration` seconds;
+//! within a stack the rule with the highest `powerRangeStart` at or below the current
+//! power applies. The energy fee is per kWh; parking and overstay fees are per
+//! `parkingFeePeriod` / `overstayFeePeriod` seconds, charged pro rata (a parking fee
+//! without period is not charged). Overstay starts at `timeAnchor` +
+//! `overstayTimeThreshold`, or once the power drops below `overstayPowerThreshold` after
+//! charging began; the overstay rule with the highest `startTime` at or below the time
+//! spent in overstay applies. `taxRate` is a percentage.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, FixedOffset, Offset, TimeDelta, Utc};
+
+use super::datatypes::{
+    AbsolutePriceScheduleType, AdditionalSelectedServicesType, OverstayRuleListType,
+    OverstayRuleType, PriceRuleStackType, PriceRuleType, RationalNumberType, TariffConditionsType,
+    TariffType, TaxRuleType,
+};
+use super::tariff::conditions::{State, cuts, holds, period_conditions, seconds};
+use super::tariff::{CostClamp, Timeline, TimelinePeriod};
+use crate::datetime::DateTimeWrapper;
+use crate::quantity::pow10;
+
+/// `priceAlgorithm` of schedules priced by power range only.
+pub const POWER_PRICE_ALGORITHM: &str = "urn:iso:std:iso:15118:-20:PriceAlgorithm:1-Power";
+
+/// Largest exponent [`RationalNumberType::from_f64`] resorts to.
+const MAX_EXPONENT: i32 = 9;
+
+/// Decimal places [`RationalNumberType::from_f64`] keeps.
+const DECIMALS: i32 = 6;
+
+impl RationalNumberType {
+    #[must_use]
+    pub const fn new(value: i32, exponent: i32) -> Self {
+        Self {
+            exponent,
+            value,
+            custom_data: None,
+        }
+    }
+
+    /// `value × 10^exponent`; `0.0` for a zero `value` whatever the exponent.
+    #[must_use]
+    pub fn to_f64(&self) -> f64 {
+        if self.value == 0 {
+            return 0.0;
+        }
+        f64::from(self.value) * pow10(self.exponent)
+    }
+
+    /// `number` to 6 decimal places (fewer if `value` would not fit), trailing zeros
+    /// folded into the exponent; `None` for non-finite or out-of-range numbers.
+    #[must_use]
+    pub fn from_f64(number: f64) -> Option<Self> {
+        if !number.is_finite() {
+            return None;
+        }
+        (-DECIMALS..=MAX_EXPONENT).find_map(|exponent| {
+            let scaled = number * pow10(-exponent);
+            #[allow(clippy::cast_possible_truncation)]
+            let rounded = (scaled + if scaled < 0.0 { -0.5 } else { 0.5 }) as i64;
+            Self::normalized(i128::from(rounded), exponent)
+        })
+    }
+
+    /// `value × 10^exponent` with trailing zeros folded into the exponent; `None` if
+    /// `value` still does not fit an `i32`.
+    fn normalized(mut value: i128, mut exponent: i32) -> Option<Self> {
+        if value == 0 {
+            return Some(Self::new(0, 0));
+        }
+        while value % 10 == 0 {
+            value /= 10;
+            exponent += 1;
+        }
+        Some(Self::new(i32::try_from(value).ok()?, exponent))
+    }
+
+    /// Exact sum; `None` if it is not representable.
+    #[must_use]
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let exponent = self.exponent.min(other.exponent);
+        let scale = |r: &Self| {
+            10_i128
+                .checked_pow(u32::try_from(r.exponent - exponent).ok()?)?
+                .checked_mul(i128::from(r.value))
+        };
+        Self::normalized(scale(self)?.checked_add(scale(other)?)?, exponent)
+    }
+
+    /// Exact product; `None` if it is not representable.
+    #[must_use]
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Self::normalized(
+            i128::from(self.value) * i128::from(other.value),
+            self.exponent.checked_add(other.exponent)?,
+        )
+    }
+}
+
+/// Energy and parking fee under one price rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceRuleLine {
+    /// Index into `priceRuleStacks`.
+    pub stack: usize,
+    /// Index into the stack's `priceRule`.
+    pub rule: usize,
+    /// kWh.
+    pub energy: f64,
+    pub seconds: f64,
+    pub energy_fee: f64,
+    pub parking_fee: f64,
+}
+
+/// Overstay fee under one overstay rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverstayLine {
+    /// Index into `overstayRule`.
+    pub rule: usize,
+    pub seconds: f64,
+    pub fee: f64,
+}
+
+/// Tax under one tax rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleTax {
+    pub tax_rule_id: i32,
+    pub amount: f64,
+}
+
+/// Price of a [`Timeline`] under an absolute price schedule. Fees exclude tax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedulePrice {
+    pub currency: String,
+    pub lines: Vec<PriceRuleLine>,
+    pub overstay: Vec<OverstayLine>,
+    pub energy_fee: f64,
+    pub parking_fee: f64,
+    pub overstay_fee: f64,
+    /// `additionalSelectedServices`.
+    pub service_fee: f64,
+    pub taxes: Vec<ScheduleTax>,
+    /// Total before tax, after clamping.
+    pub excl_tax: f64,
+    /// Total including tax, after clamping.
+    pub incl_tax: f64,
+    pub clamped: Option<CostClamp>,
+    /// Seconds of the timeline before `timeAnchor` or after the last stack: not priced.
+    pub uncovered: f64,
+}
+
+/// Which fee a tax rule applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fee {
+    Energy,
+    Parking,
+    Overstay,
+    MinMax,
+}
+
+const fn applies(rule: &TaxRuleType, fee: Fee) -> bool {
+    match fee {
+        Fee::Energy => rule.applies_to_energy_fee,
+        Fee::Parking => rule.applies_to_parking_fee,
+        Fee::Overstay => rule.applies_to_overstay_fee,
+        Fee::MinMax => rule.applies_to_minimum_maximum_cost,
+    }
+}
+
+/// `amount` of `fee` split into (excluding tax, taxes): rules with `taxIncludedInPrice`
+/// are taken out of `amount`, the others added on top.
+fn tax(rules: &[TaxRuleType], fee: Fee, amount: f64) -> (f64, Vec<ScheduleTax>) {
+    let rules: Vec<&TaxRuleType> = rules.iter().filter(|r| applies(r, fee)).collect();
+    let included: f64 = rules
+        .iter()
+        .filter(|r| r.tax_included_in_price == Some(true))
+        .map(|r| r.tax_rate.to_f64())
+        .sum();
+    let net = amount / (1.0 + included / 100.0);
+    let taxes = rules
+        .iter()
+        .map(|r| ScheduleTax {
+            tax_rule_id: r.tax_rule_id,
+            amount: net * r.tax_rate.to_f64() / 100.0,
+        })
+        .collect();
+    (net, taxes)
+}
+
+fn add_taxes(totals: &mut Vec<ScheduleTax>, taxes: Vec<ScheduleTax>) {
+    for tax in taxes {
+        match totals.iter_mut().find(|t| t.tax_rule_id == tax.tax_rule_id) {
+            Some(total) => total.amount += tax.amount,
+            None => totals.push(tax),
+        }
+    }
+}
+
+/// Rule of `rules` for `power`: highest `powerRangeStart` ≤ `power`, else the lowest.
+fn rule_for(rules: &[PriceRuleType], power: f64) -> Option<usize> {
+    let start = |i: &usize| rules[*i].power_range_start.to_f64();
+    (0..rules.len())
+        .filter(|i| start(i) <= power)
+        .max_by(|a, b| start(a).total_cmp(&start(b)))
+        .or_else(|| (0..rules.len()).min_by(|a, b| start(a).total_cmp(&start(b))))
+}
+
+/// Overstay rule for `spent` milliseconds in overstay, and when (in milliseconds) the next
+/// one starts.
+fn overstay_rule_for(rules: &[OverstayRuleType], spent: i64) -> (Option<usize>, Option<i64>) {
+    let start = |i: usize| i64::from(rules[i].start_time) * 1000;
+    let current = (0..rules.len())
+        .filter(|i| start(*i) <= spent)
+        .max_by_key(|i| start(*i));
+    let next = (0..rules.len()).map(start).filter(|s| *s > spent).min();
+    (current, next)
+}
+
+/// Per-second rate of a fee per `period` seconds.
+fn per_second(fee: Option<&RationalNumberType>, period: Option<i32>) -> f64 {
+    match (fee, period) {
+        (Some(fee), Some(period)) if period > 0 => fee.to_f64() / f64::from(period),
+        _ => 0.0,
+    }
+}
+
+impl AbsolutePriceScheduleType {
+    /// Start and end of each stack.
+    fn stack_bounds(&self) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let mut start = self.time_anchor.inner();
+        self.price_rule_stacks
+            .iter()
+            .map(|stack| {
+                let end = start + TimeDelta::seconds(i64::from(stack.duration));
+                let bounds = (start, end);
+                start = end;
+                bounds
+            })
+            .collect()
+    }
+
+    /// Price of `timeline`; see the module docs. Reservations, calendar data and
+    /// current are not used.
+    #[must_use]
+    pub fn price(&self, timeline: &Timeline) -> SchedulePrice {
+        let stacks = self.stack_bounds();
+        let overstay = self.overstay_rule_list.as_ref();
+        let overstay_rules = overstay.map_or(&[][..], |o| &o.overstay_rule[..]);
+        let overstay_at = overstay
+            .and_then(|o| o.overstay_time_threshold)
+            .map(|s| self.time_anchor.inner() + TimeDelta::seconds(i64::from(s)));
+        let power_threshold = overstay
+            .and_then(|o| o.overstay_power_threshold.as_ref())
+            .map(RationalNumberType::to_f64);
+
+        let mut price = SchedulePrice {
+            currency: self.currency.clone(),
+            lines: Vec::new(),
+            overstay: Vec::new(),
+            energy_fee: 0.0,
+            parking_fee: 0.0,
+            overstay_fee: 0.0,
+            service_fee: 0.0,
+            taxes: Vec::new(),
+            excl_tax: 0.0,
+            incl_tax: 0.0,
+            clamped: None,
+            uncovered: 0.0,
+        };
+        let mut charged = false;
+        // In whole milliseconds, so the next rule always starts strictly after `from`.
+        let mut overstay_spent = 0;
+        for period in &timeline.periods {
+            let duration = seconds(period.end - period.start);
+            if duration <= 0.0 {
+                continue;
+            }
+            let power = period.power.unwrap_or(period.energy * 3600.0 / duration);
+            charged |= period.charging && power > 0.0;
+            let mut from = period.start;
+            while from < period.end {
+                let stack = stacks.iter().position(|(s, e)| *s <= from && from < *e);
+                let in_overstay = overstay.is_some()
+                    && (overstay_at.is_some_and(|t| from >= t)
+                        || power_threshold.is_some_and(|p| charged && power < p));
+                let (overstay_rule, next_rule) = if in_overstay {
+                    overstay_rule_for(overstay_rules, overstay_spent)
+                } else {
+                    (None, None)
+                };
+                let to = [
+                    Some(period.end),
+                    stacks.iter().map(|(s, _)| *s).find(|s| *s > from),
+                    stack.map(|i| stacks[i].1),
+                    overstay_at.filter(|t| *t > from),
+                    next_rule.and_then(|next| {
+                        from.checked_add_signed(TimeDelta::milliseconds(next - overstay_spent))
+                    }),
+                ]
+                .into_iter()
+                .flatten()
+                .min()
+                .unwrap_or(period.end);
+                let slice = seconds(to - from);
+                let energy = period.energy * slice / duration / 1000.0;
+                if let Some(index) = stack {
+                    let rules = &self.price_rule_stacks[index].price_rule;
+                    if let Some(rule_index) = rule_for(rules, power) {
+                        let rule = &rules[rule_index];
+                        let energy_fee = energy * rule.energy_fee.to_f64();
+                        let parking_fee =
+                            slice * per_second(rule.parking_fee.as_ref(), rule.parking_fee_period);
+                        match price
+                            .lines
+                            .iter_mut()
+                            .find(|l| l.stack == index && l.rule == rule_index)
+                        {
+                            Some(line) => {
+                                line.energy += energy;
+                                line.seconds += slice;
+                                line.energy_fee += energy_fee;
+                                line.parking_fee += parking_fee;
+                            }
+                            None => price.lines.push(PriceRuleLine {
+                                stack: index,
+                                rule: rule_index,
+                                energy,
+                                seconds: slice,
+                                energy_fee,
+                                parking_fee,
+                            }),
+                        }
+                    }
+                } else {
+                    price.uncovered += slice;
+                }
+                if in_overstay {
+                    if let Some(rule_index) = overstay_rule {
+                        let rule = &overstay_rules[rule_index];
+                        let fee = slice
+                            * per_second(Some(&rule.overstay_fee), Some(rule.overstay_fee_period));
+                        match price.overstay.iter_mut().find(|l| l.rule == rule_index) {
+                            Some(line) => {
+                                line.seconds += slice;
+                                line.fee += fee;
+                            }
+                            None => price.overstay.push(OverstayLine {
+                                rule: rule_index,
+                                seconds: slice,
+                                fee,
+                            }),
+                        }
+                    }
+                    overstay_spent += (to - from).num_milliseconds();
+                }
+                from = to;
+            }
+        }
+
+        let rules = self.tax_rules.as_deref().unwrap_or_default();
+        let gross = [
+            (
+                Fee::Energy,
+                price.lines.iter().map(|l| l.energy_fee).sum::<f64>(),
+            ),
+            (
+                Fee::Parking,
+                price.lines.iter().map(|l| l.parking_fee).sum(),
+            ),
+            (Fee::Overstay, price.overstay.iter().map(|l| l.fee).sum()),
+        ];
+        for (fee, amount) in gross {
+            let (net, taxes) = tax(rules, fee, amount);
+            match fee {
+                Fee::Energy => price.energy_fee = net,
+                Fee::Parking => price.parking_fee = net,
+                _ => price.overstay_fee = net,
+            }
+            add_taxes(&mut price.taxes, taxes);
+        }
+        price.service_fee = self
+            .additional_selected_services
+            .iter()
+            .flatten()
+            .map(|s| s.service_fee.to_f64())
+            .sum();
+        price.excl_tax = price.energy_fee + price.parking_fee + price.overstay_fee;
+        let bound = match (&self.minimum_cost, &self.maximum_cost) {
+            (Some(min), _) if price.excl_tax < min.to_f64() => Some((min, CostClamp::MinCost)),
+            (_, Some(max)) if price.excl_tax > max.to_f64() => Some((max, CostClamp::MaxCost)),
+            _ => None,
+        };
+        if let Some((bound, clamp)) = bound {
+            let (net, taxes) = tax(rules, Fee::MinMax, bound.to_f64());
+            price.excl_tax = net;
+            price.taxes.clear();
+            add_taxes(&mut price.taxes, taxes);
+            price.clamped = Some(clamp);
+        }
+        price.excl_tax += price.service_fee;
+        price.incl_tax = price.excl_tax + price.taxes.iter().map(|t| t.amount).sum::<f64>();
+        price
+    }
+}
+
+/// How [`TariffType::to_absolute_price_schedule`] frames the schedule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceScheduleOptions {
+    /// Session start: `timeAnchor`, and where duration conditions count from.
+    pub time_anchor: DateTime<Utc>,
+    pub price_schedule_id: i32,
+    pub language: String,
+    /// Seconds covered by the stacks.
+    pub duration: i32,
+    /// Local time of the tariff's time-of-day, day and date conditions.
+    pub utc_offset: FixedOffset,
+}
+
+impl PriceScheduleOptions {
+    /// English, 24 hours, UTC.
+    #[must_use]
+    pub fn new(time_anchor: DateTime<Utc>, price_schedule_id: i32) -> Self {
+        Self {
+            time_anchor,
+            price_schedule_id,
+            language: "en".into(),
+            duration: 86_400,
+            utc_offset: Utc.fix(),
+        }
+    }
+}
+
+/// Price of the first element whose conditions hold in `state`.
+fn first_price<'a>(
+    mut prices: impl Iterator<Item = (f64, Option<&'a TariffConditionsType>)>,
+    state: &State,
+) -> Option<f64> {
+    prices
+        .find(|(_, c)| c.is_none_or(|c| holds(c, state, None)))
+        .map(|(price, _)| price)
+}
+
+fn rational(number: f64) -> RationalNumberType {
+    RationalNumberType::from_f64(number).unwrap_or(RationalNumberType::new(0, 0))
+}
+
+impl TariffType {
+    /// Equivalent absolute price schedule, for handing to the EV.
+    ///
+    /// Stacks change where a time-of-day, day, date or session-duration condition does;
+    /// rules per stack where a power condition does. Energy prices become energy fees,
+    /// charging-time prices parking fees per minute, idle-time prices overstay rules per
+    /// minute (overstay = power below 1 W after charging), the fixed fee an additional
+    /// service, and tax rates flat tax rules. Energy and current conditions cannot be
+    /// expressed: they are evaluated as at session start (no energy, unknown current).
+    #[must_use]
+    pub fn to_absolute_price_schedule(
+        &self,
+        options: &PriceScheduleOptions,
+    ) -> AbsolutePriceScheduleType {
+        let conditions = period_conditions(self);
+        let timeline = Timeline::new(options.utc_offset);
+        let anchor = options.time_anchor;
+        let end = anchor + TimeDelta::seconds(i64::from(options.duration.max(1)));
+        let span = TimelinePeriod {
+            start: anchor,
+            end,
+            charging: false,
+            energy: 0.0,
+            power: None,
+            current: None,
+        };
+        let start_state = State::start(&timeline, anchor);
+        let mut bounds = alloc::vec![anchor];
+        bounds.extend(cuts(&conditions, &timeline, &span, &start_state));
+        bounds.push(end);
+
+        let mut powers: Vec<f64> = alloc::vec![0.0];
+        powers.extend(
+            conditions
+                .iter()
+                .flat_map(|c| [c.min_power, c.max_power])
+                .flatten()
+                .filter(|p| *p > 0.0),
+        );
+        powers.sort_by(f64::total_cmp);
+        powers.dedup();
+
+        let mut stacks: Vec<PriceRuleStackType> = Vec::new();
+        for slice in bounds.windows(2) {
+            let elapsed = seconds(slice[0] - anchor);
+            let mut rules: Vec<PriceRuleType> = Vec::new();
+            for power in &powers {
+                let state = State {
+                    elapsed,
+                    charging: elapsed,
+                    power: *power,
+                    ..State::start(&timeline, slice[0])
+                };
+                let energy_fee = first_price(
+                    self.energy.iter().flat_map(|e| {
+                        e.prices
+                            .iter()
+                            .map(|p| (p.price_kwh, p.conditions.as_ref()))
+                    }),
+                    &state,
+                );
+                let parking_fee = first_price(
+                    self.charging_time.iter().flat_map(|t| {
+                        t.prices
+                            .iter()
+                            .map(|p| (p.price_minute, p.conditions.as_ref()))
+                    }),
+                    &state,
+                );
+                let rule = PriceRuleType {
+                    parking_fee_period: parking_fee.map(|_| 60),
+                    carbon_dioxide_emission: None,
+                    renewable_generation_percentage: None,
+                    energy_fee: rational(energy_fee.unwrap_or(0.0)),
+                    parking_fee: parking_fee.map(rational),
+                    power_range_start: rational(*power),
+                    custom_data: None,
+                };
+                let same = |r: &PriceRuleType| {
+                    r.energy_fee == rule.energy_fee && r.parking_fee == rule.parking_fee
+                };
+                if !rules.last().is_some_and(same) {
+                    rules.push(rule);
+                }
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            let duration = seconds(slice[1] - slice[0]) as i32;
+            match stacks.last_mut() {
+                Some(last) if last.price_rule == rules => last.duration += duration,
+                _ => stacks.push(PriceRuleStackType {
+                    duration,
+                    price_rule: rules,
+                    custom_data: None,
+                }),
+            }
+        }
+
+        AbsolutePriceScheduleType {
+            time_anchor: DateTimeWrapper::new(anchor),
+            price_schedule_id: options.price_schedule_id,
+            price_schedule_description: None,
+            currency: self.currency.clone(),
+            language: options.language.clone(),
+            price_algorithm: POWER_PRICE_ALGORITHM.into(),
+            minimum_cost: self
+                .min_cost
+                .as_ref()
+                .and_then(|c| c.excl_tax.or(c.incl_tax))
+                .map(rational),
+            maximum_cost: self
+                .max_cost
+                .as_ref()
+                .and_then(|c| c.excl_tax.or(c.incl_tax))
+                .map(rational),
+            price_rule_stacks: stacks,
+            tax_rules: self.tax_rules(),
+            overstay_rule_list: self.overstay_rules(&start_state),
+            additional_selected_services: self.fixed_fee.as_ref().and_then(|fee| {
+                let price = fee.prices.first()?;
+                Some(alloc::vec![AdditionalSelectedServicesType {
+                    service_fee: rational(price.price_fixed),
+                    service_name: "Fixed fee".into(),
+                    custom_data: None,
+                }])
+            }),
+            custom_data: None,
+        }
+    }
+
+    /// Overstay rules from `idleTime`, changing where an idle-time condition does.
+    fn overstay_rules(&self, start: &State) -> Option<OverstayRuleListType> {
+        let idle = self.idle_time.as_ref()?;
+        let mut starts: Vec<i32> = alloc::vec![0];
+        starts.extend(
+            idle.prices
+                .iter()
+                .filter_map(|p| p.conditions.as_ref())
+                .flat_map(|c| [c.min_idle_time, c.max_idle_time])
+                .flatten()
+                .filter(|s| *s > 0),
+        );
+        starts.sort_unstable();
+        starts.dedup();
+        let rules: Vec<OverstayRuleType> = starts
+            .into_iter()
+            .map(|start_time| {
+                let state = State {
+                    idle: f64::from(start_time),
+                    ..*start
+                };
+                let fee = idle
+                    .prices
+                    .iter()
+                    .find(|p| p.conditions.as_ref().is_none_or(|c| holds(c, &state, None)))
+                    .map_or(0.0, |p| p.price_minute);
+                OverstayRuleType {
+                    overstay_fee: rational(fee),
+                    overstay_rule_description: None,
+                    start_time,
+                    overstay_fee_period: 60,
+                    custom_data: None,
+                }
+            })
+            .collect();
+        Some(OverstayRuleListType {
+            overstay_power_threshold: Some(RationalNumberType::new(1, 0)),
+            overstay_rule: rules,
+            overstay_time_threshold: None,
+            custom_data: None,
+        })
+    }
+
+    /// One flat tax rule per distinct (type, rate), flagged for the fees it applies to.
+    fn tax_rules(&self) -> Option<Vec<TaxRuleType>> {
+        let sources = [
+            (
+                Fee::Energy,
+                self.energy.as_ref().and_then(|e| e.tax_rates.as_ref()),
+            ),
+            (
+                Fee::Parking,
+                self.charging_time
+                    .as_ref()
+                    .and_then(|t| t.tax_rates.as_ref()),
+            ),
+            (
+                Fee::Overstay,
+                self.idle_time.as_ref().and_then(|t| t.tax_rates.as_ref()),
+            ),
+            (
+                Fee::MinMax,
+                self.min_cost
+                    .as_ref()
+                    .or(self.max_cost.as_ref())
+                    .and_then(|c| c.tax_rates.as_ref()),
+            ),
+        ];
+        let mut rules: Vec<TaxRuleType> = Vec::new();
+        for (fee, rates) in sources {
+            for rate in rates.into_iter().flatten() {
+                let tax_rate = rational(rate.tax);
+                let index = rules.iter().position(|r| {
+                    r.tax_rule_name.as_deref() == Some(rate.type_.as_str())
+                        && r.tax_rate == tax_rate
+                });
+                let index = index.unwrap_or_else(|| {
+                    rules.push(TaxRuleType {
+                        tax_rule_id: i32::try_from(rules.len() + 1).unwrap_or(i32::MAX),
+                        tax_rule_name: Some(rate.type_.clone()),
+                        tax_included_in_price: Some(false),
+                        applies_to_energy_fee: false,
+                        applies_to_parking_fee: false,
+                        applies_to_overstay_fee: false,
+                        applies_to_minimum_maximum_cost: false,
+                        tax_rate,
+                        custom_data: None,
+                    });
+                    rules.len() - 1
+                });
+                let rule = &mut rules[index];
+                match fee {
+                    Fee::Energy => rule.applies_to_energy_fee = true,
+                    Fee::Parking => rule.applies_to_parking_fee = true,
+                    Fee::Overstay => rule.applies_to_overstay_fee = true,
+                    Fee::MinMax => rule.applies_to_minimum_maximum_cost = true,
+                }
+            }
+        }
+        (!rules.is_empty()).then_some(rules)
+    }
+}
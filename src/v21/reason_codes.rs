@@ -0,0 +1,51 @@
+//! Common `StatusInfoType.reasonCode` wire strings (OCPP 2.1 Appendix "Reason codes").
+//!
+//! Only the codes this crate emits itself are listed. Errata-specific spellings live in
+//! [`crate::v21::errata`].
+
+use alloc::string::{String, ToString};
+
+use super::datatypes::StatusInfoType;
+
+/// Value does not parse for the variable's data type or is not in its values list.
+pub const INVALID_VALUE: &str = "InvalidValue";
+/// Numeric value (or string length) is outside `minLimit` / `maxLimit`.
+pub const VALUE_OUT_OF_RANGE: &str = "ValueOutOfRange";
+/// List value has more members than `maxElements`.
+pub const TOO_MANY_ELEMENTS: &str = "TooManyElements";
+/// Attempt to write a `ReadOnly` or constant attribute.
+pub const READ_ONLY: &str = "ReadOnly";
+/// Attempt to read a `WriteOnly` attribute.
+pub const WRITE_ONLY: &str = "WriteOnly";
+/// The addressed EVSE does not exist.
+pub const UNKNOWN_EVSE: &str = "UnknownEvse";
+/// No (matching) transaction is active on the EVSE.
+pub const TX_NOT_FOUND: &str = "TxNotFound";
+/// Another profile already has this stack level and purpose on the EVSE.
+pub const DUPLICATE_PROFILE: &str = "DuplicateProfile";
+/// Charging profile fields are inconsistent (purpose, EVSE, recurrency, …).
+pub const INVALID_PROFILE: &str = "InvalidProfile";
+/// Charging schedule periods or start are inconsistent.
+pub const INVALID_SCHEDULE: &str = "InvalidSchedule";
+/// Generic rejection without a more specific code.
+pub const UNSPECIFIED: &str = "Unspecified";
+
+/// Build a [`StatusInfoType`] carrying only a reason code.
+#[must_use]
+pub fn status_info(reason_code: &str) -> StatusInfoType {
+    StatusInfoType {
+        reason_code: reason_code.to_string(),
+        additional_info: None,
+        custom_data: None,
+    }
+}
+
+/// Build a [`StatusInfoType`] with a reason code and free-text detail.
+#[must_use]
+pub fn status_info_with(reason_code: &str, additional_info: impl Into<String>) -> StatusInfoType {
+    StatusInfoType {
+        reason_code: reason_code.to_string(),
+        additional_info: Some(additional_info.into()),
+        custom_data: None,
+    }
+}
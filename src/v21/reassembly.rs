@@ -0,0 +1,545 @@
+//! CSMS-side reassembly of fragmented reports (`requestId` + `seqNo` + `tbc`).
+//!
+//! `NotifyReport`, `NotifyMonitoringReport`, `NotifyCustomerInformation` and
+//! `NotifyDisplayMessages` stream their results over several CALLs. [`Reassembler`] collects
+//! fragments per `requestId` (out of order, duplicates ignored), reports missing `seqNo`s and
+//! expires reports that stop arriving. A completed `requestId` is remembered for the same
+//! timeout so that retransmitted fragments are not taken for a new report.
+//!
+//! Completed `NotifyReport`s group into an [`Inventory`], `NotifyMonitoringReport`s into a
+//! [`MonitoringInventory`].
+//!
+//! `NotifyDisplayMessages` carries no `seqNo`; its fragments are numbered by arrival order, so
+//! duplicates and gaps cannot be detected for it.
+//!
+//! No clock is read: callers pass `now` (any UTC source) to [`Reassembler::push`] and
+//! [`Reassembler::expire`].
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::datatypes::component_type::same_component;
+use super::datatypes::variable_type::same_variable;
+use super::datatypes::{ComponentType, MessageInfoType, VariableType};
+use super::enumerations::AttributeEnumType;
+use super::messages::notify_customer_information::NotifyCustomerInformationRequest;
+use super::messages::notify_display_messages::NotifyDisplayMessagesRequest;
+use super::messages::notify_monitoring_report::{
+    MonitoringDataType, NotifyMonitoringReportRequest, VariableMonitoringType,
+};
+use super::messages::notify_report::{
+    NotifyReportRequest, ReportDataType, VariableAttributeType, VariableCharacteristicsType,
+};
+
+/// One fragment of a `tbc`-streamed report.
+pub trait Fragment {
+    type Item;
+
+    fn request_id(&self) -> i32;
+    /// `None` for messages without `seqNo` (numbered by arrival).
+    fn seq_no(&self) -> Option<i32>;
+    /// "To be continued": more fragments follow.
+    fn tbc(&self) -> bool;
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+impl Fragment for NotifyReportRequest {
+    type Item = ReportDataType;
+
+    fn request_id(&self) -> i32 {
+        self.request_id
+    }
+    fn seq_no(&self) -> Option<i32> {
+        Some(self.seq_no)
+    }
+    fn tbc(&self) -> bool {
+        self.tbc.unwrap_or(false)
+    }
+    fn into_items(self) -> Vec<ReportDataType> {
+        self.report_data.unwrap_or_default()
+    }
+}
+
+impl Fragment for NotifyMonitoringReportRequest {
+    type Item = MonitoringDataType;
+
+    fn request_id(&self) -> i32 {
+        self.request_id
+    }
+    fn seq_no(&self) -> Option<i32> {
+        Some(self.seq_no)
+    }
+    fn tbc(&self) -> bool {
+        self.tbc.unwrap_or(false)
+    }
+    fn into_items(self) -> Vec<MonitoringDataType> {
+        self.monitor.unwrap_or_default()
+    }
+}
+
+impl Fragment for NotifyCustomerInformationRequest {
+    type Item = String;
+
+    fn request_id(&self) -> i32 {
+        self.request_id
+    }
+    fn seq_no(&self) -> Option<i32> {
+        Some(self.seq_no)
+    }
+    fn tbc(&self) -> bool {
+        self.tbc.unwrap_or(false)
+    }
+    fn into_items(self) -> Vec<String> {
+        alloc::vec![self.data]
+    }
+}
+
+impl Fragment for NotifyDisplayMessagesRequest {
+    type Item = MessageInfoType;
+
+    fn request_id(&self) -> i32 {
+        self.request_id
+    }
+    fn seq_no(&self) -> Option<i32> {
+        None
+    }
+    fn tbc(&self) -> bool {
+        self.tbc.unwrap_or(false)
+    }
+    fn into_items(self) -> Vec<MessageInfoType> {
+        self.message_info.unwrap_or_default()
+    }
+}
+
+/// Highest number of fragments one report may span; a `seqNo` at or above this is
+/// [`FragmentOutcome::Invalid`].
+pub const MAX_FRAGMENTS: i32 = 4096;
+
+/// All items of one `requestId`, in `seqNo` order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletedReport<T> {
+    pub request_id: i32,
+    pub items: Vec<T>,
+    /// Number of fragments the report arrived in.
+    pub fragments: usize,
+}
+
+/// A report that timed out before all fragments arrived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiredReport<T> {
+    pub request_id: i32,
+    /// Items of the fragments that did arrive, in `seqNo` order.
+    pub items: Vec<T>,
+    /// `seqNo`s known to be missing (gaps below the highest / final `seqNo` seen).
+    pub missing: Vec<i32>,
+    /// `false` when the final (`tbc = false`) fragment never arrived.
+    pub final_received: bool,
+}
+
+/// Result of [`Reassembler::push`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FragmentOutcome<T> {
+    /// Stored; the report is not complete yet.
+    Pending { missing: Vec<i32> },
+    /// Same `seqNo` already received for this `requestId`; fragment ignored.
+    Duplicate,
+    /// The `requestId` completed within the timeout; fragment ignored as a retransmission.
+    AlreadyComplete,
+    /// Negative `seqNo`, a `seqNo` of [`MAX_FRAGMENTS`] or more, a `seqNo` past the final
+    /// fragment (either order), or a second final fragment.
+    Invalid,
+    /// Every fragment `0..=final` has arrived.
+    Complete(CompletedReport<T>),
+}
+
+#[derive(Debug, Clone)]
+struct Partial<T> {
+    fragments: BTreeMap<i32, Vec<T>>,
+    final_seq: Option<i32>,
+    last_seen: DateTime<Utc>,
+}
+
+impl<T> Partial<T> {
+    /// Gaps below the highest stored `seqNo` (the final one, once it has arrived).
+    fn missing(&self) -> Vec<i32> {
+        let mut missing = Vec::new();
+        let mut next = 0;
+        for &seq_no in self.fragments.keys() {
+            missing.extend(next..seq_no);
+            next = seq_no.saturating_add(1);
+        }
+        missing
+    }
+
+    fn into_items(self) -> Vec<T> {
+        self.fragments.into_values().flatten().collect()
+    }
+}
+
+/// Collects `tbc` fragments per `requestId` until complete.
+#[derive(Debug, Clone)]
+pub struct Reassembler<F: Fragment> {
+    partial: BTreeMap<i32, Partial<F::Item>>,
+    /// Completion time of recently completed `requestId`s.
+    completed: BTreeMap<i32, DateTime<Utc>>,
+    timeout: TimeDelta,
+}
+
+impl<F: Fragment> Reassembler<F> {
+    /// `timeout`: inactivity after which [`Self::expire`] gives up on a report.
+    #[must_use]
+    pub const fn new(timeout: TimeDelta) -> Self {
+        Self {
+            partial: BTreeMap::new(),
+            completed: BTreeMap::new(),
+            timeout,
+        }
+    }
+
+    /// Number of reports still waiting for fragments.
+    #[must_use]
+    pub fn pending_len(&self) -> usize {
+        self.partial.len()
+    }
+
+    #[must_use]
+    pub fn is_pending(&self, request_id: i32) -> bool {
+        self.partial.contains_key(&request_id)
+    }
+
+    /// Missing `seqNo`s of a pending report (`None` if not pending).
+    #[must_use]
+    pub fn missing(&self, request_id: i32) -> Option<Vec<i32>> {
+        self.partial.get(&request_id).map(Partial::missing)
+    }
+
+    /// Add a received fragment.
+    pub fn push(&mut self, fragment: F, now: DateTime<Utc>) -> FragmentOutcome<F::Item> {
+        let request_id = fragment.request_id();
+        let tbc = fragment.tbc();
+        if let Some(&completed_at) = self.completed.get(&request_id) {
+            if now - completed_at <= self.timeout {
+                return FragmentOutcome::AlreadyComplete;
+            }
+            self.completed.remove(&request_id);
+        }
+        let partial = self.partial.entry(request_id).or_insert_with(|| Partial {
+            fragments: BTreeMap::new(),
+            final_seq: None,
+            last_seen: now,
+        });
+        let seq_no = match fragment.seq_no() {
+            Some(s) => s,
+            None => partial
+                .fragments
+                .keys()
+                .next_back()
+                .map_or(0, |s| s.saturating_add(1)),
+        };
+        let past_final = partial.final_seq.is_some_and(|f| seq_no > f)
+            || (!tbc && partial.fragments.keys().next_back() > Some(&seq_no));
+        let second_final = !tbc && partial.final_seq.is_some_and(|f| f != seq_no);
+        let outcome = if partial.fragments.contains_key(&seq_no) {
+            Some(FragmentOutcome::Duplicate)
+        } else if !(0..MAX_FRAGMENTS).contains(&seq_no) || past_final || second_final {
+            Some(FragmentOutcome::Invalid)
+        } else {
+            None
+        };
+        if let Some(outcome) = outcome {
+            if partial.fragments.is_empty() {
+                self.partial.remove(&request_id);
+            }
+            return outcome;
+        }
+
+        partial.last_seen = now;
+        if !tbc {
+            partial.final_seq = Some(seq_no);
+        }
+        partial.fragments.insert(seq_no, fragment.into_items());
+        let missing = partial.missing();
+        if partial.final_seq.is_none() || !missing.is_empty() {
+            return FragmentOutcome::Pending { missing };
+        }
+        let Some(done) = self.partial.remove(&request_id) else {
+            return FragmentOutcome::Pending { missing };
+        };
+        self.completed.insert(request_id, now);
+        let fragments = done.fragments.len();
+        FragmentOutcome::Complete(CompletedReport {
+            request_id,
+            items: done.into_items(),
+            fragments,
+        })
+    }
+
+    /// Remove and return reports with no fragment for longer than the timeout, and forget
+    /// `requestId`s completed longer ago than that.
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<ExpiredReport<F::Item>> {
+        let timeout = self.timeout;
+        self.completed.retain(|_, at| now - *at <= timeout);
+        let (expired, live) = core::mem::take(&mut self.partial)
+            .into_iter()
+            .partition(|(_, p)| now - p.last_seen > timeout);
+        self.partial = live;
+        expired
+            .into_iter()
+            .map(
+                |(request_id, partial): (i32, Partial<F::Item>)| ExpiredReport {
+                    request_id,
+                    missing: partial.missing(),
+                    final_received: partial.final_seq.is_some(),
+                    items: partial.into_items(),
+                },
+            )
+            .collect()
+    }
+}
+
+/// One variable of a reassembled [`Inventory`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryVariable {
+    pub variable: VariableType,
+    pub attributes: Vec<VariableAttributeType>,
+    pub characteristics: Option<VariableCharacteristicsType>,
+}
+
+impl InventoryVariable {
+    /// Value of an attribute (an attribute without `type` counts as `Actual`).
+    #[must_use]
+    pub fn value(&self, attribute_type: &AttributeEnumType) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.type_.as_ref().unwrap_or(&AttributeEnumType::Actual) == attribute_type)
+            .and_then(|a| a.value.as_deref())
+    }
+}
+
+/// One component of a reassembled [`Inventory`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryComponent {
+    pub component: ComponentType,
+    pub variables: Vec<InventoryVariable>,
+}
+
+impl InventoryComponent {
+    /// Variable by name (case-insensitive) and instance.
+    #[must_use]
+    pub fn variable(&self, name: &str, instance: Option<&str>) -> Option<&InventoryVariable> {
+        self.variables.iter().find(|v| {
+            v.variable.name.eq_ignore_ascii_case(name) && v.variable.instance.as_deref() == instance
+        })
+    }
+}
+
+/// Components / variables / attributes of a reassembled `NotifyReport`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Inventory {
+    pub components: Vec<InventoryComponent>,
+}
+
+impl Inventory {
+    /// Group report items by component (first-seen order). Later items for the same
+    /// component/variable replace earlier ones.
+    #[must_use]
+    pub fn from_report_data(items: impl IntoIterator<Item = ReportDataType>) -> Self {
+        let mut inventory = Self::default();
+        for item in items {
+            let idx = if let Some(idx) = inventory
+                .components
+                .iter()
+                .position(|c| same_component(&c.component, &item.component))
+            {
+                idx
+            } else {
+                inventory.components.push(InventoryComponent {
+                    component: item.component.clone(),
+                    variables: Vec::new(),
+                });
+                inventory.components.len() - 1
+            };
+            let variables = &mut inventory.components[idx].variables;
+            let variable = InventoryVariable {
+                variable: item.variable,
+                attributes: item.variable_attribute,
+                characteristics: item.variable_characteristics,
+            };
+            if let Some(existing) = variables
+                .iter_mut()
+                .find(|v| same_variable(&v.variable, &variable.variable))
+            {
+                *existing = variable;
+            } else {
+                variables.push(variable);
+            }
+        }
+        inventory
+    }
+
+    /// Every component with this name (all EVSEs / instances).
+    pub fn components_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a InventoryComponent> + 'a {
+        self.components
+            .iter()
+            .filter(move |c| c.component.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Component matching name, instance and EVSE / connector exactly.
+    #[must_use]
+    pub fn component(&self, component: &ComponentType) -> Option<&InventoryComponent> {
+        self.components
+            .iter()
+            .find(|c| same_component(&c.component, component))
+    }
+
+    /// Total number of variables across components.
+    #[must_use]
+    pub fn variable_count(&self) -> usize {
+        self.components.iter().map(|c| c.variables.len()).sum()
+    }
+}
+
+impl From<CompletedReport<ReportDataType>> for Inventory {
+    fn from(report: CompletedReport<ReportDataType>) -> Self {
+        Self::from_report_data(report.items)
+    }
+}
+
+/// Monitors of one variable in a reassembled [`MonitoringInventory`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitoredVariable {
+    pub variable: VariableType,
+    pub monitors: Vec<VariableMonitoringType>,
+}
+
+/// One component of a reassembled [`MonitoringInventory`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitoredComponent {
+    pub component: ComponentType,
+    pub variables: Vec<MonitoredVariable>,
+}
+
+impl MonitoredComponent {
+    /// Variable by name (case-insensitive) and instance.
+    #[must_use]
+    pub fn variable(&self, name: &str, instance: Option<&str>) -> Option<&MonitoredVariable> {
+        self.variables.iter().find(|v| {
+            v.variable.name.eq_ignore_ascii_case(name) && v.variable.instance.as_deref() == instance
+        })
+    }
+}
+
+/// Components / variables / monitors of a reassembled `NotifyMonitoringReport`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MonitoringInventory {
+    pub components: Vec<MonitoredComponent>,
+}
+
+impl MonitoringInventory {
+    /// Group report items by component and variable (first-seen order). A later monitor with
+    /// the same `id` replaces the earlier one.
+    #[must_use]
+    pub fn from_monitoring_data(items: impl IntoIterator<Item = MonitoringDataType>) -> Self {
+        let mut inventory = Self::default();
+        for item in items {
+            let idx = if let Some(idx) = inventory
+                .components
+                .iter()
+                .position(|c| same_component(&c.component, &item.component))
+            {
+                idx
+            } else {
+                inventory.components.push(MonitoredComponent {
+                    component: item.component.clone(),
+                    variables: Vec::new(),
+                });
+                inventory.components.len() - 1
+            };
+            let variables = &mut inventory.components[idx].variables;
+            let position = variables
+                .iter()
+                .position(|v| same_variable(&v.variable, &item.variable));
+            let variable = if let Some(position) = position {
+                &mut variables[position]
+            } else {
+                variables.push(MonitoredVariable {
+                    variable: item.variable,
+                    monitors: Vec::new(),
+                });
+                let last = variables.len() - 1;
+                &mut variables[last]
+            };
+            for monitor in item.variable_monitoring {
+                if let Some(existing) = variable.monitors.iter_mut().find(|m| m.id == monitor.id) {
+                    *existing = monitor;
+                } else {
+                    variable.monitors.push(monitor);
+                }
+            }
+        }
+        inventory
+    }
+
+    /// Every component with this name (all EVSEs / instances).
+    pub fn components_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a MonitoredComponent> + 'a {
+        self.components
+            .iter()
+            .filter(move |c| c.component.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Component matching name, instance and EVSE / connector exactly.
+    #[must_use]
+    pub fn component(&self, component: &ComponentType) -> Option<&MonitoredComponent> {
+        self.components
+            .iter()
+            .find(|c| same_component(&c.component, component))
+    }
+
+    /// Monitor by id, with the component and variable it watches.
+    #[must_use]
+    pub fn monitor(
+        &self,
+        id: i32,
+    ) -> Option<(&ComponentType, &VariableType, &VariableMonitoringType)> {
+        self.components.iter().find_map(|c| {
+            c.variables.iter().find_map(|v| {
+                v.monitors
+                    .iter()
+                    .find(|m| m.id == id)
+                    .map(|m| (&c.component, &v.variable, m))
+            })
+        })
+    }
+
+    /// Total number of monitors across components.
+    #[must_use]
+    pub fn monitor_count(&self) -> usize {
+        self.components
+            .iter()
+            .flat_map(|c| &c.variables)
+            .map(|v| v.monitors.len())
+            .sum()
+    }
+}
+
+impl From<CompletedReport<MonitoringDataType>> for MonitoringInventory {
+    fn from(report: CompletedReport<MonitoringDataType>) -> Self {
+        Self::from_monitoring_data(report.items)
+    }
+}
+
+impl CompletedReport<String> {
+    /// Concatenate `NotifyCustomerInformation` `data` chunks.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.items.concat()
+    }
+}
@@ -0,0 +1,110 @@
+//! 2.1 front end of the registration state machine ([`crate::registration`]).
+//!
+//! While `Pending` a 2.1 station only sends `BootNotification` and what the CSMS asked for:
+//! messages named in a `TriggerMessage`, and `NotifyReport` / `NotifyMonitoringReport` after a
+//! report request. `RequestStartTransaction` and `RequestStopTransaction` are not processed
+//! before `Accepted`, and `TriggerMessage(BootNotification)` is rejected once accepted.
+
+use chrono::{DateTime, Utc};
+
+use super::call::Action;
+use super::messages::boot_notification::{BootNotificationResponse, RegistrationStatusEnumType};
+use super::messages::trigger_message::{MessageTriggerEnumType, TriggerMessageStatusEnumType};
+use crate::registration::{Registration, RegistrationState};
+
+#[must_use]
+pub const fn registration_state(status: &RegistrationStatusEnumType) -> RegistrationState {
+    match status {
+        RegistrationStatusEnumType::Accepted => RegistrationState::Accepted,
+        RegistrationStatusEnumType::Pending => RegistrationState::Pending,
+        RegistrationStatusEnumType::Rejected => RegistrationState::Rejected,
+    }
+}
+
+/// Apply a `BootNotificationResponse`.
+pub fn on_boot_response(
+    registration: &mut Registration,
+    response: &BootNotificationResponse,
+    now: DateTime<Utc>,
+) {
+    registration.on_boot_response(registration_state(&response.status), response.interval, now);
+}
+
+/// Whether the station may send this CALL now.
+#[must_use]
+pub fn may_send(registration: &Registration, action: &Action) -> bool {
+    registration.may_send(action.action_name())
+}
+
+/// Whether a CALL from the CSMS is processed in the current state. Report requests grant the
+/// matching notification while not accepted.
+pub fn on_csms_call(registration: &mut Registration, action: &Action) -> bool {
+    if registration.is_accepted() {
+        return true;
+    }
+    if !registration.answers_csms() {
+        return false;
+    }
+    match action {
+        Action::RequestStartTransaction(_) | Action::RequestStopTransaction(_) => false,
+        Action::GetBaseReport(_) | Action::GetReport(_) => {
+            registration.grant("NotifyReport");
+            true
+        }
+        Action::GetMonitoringReport(_) => {
+            registration.grant("NotifyMonitoringReport");
+            true
+        }
+        _ => true,
+    }
+}
+
+/// Action name a trigger asks for; `None` for `CustomTrigger`.
+#[must_use]
+pub const fn triggered_action(requested: &MessageTriggerEnumType) -> Option<&'static str> {
+    Some(match requested {
+        MessageTriggerEnumType::BootNotification => "BootNotification",
+        MessageTriggerEnumType::LogStatusNotification => "LogStatusNotification",
+        MessageTriggerEnumType::FirmwareStatusNotification => "FirmwareStatusNotification",
+        MessageTriggerEnumType::Heartbeat => "Heartbeat",
+        MessageTriggerEnumType::MeterValues => "MeterValues",
+        MessageTriggerEnumType::SignChargingStationCertificate
+        | MessageTriggerEnumType::SignV2GCertificate
+        | MessageTriggerEnumType::SignV2G20Certificate
+        | MessageTriggerEnumType::SignCombinedCertificate => "SignCertificate",
+        MessageTriggerEnumType::StatusNotification => "StatusNotification",
+        MessageTriggerEnumType::TransactionEvent => "TransactionEvent",
+        MessageTriggerEnumType::PublishFirmwareStatusNotification => {
+            "PublishFirmwareStatusNotification"
+        }
+        MessageTriggerEnumType::CustomTrigger => return None,
+    })
+}
+
+/// Status for a `TriggerMessage` with `requested`.
+///
+/// `BootNotification` is scheduled at `now` unless already accepted; other messages are
+/// granted while not accepted. `CustomTrigger` is left to the caller (`NotImplemented`).
+pub fn on_trigger_message(
+    registration: &mut Registration,
+    requested: &MessageTriggerEnumType,
+    now: DateTime<Utc>,
+) -> TriggerMessageStatusEnumType {
+    let Some(action_name) = triggered_action(requested) else {
+        return TriggerMessageStatusEnumType::NotImplemented;
+    };
+    if *requested == MessageTriggerEnumType::BootNotification {
+        return if !registration.is_accepted() && registration.trigger_boot(now) {
+            TriggerMessageStatusEnumType::Accepted
+        } else {
+            TriggerMessageStatusEnumType::Rejected
+        };
+    }
+    if !registration.answers_csms() {
+        return TriggerMessageStatusEnumType::Rejected;
+    }
+    if !registration.is_accepted() {
+        registration.grant(action_name);
+    }
+    TriggerMessageStatusEnumType::Accepted
+}
@@ -0,0 +1,225 @@
+//! Station-side `NotifyReport` generation for `GetBaseReport` / `GetReport`.
+//!
+//! [`filter_report_data`] applies the `GetReportRequest` component / variable filters and
+//! component criteria; [`build_notify_reports`] splits the result into `NotifyReportRequest`s
+//! with `seqNo` / `tbc`, honouring `ItemsPerMessage` / `BytesPerMessage`
+//! (`DeviceDataCtrlr`, instance `GetReport`).
+//!
+//! Sizes are measured on the complete OCPP-J CALL frame produced by
+//! [`parse::serialize_message`], using a placeholder message id of the maximum allowed length,
+//! so a frame never exceeds the limit whatever id the caller assigns.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::Serialize;
+
+use super::call::{Action, Call};
+use super::datatypes::{ComponentVariableType, DateTimeWrapper};
+use super::enumerations::AttributeEnumType;
+use super::messages::get_report::{ComponentCriterionEnumType, GetReportRequest};
+use super::messages::notify_report::{NotifyReportRequest, ReportDataType};
+use super::parse::{self, Message};
+use crate::errors::{Error, Result};
+use crate::validate::{ConstraintViolation, MESSAGE_ID_MAX_LEN};
+
+/// `ItemsPerMessage` / `BytesPerMessage` limits for one message kind. `None` = unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MessageLimits {
+    pub items_per_message: Option<usize>,
+    pub bytes_per_message: Option<usize>,
+}
+
+impl MessageLimits {
+    #[must_use]
+    pub const fn new(items_per_message: Option<usize>, bytes_per_message: Option<usize>) -> Self {
+        Self {
+            items_per_message,
+            bytes_per_message,
+        }
+    }
+}
+
+/// Serialized length of a CALL frame carrying `action`, with a worst-case message id.
+///
+/// # Errors
+/// Propagates [`parse::serialize_message`] errors.
+pub fn call_frame_len(action: Action) -> Result<usize> {
+    let id: String = "0".repeat(MESSAGE_ID_MAX_LEN);
+    parse::serialize_message(&Message::Call(Call::new(id, action))).map(|s| s.len())
+}
+
+fn json_len<T: Serialize>(value: &T) -> Result<usize> {
+    serde_json::to_value(value)
+        .map(|v| v.to_string().len())
+        .map_err(Error::SerdeJson)
+}
+
+/// Greedily pack `items` into chunks that respect `limits`.
+///
+/// `empty_frame_len(seq_no)` must return the frame length of chunk `seq_no` with an empty
+/// (but present) item array; each item adds its JSON length plus a separating comma.
+pub(crate) fn chunk_by_limits<T: Serialize>(
+    items: impl IntoIterator<Item = T>,
+    limits: &MessageLimits,
+    path: &str,
+    mut empty_frame_len: impl FnMut(usize) -> Result<usize>,
+) -> Result<Vec<Vec<T>>> {
+    let max_items = limits.items_per_message.unwrap_or(usize::MAX).max(1);
+    let mut chunks: Vec<Vec<T>> = Vec::new();
+    let mut current: Vec<T> = Vec::new();
+    let mut base = empty_frame_len(0)?;
+    let mut used = base;
+    for (i, item) in items.into_iter().enumerate() {
+        let item_len = json_len(&item)?;
+        let sep = usize::from(!current.is_empty());
+        let fits_bytes = limits
+            .bytes_per_message
+            .is_none_or(|max| used + sep + item_len <= max);
+        if !current.is_empty() && (current.len() >= max_items || !fits_bytes) {
+            chunks.push(core::mem::take(&mut current));
+            base = empty_frame_len(chunks.len())?;
+            used = base;
+        }
+        if let Some(max) = limits.bytes_per_message {
+            if base + item_len > max {
+                return Err(ConstraintViolation::max_length(
+                    format!("{path}[{i}]"),
+                    max,
+                    base + item_len,
+                )
+                .into());
+            }
+        }
+        used += usize::from(!current.is_empty()) + item_len;
+        current.push(item);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    Ok(chunks)
+}
+
+/// Split `data` into `NotifyReportRequest`s (`seqNo` from 0, `tbc` on all but the last).
+///
+/// Returns an empty list for empty `data` (answer the request with `EmptyResultSet`).
+///
+/// # Errors
+/// * [`Error::ConstraintViolation`] if a single `ReportDataType` does not fit in
+///   `bytes_per_message` on its own
+/// * serialization errors while measuring
+pub fn build_notify_reports(
+    request_id: i32,
+    generated_at: DateTimeWrapper,
+    data: impl IntoIterator<Item = ReportDataType>,
+    limits: &MessageLimits,
+) -> Result<Vec<NotifyReportRequest>> {
+    let frame = |seq_no: usize, report_data: Option<Vec<ReportDataType>>, tbc: Option<bool>| {
+        Ok(NotifyReportRequest {
+            request_id,
+            generated_at,
+            report_data,
+            tbc,
+            seq_no: i32::try_from(seq_no).map_err(|_| Error::from("seqNo overflow"))?,
+            custom_data: None,
+        })
+    };
+    let chunks = chunk_by_limits(data, limits, "reportData", |seq_no| {
+        call_frame_len(Action::NotifyReport(frame(
+            seq_no,
+            Some(Vec::new()),
+            Some(true),
+        )?))
+    })?;
+    let last = chunks.len().saturating_sub(1);
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(seq_no, chunk)| frame(seq_no, Some(chunk), (seq_no < last).then_some(true)))
+        .collect()
+}
+
+/// Whether `data` is selected by a `GetReport` `componentVariable` filter.
+///
+/// Absent filter fields (EVSE, connector, instances, variable) act as wildcards.
+#[must_use]
+pub fn matches_component_variable(filter: &ComponentVariableType, data: &ReportDataType) -> bool {
+    let fc = &filter.component;
+    let dc = &data.component;
+    if !fc.name.eq_ignore_ascii_case(&dc.name) {
+        return false;
+    }
+    if fc.instance.is_some() && fc.instance != dc.instance {
+        return false;
+    }
+    if let Some(fe) = &fc.evse {
+        let Some(de) = &dc.evse else {
+            return false;
+        };
+        if fe.id != de.id || (fe.connector_id.is_some() && fe.connector_id != de.connector_id) {
+            return false;
+        }
+    }
+    filter.variable.as_ref().is_none_or(|fv| {
+        fv.name.eq_ignore_ascii_case(&data.variable.name)
+            && (fv.instance.is_none() || fv.instance == data.variable.instance)
+    })
+}
+
+fn actual_flag(data: &[&ReportDataType], variable: &str) -> Option<bool> {
+    data.iter()
+        .filter(|d| d.variable.name.eq_ignore_ascii_case(variable) && d.variable.instance.is_none())
+        .flat_map(|d| d.variable_attribute.iter())
+        .find(|a| matches!(a.type_, None | Some(AttributeEnumType::Actual)))
+        .and_then(|a| a.value.as_deref())
+        .map(|v| v.eq_ignore_ascii_case("true"))
+}
+
+/// Whether a component (all of its `ReportDataType`s) meets **any** of `criteria`.
+///
+/// Components without an `Active` / `Available` / `Enabled` variable count as meeting that
+/// criterion; components without a `Problem` variable do not meet `Problem`.
+#[must_use]
+pub fn meets_criteria(
+    criteria: &[ComponentCriterionEnumType],
+    component: &[&ReportDataType],
+) -> bool {
+    criteria.iter().any(|c| match c {
+        ComponentCriterionEnumType::Active => actual_flag(component, "Active").unwrap_or(true),
+        ComponentCriterionEnumType::Available => {
+            actual_flag(component, "Available").unwrap_or(true)
+        }
+        ComponentCriterionEnumType::Enabled => actual_flag(component, "Enabled").unwrap_or(true),
+        ComponentCriterionEnumType::Problem => actual_flag(component, "Problem").unwrap_or(false),
+    })
+}
+
+/// Apply a `GetReportRequest`'s `componentVariable` filters and `componentCriteria`.
+///
+/// An item is kept when it matches any filter (or no filters were sent) **and** its component
+/// meets any criterion (or no criteria were sent). Order is preserved.
+#[must_use]
+pub fn filter_report_data(
+    data: Vec<ReportDataType>,
+    request: &GetReportRequest,
+) -> Vec<ReportDataType> {
+    let filters = request.component_variable.as_deref().unwrap_or_default();
+    let criteria = request.component_criteria.as_deref().unwrap_or_default();
+    let keep: Vec<bool> = data
+        .iter()
+        .map(|d| {
+            let selected =
+                filters.is_empty() || filters.iter().any(|f| matches_component_variable(f, d));
+            selected
+                && (criteria.is_empty() || {
+                    let same_component: Vec<&ReportDataType> =
+                        data.iter().filter(|o| o.component == d.component).collect();
+                    meets_criteria(criteria, &same_component)
+                })
+        })
+        .collect();
+    data.into_iter()
+        .zip(keep)
+        .filter_map(|(d, k)| k.then_some(d))
+        .collect()
+}
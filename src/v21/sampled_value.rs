@@ -0,0 +1,139 @@
+//! Typed access to 2.1 [`SampledValueType`]s: the value as a normalized [`Quantity`] and
+//! the optional fields with their 2.1 defaults applied; decoding and verification of
+//! their [`SignedMeterValueType`].
+
+use super::datatypes::{SampledValueType, SignedMeterValueType};
+use super::enumerations::{LocationEnumType, MeasurandEnumType, ReadingContextEnumType};
+use crate::quantity::{Quantity, QuantityError, Unit};
+use crate::signed_meter::{Ocmf, SignatureVerifier, SignedMeterError, decode_public_key};
+use alloc::vec::Vec;
+
+/// Unit assumed when a sampled value of `measurand` carries none (2.1 defaults `unit` to
+/// Wh; the measurand decides in practice).
+#[must_use]
+pub const fn default_unit(measurand: &MeasurandEnumType) -> Option<Unit> {
+    use MeasurandEnumType as M;
+    Some(match measurand {
+        M::EnergyReactiveExportInterval
+        | M::EnergyReactiveExportRegister
+        | M::EnergyReactiveImportInterval
+        | M::EnergyReactiveImportRegister
+        | M::EnergyReactiveNet => Unit::Varh,
+        M::EnergyApparentExport | M::EnergyApparentImport | M::EnergyApparentNet => Unit::Vah,
+        M::PowerActiveExport
+        | M::PowerActiveImport
+        | M::PowerActiveSetpoint
+        | M::PowerActiveResidual
+        | M::PowerExportMinimum
+        | M::PowerExportOffered
+        | M::PowerImportOffered
+        | M::PowerImportMinimum
+        | M::PowerOffered => Unit::W,
+        M::PowerReactiveExport | M::PowerReactiveImport => Unit::Var,
+        M::CurrentExport
+        | M::CurrentExportOffered
+        | M::CurrentExportMinimum
+        | M::CurrentImport
+        | M::CurrentImportOffered
+        | M::CurrentImportMinimum
+        | M::CurrentOffered => Unit::A,
+        M::Voltage | M::VoltageMinimum | M::VoltageMaximum => Unit::V,
+        M::Frequency => Unit::Hz,
+        M::SoC
+        | M::DisplayPresentSOC
+        | M::DisplayMinimumSOC
+        | M::DisplayTargetSOC
+        | M::DisplayMaximumSOC => Unit::Percent,
+        M::DisplayRemainingTimeToMinimumSOC
+        | M::DisplayRemainingTimeToTargetSOC
+        | M::DisplayRemainingTimeToMaximumSOC => Unit::S,
+        M::PowerFactor | M::DisplayChargingComplete | M::DisplayInletHot => return None,
+        _ => Unit::Wh,
+    })
+}
+
+impl SampledValueType {
+    /// `measurand`, defaulting to `Energy.Active.Import.Register`.
+    #[must_use]
+    pub fn measurand_or_default(&self) -> MeasurandEnumType {
+        self.measurand
+            .clone()
+            .unwrap_or(MeasurandEnumType::EnergyActiveImportRegister)
+    }
+
+    /// `context`, defaulting to `Sample.Periodic`.
+    #[must_use]
+    pub fn context_or_default(&self) -> ReadingContextEnumType {
+        self.context
+            .clone()
+            .unwrap_or(ReadingContextEnumType::SamplePeriodic)
+    }
+
+    /// `location`, defaulting to `Outlet`.
+    #[must_use]
+    pub fn location_or_default(&self) -> LocationEnumType {
+        self.location.clone().unwrap_or(LocationEnumType::Outlet)
+    }
+
+    /// The value × 10^`multiplier` in its base unit; see [`default_unit`] when
+    /// `unitOfMeasure.unit` is absent.
+    ///
+    /// # Errors
+    /// [`QuantityError::UnknownUnit`] for a unit [`Unit::parse`] does not know.
+    pub fn quantity(&self) -> Result<Quantity, QuantityError> {
+        let unit_of_measure = self.unit_of_measure.as_ref();
+        let multiplier = unit_of_measure.and_then(|u| u.multiplier).unwrap_or(0);
+        let unit = match unit_of_measure.and_then(|u| u.unit.as_deref()) {
+            Some(unit) => {
+                Some(Unit::parse(unit).ok_or_else(|| QuantityError::UnknownUnit(unit.into()))?)
+            }
+            None => default_unit(&self.measurand_or_default()),
+        };
+        Ok(unit.map_or_else(
+            || Quantity::one(self.value * crate::quantity::pow10(multiplier)),
+            |unit| Quantity::new(self.value, unit, multiplier),
+        ))
+    }
+}
+
+impl SignedMeterValueType {
+    /// `signedMeterData` as OCMF (2.1 sends it base64 encoded; plain OCMF is accepted too).
+    ///
+    /// # Errors
+    /// [`SignedMeterError::UnsupportedEncoding`] unless `encodingMethod` is OCMF, else see
+    /// [`Ocmf::decode`].
+    pub fn ocmf(&self) -> Result<Ocmf, SignedMeterError> {
+        if !self.encoding_method.eq_ignore_ascii_case("OCMF") {
+            return Err(SignedMeterError::UnsupportedEncoding(
+                self.encoding_method.clone(),
+            ));
+        }
+        Ocmf::decode(&self.signed_meter_data)
+    }
+
+    /// `publicKey` decoded.
+    ///
+    /// # Errors
+    /// [`SignedMeterError::MissingPublicKey`] if absent, [`SignedMeterError::Encoding`] if
+    /// not hex or base64.
+    pub fn public_key_bytes(&self) -> Result<Vec<u8>, SignedMeterError> {
+        decode_public_key(
+            self.public_key
+                .as_deref()
+                .ok_or(SignedMeterError::MissingPublicKey)?,
+        )
+    }
+
+    /// Decode and verify with the `publicKey` sent along.
+    ///
+    /// Only proves the data matches the key in the message; check that key against the
+    /// one registered for the meter before trusting the readings.
+    ///
+    /// # Errors
+    /// See [`Self::ocmf`], [`Self::public_key_bytes`] and [`Ocmf::verify`].
+    pub fn verify(&self, verifier: &impl SignatureVerifier) -> Result<Ocmf, SignedMeterError> {
+        let ocmf = self.ocmf()?;
+        ocmf.verify(&self.public_key_bytes()?, verifier)?;
+        Ok(ocmf)
+    }
+}
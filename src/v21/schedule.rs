@@ -0,0 +1,116 @@
+//! Time queries on a 2.1 [`ChargingScheduleType`].
+//!
+//! Thin wrappers over [`crate::charging_schedule`]; `relative_start` is the instant a schedule
+//! without `startSchedule` started. Periods may omit `limit` (setpoint-only V2X periods), and
+//! `useLocalTime` / `randomizedDelay` are not applied.
+
+use chrono::{DateTime, Utc};
+
+use super::composite::convert_limit;
+use super::datatypes::{ChargingSchedulePeriodType, ChargingScheduleType};
+use super::enumerations::ChargingRateUnitEnumType;
+use crate::charging_schedule::{self, Schedule};
+
+/// A 2.1 schedule period resolved to absolute time: `[start, end)`.
+pub type EffectivePeriod<'a> = charging_schedule::EffectivePeriod<'a, ChargingSchedulePeriodType>;
+
+impl Schedule for ChargingScheduleType {
+    type Period = ChargingSchedulePeriodType;
+
+    fn start_schedule(&self) -> Option<DateTime<Utc>> {
+        self.start_schedule.map(|start| start.inner())
+    }
+
+    fn duration(&self) -> Option<i32> {
+        self.duration
+    }
+
+    fn periods(&self) -> &[ChargingSchedulePeriodType] {
+        &self.charging_schedule_period
+    }
+
+    fn start_period(period: &ChargingSchedulePeriodType) -> i32 {
+        period.start_period
+    }
+}
+
+impl ChargingScheduleType {
+    /// Instant the schedule starts: `startSchedule`, else `relative_start`.
+    #[must_use]
+    pub fn anchor(&self, relative_start: DateTime<Utc>) -> DateTime<Utc> {
+        charging_schedule::anchor(self, relative_start)
+    }
+
+    /// End of the schedule, if it has a `duration`.
+    #[must_use]
+    pub fn end(&self, relative_start: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        charging_schedule::end(self, relative_start)
+    }
+
+    /// Periods in `startPeriod` order; periods starting at or after the end of the schedule
+    /// are skipped.
+    pub fn effective_periods(
+        &self,
+        relative_start: DateTime<Utc>,
+    ) -> impl Iterator<Item = EffectivePeriod<'_>> + '_ {
+        charging_schedule::effective_periods(self, relative_start)
+    }
+
+    /// Period in effect at `t`.
+    #[must_use]
+    pub fn period_at(
+        &self,
+        relative_start: DateTime<Utc>,
+        t: DateTime<Utc>,
+    ) -> Option<EffectivePeriod<'_>> {
+        charging_schedule::period_at(self, relative_start, t)
+    }
+
+    /// Limit (in `chargingRateUnit`) at `t`; `None` before the first period, after the end
+    /// of the schedule or when the period sets no `limit`.
+    #[must_use]
+    pub fn limit_at(&self, relative_start: DateTime<Utc>, t: DateTime<Utc>) -> Option<f64> {
+        self.period_at(relative_start, t)
+            .and_then(|p| p.period.limit)
+    }
+
+    /// First instant after `t` at which the period in effect changes.
+    #[must_use]
+    pub fn next_change(
+        &self,
+        relative_start: DateTime<Utc>,
+        t: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        charging_schedule::next_change(self, relative_start, t)
+    }
+
+    /// Most energy (Wh) the schedule lets through between `from` and `to`. Limits in A are
+    /// converted with the period's `numberPhases` (3 when absent) at `voltage`, using
+    /// `limit_L2` / `limit_L3` for those phases when given. Periods without `limit` add
+    /// nothing.
+    #[must_use]
+    pub fn max_energy(
+        &self,
+        relative_start: DateTime<Utc>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        voltage: f64,
+    ) -> f64 {
+        charging_schedule::max_energy(self, relative_start, from, to, |period| {
+            let Some(limit) = period.limit else {
+                return 0.0;
+            };
+            let phases = period.number_phases.unwrap_or(3);
+            match (&self.charging_rate_unit, phases) {
+                (ChargingRateUnitEnumType::A, 3) => {
+                    let l2 = period.limit_l2.unwrap_or(limit);
+                    let l3 = period.limit_l3.unwrap_or(limit);
+                    (limit + l2 + l3) * voltage
+                }
+                (unit, _) => {
+                    convert_limit(limit, unit, &ChargingRateUnitEnumType::W, phases, voltage)
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,436 @@
+//! Cost of a transaction under a 2.1 [`TariffType`].
+//!
+//! A [`Timeline`] describes the transaction as consecutive charging / idle periods with the
+//! energy delivered in each. [`TariffType::cost`] applies, per dimension, the first price
+//! element whose conditions hold — evaluated piecewise, so a period crossing a time-of-day,
+//! energy or duration boundary is split there (energy assumed delivered linearly within a
+//! period) — then the dimension's tax rates and the tariff's `minCost` / `maxCost`.
+//!
+//! Condition units follow 2.1: energy in Wh, power in W, current in A, durations in
+//! seconds; `startTimeOfDay` / `endTimeOfDay` (`HH:MM`), `dayOfWeek` and
+//! `validFromDate` / `validToDate` (`YYYY-MM-DD`, to-date exclusive) are read in the
+//! timeline's local time. A condition that does not parse, or needs a value the timeline
+//! lacks (current, payment brand), does not hold. Amounts are not rounded.
+
+pub(crate) mod conditions;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+
+use super::datatypes::{PriceType, TariffFixedType, TariffTimeType, TariffType, TaxRateType};
+use super::enumerations::EvseKindEnumType;
+use super::messages::cost_updated::CostUpdatedRequest;
+use conditions::{State, cuts, holds, holds_fixed, period_conditions, seconds};
+
+/// One stretch of the transaction, `[start, end)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimelinePeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Whether the EV was charging (else idle: connected, not charging).
+    pub charging: bool,
+    /// Energy delivered in the period, Wh.
+    pub energy: f64,
+    /// Power, W; `None`: the period's average.
+    pub power: Option<f64>,
+    /// Current, A.
+    pub current: Option<f64>,
+}
+
+/// A transaction as seen by the tariff; see the module docs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    /// Offset of the station's local time, for time-of-day, day and date conditions.
+    pub utc_offset: FixedOffset,
+    /// Periods in time order.
+    pub periods: Vec<TimelinePeriod>,
+    pub evse_kind: Option<EvseKindEnumType>,
+    /// Reservation window preceding the transaction, if one was used.
+    pub reservation: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    pub payment_brand: Option<String>,
+    pub payment_recognition: Option<String>,
+}
+
+impl Timeline {
+    #[must_use]
+    pub const fn new(utc_offset: FixedOffset) -> Self {
+        Self {
+            utc_offset,
+            periods: Vec::new(),
+            evse_kind: None,
+            reservation: None,
+            payment_brand: None,
+            payment_recognition: None,
+        }
+    }
+
+    /// Append a charging period delivering `energy` Wh.
+    pub fn charging(&mut self, start: DateTime<Utc>, end: DateTime<Utc>, energy: f64) {
+        self.periods.push(TimelinePeriod {
+            start,
+            end,
+            charging: true,
+            energy,
+            power: None,
+            current: None,
+        });
+    }
+
+    /// Append an idle period.
+    pub fn idle(&mut self, start: DateTime<Utc>, end: DateTime<Utc>) {
+        self.periods.push(TimelinePeriod {
+            start,
+            end,
+            charging: false,
+            energy: 0.0,
+            power: None,
+            current: None,
+        });
+    }
+
+    fn local(&self, t: DateTime<Utc>) -> NaiveDateTime {
+        t.with_timezone(&self.utc_offset).naive_local()
+    }
+}
+
+/// What a [`CostLine`] charges for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostDimension {
+    /// `energy`, per kWh.
+    Energy,
+    /// `chargingTime`, per minute.
+    ChargingTime,
+    /// `idleTime`, per minute.
+    IdleTime,
+    /// `fixedFee`, once.
+    FixedFee,
+    /// `reservationTime`, per minute.
+    ReservationTime,
+    /// `reservationFixed`, once.
+    ReservationFixed,
+}
+
+/// A tax on a [`CostLine`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxAmount {
+    pub type_: String,
+    /// Rate, %.
+    pub rate: f64,
+    pub amount: f64,
+}
+
+/// Cost under one price element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostLine {
+    pub dimension: CostDimension,
+    /// Index of the element in the dimension's `prices`.
+    pub element: usize,
+    /// kWh, minutes or 1, per `dimension`.
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub excl_tax: f64,
+    pub incl_tax: f64,
+    pub taxes: Vec<TaxAmount>,
+}
+
+/// Which `TariffType` bound the total was clamped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostClamp {
+    MinCost,
+    MaxCost,
+}
+
+/// Cost of a [`Timeline`] under a tariff.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TariffCost {
+    pub tariff_id: String,
+    pub currency: String,
+    pub lines: Vec<CostLine>,
+    /// Total before tax, after clamping.
+    pub excl_tax: f64,
+    /// Total including tax, after clamping.
+    pub incl_tax: f64,
+    pub clamped: Option<CostClamp>,
+}
+
+impl TariffCost {
+    /// Sum of the lines of `dimension`, excluding tax.
+    #[must_use]
+    pub fn dimension_excl_tax(&self, dimension: CostDimension) -> f64 {
+        self.lines
+            .iter()
+            .filter(|l| l.dimension == dimension)
+            .map(|l| l.excl_tax)
+            .sum()
+    }
+
+    /// `CostUpdated` carrying the total including tax.
+    #[must_use]
+    pub const fn cost_updated(&self, transaction_id: String) -> CostUpdatedRequest {
+        CostUpdatedRequest {
+            total_cost: self.incl_tax,
+            transaction_id,
+            custom_data: None,
+        }
+    }
+}
+
+/// Tax on `amount` under `rates`: each `stack` level is taxed on the amount including the
+/// taxes of the levels below it (absent `stack` = 0).
+fn taxes(amount: f64, rates: Option<&Vec<TaxRateType>>) -> Vec<TaxAmount> {
+    let mut rates: Vec<&TaxRateType> = rates.into_iter().flatten().collect();
+    rates.sort_by_key(|r| r.stack.unwrap_or(0));
+    let mut taxes: Vec<TaxAmount> = Vec::new();
+    for level in rates.chunk_by(|a, b| a.stack.unwrap_or(0) == b.stack.unwrap_or(0)) {
+        let base = amount + taxes.iter().map(|t| t.amount).sum::<f64>();
+        taxes.extend(level.iter().map(|r| TaxAmount {
+            type_: r.type_.clone(),
+            rate: r.tax,
+            amount: base * r.tax / 100.0,
+        }));
+    }
+    taxes
+}
+
+/// Accumulates [`CostLine`]s per element.
+struct Lines(Vec<CostLine>);
+
+impl Lines {
+    fn add(&mut self, dimension: CostDimension, element: usize, quantity: f64, unit_price: f64) {
+        if let Some(line) = self
+            .0
+            .iter_mut()
+            .find(|l| l.dimension == dimension && l.element == element)
+        {
+            line.quantity += quantity;
+        } else {
+            self.0.push(CostLine {
+                dimension,
+                element,
+                quantity,
+                unit_price,
+                excl_tax: 0.0,
+                incl_tax: 0.0,
+                taxes: Vec::new(),
+            });
+        }
+    }
+
+    fn time(
+        &mut self,
+        dimension: CostDimension,
+        tariff: Option<&TariffTimeType>,
+        state: &State,
+        evse_kind: Option<&EvseKindEnumType>,
+        minutes: f64,
+    ) {
+        let element = tariff.and_then(|t| {
+            t.prices.iter().enumerate().find(|(_, p)| {
+                p.conditions
+                    .as_ref()
+                    .is_none_or(|c| holds(c, state, evse_kind))
+            })
+        });
+        if let Some((index, price)) = element {
+            self.add(dimension, index, minutes, price.price_minute);
+        }
+    }
+
+    fn fixed(
+        &mut self,
+        dimension: CostDimension,
+        tariff: Option<&TariffFixedType>,
+        timeline: &Timeline,
+        at: DateTime<Utc>,
+    ) {
+        let element = tariff.and_then(|t| {
+            t.prices.iter().enumerate().find(|(_, p)| {
+                p.conditions
+                    .as_ref()
+                    .is_none_or(|c| holds_fixed(c, timeline, at))
+            })
+        });
+        if let Some((index, price)) = element {
+            self.add(dimension, index, 1.0, price.price_fixed);
+        }
+    }
+}
+
+/// `value` scaled by `to / from`; `to` itself when `from` is not positive.
+fn rescale(value: f64, from: f64, to: f64) -> f64 {
+    if from > 0.0 { value * to / from } else { to }
+}
+
+/// `total` (excl., incl. tax) clamped to `bound` when `outside` says it lies beyond it.
+/// A bound given on one side only moves the other side proportionally.
+fn clamp(
+    total: (f64, f64),
+    bound: &PriceType,
+    outside: impl Fn(f64, f64) -> bool,
+) -> Option<(f64, f64)> {
+    let (excl, incl) = total;
+    match (bound.excl_tax, bound.incl_tax) {
+        (_, Some(bound_incl)) if outside(incl, bound_incl) => Some((
+            bound
+                .excl_tax
+                .unwrap_or_else(|| rescale(excl, incl, bound_incl)),
+            bound_incl,
+        )),
+        (Some(bound_excl), None) if outside(excl, bound_excl) => {
+            Some((bound_excl, rescale(incl, excl, bound_excl)))
+        }
+        _ => None,
+    }
+}
+
+impl TariffType {
+    /// Cost of `timeline` under this tariff; see the module docs.
+    #[must_use]
+    pub fn cost(&self, timeline: &Timeline) -> TariffCost {
+        let conditions = period_conditions(self);
+        let evse_kind = timeline.evse_kind.as_ref();
+        let mut lines = Lines(Vec::new());
+        let start = timeline.periods.first().map(|p| p.start);
+        let mut state = State::start(timeline, start.unwrap_or_default());
+        for period in &timeline.periods {
+            let duration = seconds(period.end - period.start);
+            if duration <= 0.0 {
+                continue;
+            }
+            let mut bounds = alloc::vec![period.start];
+            bounds.extend(cuts(&conditions, timeline, period, &state));
+            bounds.push(period.end);
+            for slice in bounds.windows(2) {
+                let slice_seconds = seconds(slice[1] - slice[0]);
+                let energy = period.energy * slice_seconds / duration;
+                state.local = timeline.local(slice[0]);
+                state.power = period.power.unwrap_or(period.energy * 3600.0 / duration);
+                state.current = period.current;
+                let element = self.energy.as_ref().and_then(|e| {
+                    e.prices.iter().enumerate().find(|(_, p)| {
+                        p.conditions
+                            .as_ref()
+                            .is_none_or(|c| holds(c, &state, evse_kind))
+                    })
+                });
+                if let Some((index, price)) = element.filter(|_| energy > 0.0) {
+                    lines.add(
+                        CostDimension::Energy,
+                        index,
+                        energy / 1000.0,
+                        price.price_kwh,
+                    );
+                }
+                let (dimension, time) = if period.charging {
+                    (CostDimension::ChargingTime, self.charging_time.as_ref())
+                } else {
+                    (CostDimension::IdleTime, self.idle_time.as_ref())
+                };
+                lines.time(dimension, time, &state, evse_kind, slice_seconds / 60.0);
+                state.energy += energy;
+                state.elapsed += slice_seconds;
+                if period.charging {
+                    state.charging += slice_seconds;
+                } else {
+                    state.idle += slice_seconds;
+                }
+            }
+        }
+        if let Some(start) = start {
+            lines.fixed(
+                CostDimension::FixedFee,
+                self.fixed_fee.as_ref(),
+                timeline,
+                start,
+            );
+        }
+        if let Some((from, to)) = timeline.reservation {
+            lines.fixed(
+                CostDimension::ReservationFixed,
+                self.reservation_fixed.as_ref(),
+                timeline,
+                from,
+            );
+            let period = TimelinePeriod {
+                start: from,
+                end: to,
+                charging: false,
+                energy: 0.0,
+                power: None,
+                current: None,
+            };
+            let mut bounds = alloc::vec![from];
+            let idle = State::start(timeline, from);
+            bounds.extend(cuts(&conditions, timeline, &period, &idle));
+            bounds.push(to);
+            for slice in bounds.windows(2).filter(|s| s[0] < s[1]) {
+                let reserved = State {
+                    local: timeline.local(slice[0]),
+                    elapsed: seconds(slice[0] - from),
+                    idle: seconds(slice[0] - from),
+                    ..idle
+                };
+                lines.time(
+                    CostDimension::ReservationTime,
+                    self.reservation_time.as_ref(),
+                    &reserved,
+                    evse_kind,
+                    seconds(slice[1] - slice[0]) / 60.0,
+                );
+            }
+        }
+
+        let mut lines = lines.0;
+        for line in &mut lines {
+            line.excl_tax = line.quantity * line.unit_price;
+            let rates = match line.dimension {
+                CostDimension::Energy => self.energy.as_ref().and_then(|e| e.tax_rates.as_ref()),
+                CostDimension::ChargingTime => self
+                    .charging_time
+                    .as_ref()
+                    .and_then(|t| t.tax_rates.as_ref()),
+                CostDimension::IdleTime => {
+                    self.idle_time.as_ref().and_then(|t| t.tax_rates.as_ref())
+                }
+                CostDimension::ReservationTime => self
+                    .reservation_time
+                    .as_ref()
+                    .and_then(|t| t.tax_rates.as_ref()),
+                CostDimension::FixedFee => {
+                    self.fixed_fee.as_ref().and_then(|f| f.tax_rates.as_ref())
+                }
+                CostDimension::ReservationFixed => self
+                    .reservation_fixed
+                    .as_ref()
+                    .and_then(|f| f.tax_rates.as_ref()),
+            };
+            line.taxes = taxes(line.excl_tax, rates);
+            line.incl_tax = line.excl_tax + line.taxes.iter().map(|t| t.amount).sum::<f64>();
+        }
+        let total = (
+            lines.iter().map(|l| l.excl_tax).sum(),
+            lines.iter().map(|l| l.incl_tax).sum(),
+        );
+        let (total, clamped) = self
+            .min_cost
+            .as_ref()
+            .and_then(|min| clamp(total, min, |value, bound| value < bound))
+            .map(|clamped| (clamped, Some(CostClamp::MinCost)))
+            .or_else(|| {
+                self.max_cost
+                    .as_ref()
+                    .and_then(|max| clamp(total, max, |value, bound| value > bound))
+                    .map(|clamped| (clamped, Some(CostClamp::MaxCost)))
+            })
+            .unwrap_or((total, None));
+        TariffCost {
+            tariff_id: self.tariff_id.clone(),
+            currency: self.currency.clone(),
+            lines,
+            excl_tax: total.0,
+            incl_tax: total.1,
+            clamped,
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Evaluation of tariff conditions, shared by the cost engine and
+//! [`crate::v21::price_schedule`]: whether a `TariffConditionsType` /
+//! `TariffConditionsFixedType` holds, and where within a period that may change.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{
+    DateTime, Datelike, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike, Utc, Weekday,
+};
+
+use super::{Timeline, TimelinePeriod};
+use crate::v21::datatypes::{TariffConditionsFixedType, TariffConditionsType, TariffType};
+use crate::v21::enumerations::{DayOfWeekEnumType, EvseKindEnumType};
+
+/// Where the transaction stands at the start of a slice.
+#[derive(Debug, Clone, Copy)]
+pub struct State {
+    pub local: NaiveDateTime,
+    /// Wh delivered so far.
+    pub energy: f64,
+    /// Seconds since the transaction started, charging, idle.
+    pub elapsed: f64,
+    pub charging: f64,
+    pub idle: f64,
+    pub power: f64,
+    pub current: Option<f64>,
+}
+
+impl State {
+    /// Nothing delivered or elapsed yet, at `t` in `timeline`'s local time.
+    pub fn start(timeline: &Timeline, t: DateTime<Utc>) -> Self {
+        Self {
+            local: timeline.local(t),
+            energy: 0.0,
+            elapsed: 0.0,
+            charging: 0.0,
+            idle: 0.0,
+            power: 0.0,
+            current: None,
+        }
+    }
+}
+
+/// `HH:MM` as minutes after midnight.
+pub fn minute_of_day(text: &str) -> Option<u32> {
+    let (hours, minutes) = text.split_once(':')?;
+    let (hours, minutes) = (hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// `YYYY-MM-DD`.
+pub fn date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+const fn weekday(day: &DayOfWeekEnumType) -> Weekday {
+    match day {
+        DayOfWeekEnumType::Monday => Weekday::Mon,
+        DayOfWeekEnumType::Tuesday => Weekday::Tue,
+        DayOfWeekEnumType::Wednesday => Weekday::Wed,
+        DayOfWeekEnumType::Thursday => Weekday::Thu,
+        DayOfWeekEnumType::Friday => Weekday::Fri,
+        DayOfWeekEnumType::Saturday => Weekday::Sat,
+        DayOfWeekEnumType::Sunday => Weekday::Sun,
+    }
+}
+
+/// The calendar part both condition types share.
+struct Calendar<'a> {
+    start_time_of_day: Option<&'a str>,
+    end_time_of_day: Option<&'a str>,
+    day_of_week: Option<&'a [DayOfWeekEnumType]>,
+    valid_from_date: Option<&'a str>,
+    valid_to_date: Option<&'a str>,
+    evse_kind: Option<&'a EvseKindEnumType>,
+}
+
+impl<'a> Calendar<'a> {
+    fn of(c: &'a TariffConditionsType) -> Self {
+        Self {
+            start_time_of_day: c.start_time_of_day.as_deref(),
+            end_time_of_day: c.end_time_of_day.as_deref(),
+            day_of_week: c.day_of_week.as_deref(),
+            valid_from_date: c.valid_from_date.as_deref(),
+            valid_to_date: c.valid_to_date.as_deref(),
+            evse_kind: c.evse_kind.as_ref(),
+        }
+    }
+
+    fn of_fixed(c: &'a TariffConditionsFixedType) -> Self {
+        Self {
+            start_time_of_day: c.start_time_of_day.as_deref(),
+            end_time_of_day: c.end_time_of_day.as_deref(),
+            day_of_week: c.day_of_week.as_deref(),
+            valid_from_date: c.valid_from_date.as_deref(),
+            valid_to_date: c.valid_to_date.as_deref(),
+            evse_kind: c.evse_kind.as_ref(),
+        }
+    }
+
+    fn holds(&self, local: NaiveDateTime, evse_kind: Option<&EvseKindEnumType>) -> bool {
+        let minute = local.hour() * 60 + local.minute();
+        let start = self.start_time_of_day.map(minute_of_day);
+        let end = self.end_time_of_day.map(minute_of_day);
+        let time_of_day = match (start, end) {
+            (Some(None), _) | (_, Some(None)) => false,
+            (Some(Some(start)), Some(Some(end))) if start > end => minute >= start || minute < end,
+            (start, end) => {
+                start.flatten().is_none_or(|s| minute >= s)
+                    && end.flatten().is_none_or(|e| minute < e)
+            }
+        };
+        time_of_day
+            && self
+                .day_of_week
+                .is_none_or(|days| days.iter().any(|d| weekday(d) == local.weekday()))
+            && self
+                .valid_from_date
+                .is_none_or(|from| date(from).is_some_and(|from| local.date() >= from))
+            && self
+                .valid_to_date
+                .is_none_or(|to| date(to).is_some_and(|to| local.date() < to))
+            && self.evse_kind.is_none_or(|kind| evse_kind == Some(kind))
+    }
+}
+
+/// `min <= value < max`, each bound optional.
+fn within(value: f64, min: Option<f64>, max: Option<f64>) -> bool {
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value < max)
+}
+
+/// Whether `c` holds in `state`.
+pub fn holds(
+    c: &TariffConditionsType,
+    state: &State,
+    evse_kind: Option<&EvseKindEnumType>,
+) -> bool {
+    let seconds = |s: Option<i32>| s.map(f64::from);
+    Calendar::of(c).holds(state.local, evse_kind)
+        && within(state.energy, c.min_energy, c.max_energy)
+        && within(state.power, c.min_power, c.max_power)
+        && (c.min_current.is_none() && c.max_current.is_none()
+            || state
+                .current
+                .is_some_and(|a| within(a, c.min_current, c.max_current)))
+        && within(state.elapsed, seconds(c.min_time), seconds(c.max_time))
+        && within(
+            state.charging,
+            seconds(c.min_charging_time),
+            seconds(c.max_charging_time),
+        )
+        && within(
+            state.idle,
+            seconds(c.min_idle_time),
+            seconds(c.max_idle_time),
+        )
+}
+
+/// Whether `c` holds at `at` in `timeline`.
+pub fn holds_fixed(c: &TariffConditionsFixedType, timeline: &Timeline, at: DateTime<Utc>) -> bool {
+    let payment =
+        |wanted: &Option<String>, actual: &Option<String>| wanted.is_none() || wanted == actual;
+    Calendar::of_fixed(c).holds(timeline.local(at), timeline.evse_kind.as_ref())
+        && payment(&c.payment_brand, &timeline.payment_brand)
+        && payment(&c.payment_recognition, &timeline.payment_recognition)
+}
+
+/// Every `TariffConditionsType` of `tariff` that applies to transaction periods.
+pub fn period_conditions(tariff: &TariffType) -> Vec<&TariffConditionsType> {
+    let energy = tariff
+        .energy
+        .iter()
+        .flat_map(|e| &e.prices)
+        .filter_map(|p| p.conditions.as_ref());
+    let time = [
+        &tariff.charging_time,
+        &tariff.idle_time,
+        &tariff.reservation_time,
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|t| &t.prices)
+    .filter_map(|p| p.conditions.as_ref());
+    energy.chain(time).collect()
+}
+
+/// `delta` in seconds, to the millisecond.
+#[allow(clippy::cast_precision_loss)]
+pub fn seconds(delta: TimeDelta) -> f64 {
+    delta.num_milliseconds() as f64 / 1000.0
+}
+
+/// `t` plus `seconds`, truncated to milliseconds; `None` if that is not representable.
+#[allow(clippy::cast_possible_truncation)]
+fn after(t: DateTime<Utc>, seconds: f64) -> Option<DateTime<Utc>> {
+    let millis = seconds * 1000.0;
+    if !millis.is_finite() {
+        return None;
+    }
+    t.checked_add_signed(TimeDelta::try_milliseconds(millis as i64)?)
+}
+
+/// Instants in `(start, end)` where a condition may change: local midnights and
+/// time-of-day bounds, and where `state` reaches an energy or duration bound.
+pub fn cuts(
+    conditions: &[&TariffConditionsType],
+    timeline: &Timeline,
+    period: &TimelinePeriod,
+    state: &State,
+) -> Vec<DateTime<Utc>> {
+    let mut cuts = Vec::new();
+    let mut minutes = alloc::vec![0];
+    minutes.extend(
+        conditions
+            .iter()
+            .flat_map(|c| [&c.start_time_of_day, &c.end_time_of_day])
+            .filter_map(|t| t.as_deref().and_then(minute_of_day)),
+    );
+    let (first_day, last_day) = (
+        timeline.local(period.start).date(),
+        timeline.local(period.end).date(),
+    );
+    for day in first_day.iter_days().take_while(|d| *d <= last_day) {
+        for minute in &minutes {
+            let local =
+                day.and_time(chrono::NaiveTime::MIN) + TimeDelta::minutes(i64::from(*minute));
+            if let Some(t) = timeline.utc_offset.from_local_datetime(&local).single() {
+                cuts.push(t.with_timezone(&Utc));
+            }
+        }
+    }
+    let duration = seconds(period.end - period.start);
+    // Offsets outside the period are dropped before conversion: a bound divided by a
+    // near-zero energy would not fit a timestamp.
+    let mut offsets = Vec::new();
+    if period.energy > 0.0 {
+        for bound in conditions
+            .iter()
+            .flat_map(|c| [c.min_energy, c.max_energy])
+            .flatten()
+        {
+            let fraction = (bound - state.energy) / period.energy;
+            offsets.push(fraction * duration);
+        }
+    }
+    let running = if period.charging {
+        state.charging
+    } else {
+        state.idle
+    };
+    for c in conditions {
+        let counter = if period.charging {
+            [c.min_charging_time, c.max_charging_time]
+        } else {
+            [c.min_idle_time, c.max_idle_time]
+        };
+        for bound in [c.min_time, c.max_time].into_iter().flatten() {
+            offsets.push(f64::from(bound) - state.elapsed);
+        }
+        for bound in counter.into_iter().flatten() {
+            offsets.push(f64::from(bound) - running);
+        }
+    }
+    cuts.extend(
+        offsets
+            .into_iter()
+            .filter(|offset| 0.0 < *offset && *offset < duration)
+            .filter_map(|offset| after(period.start, offset)),
+    );
+    cuts.retain(|t| period.start < *t && *t < period.end);
+    cuts.sort_unstable();
+    cuts.dedup();
+    cuts
+}
@@ -0,0 +1,598 @@
+//! Station-side store of tariffs.
+//!
+//! [`TariffStore`] applies `SetDefaultTariffRequest`, `GetTariffsRequest` and
+//! `ClearTariffsRequest`, and keeps the driver tariffs received in `AuthorizeResponse`:
+//!
+//! - a default tariff is set per EVSE, or for the whole station with `evseId` 0; several
+//!   may be held per EVSE with different `validFrom`, the latest one already valid applies;
+//! - a `tariffId` already installed with different content is a `DuplicateTariffId`
+//!   (setting the identical tariff again, on another EVSE, is fine);
+//! - `MaxElements[Tariff]` (`TooManyElements`), `ConditionsSupported[Tariff]`
+//!   (`ConditionNotSupported`) and the [`check_tariff`] consistency rules (currency, price
+//!   elements, conditions) are enforced.
+//!
+//! [`TariffStore::tariff_for`] picks the tariff a transaction starts with.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, Utc};
+use core::fmt;
+
+use super::datatypes::{
+    StatusInfoType, TariffConditionsFixedType, TariffConditionsType, TariffFixedType,
+    TariffTimeType, TariffType,
+};
+use super::evse::EvseState;
+use super::messages::authorize::AuthorizeResponse;
+use super::messages::clear_tariffs::{
+    ClearTariffsRequest, ClearTariffsResponse, ClearTariffsResultType, TariffClearStatusEnumType,
+};
+use super::messages::get_tariffs::{
+    GetTariffsRequest, GetTariffsResponse, TariffAssignmentType, TariffGetStatusEnumType,
+    TariffKindEnumType,
+};
+use super::messages::set_default_tariff::{
+    SetDefaultTariffRequest, SetDefaultTariffResponse, TariffSetStatusEnumType,
+};
+use super::reason_codes::{self, status_info, status_info_with};
+use super::tariff::CostDimension;
+use super::tariff::conditions::{date, minute_of_day};
+
+/// `TariffCostCtrlr` settings the store enforces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TariffLimits {
+    /// `MaxElements[Tariff]`: price elements per tariff, all dimensions together. `None` =
+    /// unlimited.
+    pub max_elements: Option<usize>,
+    /// `ConditionsSupported[Tariff]`.
+    pub conditions_supported: bool,
+    /// `Currency`; `None` = any, as long as all default tariffs agree.
+    pub currency: Option<String>,
+}
+
+impl Default for TariffLimits {
+    fn default() -> Self {
+        Self {
+            max_elements: None,
+            conditions_supported: true,
+            currency: None,
+        }
+    }
+}
+
+/// A problem [`check_tariff`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TariffIssue {
+    /// Not a single price element in any dimension.
+    NoPriceElement,
+    /// Another tariff with this `tariffId` and different content is installed.
+    DuplicateTariffId,
+    CurrencyMismatch {
+        expected: String,
+        found: String,
+    },
+    TooManyElements {
+        max: usize,
+        actual: usize,
+    },
+    /// The tariff has conditions, `ConditionsSupported[Tariff]` is false.
+    ConditionNotSupported,
+    /// A condition does not parse, or its minimum exceeds its maximum.
+    InvalidCondition {
+        dimension: CostDimension,
+        element: usize,
+    },
+    /// An earlier element without conditions, or with the same conditions, always wins:
+    /// this one never applies. Reported, not rejected.
+    ShadowedElement {
+        dimension: CostDimension,
+        element: usize,
+    },
+}
+
+impl TariffIssue {
+    /// Whether the tariff must be rejected for this issue.
+    #[must_use]
+    pub const fn is_fatal(&self) -> bool {
+        !matches!(self, Self::ShadowedElement { .. })
+    }
+}
+
+impl fmt::Display for TariffIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPriceElement => write!(f, "tariff has no price element"),
+            Self::DuplicateTariffId => write!(f, "another tariff with this id is installed"),
+            Self::CurrencyMismatch { expected, found } => {
+                write!(f, "currency {found}, expected {expected}")
+            }
+            Self::TooManyElements { max, actual } => {
+                write!(f, "{actual} price elements, at most {max} supported")
+            }
+            Self::ConditionNotSupported => write!(f, "tariff conditions are not supported"),
+            Self::InvalidCondition { dimension, element } => {
+                write!(f, "invalid conditions on {dimension:?} element {element}")
+            }
+            Self::ShadowedElement { dimension, element } => {
+                write!(f, "{dimension:?} element {element} never applies")
+            }
+        }
+    }
+}
+
+/// Per dimension, the conditions of each price element.
+fn elements(tariff: &TariffType) -> Vec<(CostDimension, Vec<Conditions<'_>>)> {
+    fn time(t: Option<&TariffTimeType>) -> Vec<Conditions<'_>> {
+        t.iter()
+            .flat_map(|t| &t.prices)
+            .map(|p| Conditions::Period(p.conditions.as_ref()))
+            .collect()
+    }
+    fn fixed(f: Option<&TariffFixedType>) -> Vec<Conditions<'_>> {
+        f.iter()
+            .flat_map(|f| &f.prices)
+            .map(|p| Conditions::Fixed(p.conditions.as_ref()))
+            .collect()
+    }
+    alloc::vec![
+        (
+            CostDimension::Energy,
+            tariff
+                .energy
+                .iter()
+                .flat_map(|e| &e.prices)
+                .map(|p| Conditions::Period(p.conditions.as_ref()))
+                .collect(),
+        ),
+        (
+            CostDimension::ChargingTime,
+            time(tariff.charging_time.as_ref())
+        ),
+        (CostDimension::IdleTime, time(tariff.idle_time.as_ref())),
+        (CostDimension::FixedFee, fixed(tariff.fixed_fee.as_ref())),
+        (
+            CostDimension::ReservationTime,
+            time(tariff.reservation_time.as_ref())
+        ),
+        (
+            CostDimension::ReservationFixed,
+            fixed(tariff.reservation_fixed.as_ref())
+        ),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Conditions<'a> {
+    Period(Option<&'a TariffConditionsType>),
+    Fixed(Option<&'a TariffConditionsFixedType>),
+}
+
+impl Conditions<'_> {
+    const fn is_none(&self) -> bool {
+        matches!(self, Self::Period(None) | Self::Fixed(None))
+    }
+
+    fn is_valid(&self) -> bool {
+        let calendar = |start: &Option<String>,
+                        end: &Option<String>,
+                        from: &Option<String>,
+                        to: &Option<String>| {
+            [start, end]
+                .into_iter()
+                .flatten()
+                .all(|t| minute_of_day(t).is_some())
+                && [from, to].into_iter().flatten().all(|d| date(d).is_some())
+        };
+        let ordered = |min: Option<f64>, max: Option<f64>| {
+            min.is_none_or(|min| min >= 0.0) && min.zip(max).is_none_or(|(min, max)| min <= max)
+        };
+        let seconds =
+            |min: Option<i32>, max: Option<i32>| ordered(min.map(f64::from), max.map(f64::from));
+        match self {
+            Self::Period(None) | Self::Fixed(None) => true,
+            Self::Period(Some(c)) => {
+                calendar(
+                    &c.start_time_of_day,
+                    &c.end_time_of_day,
+                    &c.valid_from_date,
+                    &c.valid_to_date,
+                ) && ordered(c.min_energy, c.max_energy)
+                    && ordered(c.min_current, c.max_current)
+                    && ordered(c.min_power, c.max_power)
+                    && seconds(c.min_time, c.max_time)
+                    && seconds(c.min_charging_time, c.max_charging_time)
+                    && seconds(c.min_idle_time, c.max_idle_time)
+            }
+            Self::Fixed(Some(c)) => calendar(
+                &c.start_time_of_day,
+                &c.end_time_of_day,
+                &c.valid_from_date,
+                &c.valid_to_date,
+            ),
+        }
+    }
+}
+
+/// Consistency of `tariff` against `limits`: all issues found, fatal or not.
+#[must_use]
+pub fn check_tariff(tariff: &TariffType, limits: &TariffLimits) -> Vec<TariffIssue> {
+    let mut issues = Vec::new();
+    let dimensions = elements(tariff);
+    let count: usize = dimensions.iter().map(|(_, e)| e.len()).sum();
+    if count == 0 {
+        issues.push(TariffIssue::NoPriceElement);
+    }
+    if let Some(expected) = limits.currency.as_ref().filter(|c| **c != tariff.currency) {
+        issues.push(TariffIssue::CurrencyMismatch {
+            expected: expected.clone(),
+            found: tariff.currency.clone(),
+        });
+    }
+    if let Some(max) = limits.max_elements.filter(|max| count > *max) {
+        issues.push(TariffIssue::TooManyElements { max, actual: count });
+    }
+    let conditional = dimensions
+        .iter()
+        .any(|(_, e)| e.iter().any(|c| !c.is_none()));
+    if conditional && !limits.conditions_supported {
+        issues.push(TariffIssue::ConditionNotSupported);
+    }
+    for (dimension, conditions) in &dimensions {
+        for (element, c) in conditions.iter().enumerate() {
+            if !c.is_valid() {
+                issues.push(TariffIssue::InvalidCondition {
+                    dimension: *dimension,
+                    element,
+                });
+            }
+            if conditions[..element]
+                .iter()
+                .any(|earlier| earlier.is_none() || earlier == c)
+            {
+                issues.push(TariffIssue::ShadowedElement {
+                    dimension: *dimension,
+                    element,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// A tariff as held by the store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredTariff {
+    pub kind: TariffKindEnumType,
+    /// EVSE of a default tariff, 0 = station-wide; 0 for driver tariffs.
+    pub evse_id: i32,
+    /// Token of a driver tariff.
+    pub id_token: Option<String>,
+    pub tariff: TariffType,
+}
+
+impl StoredTariff {
+    fn valid_from(&self) -> Option<DateTime<Utc>> {
+        self.tariff.valid_from.map(|v| v.inner())
+    }
+}
+
+/// Tariffs of one station.
+#[derive(Debug, Clone, Default)]
+pub struct TariffStore {
+    limits: TariffLimits,
+    tariffs: Vec<StoredTariff>,
+}
+
+const fn set_response(
+    status: TariffSetStatusEnumType,
+    status_info: Option<StatusInfoType>,
+) -> SetDefaultTariffResponse {
+    SetDefaultTariffResponse {
+        status,
+        status_info,
+        custom_data: None,
+    }
+}
+
+impl TariffStore {
+    #[must_use]
+    pub const fn new(limits: TariffLimits) -> Self {
+        Self {
+            limits,
+            tariffs: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn limits(&self) -> &TariffLimits {
+        &self.limits
+    }
+
+    /// All tariffs, in installation order.
+    #[must_use]
+    pub fn tariffs(&self) -> &[StoredTariff] {
+        &self.tariffs
+    }
+
+    #[must_use]
+    pub fn tariff(&self, tariff_id: &str) -> Option<&TariffType> {
+        self.tariffs
+            .iter()
+            .find(|t| t.tariff.tariff_id == tariff_id)
+            .map(|t| &t.tariff)
+    }
+
+    /// Fatal issues of `tariff` under `limits`: a tariff id clash, then [`check_tariff`].
+    fn fatal_issues(&self, tariff: &TariffType, limits: &TariffLimits) -> Vec<TariffIssue> {
+        let duplicate = self
+            .tariffs
+            .iter()
+            .any(|t| t.tariff.tariff_id == tariff.tariff_id && t.tariff != *tariff);
+        duplicate
+            .then_some(TariffIssue::DuplicateTariffId)
+            .into_iter()
+            .chain(check_tariff(tariff, limits))
+            .filter(TariffIssue::is_fatal)
+            .collect()
+    }
+
+    /// Why `tariff` cannot be installed, if it cannot: [`Self::fatal_issues`], without a
+    /// configured currency also checked against the default tariffs already installed.
+    fn rejection(&self, tariff: &TariffType) -> Option<SetDefaultTariffResponse> {
+        let mut limits = self.limits.clone();
+        if limits.currency.is_none() {
+            limits.currency = self
+                .tariffs
+                .iter()
+                .find(|t| t.kind == TariffKindEnumType::DefaultTariff)
+                .map(|t| t.tariff.currency.clone());
+        }
+        let issues = self.fatal_issues(tariff, &limits);
+        let issue = issues.first()?;
+        Some(match issue {
+            TariffIssue::DuplicateTariffId => {
+                set_response(TariffSetStatusEnumType::DuplicateTariffId, None)
+            }
+            TariffIssue::TooManyElements { .. } => set_response(
+                TariffSetStatusEnumType::TooManyElements,
+                Some(status_info(reason_codes::TOO_MANY_ELEMENTS)),
+            ),
+            TariffIssue::ConditionNotSupported => {
+                set_response(TariffSetStatusEnumType::ConditionNotSupported, None)
+            }
+            _ => set_response(
+                TariffSetStatusEnumType::Rejected,
+                Some(status_info_with(
+                    reason_codes::INVALID_VALUE,
+                    alloc::format!("{issue}"),
+                )),
+            ),
+        })
+    }
+
+    /// Handle a `SetDefaultTariffRequest`. A default tariff on the same EVSE with the same
+    /// `validFrom` is replaced.
+    pub fn set_default_tariff(
+        &mut self,
+        request: &SetDefaultTariffRequest,
+        evses: &impl EvseState,
+    ) -> SetDefaultTariffResponse {
+        let evse_id = request.evse_id;
+        if evse_id != 0 && !evses.evse_exists(evse_id) {
+            return set_response(
+                TariffSetStatusEnumType::Rejected,
+                Some(status_info(reason_codes::UNKNOWN_EVSE)),
+            );
+        }
+        if let Some(response) = self.rejection(&request.tariff) {
+            return response;
+        }
+        let valid_from = request.tariff.valid_from;
+        self.tariffs.retain(|t| {
+            !(t.kind == TariffKindEnumType::DefaultTariff
+                && t.evse_id == evse_id
+                && t.tariff.valid_from == valid_from)
+        });
+        self.tariffs.push(StoredTariff {
+            kind: TariffKindEnumType::DefaultTariff,
+            evse_id,
+            id_token: None,
+            tariff: request.tariff.clone(),
+        });
+        set_response(TariffSetStatusEnumType::Accepted, None)
+    }
+
+    /// Keep the driver tariff for `id_token`, replacing the previous one.
+    ///
+    /// # Errors
+    /// The fatal [`TariffIssue`]s, if any (then nothing is stored), including a
+    /// [`TariffIssue::DuplicateTariffId`] as for default tariffs.
+    pub fn set_driver_tariff(
+        &mut self,
+        id_token: &str,
+        tariff: TariffType,
+    ) -> Result<(), Vec<TariffIssue>> {
+        let issues = self.fatal_issues(&tariff, &self.limits);
+        if !issues.is_empty() {
+            return Err(issues);
+        }
+        self.tariffs
+            .retain(|t| t.id_token.as_deref() != Some(id_token));
+        self.tariffs.push(StoredTariff {
+            kind: TariffKindEnumType::DriverTariff,
+            evse_id: 0,
+            id_token: Some(id_token.into()),
+            tariff,
+        });
+        Ok(())
+    }
+
+    /// Keep the `tariff` of an `AuthorizeResponse` for `id_token`; see
+    /// [`Self::set_driver_tariff`]. No-op without a tariff.
+    ///
+    /// # Errors
+    /// As [`Self::set_driver_tariff`].
+    pub fn apply_authorize_response(
+        &mut self,
+        id_token: &str,
+        response: &AuthorizeResponse,
+    ) -> Result<(), Vec<TariffIssue>> {
+        response.tariff.as_ref().map_or(Ok(()), |tariff| {
+            self.set_driver_tariff(id_token, tariff.clone())
+        })
+    }
+
+    /// Default tariff of `evse_id` at `at`: the EVSE's own before the station-wide one,
+    /// each the latest with `validFrom` at or before `at`.
+    #[must_use]
+    pub fn default_tariff(&self, evse_id: i32, at: DateTime<Utc>) -> Option<&TariffType> {
+        let latest = |evse: i32| {
+            self.tariffs
+                .iter()
+                .filter(|t| t.kind == TariffKindEnumType::DefaultTariff && t.evse_id == evse)
+                .filter(|t| t.valid_from().is_none_or(|from| from <= at))
+                .max_by_key(|t| t.valid_from())
+                .map(|t| &t.tariff)
+        };
+        latest(evse_id).or_else(|| latest(0))
+    }
+
+    /// Tariff of a transaction starting at `at` on `evse_id` for `id_token`: the driver
+    /// tariff if there is one (and it is valid), else [`Self::default_tariff`].
+    #[must_use]
+    pub fn tariff_for(
+        &self,
+        evse_id: i32,
+        id_token: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> Option<&TariffType> {
+        id_token
+            .and_then(|token| {
+                self.tariffs.iter().find(|t| {
+                    t.id_token.as_deref() == Some(token)
+                        && t.valid_from().is_none_or(|from| from <= at)
+                })
+            })
+            .map(|t| &t.tariff)
+            .or_else(|| self.default_tariff(evse_id, at))
+    }
+
+    /// Handle a `GetTariffsRequest`: with `evseId` 0 every tariff, else the defaults of
+    /// that EVSE (its own and station-wide). One assignment per `tariffId`.
+    pub fn get_tariffs(
+        &self,
+        request: &GetTariffsRequest,
+        evses: &impl EvseState,
+    ) -> GetTariffsResponse {
+        let evse_id = request.evse_id;
+        if evse_id != 0 && !evses.evse_exists(evse_id) {
+            return GetTariffsResponse {
+                status: TariffGetStatusEnumType::Rejected,
+                status_info: Some(status_info(reason_codes::UNKNOWN_EVSE)),
+                tariff_assignments: None,
+                custom_data: None,
+            };
+        }
+        let mut assignments: Vec<TariffAssignmentType> = Vec::new();
+        for stored in self.tariffs.iter().filter(|t| {
+            evse_id == 0
+                || t.kind == TariffKindEnumType::DefaultTariff
+                    && (t.evse_id == evse_id || t.evse_id == 0)
+        }) {
+            let index = assignments
+                .iter()
+                .position(|a| a.tariff_id == stored.tariff.tariff_id)
+                .unwrap_or_else(|| {
+                    assignments.push(TariffAssignmentType {
+                        tariff_id: stored.tariff.tariff_id.clone(),
+                        tariff_kind: stored.kind.clone(),
+                        valid_from: stored.tariff.valid_from,
+                        evse_ids: None,
+                        id_tokens: None,
+                        custom_data: None,
+                    });
+                    assignments.len() - 1
+                });
+            let assignment = &mut assignments[index];
+            if let Some(token) = &stored.id_token {
+                assignment
+                    .id_tokens
+                    .get_or_insert_default()
+                    .push(token.clone());
+            } else if stored.evse_id != 0 {
+                assignment
+                    .evse_ids
+                    .get_or_insert_default()
+                    .push(stored.evse_id);
+            } else {
+                // Station-wide default: no `evseIds`.
+            }
+        }
+        GetTariffsResponse {
+            status: if assignments.is_empty() {
+                TariffGetStatusEnumType::NoTariff
+            } else {
+                TariffGetStatusEnumType::Accepted
+            },
+            status_info: None,
+            tariff_assignments: (!assignments.is_empty()).then_some(assignments),
+            custom_data: None,
+        }
+    }
+
+    /// Handle a `ClearTariffsRequest`: remove the listed tariffs (all without `tariffIds`),
+    /// restricted to the defaults of `evseId` when given. One result per requested or
+    /// removed `tariffId`; `NoTariff` when nothing matched.
+    pub fn clear_tariffs(&mut self, request: &ClearTariffsRequest) -> ClearTariffsResponse {
+        let matches = |t: &StoredTariff| {
+            request
+                .tariff_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&t.tariff.tariff_id))
+                && request.evse_id.is_none_or(|evse_id| {
+                    t.kind == TariffKindEnumType::DefaultTariff && t.evse_id == evse_id
+                })
+        };
+        let mut removed: Vec<String> = Vec::new();
+        self.tariffs.retain(|t| {
+            if matches(t) {
+                if !removed.contains(&t.tariff.tariff_id) {
+                    removed.push(t.tariff.tariff_id.clone());
+                }
+                false
+            } else {
+                true
+            }
+        });
+        let result = |tariff_id: Option<String>, status| ClearTariffsResultType {
+            status_info: None,
+            tariff_id,
+            status,
+            custom_data: None,
+        };
+        let results = match &request.tariff_ids {
+            Some(ids) => ids
+                .iter()
+                .map(|id| {
+                    let status = if removed.contains(id) {
+                        TariffClearStatusEnumType::Accepted
+                    } else {
+                        TariffClearStatusEnumType::NoTariff
+                    };
+                    result(Some(id.clone()), status)
+                })
+                .collect(),
+            None if removed.is_empty() => {
+                alloc::vec![result(None, TariffClearStatusEnumType::NoTariff)]
+            }
+            None => removed
+                .into_iter()
+                .map(|id| result(Some(id), TariffClearStatusEnumType::Accepted))
+                .collect(),
+        };
+        ClearTariffsResponse {
+            clear_tariffs_result: results,
+            custom_data: None,
+        }
+    }
+}
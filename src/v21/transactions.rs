@@ -0,0 +1,1020 @@
+//! `TransactionEvent` streams: built on the station, checked on the CSMS.
+//!
+//! [`TransactionManager`] turns hardware and authorization events into
+//! `TransactionEventRequest`s. A transaction starts when one of the `TxStartPoint`
+//! conditions becomes true and ends when one of the `TxStopPoint` conditions stops being
+//! true ([`TxPoint`]); in between, every change is an `Updated` event. Each transaction
+//! numbers its events from `seqNo` 0, reports `chargingState` and `numberOfPhasesUsed` when
+//! they change, and carries the `SampledDataCtrlr` measurands: `TxStartedMeasurands` in
+//! `Started`, `TxUpdatedMeasurands` every `TxUpdatedInterval`, `TxEndedMeasurands` in
+//! `Ended`. Events created while offline have `offline` set. Like
+//! [`crate::v16::charge_point`], requests queue up for [`TransactionManager::take_requests`].
+//!
+//! [`TransactionEventValidator`] checks what a CSMS receives: `seqNo` gaps and repeats,
+//! events out of the `Started` → `Updated`* → `Ended` order, timestamps going back, and
+//! transactions replaced on their EVSE without an `Ended`.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, TimeDelta, Utc};
+use core::fmt;
+
+use super::datatypes::{DateTimeWrapper, EVSEType, IdTokenType, MeterValueType, SampledValueType};
+use super::enumerations::{
+    AuthorizationStatusEnumType, MeasurandEnumType, ReadingContextEnumType,
+    RequestStartStopStatusEnumType,
+};
+use super::messages::request_start_transaction::{
+    RequestStartTransactionRequest, RequestStartTransactionResponse,
+};
+use super::messages::request_stop_transaction::{
+    RequestStopTransactionRequest, RequestStopTransactionResponse,
+};
+use super::messages::transaction_event::{
+    ChargingStateEnumType, ReasonEnumType, TransactionEventEnumType, TransactionEventRequest,
+    TransactionEventResponse, TransactionType, TriggerReasonEnumType,
+};
+use super::reason_codes::{self, status_info};
+
+/// A `TxStartPoint` / `TxStopPoint` member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxPoint {
+    ParkingBayOccupancy,
+    EVConnected,
+    Authorized,
+    DataSigned,
+    PowerPathClosed,
+    EnergyTransfer,
+}
+
+impl TxPoint {
+    pub const ALL: [Self; 6] = [
+        Self::ParkingBayOccupancy,
+        Self::EVConnected,
+        Self::Authorized,
+        Self::DataSigned,
+        Self::PowerPathClosed,
+        Self::EnergyTransfer,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ParkingBayOccupancy => "ParkingBayOccupancy",
+            Self::EVConnected => "EVConnected",
+            Self::Authorized => "Authorized",
+            Self::DataSigned => "DataSigned",
+            Self::PowerPathClosed => "PowerPathClosed",
+            Self::EnergyTransfer => "EnergyTransfer",
+        }
+    }
+
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == value)
+    }
+
+    /// Parse a `MemberList` value (`"EVConnected,Authorized"`); `None` if a member is
+    /// unknown.
+    #[must_use]
+    pub fn parse_list(value: &str) -> Option<Vec<Self>> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|member| !member.is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+}
+
+/// `TxCtrlr` and `SampledDataCtrlr` settings the manager honours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionConfig {
+    /// `TxStartPoint`.
+    pub tx_start_point: Vec<TxPoint>,
+    /// `TxStopPoint`.
+    pub tx_stop_point: Vec<TxPoint>,
+    /// `StopTxOnEVSideDisconnect`.
+    pub stop_tx_on_ev_side_disconnect: bool,
+    /// `StopTxOnInvalidId`.
+    pub stop_tx_on_invalid_id: bool,
+    /// `EVConnectionTimeOut`, seconds.
+    pub ev_connection_timeout: u32,
+    /// `TxStartedMeasurands`.
+    pub tx_started_measurands: Vec<MeasurandEnumType>,
+    /// `TxUpdatedMeasurands`.
+    pub tx_updated_measurands: Vec<MeasurandEnumType>,
+    /// `TxEndedMeasurands`.
+    pub tx_ended_measurands: Vec<MeasurandEnumType>,
+    /// `TxUpdatedInterval`, seconds; 0 = no periodic meter values.
+    pub tx_updated_interval: u32,
+}
+
+impl Default for TransactionConfig {
+    fn default() -> Self {
+        let energy = alloc::vec![MeasurandEnumType::EnergyActiveImportRegister];
+        Self {
+            tx_start_point: alloc::vec![TxPoint::EVConnected, TxPoint::Authorized],
+            tx_stop_point: alloc::vec![TxPoint::EVConnected, TxPoint::Authorized],
+            stop_tx_on_ev_side_disconnect: true,
+            stop_tx_on_invalid_id: true,
+            ev_connection_timeout: 60,
+            tx_started_measurands: energy.clone(),
+            tx_updated_measurands: energy.clone(),
+            tx_ended_measurands: energy,
+            tx_updated_interval: 0,
+        }
+    }
+}
+
+/// A running transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub transaction_id: String,
+    /// `seqNo` of the next event.
+    pub seq_no: i32,
+    pub started_at: DateTime<Utc>,
+    pub remote_start_id: Option<i32>,
+    /// `idTokenInfo` was not accepted and `StopTxOnInvalidId` is false, or the driver
+    /// stopped the authorization without `Authorized` in `TxStopPoint`: no energy.
+    pub deauthorized: bool,
+    charging_seconds: i64,
+    charging_since: Option<DateTime<Utc>>,
+    sent_state: Option<ChargingStateEnumType>,
+    sent_phases: Option<i32>,
+    last_periodic: DateTime<Utc>,
+}
+
+impl Transaction {
+    /// `timeSpentCharging` at `now`, seconds.
+    #[must_use]
+    pub fn time_spent_charging(&self, now: DateTime<Utc>) -> i64 {
+        self.charging_seconds
+            + self
+                .charging_since
+                .map_or(0, |since| (now - since).num_seconds())
+    }
+}
+
+/// What the manager knows of one EVSE.
+#[derive(Debug, Clone, PartialEq)]
+struct Evse {
+    id: i32,
+    connector_id: Option<i32>,
+    parking_bay_occupied: bool,
+    connected: bool,
+    /// Authorized token, with when it was authorized.
+    id_token: Option<(IdTokenType, DateTime<Utc>)>,
+    data_signed: bool,
+    /// `Charging`, `SuspendedEV` or `SuspendedEVSE` as reported by the hardware.
+    flow: Option<ChargingStateEnumType>,
+    phases: Option<i32>,
+    remote_start_id: Option<i32>,
+    readings: Vec<(MeasurandEnumType, f64)>,
+    transaction: Option<Transaction>,
+}
+
+impl Evse {
+    const fn new(id: i32) -> Self {
+        Self {
+            id,
+            connector_id: None,
+            parking_bay_occupied: false,
+            connected: false,
+            id_token: None,
+            data_signed: false,
+            flow: None,
+            phases: None,
+            remote_start_id: None,
+            readings: Vec::new(),
+            transaction: None,
+        }
+    }
+
+    fn charging_state(&self) -> ChargingStateEnumType {
+        if !self.connected {
+            ChargingStateEnumType::Idle
+        } else if self.transaction.as_ref().is_some_and(|tx| tx.deauthorized) {
+            ChargingStateEnumType::SuspendedEVSE
+        } else {
+            self.flow
+                .clone()
+                .unwrap_or(ChargingStateEnumType::EVConnected)
+        }
+    }
+
+    fn holds(&self, point: TxPoint) -> bool {
+        let state = self.charging_state();
+        match point {
+            TxPoint::ParkingBayOccupancy => self.parking_bay_occupied,
+            TxPoint::EVConnected => self.connected,
+            TxPoint::Authorized => self.id_token.is_some(),
+            TxPoint::DataSigned => self.data_signed,
+            TxPoint::PowerPathClosed => matches!(
+                state,
+                ChargingStateEnumType::Charging | ChargingStateEnumType::SuspendedEV
+            ),
+            TxPoint::EnergyTransfer => state == ChargingStateEnumType::Charging,
+        }
+    }
+
+    fn conditions(&self) -> [bool; 6] {
+        TxPoint::ALL.map(|point| self.holds(point))
+    }
+
+    fn meter_values(
+        &self,
+        measurands: &[MeasurandEnumType],
+        context: &ReadingContextEnumType,
+        now: DateTime<Utc>,
+    ) -> Option<Vec<MeterValueType>> {
+        let sampled_value: Vec<SampledValueType> = measurands
+            .iter()
+            .filter_map(|measurand| {
+                let (_, value) = self.readings.iter().find(|(m, _)| m == measurand)?;
+                Some(SampledValueType {
+                    value: *value,
+                    measurand: Some(measurand.clone()),
+                    context: Some(context.clone()),
+                    phase: None,
+                    location: None,
+                    signed_meter_value: None,
+                    unit_of_measure: None,
+                    custom_data: None,
+                })
+            })
+            .collect();
+        (!sampled_value.is_empty()).then(|| {
+            alloc::vec![MeterValueType {
+                sampled_value,
+                timestamp: DateTimeWrapper::new(now),
+                custom_data: None,
+            }]
+        })
+    }
+}
+
+/// How an event ends a transaction when it ends one.
+#[derive(Debug, Clone)]
+struct Cause {
+    trigger: TriggerReasonEnumType,
+    reason: ReasonEnumType,
+    /// Token to report with the event.
+    id_token: Option<IdTokenType>,
+}
+
+impl Cause {
+    const fn new(trigger: TriggerReasonEnumType, reason: ReasonEnumType) -> Self {
+        Self {
+            trigger,
+            reason,
+            id_token: None,
+        }
+    }
+}
+
+/// Station-side builder of `TransactionEventRequest`s for EVSEs `1..=n`.
+#[derive(Debug, Clone)]
+pub struct TransactionManager {
+    config: TransactionConfig,
+    id_prefix: String,
+    next_id: u64,
+    offline: bool,
+    evses: Vec<Evse>,
+    outbox: Vec<TransactionEventRequest>,
+}
+
+impl TransactionManager {
+    /// Transaction ids are `id_prefix` followed by a counter: make the prefix unique per
+    /// boot (e.g. station serial and boot count).
+    #[must_use]
+    pub fn new(evses: i32, config: TransactionConfig, id_prefix: impl Into<String>) -> Self {
+        Self {
+            config,
+            id_prefix: id_prefix.into(),
+            next_id: 1,
+            offline: false,
+            evses: (1..=evses).map(Evse::new).collect(),
+            outbox: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &TransactionConfig {
+        &self.config
+    }
+
+    pub const fn config_mut(&mut self) -> &mut TransactionConfig {
+        &mut self.config
+    }
+
+    /// Mark the events created from now on as `offline`.
+    pub const fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// Requests queued since the last call, in order.
+    pub fn take_requests(&mut self) -> Vec<TransactionEventRequest> {
+        core::mem::take(&mut self.outbox)
+    }
+
+    fn evse(&self, evse_id: i32) -> Option<&Evse> {
+        self.evses.iter().find(|e| e.id == evse_id)
+    }
+
+    fn evse_mut(&mut self, evse_id: i32) -> Option<&mut Evse> {
+        self.evses.iter_mut().find(|e| e.id == evse_id)
+    }
+
+    /// Running transaction on `evse_id`.
+    #[must_use]
+    pub fn transaction(&self, evse_id: i32) -> Option<&Transaction> {
+        self.evse(evse_id)?.transaction.as_ref()
+    }
+
+    /// EVSE of the running transaction `transaction_id`.
+    #[must_use]
+    pub fn transaction_evse(&self, transaction_id: &str) -> Option<i32> {
+        self.evses
+            .iter()
+            .find(|e| {
+                e.transaction
+                    .as_ref()
+                    .is_some_and(|tx| tx.transaction_id == transaction_id)
+            })
+            .map(|e| e.id)
+    }
+
+    /// `chargingState` of `evse_id`.
+    #[must_use]
+    pub fn charging_state(&self, evse_id: i32) -> Option<ChargingStateEnumType> {
+        self.evse(evse_id).map(Evse::charging_state)
+    }
+
+    /// Latest meter reading of `measurand` on `evse_id`, in the measurand's default unit
+    /// ([`super::sampled_value::default_unit`]).
+    pub fn set_reading(&mut self, evse_id: i32, measurand: MeasurandEnumType, value: f64) {
+        if let Some(evse) = self.evse_mut(evse_id) {
+            match evse.readings.iter_mut().find(|(m, _)| *m == measurand) {
+                Some(reading) => reading.1 = value,
+                None => evse.readings.push((measurand, value)),
+            }
+        }
+    }
+
+    /// Phases the EV draws on `evse_id`; reported with the next event.
+    pub fn set_phases(&mut self, evse_id: i32, phases: i32) {
+        if let Some(evse) = self.evse_mut(evse_id) {
+            evse.phases = Some(phases);
+        }
+    }
+
+    /// Apply `change` to `evse_id` and emit what it means: `Started` when a
+    /// `TxStartPoint` became true, `Ended` when a `TxStopPoint` became false (or `force_end`),
+    /// otherwise `Updated` if anything changed.
+    fn apply(
+        &mut self,
+        evse_id: i32,
+        now: DateTime<Utc>,
+        cause: Cause,
+        force_end: bool,
+        change: impl FnOnce(&mut Evse),
+    ) {
+        let start_points = self.config.tx_start_point.clone();
+        let stop_on_disconnect = self.config.stop_tx_on_ev_side_disconnect;
+        let stop_points: Vec<TxPoint> = self
+            .config
+            .tx_stop_point
+            .iter()
+            .copied()
+            .filter(|p| *p != TxPoint::EVConnected || stop_on_disconnect)
+            .collect();
+        let Some(evse) = self.evse_mut(evse_id) else {
+            return;
+        };
+        let before = evse.clone();
+        change(evse);
+        let state = evse.charging_state();
+        if let Some(tx) = evse.transaction.as_mut() {
+            let charging = state == ChargingStateEnumType::Charging;
+            match (tx.charging_since, charging) {
+                (Some(since), false) => {
+                    tx.charging_seconds += (now - since).num_seconds();
+                    tx.charging_since = None;
+                }
+                (None, true) => tx.charging_since = Some(now),
+                _ => {}
+            }
+        }
+        let (was, is) = (before.conditions(), evse.conditions());
+        let edge = |points: &[TxPoint], from: bool| {
+            points.iter().any(|point| {
+                let index = TxPoint::ALL.iter().position(|p| p == point).unwrap_or(0);
+                was[index] == from && is[index] != from
+            })
+        };
+        let has_transaction = evse.transaction.is_some();
+        let changed = *evse != before;
+        if !has_transaction && edge(&start_points, false) {
+            self.start(evse_id, now, cause);
+        } else if has_transaction && (force_end || edge(&stop_points, true)) {
+            self.end(evse_id, now, cause);
+        } else if has_transaction && changed {
+            self.event(evse_id, TransactionEventEnumType::Updated, now, cause, None);
+        } else {
+            // No transaction, or nothing changed: nothing to report.
+        }
+    }
+
+    fn start(&mut self, evse_id: i32, now: DateTime<Utc>, cause: Cause) {
+        let transaction_id = format!("{}{}", self.id_prefix, self.next_id);
+        self.next_id += 1;
+        let Some(evse) = self.evse_mut(evse_id) else {
+            return;
+        };
+        let charging = evse.charging_state() == ChargingStateEnumType::Charging;
+        evse.transaction = Some(Transaction {
+            transaction_id,
+            seq_no: 0,
+            started_at: now,
+            remote_start_id: evse.remote_start_id.take(),
+            deauthorized: false,
+            charging_seconds: 0,
+            charging_since: charging.then_some(now),
+            sent_state: None,
+            sent_phases: None,
+            last_periodic: now,
+        });
+        let cause = Cause {
+            id_token: cause
+                .id_token
+                .or_else(|| evse.id_token.as_ref().map(|(token, _)| token.clone())),
+            ..cause
+        };
+        let meter_value = self.evse(evse_id).and_then(|started| {
+            started.meter_values(
+                &self.config.tx_started_measurands,
+                &ReadingContextEnumType::TransactionBegin,
+                now,
+            )
+        });
+        self.event(
+            evse_id,
+            TransactionEventEnumType::Started,
+            now,
+            cause,
+            meter_value,
+        );
+    }
+
+    fn end(&mut self, evse_id: i32, now: DateTime<Utc>, cause: Cause) {
+        let Some(evse) = self.evse(evse_id) else {
+            return;
+        };
+        let meter_value = evse.meter_values(
+            &self.config.tx_ended_measurands,
+            &ReadingContextEnumType::TransactionEnd,
+            now,
+        );
+        self.event(
+            evse_id,
+            TransactionEventEnumType::Ended,
+            now,
+            cause,
+            meter_value,
+        );
+        if let Some(ended) = self.evse_mut(evse_id) {
+            ended.transaction = None;
+            ended.data_signed = false;
+        }
+    }
+
+    /// Queue an event of the running transaction on `evse_id`.
+    fn event(
+        &mut self,
+        evse_id: i32,
+        event_type: TransactionEventEnumType,
+        now: DateTime<Utc>,
+        cause: Cause,
+        meter_value: Option<Vec<MeterValueType>>,
+    ) {
+        let offline = self.offline;
+        let Some(evse) = self.evse_mut(evse_id) else {
+            return;
+        };
+        let state = evse.charging_state();
+        let phases = evse.phases;
+        let connector_id = evse.connector_id;
+        let Some(tx) = evse.transaction.as_mut() else {
+            return;
+        };
+        let started = event_type == TransactionEventEnumType::Started;
+        let ended = event_type == TransactionEventEnumType::Ended;
+        let remote_start = started || cause.trigger == TriggerReasonEnumType::RemoteStart;
+        let time_spent_charging = i32::try_from(tx.time_spent_charging(now)).unwrap_or(i32::MAX);
+        let request = TransactionEventRequest {
+            cost_details: None,
+            event_type,
+            meter_value,
+            timestamp: DateTimeWrapper::new(now),
+            trigger_reason: cause.trigger,
+            seq_no: tx.seq_no,
+            offline: offline.then_some(true),
+            number_of_phases_used: phases.filter(|_| phases != tx.sent_phases),
+            cable_max_current: None,
+            reservation_id: None,
+            preconditioning_status: None,
+            evse_sleep: None,
+            transaction_info: TransactionType {
+                transaction_id: tx.transaction_id.clone(),
+                charging_state: (tx.sent_state.as_ref() != Some(&state)).then(|| state.clone()),
+                time_spent_charging: (!started).then_some(time_spent_charging),
+                stopped_reason: ended.then_some(cause.reason),
+                remote_start_id: tx.remote_start_id.filter(|_| remote_start),
+                operation_mode: None,
+                tariff_id: None,
+                transaction_limit: None,
+                custom_data: None,
+            },
+            evse: started.then_some(EVSEType {
+                id: evse_id,
+                connector_id,
+                custom_data: None,
+            }),
+            id_token: cause.id_token,
+            custom_data: None,
+        };
+        tx.seq_no += 1;
+        tx.sent_state = Some(state);
+        tx.sent_phases = phases;
+        self.outbox.push(request);
+    }
+
+    /// EV plugged into `connector_id` of `evse_id` (`CablePluggedIn`).
+    pub fn ev_connected(&mut self, evse_id: i32, connector_id: i32, now: DateTime<Utc>) {
+        let cause = Cause::new(TriggerReasonEnumType::CablePluggedIn, ReasonEnumType::Other);
+        self.apply(evse_id, now, cause, false, |evse| {
+            evse.connected = true;
+            evse.connector_id = Some(connector_id);
+        });
+    }
+
+    /// EV unplugged from `evse_id` (`EVCommunicationLost`, ends with `EVDisconnected`).
+    pub fn ev_disconnected(&mut self, evse_id: i32, now: DateTime<Utc>) {
+        let cause = Cause::new(
+            TriggerReasonEnumType::EVCommunicationLost,
+            ReasonEnumType::EVDisconnected,
+        );
+        self.apply(evse_id, now, cause, false, |evse| {
+            evse.connected = false;
+            evse.flow = None;
+        });
+    }
+
+    /// Parking bay of `evse_id` occupied (`EVDetected`) or left (`EVDeparted`).
+    pub fn parking_bay(&mut self, evse_id: i32, occupied: bool, now: DateTime<Utc>) {
+        let trigger = if occupied {
+            TriggerReasonEnumType::EVDetected
+        } else {
+            TriggerReasonEnumType::EVDeparted
+        };
+        let cause = Cause::new(trigger, ReasonEnumType::EVDisconnected);
+        self.apply(evse_id, now, cause, false, |evse| {
+            evse.parking_bay_occupied = occupied;
+        });
+    }
+
+    /// Signed meter data is available for the start of the transaction (`SignedDataReceived`).
+    pub fn data_signed(&mut self, evse_id: i32, now: DateTime<Utc>) {
+        let cause = Cause::new(
+            TriggerReasonEnumType::SignedDataReceived,
+            ReasonEnumType::Other,
+        );
+        self.apply(evse_id, now, cause, false, |evse| evse.data_signed = true);
+    }
+
+    /// Energy flow on `evse_id` (`ChargingStateChanged`): `Charging` (contactor closed, EV
+    /// draws), `SuspendedEV` (contactor closed, EV idle) or `SuspendedEVSE` (contactor open).
+    pub fn set_charging_state(
+        &mut self,
+        evse_id: i32,
+        state: ChargingStateEnumType,
+        now: DateTime<Utc>,
+    ) {
+        let cause = Cause::new(
+            TriggerReasonEnumType::ChargingStateChanged,
+            ReasonEnumType::StoppedByEV,
+        );
+        self.apply(evse_id, now, cause, false, |evse| evse.flow = Some(state));
+    }
+
+    /// An accepted token presented on `evse_id`. The token of the running transaction stops
+    /// it (`StopAuthorized`, reason `Local`): the transaction ends if `Authorized` is a
+    /// `TxStopPoint`, else charging stops. Any other token authorizes (`Authorized`).
+    pub fn authorize(&mut self, evse_id: i32, id_token: IdTokenType, now: DateTime<Utc>) {
+        let Some(evse) = self.evse(evse_id) else {
+            return;
+        };
+        let stops = evse.transaction.is_some()
+            && evse
+                .id_token
+                .as_ref()
+                .is_some_and(|(token, _)| token.id_token == id_token.id_token);
+        if stops {
+            let cause = Cause {
+                id_token: Some(id_token),
+                ..Cause::new(TriggerReasonEnumType::StopAuthorized, ReasonEnumType::Local)
+            };
+            // Ends the transaction when `Authorized` is a stop point; otherwise only the
+            // energy stops until another stop point ends it.
+            self.apply(evse_id, now, cause, false, |target| {
+                target.id_token = None;
+                if let Some(tx) = target.transaction.as_mut() {
+                    tx.deauthorized = true;
+                }
+            });
+        } else {
+            let cause = Cause {
+                id_token: Some(id_token.clone()),
+                ..Cause::new(TriggerReasonEnumType::Authorized, ReasonEnumType::Other)
+            };
+            self.apply(evse_id, now, cause, false, |target| {
+                target.id_token = Some((id_token, now));
+            });
+        }
+    }
+
+    /// Stop the running transaction on `evse_id` for a station-side reason (emergency stop,
+    /// fault, limit reached, …).
+    pub fn stop(
+        &mut self,
+        evse_id: i32,
+        trigger: TriggerReasonEnumType,
+        reason: ReasonEnumType,
+        now: DateTime<Utc>,
+    ) {
+        if self.transaction(evse_id).is_some() {
+            self.apply(evse_id, now, Cause::new(trigger, reason), true, |evse| {
+                evse.id_token = None;
+            });
+        }
+    }
+
+    /// Handle a `RequestStartTransactionRequest`: authorizes `idToken` (`RemoteStart`) on
+    /// the requested EVSE, or the first one without an authorized token. An EVSE whose
+    /// transaction started without a token (EV plugged in first) accepts it and answers with
+    /// that transaction's id. A `chargingProfile` is left to the caller.
+    pub fn request_start_transaction(
+        &mut self,
+        request: &RequestStartTransactionRequest,
+        now: DateTime<Utc>,
+    ) -> RequestStartTransactionResponse {
+        let evse_id = request.evse_id.or_else(|| {
+            self.evses
+                .iter()
+                .find(|e| e.id_token.is_none())
+                .map(|e| e.id)
+        });
+        let free = evse_id
+            .and_then(|id| self.evse(id))
+            .filter(|e| e.id_token.is_none());
+        let (Some(evse_id), Some(_)) = (evse_id, free) else {
+            return RequestStartTransactionResponse {
+                status: RequestStartStopStatusEnumType::Rejected,
+                status_info: evse_id
+                    .filter(|id| self.evse(*id).is_none())
+                    .map(|_| status_info(reason_codes::UNKNOWN_EVSE)),
+                transaction_id: None,
+                custom_data: None,
+            };
+        };
+        let id_token = request.id_token.clone();
+        let cause = Cause {
+            id_token: Some(id_token.clone()),
+            ..Cause::new(TriggerReasonEnumType::RemoteStart, ReasonEnumType::Other)
+        };
+        let remote_start_id = request.remote_start_id;
+        self.apply(evse_id, now, cause, false, |evse| {
+            evse.id_token = Some((id_token, now));
+            if let Some(tx) = evse.transaction.as_mut() {
+                tx.remote_start_id = Some(remote_start_id);
+            } else {
+                evse.remote_start_id = Some(remote_start_id);
+            }
+        });
+        RequestStartTransactionResponse {
+            status: RequestStartStopStatusEnumType::Accepted,
+            status_info: None,
+            transaction_id: self
+                .transaction(evse_id)
+                .map(|tx| tx.transaction_id.clone()),
+            custom_data: None,
+        }
+    }
+
+    /// Handle a `RequestStopTransactionRequest` (`RemoteStop`, reason `Remote`).
+    pub fn request_stop_transaction(
+        &mut self,
+        request: &RequestStopTransactionRequest,
+        now: DateTime<Utc>,
+    ) -> RequestStopTransactionResponse {
+        let evse_id = self.transaction_evse(&request.transaction_id);
+        if let Some(evse_id) = evse_id {
+            self.stop(
+                evse_id,
+                TriggerReasonEnumType::RemoteStop,
+                ReasonEnumType::Remote,
+                now,
+            );
+        }
+        RequestStopTransactionResponse {
+            status: if evse_id.is_some() {
+                RequestStartStopStatusEnumType::Accepted
+            } else {
+                RequestStartStopStatusEnumType::Rejected
+            },
+            status_info: evse_id
+                .is_none()
+                .then(|| status_info(reason_codes::TX_NOT_FOUND)),
+            custom_data: None,
+        }
+    }
+
+    /// Handle the response to `request`: an `idTokenInfo` that is not `Accepted`
+    /// deauthorizes the transaction (`Deauthorized`), which ends it with `DeAuthorized`
+    /// when `StopTxOnInvalidId` is set, else suspends it.
+    pub fn on_transaction_event_response(
+        &mut self,
+        request: &TransactionEventRequest,
+        response: &TransactionEventResponse,
+        now: DateTime<Utc>,
+    ) {
+        let rejected = response
+            .id_token_info
+            .as_ref()
+            .is_some_and(|info| info.status != AuthorizationStatusEnumType::Accepted);
+        let Some(evse_id) = self
+            .transaction_evse(&request.transaction_info.transaction_id)
+            .filter(|_| rejected)
+        else {
+            return;
+        };
+        let cause = Cause::new(
+            TriggerReasonEnumType::Deauthorized,
+            ReasonEnumType::DeAuthorized,
+        );
+        let stop = self.config.stop_tx_on_invalid_id;
+        self.apply(evse_id, now, cause, stop, |evse| {
+            if stop {
+                evse.id_token = None;
+            }
+            if let Some(tx) = evse.transaction.as_mut() {
+                tx.deauthorized = true;
+            }
+        });
+    }
+
+    /// When [`Self::poll`] has something to do: a periodic meter value, or an authorization
+    /// waiting for the EV past `EVConnectionTimeOut`.
+    #[must_use]
+    pub fn next_poll(&self) -> Option<DateTime<Utc>> {
+        let interval = TimeDelta::seconds(i64::from(self.config.tx_updated_interval));
+        let timeout = TimeDelta::seconds(i64::from(self.config.ev_connection_timeout));
+        self.evses
+            .iter()
+            .flat_map(|evse| {
+                let periodic = evse
+                    .transaction
+                    .as_ref()
+                    .filter(|_| self.config.tx_updated_interval > 0)
+                    .map(|tx| tx.last_periodic + interval);
+                let connect = evse
+                    .id_token
+                    .as_ref()
+                    .filter(|_| !evse.connected)
+                    .map(|(_, at)| *at + timeout);
+                [periodic, connect]
+            })
+            .flatten()
+            .min()
+    }
+
+    /// Send the periodic meter values that are due (`MeterValuePeriodic`) and drop the
+    /// authorizations past `EVConnectionTimeOut` (`EVConnectTimeout`, reason `Timeout`).
+    pub fn poll(&mut self, now: DateTime<Utc>) {
+        let interval = TimeDelta::seconds(i64::from(self.config.tx_updated_interval));
+        let timeout = TimeDelta::seconds(i64::from(self.config.ev_connection_timeout));
+        for index in 0..self.evses.len() {
+            let evse = &self.evses[index];
+            let evse_id = evse.id;
+            let timed_out = !evse.connected
+                && evse
+                    .id_token
+                    .as_ref()
+                    .is_some_and(|(_, at)| now - *at >= timeout);
+            if timed_out {
+                let cause = Cause::new(
+                    TriggerReasonEnumType::EVConnectTimeout,
+                    ReasonEnumType::Timeout,
+                );
+                let force = evse.transaction.is_some();
+                self.apply(evse_id, now, cause, force, |target| target.id_token = None);
+                continue;
+            }
+            let due = self.config.tx_updated_interval > 0
+                && evse
+                    .transaction
+                    .as_ref()
+                    .is_some_and(|tx| now - tx.last_periodic >= interval);
+            if due {
+                let meter_value = evse.meter_values(
+                    &self.config.tx_updated_measurands,
+                    &ReadingContextEnumType::SamplePeriodic,
+                    now,
+                );
+                let cause = Cause::new(
+                    TriggerReasonEnumType::MeterValuePeriodic,
+                    ReasonEnumType::Other,
+                );
+                self.event(
+                    evse_id,
+                    TransactionEventEnumType::Updated,
+                    now,
+                    cause,
+                    meter_value,
+                );
+                if let Some(tx) = self.evses[index].transaction.as_mut() {
+                    tx.last_periodic = now;
+                }
+            }
+        }
+    }
+}
+
+/// A problem in a received `TransactionEvent` stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionEventIssue {
+    /// `seqNo` skipped values: events were lost (or are still queued offline).
+    SeqNoGap {
+        transaction_id: String,
+        expected: i32,
+        found: i32,
+    },
+    /// `seqNo` already received, or lower than one received.
+    SeqNoRepeated { transaction_id: String, seq_no: i32 },
+    /// First event of the transaction is not `Started`.
+    MissingStarted { transaction_id: String },
+    /// `Started` for a transaction already started.
+    DuplicateStarted { transaction_id: String },
+    /// Event after `Ended`.
+    EventAfterEnded { transaction_id: String },
+    /// `timestamp` earlier than the previous event's.
+    TimestampRegression { transaction_id: String },
+    /// A new transaction started on the EVSE of this one, which never ended.
+    MissingEnded { transaction_id: String },
+}
+
+impl fmt::Display for TransactionEventIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SeqNoGap {
+                transaction_id,
+                expected,
+                found,
+            } => write!(f, "{transaction_id}: seqNo {found}, expected {expected}"),
+            Self::SeqNoRepeated {
+                transaction_id,
+                seq_no,
+            } => write!(f, "{transaction_id}: seqNo {seq_no} repeated"),
+            Self::MissingStarted { transaction_id } => {
+                write!(f, "{transaction_id}: no Started event")
+            }
+            Self::DuplicateStarted { transaction_id } => {
+                write!(f, "{transaction_id}: Started twice")
+            }
+            Self::EventAfterEnded { transaction_id } => {
+                write!(f, "{transaction_id}: event after Ended")
+            }
+            Self::TimestampRegression { transaction_id } => {
+                write!(f, "{transaction_id}: timestamp went back")
+            }
+            Self::MissingEnded { transaction_id } => {
+                write!(f, "{transaction_id}: replaced without Ended")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tracked {
+    evse_id: Option<i32>,
+    /// Past `i32::MAX` once that `seqNo` was received.
+    next_seq_no: i64,
+    timestamp: DateTime<Utc>,
+    ended: bool,
+}
+
+/// CSMS-side checker of the `TransactionEvent`s of one station.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionEventValidator {
+    transactions: BTreeMap<String, Tracked>,
+}
+
+impl TransactionEventValidator {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            transactions: BTreeMap::new(),
+        }
+    }
+
+    /// Check `request` against the events received before it, then record it.
+    pub fn check(&mut self, request: &TransactionEventRequest) -> Vec<TransactionEventIssue> {
+        let mut issues = Vec::new();
+        let id = &request.transaction_info.transaction_id;
+        let timestamp = request.timestamp.inner();
+        let evse_id = request.evse.as_ref().map(|evse| evse.id);
+        let started = request.event_type == TransactionEventEnumType::Started;
+        let ended = request.event_type == TransactionEventEnumType::Ended;
+
+        if let Some(evse_id) = evse_id.filter(|_| started) {
+            for (other, tracked) in &mut self.transactions {
+                if other != id && !tracked.ended && tracked.evse_id == Some(evse_id) {
+                    tracked.ended = true;
+                    issues.push(TransactionEventIssue::MissingEnded {
+                        transaction_id: other.clone(),
+                    });
+                }
+            }
+        }
+
+        let transaction_id = || id.clone();
+        let tracked = if let Some(tracked) = self.transactions.get_mut(id) {
+            if started {
+                issues.push(TransactionEventIssue::DuplicateStarted {
+                    transaction_id: transaction_id(),
+                });
+            }
+            if tracked.ended {
+                issues.push(TransactionEventIssue::EventAfterEnded {
+                    transaction_id: transaction_id(),
+                });
+            }
+            tracked
+        } else {
+            if !started {
+                issues.push(TransactionEventIssue::MissingStarted {
+                    transaction_id: transaction_id(),
+                });
+            }
+            // The first event of a transaction has `seqNo` 0.
+            self.transactions.entry(id.clone()).or_insert(Tracked {
+                evse_id,
+                next_seq_no: 0,
+                timestamp,
+                ended: false,
+            })
+        };
+        let seq_no = i64::from(request.seq_no);
+        if seq_no < tracked.next_seq_no {
+            issues.push(TransactionEventIssue::SeqNoRepeated {
+                transaction_id: transaction_id(),
+                seq_no: request.seq_no,
+            });
+        } else {
+            if seq_no > tracked.next_seq_no {
+                issues.push(TransactionEventIssue::SeqNoGap {
+                    transaction_id: transaction_id(),
+                    // Below `seqNo`, so within `i32`.
+                    expected: i32::try_from(tracked.next_seq_no).unwrap_or(i32::MAX),
+                    found: request.seq_no,
+                });
+            }
+            tracked.next_seq_no = seq_no + 1;
+        }
+        if timestamp < tracked.timestamp {
+            issues.push(TransactionEventIssue::TimestampRegression {
+                transaction_id: transaction_id(),
+            });
+        }
+        tracked.timestamp = tracked.timestamp.max(timestamp);
+        tracked.evse_id = tracked.evse_id.or(evse_id);
+        tracked.ended |= ended;
+        issues
+    }
+
+    /// Transactions without `Ended` so far.
+    pub fn open_transactions(&self) -> impl Iterator<Item = &str> {
+        self.transactions
+            .iter()
+            .filter(|(_, tracked)| !tracked.ended)
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Stop tracking `transaction_id` (settled, or too old to matter).
+    pub fn forget(&mut self, transaction_id: &str) {
+        self.transactions.remove(transaction_id);
+    }
+}
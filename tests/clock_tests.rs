@@ -0,0 +1,137 @@
+//! Station clock disciplined by CSMS timestamps.
+
+use core::cell::Cell;
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ocpp_rs::clock::{MonotonicClock, StationClock};
+use ocpp_rs::datetime::DateTimeWrapper;
+
+struct Ticks(Cell<u64>);
+
+impl MonotonicClock for Ticks {
+    fn millis(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+fn base() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 14, 12, 0, 0).unwrap()
+}
+
+/// Local ticks of a clock running 100 ppm slow, `real_ms` after start.
+fn slow_tick(real_ms: u64) -> u64 {
+    real_ms - real_ms / 10_000
+}
+
+#[test]
+fn estimates_drift_and_corrects_now() {
+    let ticks = Ticks(Cell::new(0));
+    let mut clock = StationClock::new(&ticks);
+    assert!(clock.now().is_none());
+
+    // A Heartbeat every 5 minutes with a 200 ms round trip.
+    for k in 0..8 {
+        let sent = k * 300_000;
+        ticks.0.set(slow_tick(sent));
+        let sent_at = clock.tick();
+        ticks.0.set(slow_tick(sent + 200));
+        let csms_time = base() + TimeDelta::milliseconds(i64::try_from(sent + 100).unwrap());
+        let error = clock.sync(DateTimeWrapper::new(csms_time), sent_at);
+        assert_eq!(error.is_none(), k == 0);
+        if let Some(error) = error {
+            assert!(error.abs() < TimeDelta::milliseconds(50), "{error}");
+        }
+    }
+    assert!(clock.is_synced());
+    assert!(
+        (clock.drift_ppm() - 100.0).abs() < 5.0,
+        "{}",
+        clock.drift_ppm()
+    );
+    assert_eq!(clock.uncertainty(), Some(TimeDelta::milliseconds(100)));
+
+    // An hour later the estimate is still within a few ms.
+    let real = 3_600_000;
+    ticks.0.set(slow_tick(real));
+    let now = clock.now().unwrap().inner();
+    let truth = base() + TimeDelta::milliseconds(real.try_into().unwrap());
+    assert!(
+        (now - truth).abs() < TimeDelta::milliseconds(10),
+        "{}",
+        now - truth
+    );
+    let rtc = truth + TimeDelta::seconds(30);
+    let offset = clock.offset_of(rtc).unwrap();
+    assert!((offset - TimeDelta::seconds(30)).abs() < TimeDelta::milliseconds(10));
+}
+
+#[test]
+fn csms_clock_step_restarts_the_fit() {
+    let ticks = Ticks(Cell::new(1_000));
+    let mut clock = StationClock::new(&ticks);
+    clock.sync(DateTimeWrapper::new(base()), 1_000);
+    ticks.0.set(61_000);
+    let error = clock.sync(
+        DateTimeWrapper::new(base() + TimeDelta::seconds(60)),
+        61_000,
+    );
+    assert_eq!(error, Some(TimeDelta::zero()));
+    assert_eq!(clock.samples().count(), 2);
+
+    ticks.0.set(121_000);
+    let error = clock.sync(
+        DateTimeWrapper::new(base() + TimeDelta::minutes(62)),
+        121_000,
+    );
+    assert_eq!(error, Some(TimeDelta::hours(1)));
+    assert_eq!(clock.samples().count(), 1);
+    assert_eq!(clock.at(121_000), Some(base() + TimeDelta::minutes(62)));
+}
+
+#[test]
+fn corrections_stay_within_the_step_threshold() {
+    let ticks = Ticks(Cell::new(0));
+    let mut clock = StationClock::with_window(&ticks, 4, TimeDelta::seconds(1));
+    clock.sync(DateTimeWrapper::new(base()), 0);
+
+    // Off by exactly the threshold: still drift, the fit keeps both samples.
+    ticks.0.set(60_000);
+    let error = clock.sync(
+        DateTimeWrapper::new(base() + TimeDelta::milliseconds(61_000)),
+        60_000,
+    );
+    assert_eq!(error, Some(TimeDelta::seconds(1)));
+    assert_eq!(clock.samples().count(), 2);
+    assert_eq!(
+        clock.at(60_000),
+        Some(base() + TimeDelta::milliseconds(61_000))
+    );
+
+    // One millisecond beyond it is a step.
+    ticks.0.set(120_000);
+    let estimate = clock.at(120_000).unwrap();
+    let error = clock.sync(
+        DateTimeWrapper::new(estimate - TimeDelta::milliseconds(1_001)),
+        120_000,
+    );
+    assert_eq!(error, Some(-TimeDelta::milliseconds(1_001)));
+    assert_eq!(clock.samples().count(), 1);
+    assert!(clock.drift_ppm().abs() < f64::EPSILON);
+}
+
+#[test]
+fn ticks_beyond_the_datetime_range_give_none() {
+    let ticks = Ticks(Cell::new(0));
+    let mut clock = StationClock::new(&ticks);
+    clock.sync(DateTimeWrapper::new(base()), 0);
+    assert_eq!(clock.at(0), Some(base()));
+    assert_eq!(clock.at(u64::MAX), None);
+
+    ticks.0.set(u64::MAX);
+    assert!(clock.now().is_none());
+    assert!(clock.offset_of(base()).is_none());
+    // An estimate out of range counts as a step.
+    assert_eq!(clock.sync(DateTimeWrapper::new(base()), u64::MAX), None);
+    assert_eq!(clock.samples().count(), 1);
+    assert_eq!(clock.at(u64::MAX), Some(base()));
+}
@@ -0,0 +1,8 @@
+//! Fixtures shared by the integration tests.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+
+/// `second`s after the shared test epoch (2025-03-14 12:00:00 UTC).
+pub fn at(second: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 14, 12, 0, 0).unwrap() + TimeDelta::seconds(second)
+}
@@ -0,0 +1,181 @@
+//! Typed controller variable handles (feature `device_model_catalog`).
+
+#![cfg(feature = "device_model_catalog")]
+
+use core::time::Duration;
+
+use ocpp_rs::v21::datatypes::{ComponentType, VariableType};
+use ocpp_rs::v21::device_model::controllers::{
+    auth_ctrlr, ocpp_comm_ctrlr, sampled_data_ctrlr, tx_ctrlr,
+};
+use ocpp_rs::v21::device_model::{ComponentKey, DeviceModel, VariableCatalog};
+use ocpp_rs::v21::enumerations::MeasurandEnumType;
+use ocpp_rs::v21::messages::get_variables::{
+    GetVariableResultType, GetVariableStatusEnumType, GetVariablesResponse,
+};
+
+fn result(
+    component: &str,
+    variable: &str,
+    instance: Option<&str>,
+    status: GetVariableStatusEnumType,
+    value: &str,
+) -> GetVariableResultType {
+    GetVariableResultType {
+        attribute_status: status,
+        attribute_status_info: None,
+        attribute_type: None,
+        attribute_value: Some(value.into()),
+        component: ComponentType {
+            evse: None,
+            name: component.into(),
+            instance: None,
+            custom_data: None,
+        },
+        variable: VariableType {
+            name: variable.into(),
+            instance: instance.map(String::from),
+            custom_data: None,
+        },
+        custom_data: None,
+    }
+}
+
+#[test]
+fn builds_requests_matching_the_catalog() {
+    let set = ocpp_comm_ctrlr::HEARTBEAT_INTERVAL.set(&Duration::from_secs(300));
+    assert_eq!(set.component.name, "OCPPCommCtrlr");
+    assert_eq!(set.variable.name, "HeartbeatInterval");
+    assert_eq!(set.attribute_value, "300");
+
+    let get = ocpp_comm_ctrlr::MESSAGE_ATTEMPTS_TRANSACTION_EVENT.get();
+    assert_eq!(get.variable.instance.as_deref(), Some("TransactionEvent"));
+
+    let catalog = VariableCatalog::new();
+    assert!(catalog.check_set(&set).is_ok());
+    assert!(catalog.check_get(&get).is_ok());
+    assert!(
+        catalog
+            .check_set(&tx_ctrlr::STOP_TX_ON_INVALID_ID.set(&true))
+            .is_ok()
+    );
+    assert!(
+        catalog
+            .check_set(&tx_ctrlr::EV_CONNECTION_TIME_OUT.set(&Duration::from_secs(90)))
+            .is_ok()
+    );
+}
+
+fn response() -> GetVariablesResponse {
+    GetVariablesResponse {
+        get_variable_result: vec![
+            result(
+                "OCPPCommCtrlr",
+                "HeartbeatInterval",
+                None,
+                GetVariableStatusEnumType::Accepted,
+                "120",
+            ),
+            result(
+                "TxCtrlr",
+                "EVConnectionTimeOut",
+                None,
+                GetVariableStatusEnumType::Rejected,
+                "60",
+            ),
+            result(
+                "SampledDataCtrlr",
+                "TxUpdatedMeasurands",
+                None,
+                GetVariableStatusEnumType::Accepted,
+                "Energy.Active.Import.Register, Power.Active.Import",
+            ),
+            result(
+                "AuthCtrlr",
+                "AuthorizeRemoteStart",
+                None,
+                GetVariableStatusEnumType::Accepted,
+                "maybe",
+            ),
+        ],
+        custom_data: None,
+    }
+}
+
+#[test]
+fn parses_accepted_results() {
+    assert_eq!(
+        ocpp_comm_ctrlr::HEARTBEAT_INTERVAL.from_response(&response()),
+        Some(Duration::from_secs(120))
+    );
+}
+
+#[test]
+fn rejected_results_have_no_value() {
+    assert_eq!(
+        tx_ctrlr::EV_CONNECTION_TIME_OUT.from_response(&response()),
+        None
+    );
+}
+
+#[test]
+fn member_lists_round_trip() {
+    let measurands = sampled_data_ctrlr::TX_UPDATED_MEASURANDS
+        .from_response(&response())
+        .unwrap();
+    assert_eq!(
+        measurands,
+        vec![
+            MeasurandEnumType::EnergyActiveImportRegister,
+            MeasurandEnumType::PowerActiveImport
+        ]
+    );
+    assert_eq!(
+        sampled_data_ctrlr::TX_UPDATED_MEASURANDS
+            .set(&measurands)
+            .attribute_value,
+        "Energy.Active.Import.Register,Power.Active.Import"
+    );
+}
+
+#[test]
+fn unparseable_values_are_ignored() {
+    assert_eq!(
+        auth_ctrlr::AUTHORIZE_REMOTE_START.from_response(&response()),
+        None
+    );
+}
+
+#[test]
+fn result_instance_must_match() {
+    assert_eq!(
+        ocpp_comm_ctrlr::MESSAGE_TIMEOUT_DEFAULT.from_result(&result(
+            "OCPPCommCtrlr",
+            "MessageTimeout",
+            None,
+            GetVariableStatusEnumType::Accepted,
+            "30",
+        )),
+        None
+    );
+}
+
+#[test]
+fn reads_and_writes_station_device_model() {
+    let mut dm = DeviceModel::new();
+    dm.seed_required(&ComponentKey::new("OCPPCommCtrlr"));
+    dm.seed_required(&ComponentKey::new("TxCtrlr"));
+    assert_eq!(ocpp_comm_ctrlr::OFFLINE_THRESHOLD.read(&dm), None);
+    assert!(ocpp_comm_ctrlr::OFFLINE_THRESHOLD.write(&mut dm, &Duration::from_secs(600)));
+    assert_eq!(
+        ocpp_comm_ctrlr::OFFLINE_THRESHOLD.read(&dm),
+        Some(Duration::from_secs(600))
+    );
+    assert!(tx_ctrlr::STOP_TX_ON_EV_SIDE_DISCONNECT.write(&mut dm, &false));
+    assert_eq!(
+        tx_ctrlr::STOP_TX_ON_EV_SIDE_DISCONNECT.read(&dm),
+        Some(false)
+    );
+    // Not seeded: optional variable.
+    assert!(!ocpp_comm_ctrlr::HEARTBEAT_INTERVAL.write(&mut dm, &Duration::from_secs(1)));
+}
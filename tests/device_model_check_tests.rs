@@ -0,0 +1,162 @@
+//! Catalog checks of `GetVariables` / `SetVariables` items (feature `device_model_catalog`).
+
+#![cfg(feature = "device_model_catalog")]
+
+use ocpp_rs::v21::datatypes::{ComponentType, VariableType};
+use ocpp_rs::v21::device_model::{
+    CatalogIssue, CatalogMismatch, ComponentVariableRow, VariableCatalog, VariableEntry,
+    VariableKey,
+};
+use ocpp_rs::v21::messages::get_variables::{GetVariableDataType, GetVariablesRequest};
+use ocpp_rs::v21::messages::notify_report::DataEnumType;
+use ocpp_rs::v21::messages::set_variables::{SetVariableDataType, SetVariablesRequest};
+
+fn component(name: &str) -> ComponentType {
+    ComponentType {
+        evse: None,
+        name: name.into(),
+        instance: None,
+        custom_data: None,
+    }
+}
+
+fn variable(name: &str) -> VariableType {
+    VariableType {
+        name: name.into(),
+        instance: None,
+        custom_data: None,
+    }
+}
+
+fn set(c: &str, v: &str, value: &str) -> SetVariableDataType {
+    SetVariableDataType {
+        attribute_type: None,
+        attribute_value: value.into(),
+        component: component(c),
+        variable: variable(v),
+        custom_data: None,
+    }
+}
+
+#[test]
+fn set_variables_checked_against_matrix() {
+    let catalog = VariableCatalog::new();
+    let issues = catalog.check_set_variables(&SetVariablesRequest {
+        set_variable_data: vec![
+            set("OCPPCommCtrlr", "HeartbeatInterval", "300"),
+            set("OCPPCommCtrlr", "HeartbeatInterval", "5m"),
+            set("ocppcommctrlr", "TxStartPoint", "Authorized"),
+            set("TxCtrlr", "TxStartPoint", "Authorized,EVConnected"),
+            set("ClockCtrlr", "DateTime", "yesterday"),
+            set("FluxCapacitor", "Power", "1"),
+            // Generic variable on a standard component.
+            set("EVSE", "Power", "11000"),
+        ],
+        custom_data: None,
+    });
+    assert_eq!(
+        issues,
+        vec![
+            CatalogIssue {
+                index: 1,
+                mismatch: CatalogMismatch::InvalidValue {
+                    data_type: DataEnumType::Integer,
+                    reason: "InvalidValue",
+                },
+            },
+            CatalogIssue {
+                index: 2,
+                mismatch: CatalogMismatch::UnknownVariable,
+            },
+            CatalogIssue {
+                index: 4,
+                mismatch: CatalogMismatch::InvalidValue {
+                    data_type: DataEnumType::DateTime,
+                    reason: "InvalidValue",
+                },
+            },
+            CatalogIssue {
+                index: 5,
+                mismatch: CatalogMismatch::UnknownComponent,
+            },
+        ]
+    );
+}
+
+#[test]
+fn get_variables_checked_against_matrix() {
+    let get = VariableCatalog::new().check_get_variables(&GetVariablesRequest {
+        get_variable_data: vec![GetVariableDataType {
+            attribute_type: None,
+            component: component("TxCtrlr"),
+            variable: variable("HeartbeatInterval"),
+            custom_data: None,
+        }],
+        custom_data: None,
+    });
+    assert_eq!(get.len(), 1);
+    assert_eq!(get[0].mismatch, CatalogMismatch::UnknownVariable);
+}
+
+#[test]
+fn units_follow_catalog_alternatives() {
+    let catalog = VariableCatalog::new();
+    let (evse, power) = (component("EVSE"), variable("Power"));
+    assert!(catalog.check_unit(&evse, &power, "kW").is_ok());
+    assert!(catalog.check_unit(&evse, &power, "W").is_ok());
+    assert_eq!(
+        catalog.check_unit(&evse, &power, "A"),
+        Err(CatalogMismatch::UnitMismatch {
+            allowed: vec!["W".into(), "kW".into()]
+        })
+    );
+    let tx = (component("TxCtrlr"), variable("TxStartPoint"));
+    assert!(catalog.check_unit(&tx.0, &tx.1, "").is_ok());
+    assert!(catalog.check_unit(&tx.0, &tx.1, "s").is_err());
+}
+
+#[test]
+fn vendor_components_and_variables() {
+    static VENDOR_ROWS: &[ComponentVariableRow] = &[ComponentVariableRow {
+        component: "AcmeCoolingCtrlr",
+        variable: "FanSpeed",
+        instance: "",
+        required: false,
+        data_type: "integer",
+        unit: "rpm",
+    }];
+    let mut catalog = VariableCatalog::new();
+    assert!(!catalog.has_component("AcmeCoolingCtrlr"));
+    catalog.add_rows(VENDOR_ROWS).add_variable(
+        "AcmeCoolingCtrlr",
+        VariableEntry::new(VariableKey::new("Mode"), DataEnumType::OptionList)
+            .with_values_list("Quiet,Boost"),
+    );
+    assert!(catalog.has_component("acmecoolingctrlr"));
+    assert!(
+        catalog
+            .check_set(&set("AcmeCoolingCtrlr", "FanSpeed", "1200"))
+            .is_ok()
+    );
+    assert!(
+        catalog
+            .check_set(&set("AcmeCoolingCtrlr", "Mode", "Boost"))
+            .is_ok()
+    );
+    assert_eq!(
+        catalog.check_set(&set("AcmeCoolingCtrlr", "Mode", "Turbo")),
+        Err(CatalogMismatch::InvalidValue {
+            data_type: DataEnumType::OptionList,
+            reason: "InvalidValue",
+        })
+    );
+    assert_eq!(
+        catalog.check_set(&set("AcmeCoolingCtrlr", "Colour", "Red")),
+        Err(CatalogMismatch::UnknownVariable)
+    );
+    assert!(
+        catalog
+            .check_unit(&component("AcmeCoolingCtrlr"), &variable("FanSpeed"), "rpm")
+            .is_ok()
+    );
+}
@@ -0,0 +1,389 @@
+//! Runtime device-model store tests (feature `device_model_catalog`).
+
+#![cfg(feature = "device_model_catalog")]
+
+use ocpp_rs::v21::datatypes::{ComponentType, EVSEType, VariableType};
+use ocpp_rs::v21::device_model::store::check_value;
+use ocpp_rs::v21::device_model::{
+    ComponentKey, DeviceModel, VariableAttribute, VariableEntry, VariableKey,
+};
+use ocpp_rs::v21::enumerations::AttributeEnumType;
+use ocpp_rs::v21::messages::get_variables::{
+    GetVariableDataType, GetVariableStatusEnumType, GetVariablesRequest,
+};
+use ocpp_rs::v21::messages::notify_report::{DataEnumType, MutabilityEnumType};
+use ocpp_rs::v21::messages::set_variables::{
+    SetVariableDataType, SetVariableStatusEnumType, SetVariablesRequest,
+};
+
+fn component(name: &str, evse: Option<(i32, Option<i32>)>) -> ComponentType {
+    ComponentType {
+        evse: evse.map(|(id, connector_id)| EVSEType {
+            id,
+            connector_id,
+            custom_data: None,
+        }),
+        name: name.into(),
+        instance: None,
+        custom_data: None,
+    }
+}
+
+fn variable(name: &str) -> VariableType {
+    VariableType {
+        name: name.into(),
+        instance: None,
+        custom_data: None,
+    }
+}
+
+fn get(c: ComponentType, v: &str, attr: Option<AttributeEnumType>) -> GetVariableDataType {
+    GetVariableDataType {
+        attribute_type: attr,
+        component: c,
+        variable: variable(v),
+        custom_data: None,
+    }
+}
+
+fn set(c: ComponentType, v: &str, value: &str) -> SetVariableDataType {
+    SetVariableDataType {
+        attribute_type: None,
+        attribute_value: value.into(),
+        component: c,
+        variable: variable(v),
+        custom_data: None,
+    }
+}
+
+fn model() -> DeviceModel {
+    let mut dm = DeviceModel::new();
+    assert!(dm.seed_required_controllers() > 50);
+    dm.add_standard_variable(
+        ComponentKey::new("OCPPCommCtrlr"),
+        VariableKey::new("HeartbeatInterval"),
+    )
+    .unwrap()
+    .attributes[0]
+        .value = Some("300".into());
+    dm.add_variable(
+        ComponentKey::new("EVSE").with_evse(1),
+        VariableEntry::new(VariableKey::new("Power"), DataEnumType::Decimal)
+            .with_unit("W")
+            .with_limits(Some(0.0), Some(22_000.0))
+            .with_value("11000")
+            .with_attribute(VariableAttribute::new(AttributeEnumType::MaxSet)),
+    );
+    dm.add_variable(
+        ComponentKey::new("SecurityCtrlr"),
+        VariableEntry::new(VariableKey::new("BasicAuthPassword"), DataEnumType::String)
+            .with_mutability(&MutabilityEnumType::WriteOnly),
+    );
+    dm.add_variable(
+        ComponentKey::new("ChargingStation"),
+        VariableEntry::new(VariableKey::new("Model"), DataEnumType::String)
+            .with_value("X1")
+            .with_mutability(&MutabilityEnumType::ReadOnly),
+    );
+    dm
+}
+
+#[test]
+fn seeding_uses_catalog_data_types() {
+    let dm = model();
+    let tx_start = dm
+        .variable(
+            &ComponentKey::new("txctrlr"),
+            &VariableKey::new("TxStartPoint"),
+        )
+        .unwrap();
+    assert_eq!(tx_start.characteristics.data_type, DataEnumType::MemberList);
+    let hb = dm
+        .variable(
+            &ComponentKey::new("OCPPCommCtrlr"),
+            &VariableKey::new("HeartbeatInterval"),
+        )
+        .unwrap();
+    assert_eq!(hb.characteristics.data_type, DataEnumType::Integer);
+    assert_eq!(hb.characteristics.unit.as_deref(), Some("s"));
+    assert!(
+        dm.variable(
+            &ComponentKey::new("DeviceDataCtrlr"),
+            &VariableKey::new("ItemsPerMessage").with_instance("GetReport"),
+        )
+        .is_some()
+    );
+}
+
+#[test]
+fn get_variables_statuses() {
+    let dm = model();
+    let resp = dm.get_variables(&GetVariablesRequest {
+        get_variable_data: vec![
+            get(component("OCPPCommCtrlr", None), "HeartbeatInterval", None),
+            get(component("Nope", None), "HeartbeatInterval", None),
+            get(component("OCPPCommCtrlr", None), "Nope", None),
+            get(
+                component("OCPPCommCtrlr", None),
+                "HeartbeatInterval",
+                Some(AttributeEnumType::Target),
+            ),
+            get(component("SecurityCtrlr", None), "BasicAuthPassword", None),
+            get(component("EVSE", Some((1, None))), "Power", None),
+            get(component("EVSE", Some((2, None))), "Power", None),
+        ],
+        custom_data: None,
+    });
+    let statuses: Vec<_> = resp
+        .get_variable_result
+        .iter()
+        .map(|r| r.attribute_status.clone())
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            GetVariableStatusEnumType::Accepted,
+            GetVariableStatusEnumType::UnknownComponent,
+            GetVariableStatusEnumType::UnknownVariable,
+            GetVariableStatusEnumType::NotSupportedAttributeType,
+            GetVariableStatusEnumType::Rejected,
+            GetVariableStatusEnumType::Accepted,
+            GetVariableStatusEnumType::UnknownComponent,
+        ]
+    );
+    assert_eq!(
+        resp.get_variable_result[0].attribute_value.as_deref(),
+        Some("300")
+    );
+    assert_eq!(
+        resp.get_variable_result[5].attribute_value.as_deref(),
+        Some("11000")
+    );
+}
+
+/// Status and reason code of a single `SetVariables` entry.
+fn set_status(
+    dm: &mut DeviceModel,
+    data: &SetVariableDataType,
+) -> (SetVariableStatusEnumType, Option<String>) {
+    let result = dm.set_variable(data);
+    (
+        result.attribute_status,
+        result.attribute_status_info.map(|i| i.reason_code),
+    )
+}
+
+#[test]
+fn accepted_value_is_applied() {
+    let mut dm = model();
+    assert_eq!(
+        set_status(
+            &mut dm,
+            &set(component("OCPPCommCtrlr", None), "HeartbeatInterval", "60")
+        ),
+        (SetVariableStatusEnumType::Accepted, None)
+    );
+    assert_eq!(
+        dm.value(
+            &ComponentKey::new("OCPPCommCtrlr"),
+            &VariableKey::new("HeartbeatInterval"),
+            &AttributeEnumType::Actual,
+        ),
+        Some("60")
+    );
+}
+
+#[test]
+fn value_of_the_wrong_type_is_rejected() {
+    assert_eq!(
+        set_status(
+            &mut model(),
+            &set(component("OCPPCommCtrlr", None), "HeartbeatInterval", "abc")
+        ),
+        (
+            SetVariableStatusEnumType::Rejected,
+            Some("InvalidValue".into())
+        )
+    );
+}
+
+#[test]
+fn value_outside_the_limits_is_rejected() {
+    assert_eq!(
+        set_status(
+            &mut model(),
+            &set(component("EVSE", Some((1, None))), "Power", "50000")
+        ),
+        (
+            SetVariableStatusEnumType::Rejected,
+            Some("ValueOutOfRange".into())
+        )
+    );
+}
+
+#[test]
+fn read_only_variable_is_rejected() {
+    let mut dm = model();
+    assert_eq!(
+        set_status(
+            &mut dm,
+            &set(component("ChargingStation", None), "Model", "X2")
+        ),
+        (SetVariableStatusEnumType::Rejected, Some("ReadOnly".into()))
+    );
+    assert_eq!(
+        dm.value(
+            &ComponentKey::new("ChargingStation"),
+            &VariableKey::new("Model"),
+            &AttributeEnumType::Actual,
+        ),
+        Some("X1")
+    );
+}
+
+#[test]
+fn set_variables_reports_unknown_components_and_variables() {
+    let resp = model().set_variables(&SetVariablesRequest {
+        set_variable_data: vec![
+            set(component("Nope", None), "HeartbeatInterval", "1"),
+            set(component("OCPPCommCtrlr", None), "Nope", "1"),
+        ],
+        custom_data: None,
+    });
+    let statuses: Vec<_> = resp
+        .set_variable_result
+        .iter()
+        .map(|r| r.attribute_status.clone())
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            SetVariableStatusEnumType::UnknownComponent,
+            SetVariableStatusEnumType::UnknownVariable,
+        ]
+    );
+}
+
+#[test]
+fn reboot_required_variable_asks_for_a_reboot() {
+    let mut dm = model();
+    dm.variable_mut(
+        &ComponentKey::new("OCPPCommCtrlr"),
+        &VariableKey::new("NetworkConfigurationPriority"),
+    )
+    .unwrap()
+    .reboot_required = true;
+    assert_eq!(
+        set_status(
+            &mut dm,
+            &set(
+                component("OCPPCommCtrlr", None),
+                "NetworkConfigurationPriority",
+                "1,2",
+            )
+        ),
+        (SetVariableStatusEnumType::RebootRequired, None)
+    );
+}
+
+#[test]
+fn only_present_attributes_can_be_set() {
+    let mut dm = model();
+    let mut max_set = set(component("EVSE", Some((1, None))), "Power", "16000");
+    max_set.attribute_type = Some(AttributeEnumType::MaxSet);
+    assert_eq!(
+        dm.set_variable(&max_set).attribute_status,
+        SetVariableStatusEnumType::Accepted
+    );
+    max_set.attribute_type = Some(AttributeEnumType::MinSet);
+    assert_eq!(
+        dm.set_variable(&max_set).attribute_status,
+        SetVariableStatusEnumType::NotSupportedAttributeType
+    );
+}
+
+#[test]
+fn member_list_values_are_checked() {
+    let list = VariableEntry::new(VariableKey::new("TxStartPoint"), DataEnumType::MemberList)
+        .with_values_list("ParkingBayOccupancy,EVConnected,Authorized,PowerPathClosed")
+        .with_max_elements(2);
+    assert!(check_value(&list.characteristics, "EVConnected,Authorized").is_ok());
+    assert!(check_value(&list.characteristics, "").is_ok());
+    assert_eq!(
+        check_value(&list.characteristics, "EVConnected,Bogus"),
+        Err("InvalidValue")
+    );
+    assert_eq!(
+        check_value(&list.characteristics, "EVConnected,EVConnected"),
+        Err("InvalidValue")
+    );
+    assert_eq!(
+        check_value(
+            &list.characteristics,
+            "EVConnected,Authorized,PowerPathClosed"
+        ),
+        Err("TooManyElements")
+    );
+}
+
+#[test]
+fn boolean_and_date_time_values_are_checked() {
+    let flag = VariableEntry::new(VariableKey::new("Enabled"), DataEnumType::Boolean);
+    assert!(check_value(&flag.characteristics, "true").is_ok());
+    assert!(check_value(&flag.characteristics, "1").is_err());
+
+    let when = VariableEntry::new(VariableKey::new("DateTime"), DataEnumType::DateTime);
+    assert!(check_value(&when.characteristics, "2024-01-01T00:00:00Z").is_ok());
+    assert!(check_value(&when.characteristics, "yesterday").is_err());
+}
+
+#[test]
+fn message_limits_come_from_device_data_ctrlr() {
+    let mut dm = model();
+    dm.set_value(
+        &ComponentKey::new("DeviceDataCtrlr"),
+        &VariableKey::new("ItemsPerMessage").with_instance("GetReport"),
+        &AttributeEnumType::Actual,
+        "10",
+    );
+    let limits = dm.message_limits("GetReport");
+    assert_eq!(limits.items_per_message, Some(10));
+    assert_eq!(limits.bytes_per_message, None);
+}
+
+#[test]
+fn configuration_inventory_skips_read_only_variables() {
+    use ocpp_rs::v21::messages::get_base_report::ReportBaseEnumType;
+
+    let dm = model();
+    let full = dm.base_report_data(&ReportBaseEnumType::FullInventory);
+    let config = dm.base_report_data(&ReportBaseEnumType::ConfigurationInventory);
+    assert!(config.len() < full.len());
+    assert!(
+        !config
+            .iter()
+            .any(|d| d.component.name == "ChargingStation" && d.variable.name == "Model")
+    );
+}
+
+#[test]
+fn write_only_values_are_left_out_of_reports() {
+    use ocpp_rs::v21::messages::get_base_report::ReportBaseEnumType;
+
+    let full = model().base_report_data(&ReportBaseEnumType::FullInventory);
+    let password = full
+        .iter()
+        .find(|d| d.variable.name == "BasicAuthPassword")
+        .unwrap();
+    assert!(password.variable_attribute[0].value.is_none());
+}
+
+#[test]
+fn summary_inventory_lists_availability_only() {
+    use ocpp_rs::v21::messages::get_base_report::ReportBaseEnumType;
+
+    let summary = model().base_report_data(&ReportBaseEnumType::SummaryInventory);
+    assert!(summary.iter().all(|d| {
+        ["AvailabilityState", "Available", "Problem"].contains(&d.variable.name.as_str())
+    }));
+}
@@ -0,0 +1,84 @@
+//! Heartbeat scheduling.
+
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::at;
+use ocpp_rs::heartbeat::{HeartbeatDue, HeartbeatScheduler};
+
+#[test]
+fn fixed_interval_ignores_traffic() {
+    let mut heartbeat = HeartbeatScheduler::new(60, false);
+    assert_eq!(heartbeat.next_poll(), Some(HeartbeatDue::Now));
+    assert!(heartbeat.poll(at(0)));
+    assert_eq!(heartbeat.next_poll(), Some(HeartbeatDue::At(at(60))));
+    heartbeat.on_message_sent(at(30));
+    assert!(!heartbeat.poll(at(59)));
+    assert!(heartbeat.poll(at(60)));
+
+    heartbeat.set_interval(0);
+    assert_eq!(heartbeat.next_poll(), None);
+    assert!(!heartbeat.poll(at(500)));
+}
+
+#[test]
+fn traffic_suppresses_heartbeats() {
+    let mut heartbeat = HeartbeatScheduler::new(60, true);
+    heartbeat.on_message_sent(at(0));
+    assert!(!heartbeat.poll(at(0)));
+    heartbeat.on_message_sent(at(50));
+    assert_eq!(heartbeat.next_poll(), Some(HeartbeatDue::At(at(110))));
+    assert!(!heartbeat.poll(at(60)));
+    assert!(heartbeat.poll(at(110)));
+
+    // A triggered heartbeat also restarts the interval; older timestamps do not.
+    heartbeat.on_heartbeat_sent(at(150));
+    heartbeat.on_message_sent(at(120));
+    assert_eq!(heartbeat.next_poll(), Some(HeartbeatDue::At(at(210))));
+}
+
+#[test]
+fn missed_heartbeats_make_the_connection_stale() {
+    use ocpp_rs::registry::{ChargerRegistry, ConnectionState, Protocol, RegistryConfig};
+    use ocpp_rs::v16;
+
+    let mut station = HeartbeatScheduler::new(60, false);
+    let mut csms = ChargerRegistry::new(RegistryConfig {
+        default_heartbeat_interval: 60,
+        missed_heartbeats: 2,
+    });
+    csms.connect("CP-1", Protocol::Ocpp16, at(0));
+    let deliver = |csms: &mut ChargerRegistry, second: i64| {
+        let frame = format!(r#"[2,"h{second}","Heartbeat",{{}}]"#);
+        let message = v16::parse::deserialize_to_message(&frame).unwrap();
+        csms.ingest_v16("CP-1", message, at(second)).unwrap();
+    };
+    let connection = |csms: &ChargerRegistry| csms.charger("CP-1").unwrap().connection;
+
+    assert!(station.poll(at(0)));
+    deliver(&mut csms, 0);
+    assert!(station.poll(at(60)));
+    deliver(&mut csms, 60);
+    // The next two heartbeats are due but get lost.
+    assert!(station.poll(at(120)));
+    assert!(station.poll(at(180)));
+    csms.poll(at(179));
+    assert_eq!(connection(&csms), ConnectionState::Connected);
+    csms.poll(at(180));
+    assert_eq!(connection(&csms), ConnectionState::Stale);
+
+    assert!(station.poll(at(240)));
+    deliver(&mut csms, 240);
+    assert_eq!(connection(&csms), ConnectionState::Connected);
+}
+
+#[test]
+fn due_time_saturates() {
+    let mut heartbeat = HeartbeatScheduler::new(u32::MAX, false);
+    heartbeat.on_heartbeat_sent(DateTime::<Utc>::MAX_UTC);
+    assert_eq!(
+        heartbeat.next_poll(),
+        Some(HeartbeatDue::At(DateTime::<Utc>::MAX_UTC))
+    );
+    assert!(HeartbeatDue::Now.is_due(DateTime::<Utc>::MIN_UTC));
+}
@@ -0,0 +1,306 @@
+//! CSMS load-balancing planner and its 1.6 / 2.1 front ends.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ocpp_rs::load_balancing::{FairnessPolicy, SessionDemand, SiteLimit, plan};
+use ocpp_rs::v16::call::SetChargingProfile;
+use ocpp_rs::v16::enums::{ChargingProfileKindType, ChargingProfilePurposeType};
+use ocpp_rs::v16::load_balancing as lb16;
+use ocpp_rs::v21::enumerations::ChargingProfilePurposeEnumType;
+use ocpp_rs::v21::load_balancing as lb21;
+use ocpp_rs::v21::messages::notify_ev_charging_needs::NotifyEVChargingNeedsRequest;
+use ocpp_rs::v21::messages::set_charging_profile::SetChargingProfileRequest;
+
+fn t0() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+}
+
+fn steps(plan: &ocpp_rs::load_balancing::SessionPlan) -> Vec<(i32, f64)> {
+    plan.periods
+        .iter()
+        .map(|p| (p.start_offset, p.power))
+        .collect()
+}
+
+/// A 22 kW session started an hour ago and an 11 kW one started five minutes ago that
+/// leaves in two hours.
+fn early_and_late() -> [SessionDemand; 2] {
+    let early = SessionDemand::new(t0() - TimeDelta::hours(1), 22_080.0);
+    let mut late = SessionDemand::new(t0() - TimeDelta::minutes(5), 11_040.0);
+    late.departure = Some(t0() + TimeDelta::hours(2));
+    [early, late]
+}
+
+#[test]
+fn policies_share_the_budget() {
+    let sessions = early_and_late();
+    let site = SiteLimit::Constant(16_560.0);
+    let at_start = |policy| {
+        plan(&site, &sessions, policy, t0(), 3600)
+            .iter()
+            .map(|p| p.power_at(0))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(at_start(FairnessPolicy::EqualShare), [8280.0, 8280.0]);
+    assert_eq!(
+        at_start(FairnessPolicy::ProportionalToMax),
+        [11_040.0, 5520.0]
+    );
+    assert_eq!(
+        at_start(FairnessPolicy::FirstComeFirstServed),
+        [16_560.0, 0.0]
+    );
+    assert_eq!(
+        at_start(FairnessPolicy::EarliestDepartureFirst),
+        [5520.0, 11_040.0]
+    );
+}
+
+#[test]
+fn leftover_from_a_capped_session_goes_to_the_others() {
+    let shares: Vec<f64> = plan(
+        &SiteLimit::Constant(30_000.0),
+        &early_and_late(),
+        FairnessPolicy::EqualShare,
+        t0(),
+        60,
+    )
+    .iter()
+    .map(|p| p.power_at(0))
+    .collect();
+    assert_eq!(shares, [18_960.0, 11_040.0]);
+}
+
+#[test]
+fn same_inputs_give_the_same_plan() {
+    let site = SiteLimit::Constant(16_560.0);
+    let sessions = early_and_late();
+    assert_eq!(
+        plan(&site, &sessions, FairnessPolicy::EqualShare, t0(), 3600),
+        plan(&site, &sessions, FairnessPolicy::EqualShare, t0(), 3600)
+    );
+}
+
+#[test]
+fn minimum_power_pauses_the_latest_session() {
+    let mut first = SessionDemand::new(t0() - TimeDelta::hours(1), 22_080.0);
+    first.min_power = 4140.0;
+    let mut second = first;
+    second.started = t0() - TimeDelta::minutes(1);
+    let plans = plan(
+        &SiteLimit::Constant(6000.0),
+        &[first, second],
+        FairnessPolicy::EqualShare,
+        t0(),
+        600,
+    );
+    assert_eq!(steps(&plans[0]), [(0, 6000.0)]);
+    assert_eq!(steps(&plans[1]), [(0, 0.0)]);
+}
+
+#[test]
+fn energy_needs_and_departures_follow_the_site_series() {
+    let site = SiteLimit::Series(vec![
+        (t0(), 11_040.0),
+        (t0() + TimeDelta::hours(1), 22_080.0),
+    ]);
+    let mut small = SessionDemand::new(t0(), 22_080.0);
+    small.energy_needed = Some(11_040.0);
+    let mut leaving = SessionDemand::new(t0(), 22_080.0);
+    leaving.departure = Some(t0() + TimeDelta::minutes(90));
+    let plans = plan(
+        &site,
+        &[small, leaving],
+        FairnessPolicy::EqualShare,
+        t0(),
+        4 * 3600,
+    );
+    // 5520 W for an hour, then 11040 W for the remaining 5520 Wh (30 min).
+    assert_eq!(
+        steps(&plans[0]),
+        [(0, 5520.0), (3600, 11_040.0), (5400, 0.0)]
+    );
+    assert!((plans[0].energy - 11_040.0).abs() < 1e-6);
+    assert_eq!(
+        steps(&plans[1]),
+        [(0, 5520.0), (3600, 11_040.0), (5400, 0.0)]
+    );
+}
+
+#[test]
+fn session_that_has_not_started_gets_nothing() {
+    let later = SessionDemand::new(t0() + TimeDelta::minutes(10), 11_040.0);
+    let plans = plan(
+        &SiteLimit::Constant(11_040.0),
+        &[SessionDemand::new(t0(), 11_040.0), later],
+        FairnessPolicy::EqualShare,
+        t0(),
+        3600,
+    );
+    assert_eq!(steps(&plans[0]), [(0, 11_040.0), (600, 5520.0)]);
+    assert_eq!(steps(&plans[1]), [(0, 0.0), (600, 5520.0)]);
+}
+
+#[test]
+fn huge_energy_need_does_not_overflow() {
+    let mut session = SessionDemand::new(t0(), 11_000.0);
+    session.energy_needed = Some(1e300);
+    let plans = plan(
+        &SiteLimit::Constant(11_000.0),
+        &[session],
+        FairnessPolicy::EqualShare,
+        t0(),
+        3600,
+    );
+    assert_eq!(steps(&plans[0]), [(0, 11_000.0)]);
+    assert!((plans[0].energy - 11_000.0).abs() < 1e-9);
+}
+
+#[test]
+fn horizon_past_the_end_of_time_saturates() {
+    let late = DateTime::<Utc>::MAX_UTC - TimeDelta::seconds(30);
+    let mut session = SessionDemand::new(late, 11_000.0);
+    session.energy_needed = Some(1e300);
+    let plans = plan(
+        &SiteLimit::Constant(11_000.0),
+        &[session],
+        FairnessPolicy::EqualShare,
+        late,
+        3600,
+    );
+    assert_eq!(steps(&plans[0]), [(0, 11_000.0)]);
+}
+
+/// 1.6 `SetChargingProfile` requests for a 1-phase 16 A and a 3-phase 32 A transaction
+/// sharing 15 kW.
+fn v16_requests() -> Vec<SetChargingProfile> {
+    let mut slow = lb16::PlannedTransaction::new(11, 1, t0() - TimeDelta::hours(1), 16.0);
+    slow.phases = 1;
+    let fast = lb16::PlannedTransaction::new(12, 2, t0() - TimeDelta::minutes(30), 32.0);
+    lb16::plan_tx_profiles(
+        &SiteLimit::Constant(15_000.0),
+        &[slow, fast],
+        FairnessPolicy::EqualShare,
+        t0(),
+        3600,
+        &lb16::ProfileOptions {
+            first_profile_id: 100,
+            ..lb16::ProfileOptions::default()
+        },
+    )
+}
+
+#[test]
+fn v16_tx_profiles_target_each_transaction() {
+    let requests = v16_requests();
+    assert_eq!(requests.len(), 2);
+    let first = &requests[0];
+    assert_eq!(first.connector_id, 1);
+    let profile = &first.cs_charging_profiles;
+    assert_eq!(profile.charging_profile_id, 100);
+    assert_eq!(profile.transaction_id, Some(11));
+    assert_eq!(
+        profile.charging_profile_purpose,
+        ChargingProfilePurposeType::TxProfile
+    );
+    assert_eq!(
+        profile.charging_profile_kind,
+        ChargingProfileKindType::Absolute
+    );
+    assert_eq!(
+        profile.charging_schedule.start_schedule.map(|s| s.inner()),
+        Some(t0())
+    );
+    assert_eq!(requests[1].cs_charging_profiles.charging_profile_id, 101);
+}
+
+#[test]
+fn v16_tx_profile_limits_account_for_phases() {
+    let requests = v16_requests();
+    // 1 x 230 V x 16 A = 3680 W; the 3-phase session gets the remaining 11320 W,
+    // 16.4 A after rounding down.
+    assert_eq!(
+        requests[0]
+            .cs_charging_profiles
+            .charging_schedule
+            .charging_schedule_period[0]
+            .limit,
+        16.0
+    );
+    let second = &requests[1].cs_charging_profiles;
+    assert_eq!(
+        second.charging_schedule.charging_schedule_period[0].limit,
+        16.4
+    );
+    assert_eq!(
+        second.charging_schedule.charging_schedule_period[0].number_phases,
+        Some(3)
+    );
+}
+
+/// 2.1 transaction on EVSE 1 that reported needing 4140 Wh at 8-10 A before 13:00.
+fn v21_ev() -> lb21::PlannedTransaction {
+    let needs: NotifyEVChargingNeedsRequest = serde_json::from_value(serde_json::json!({
+        "evseId": 1,
+        "chargingNeeds": {
+            "requestedEnergyTransfer": "AC_three_phase",
+            "departureTime": "2024-05-01T13:00:00Z",
+            "acChargingParameters": {
+                "energyAmount": 4140.0,
+                "evMinCurrent": 8.0,
+                "evMaxCurrent": 10.0,
+                "evMaxVoltage": 400.0,
+            },
+        },
+    }))
+    .unwrap();
+    let mut ev = lb21::PlannedTransaction::new("tx-a", 1, t0() - TimeDelta::hours(1), 32.0);
+    ev.apply_charging_needs(&needs.charging_needs);
+    ev
+}
+
+fn v21_limits(request: &SetChargingProfileRequest) -> Vec<(i32, Option<f64>)> {
+    request.charging_profile.charging_schedule[0]
+        .charging_schedule_period
+        .iter()
+        .map(|p| (p.start_period, p.limit))
+        .collect()
+}
+
+#[test]
+fn charging_needs_shape_the_demand() {
+    let demand = v21_ev().demand();
+    assert_eq!(demand.max_power, 6900.0);
+    assert_eq!(demand.min_power, 5520.0);
+    assert_eq!(demand.energy_needed, Some(4140.0));
+    assert_eq!(demand.departure, Some(t0() + TimeDelta::hours(1)));
+}
+
+#[test]
+fn v21_tx_profiles_from_charging_needs() {
+    let other = lb21::PlannedTransaction::new("tx-b", 2, t0(), 32.0);
+    let requests = lb21::plan_tx_profiles(
+        &SiteLimit::Constant(22_080.0),
+        &[v21_ev(), other],
+        FairnessPolicy::EqualShare,
+        t0(),
+        7200,
+        &lb21::ProfileOptions::default(),
+    );
+    let profile = &requests[0].charging_profile;
+    assert_eq!(requests[0].evse_id, 1);
+    assert_eq!(profile.transaction_id.as_deref(), Some("tx-a"));
+    assert_eq!(
+        profile.charging_profile_purpose,
+        ChargingProfilePurposeEnumType::TxProfile
+    );
+    // 10 A until the 4140 Wh are delivered (36 min), then paused; the other
+    // session takes the rest.
+    assert_eq!(
+        v21_limits(&requests[0]),
+        [(0, Some(10.0)), (2160, Some(0.0))]
+    );
+    assert_eq!(
+        v21_limits(&requests[1]),
+        [(0, Some(22.0)), (2160, Some(32.0))]
+    );
+}
@@ -0,0 +1,282 @@
+//! Per-transaction meter value aggregation from 1.6 and 2.1 messages.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ocpp_rs::metering::{AggregatorConfig, MeterAggregator, MeterAnomaly};
+use ocpp_rs::v16::call::{MeterValues, StartTransaction, StopTransaction};
+use ocpp_rs::v21::messages::transaction_event::TransactionEventRequest;
+
+fn at(minute: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 2, 1, 10, 0, 0).unwrap() + TimeDelta::minutes(minute)
+}
+
+fn v16<T: serde::de::DeserializeOwned>(json: serde_json::Value) -> T {
+    serde_json::from_value(json).unwrap()
+}
+
+/// 1.6 aggregator with a `StartTransaction` at 10:00 reading `meter_start` Wh.
+fn v16_started(meter_start: i32) -> MeterAggregator {
+    let mut meter = MeterAggregator::new(AggregatorConfig::default());
+    meter.add_start_transaction(&v16::<StartTransaction>(serde_json::json!({
+        "connectorId": 1,
+        "idTag": "TAG",
+        "meterStart": meter_start,
+        "timestamp": "2024-02-01T10:00:00Z",
+    })));
+    meter
+}
+
+/// 1.6 `MeterValues` with one sample set at 10:`minute`.
+fn meter_values(minute: u32, sampled_value: serde_json::Value) -> MeterValues {
+    v16(serde_json::json!({
+        "connectorId": 1,
+        "transactionId": 5,
+        "meterValue": [{
+            "timestamp": format!("2024-02-01T10:{minute:02}:00.000Z"),
+            "sampledValue": sampled_value,
+        }],
+    }))
+}
+
+/// 1.6 `StopTransaction` at 11:00 reading `meter_stop` Wh.
+fn stop(meter_stop: i32, transaction_data: serde_json::Value) -> StopTransaction {
+    v16(serde_json::json!({
+        "meterStop": meter_stop,
+        "timestamp": "2024-02-01T11:00:00Z",
+        "transactionId": 5,
+        "transactionData": transaction_data,
+    }))
+}
+
+fn event(event_type: &str, seq_no: i32, meter_value: serde_json::Value) -> TransactionEventRequest {
+    serde_json::from_value(serde_json::json!({
+        "eventType": event_type,
+        "timestamp": "2024-02-01T10:00:00Z",
+        "triggerReason": "MeterValuePeriodic",
+        "seqNo": seq_no,
+        "transactionInfo": { "transactionId": "tx-9" },
+        "meterValue": meter_value,
+    }))
+    .unwrap()
+}
+
+/// 2.1 `meterValue` with one energy register sample.
+fn register(timestamp: &str, context: &str, value: f64) -> serde_json::Value {
+    serde_json::json!([{
+        "timestamp": timestamp,
+        "sampledValue": [{ "value": value, "context": context }],
+    }])
+}
+
+#[test]
+fn v16_start_and_stop_bound_the_transaction() {
+    let mut meter = v16_started(1000);
+    meter.add_stop_transaction(&stop(2000, serde_json::json!([])));
+
+    let summary = meter.summary();
+    assert_eq!(summary.start, Some((at(0), 1000.0)));
+    assert_eq!(summary.end, Some((at(60), 2000.0)));
+    assert!((summary.energy - 1000.0).abs() < 1e-9);
+    assert_eq!(summary.average_power, Some(1000.0));
+    assert!(summary.anomalies.is_empty());
+}
+
+#[test]
+fn v16_per_phase_energy_adds_up() {
+    let mut meter = v16_started(1000);
+    meter.add_meter_values(&meter_values(
+        30,
+        serde_json::json!([
+            { "value": "1.0", "unit": "kWh", "phase": "L1" },
+            { "value": "1.0", "unit": "kWh", "phase": "L2" },
+            { "value": "1.5", "unit": "kWh", "phase": "L3" },
+        ]),
+    ));
+
+    let summary = meter.summary();
+    assert_eq!(summary.end, Some((at(30), 3500.0)));
+    assert!((summary.energy - 2500.0).abs() < 1e-9);
+}
+
+#[test]
+fn v16_power_and_soc_in_kilo_units() {
+    let mut meter = v16_started(1000);
+    meter.add_meter_values(&meter_values(
+        30,
+        serde_json::json!([
+            { "value": "7.4", "unit": "kW", "measurand": "Power.Active.Import" },
+            { "value": "40", "measurand": "SoC" },
+        ]),
+    ));
+    meter.add_stop_transaction(&stop(
+        2000,
+        serde_json::json!([{
+            "timestamp": "2024-02-01T11:00:00.000Z",
+            "sampledValue": [{ "value": "60", "measurand": "SoC" }],
+        }]),
+    ));
+
+    let summary = meter.summary();
+    assert_eq!(summary.max_power, Some(7400.0));
+    assert_eq!(
+        (summary.soc_start, summary.soc_end),
+        (Some(40.0), Some(60.0))
+    );
+}
+
+#[test]
+fn v16_signed_data_is_unreadable() {
+    let mut meter = v16_started(1000);
+    meter.add_meter_values(&meter_values(
+        40,
+        serde_json::json!([{ "value": "ABCDEF", "format": "SignedData" }]),
+    ));
+    meter.add_stop_transaction(&stop(2000, serde_json::json!([])));
+
+    assert_eq!(
+        meter.summary().anomalies,
+        [MeterAnomaly::Unreadable {
+            at: at(40),
+            value: "ABCDEF".into(),
+        }]
+    );
+}
+
+#[test]
+fn v16_meter_reset_keeps_counting() {
+    let mut meter = v16_started(1000);
+    meter.add_meter_values(&meter_values(
+        30,
+        serde_json::json!([{ "value": "3.5", "unit": "kWh" }]),
+    ));
+    // The meter was reset to 0 and counted 2 kWh before the stop.
+    meter.add_stop_transaction(&stop(2000, serde_json::json!([])));
+
+    let summary = meter.summary();
+    // 1000 -> 3500 Wh, then reset, then 2000 Wh.
+    assert!((summary.energy - 4500.0).abs() < 1e-9);
+    assert_eq!(
+        summary.anomalies,
+        [MeterAnomaly::Reset {
+            at: at(60),
+            from: 3500.0,
+            to: 2000.0,
+        }]
+    );
+}
+
+#[test]
+fn v21_events_are_ordered_by_seq_no() {
+    let mut meter = MeterAggregator::default();
+    // Ended event arrives before the started one (offline replay).
+    meter.add_transaction_event(&event(
+        "Ended",
+        1,
+        register("2024-02-01T11:00:00Z", "Transaction.End", 996_000.0),
+    ));
+    meter.add_transaction_event(&event(
+        "Started",
+        0,
+        register("2024-02-01T10:00:00Z", "Transaction.Begin", 995_000.0),
+    ));
+
+    let summary = meter.summary();
+    assert_eq!(summary.start, Some((at(0), 995_000.0)));
+    assert_eq!(summary.end, Some((at(60), 996_000.0)));
+    assert!((summary.energy - 1000.0).abs() < 1e-9);
+    assert!(summary.anomalies.is_empty());
+}
+
+#[test]
+fn v21_register_rollover_with_multiplier() {
+    let mut meter = MeterAggregator::new(AggregatorConfig::new(Some(1_000_000.0), None));
+    meter.add_transaction_event(&event(
+        "Started",
+        0,
+        register("2024-02-01T10:00:00Z", "Transaction.Begin", 995_000.0),
+    ));
+    meter.add_transaction_event(&event(
+        "Ended",
+        1,
+        serde_json::json!([{
+            "timestamp": "2024-02-01T11:00:00Z",
+            "sampledValue": [{
+                "value": 0.5,
+                "context": "Transaction.End",
+                "unitOfMeasure": { "unit": "kWh", "multiplier": 1 },
+            }],
+        }]),
+    ));
+
+    let summary = meter.summary();
+    assert_eq!(summary.end, Some((at(60), 5000.0)));
+    // Wrap from 995000 to 5000: 10000 Wh.
+    assert!((summary.energy - 10_000.0).abs() < 1e-9);
+    assert_eq!(
+        summary.anomalies,
+        [MeterAnomaly::Rollover {
+            at: at(60),
+            from: 995_000.0,
+            to: 5000.0,
+        }]
+    );
+}
+
+#[test]
+fn v21_implausible_jump_is_flagged() {
+    let mut meter = MeterAggregator::new(AggregatorConfig::new(None, Some(22_000.0)));
+    meter.add_transaction_event(&event(
+        "Started",
+        0,
+        register("2024-02-01T10:00:00Z", "Transaction.Begin", 995_000.0),
+    ));
+    meter.add_transaction_event(&event(
+        "Updated",
+        1,
+        register("2024-02-01T10:01:00Z", "Transaction.End", 999_000.0),
+    ));
+
+    let summary = meter.summary();
+    // 4000 Wh in one minute is 240 kW.
+    assert!((summary.energy - 4000.0).abs() < 1e-9);
+    assert_eq!(
+        summary.anomalies,
+        [MeterAnomaly::ImplausibleJump {
+            at: at(1),
+            energy: 4000.0,
+            seconds: 60,
+        }]
+    );
+}
+
+#[test]
+fn v21_soc_in_a_foreign_unit_is_unreadable() {
+    let mut meter = MeterAggregator::default();
+    meter.add_transaction_event(&event(
+        "Updated",
+        1,
+        serde_json::json!([{
+            "timestamp": "2024-02-01T10:01:00Z",
+            "sampledValue": [
+                { "value": 3.0, "measurand": "SoC", "unitOfMeasure": { "unit": "Celsius" } },
+            ],
+        }]),
+    ));
+
+    let summary = meter.summary();
+    assert_eq!(summary.soc_start, None);
+    assert_eq!(
+        summary.anomalies,
+        [MeterAnomaly::Unreadable {
+            at: at(1),
+            value: "3".into(),
+        }]
+    );
+}
+
+#[test]
+fn empty_aggregator_has_an_empty_summary() {
+    let summary = MeterAggregator::default().summary();
+    assert_eq!(summary.start, None);
+    assert!(summary.energy.abs() < 1e-9);
+    assert!(summary.anomalies.is_empty());
+}
@@ -0,0 +1,439 @@
+//! Offline transaction message queue.
+
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::at;
+use ocpp_rs::offline_queue::{FailureOutcome, PushOutcome, QueuedMessage, RetryPolicy};
+use ocpp_rs::v16;
+use ocpp_rs::v21;
+use ocpp_rs::v21::datatypes::IdTokenType;
+use ocpp_rs::v21::transactions::{TransactionConfig, TransactionManager};
+
+fn v16_call(id: &str, action: v16::call::Action) -> v16::call::Call {
+    v16::call::Call::new(id.into(), action)
+}
+
+fn start(connector_id: u32) -> v16::call::Action {
+    v16::call::Action::StartTransaction(v16::call::StartTransaction {
+        connector_id,
+        id_tag: "TAG".into(),
+        meter_start: 0,
+        timestamp: v16::data_types::DateTimeWrapper::new(at(0)),
+        reservation_id: None,
+    })
+}
+
+fn meter_values(transaction_id: Option<i32>) -> v16::call::Action {
+    v16::call::Action::MeterValues(v16::call::MeterValues {
+        connector_id: 1,
+        meter_value: Vec::new(),
+        transaction_id,
+    })
+}
+
+fn stop(transaction_id: i32) -> v16::call::Action {
+    v16::call::Action::StopTransaction(v16::call::StopTransaction {
+        meter_stop: 500,
+        timestamp: v16::data_types::DateTimeWrapper::new(at(9)),
+        transaction_id,
+        reason: Some(v16::enums::Reason::Local),
+        id_tag: None,
+        transaction_data: None,
+    })
+}
+
+fn start_conf(
+    transaction_id: i32,
+    status: v16::enums::AuthorizationStatus,
+) -> v16::call_result::StartTransaction {
+    v16::call_result::StartTransaction {
+        transaction_id,
+        id_tag_info: v16::data_types::IdTagInfo {
+            expiry_date: None,
+            parent_id_tag: None,
+            status,
+        },
+    }
+}
+
+/// Transaction id of each queued call, `None` for `StartTransaction`.
+fn queued_ids(queue: &v16::offline_queue::TransactionQueue) -> Vec<Option<i32>> {
+    queue
+        .entries()
+        .map(|entry| match &entry.message.payload {
+            v16::call::Action::MeterValues(values) => values.transaction_id,
+            v16::call::Action::StopTransaction(stop) => Some(stop.transaction_id),
+            _ => None,
+        })
+        .collect()
+}
+
+fn heartbeat() -> v16::call::Call {
+    v16_call("0", v16::call::Action::Heartbeat(v16::call::Heartbeat {}))
+}
+
+/// Online queue holding a `StartTransaction` ("1") and `MeterValues` ("3").
+fn v16_queued() -> (
+    v16::offline_queue::TransactionQueue,
+    v16::pending::PendingCalls,
+) {
+    let mut queue =
+        v16::offline_queue::TransactionQueue::new(v16::offline_queue::retry_policy(3, 10));
+    queue.push(v16_call("1", start(1)), at(0));
+    queue.push(v16_call("3", meter_values(Some(5))), at(2));
+    queue.set_online(true);
+    (queue, v16::pending::PendingCalls::new())
+}
+
+#[test]
+fn offline_queue_keeps_only_transaction_messages() {
+    use v16::offline_queue::{TransactionQueue, next_call, retry_policy};
+
+    let mut queue = TransactionQueue::new(retry_policy(3, 10));
+    assert_eq!(queue.push(heartbeat(), at(0)), PushOutcome::Dropped);
+    assert_eq!(
+        queue.push(v16_call("1", start(1)), at(0)),
+        PushOutcome::Queued
+    );
+    assert_eq!(
+        queue.push(v16_call("2", meter_values(None)), at(1)),
+        PushOutcome::Dropped
+    );
+    assert_eq!(
+        queue.push(v16_call("3", meter_values(Some(5))), at(2)),
+        PushOutcome::Queued
+    );
+    assert_eq!(queue.len(), 2);
+    let mut pending = v16::pending::PendingCalls::new();
+    assert!(next_call(&mut queue, &mut pending, at(3)).is_none());
+}
+
+#[test]
+fn online_queue_sends_other_messages_directly() {
+    let (mut queue, _) = v16_queued();
+    assert_eq!(queue.push(heartbeat(), at(4)), PushOutcome::SendDirectly);
+}
+
+#[test]
+fn replay_sends_one_call_at_a_time() {
+    let (mut queue, mut pending) = v16_queued();
+    let first = v16::offline_queue::next_call(&mut queue, &mut pending, at(4)).unwrap();
+    assert_eq!(first.unique_id, "1");
+    assert_eq!(pending.len(), 1);
+    assert!(queue.next_message(at(4)).is_none());
+}
+
+#[test]
+fn dropped_connection_requeues_without_counting_the_attempt() {
+    use v16::offline_queue::{disconnected, next_call};
+
+    let (mut queue, mut pending) = v16_queued();
+    next_call(&mut queue, &mut pending, at(4)).unwrap();
+    assert_eq!(
+        disconnected(&mut queue, &mut pending),
+        Some("1".to_string())
+    );
+    assert!(pending.is_empty());
+    assert_eq!(queue.entries().next().unwrap().attempts, 0);
+}
+
+#[test]
+fn only_the_in_flight_result_releases_the_next_call() {
+    use v16::offline_queue::next_call;
+
+    let (mut queue, mut pending) = v16_queued();
+    let first = next_call(&mut queue, &mut pending, at(5)).unwrap();
+    assert_eq!(queue.on_result("3"), None);
+    assert_eq!(queue.on_result(&first.unique_id), Some(first));
+    let second = next_call(&mut queue, &mut pending, at(6)).unwrap();
+    assert_eq!(second.unique_id, "3");
+    assert!(queue.on_result("3").is_some());
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn retries_back_off_then_discard() {
+    let mut queue = v16::offline_queue::TransactionQueue::new(RetryPolicy {
+        attempts: 3,
+        interval: 10,
+    });
+    queue.set_online(true);
+    queue.push(v16_call("1", start(1)), at(0));
+    queue.push(v16_call("2", start(2)), at(0));
+
+    queue.next_message(at(0)).unwrap();
+    assert_eq!(
+        queue.on_error("1", at(1)),
+        Some(FailureOutcome::Retry(at(11)))
+    );
+    assert_eq!(queue.next_poll(), Some(at(11)));
+    assert!(queue.next_message(at(10)).is_none());
+    queue.next_message(at(11)).unwrap();
+    assert_eq!(
+        queue.on_error("1", at(12)),
+        Some(FailureOutcome::Retry(at(32)))
+    );
+    queue.next_message(at(32)).unwrap();
+    let Some(FailureOutcome::Discarded(dropped)) = queue.on_error("1", at(33)) else {
+        panic!("expected discard");
+    };
+    assert_eq!(dropped.unique_id, "1");
+    assert_eq!(queue.on_error("1", at(33)), None);
+    assert_eq!(queue.next_message(at(33)).unwrap().unique_id, "2");
+}
+
+/// 2.1 queue with the `Started` event sent online and the `Updated` one queued offline.
+fn v21_queued() -> v21::offline_queue::TransactionQueue {
+    use v21::offline_queue::{TransactionQueue, retry_policy};
+
+    let mut manager = TransactionManager::new(1, TransactionConfig::default(), "TX");
+    let mut queue = TransactionQueue::new(retry_policy(5, 30));
+    let push = |queue: &mut TransactionQueue, manager: &mut TransactionManager| {
+        for event in manager.take_requests() {
+            let id = queue.len().to_string();
+            let call = v21::call::Call::new(id, v21::call::Action::TransactionEvent(event));
+            assert_eq!(queue.push(call, at(1)), PushOutcome::Queued);
+        }
+    };
+    queue.set_online(true);
+    manager.ev_connected(1, 1, at(0));
+    push(&mut queue, &mut manager);
+    queue.set_online(false);
+    manager.authorize(
+        1,
+        IdTokenType {
+            additional_info: None,
+            id_token: "TAG".into(),
+            type_: "ISO14443".into(),
+            custom_data: None,
+        },
+        at(1),
+    );
+    push(&mut queue, &mut manager);
+    queue
+}
+
+#[test]
+fn v21_events_queued_offline_are_marked() {
+    let offline = |entry: &QueuedMessage<v21::call::Call>| match &entry.message.payload {
+        v21::call::Action::TransactionEvent(event) => event.offline,
+        _ => None,
+    };
+    let flags: Vec<_> = v21_queued().entries().map(offline).collect();
+    assert_eq!(flags, vec![None, Some(true)]);
+}
+
+#[test]
+fn v21_offline_heartbeat_is_dropped() {
+    let heartbeat: v21::messages::heartbeat::HeartbeatRequest = serde_json::from_str("{}").unwrap();
+    assert_eq!(
+        v21_queued().push(
+            v21::call::Call::new("hb".into(), v21::call::Action::Heartbeat(heartbeat)),
+            at(1)
+        ),
+        PushOutcome::Dropped
+    );
+}
+
+#[test]
+fn v21_restored_queue_replays_in_order() {
+    use v21::offline_queue::{TransactionQueue, next_call};
+
+    let queue = v21_queued();
+    let stored = serde_json::to_string(&queue.entries().collect::<Vec<_>>()).unwrap();
+    let entries: Vec<QueuedMessage<v21::call::Call>> = serde_json::from_str(&stored).unwrap();
+    let mut restored = TransactionQueue::from_entries(*queue.policy(), entries);
+    assert_eq!(restored.len(), 2);
+    restored.set_online(true);
+    let mut pending = v21::pending::PendingCalls::new();
+    let replayed = next_call(&mut restored, &mut pending, at(2)).unwrap();
+    assert_eq!(replayed.unique_id, "0");
+    assert_eq!(restored.in_flight(), Some("0"));
+    assert!(pending.take("0").is_some());
+}
+
+/// Two transactions started offline: provisional ids -1 and -2 with their queued messages.
+struct OfflineSessions {
+    queue: v16::offline_queue::TransactionQueue,
+    pending: v16::pending::PendingCalls,
+    ids: v16::offline_queue::TransactionIdMap,
+    first: i32,
+    second: i32,
+}
+
+impl OfflineSessions {
+    fn new() -> Self {
+        let mut queue = v16::offline_queue::TransactionQueue::default();
+        let mut ids = v16::offline_queue::TransactionIdMap::new();
+        let first = ids.start("s1");
+        let second = ids.start("s2");
+        queue.push(v16_call("s1", start(1)), at(0));
+        queue.push(v16_call("m1", meter_values(Some(first))), at(1));
+        queue.push(v16_call("s2", start(2)), at(2));
+        queue.push(v16_call("t1", stop(first)), at(3));
+        queue.push(v16_call("t2", stop(second)), at(4));
+        queue.set_online(true);
+        Self {
+            queue,
+            pending: v16::pending::PendingCalls::new(),
+            ids,
+            first,
+            second,
+        }
+    }
+
+    /// Sends the next call and answers it, as a `StartTransaction` if it is one.
+    fn answer_next(&mut self, transaction_id: i32, status: v16::enums::AuthorizationStatus) {
+        let call =
+            v16::offline_queue::next_call(&mut self.queue, &mut self.pending, at(5)).unwrap();
+        self.ids
+            .on_start_transaction_conf(&call.unique_id, &start_conf(transaction_id, status));
+        self.ids.apply(&mut self.queue);
+        self.queue.on_result(&call.unique_id);
+    }
+}
+
+#[test]
+fn provisional_ids_count_down() {
+    let sessions = OfflineSessions::new();
+    assert_eq!((sessions.first, sessions.second), (-1, -2));
+}
+
+#[test]
+fn unconfirmed_provisional_id_cannot_be_rewritten() {
+    let sessions = OfflineSessions::new();
+    let mut orphan = v16_call("x", stop(sessions.second));
+    assert_eq!(
+        sessions.ids.rewrite(&mut orphan),
+        v16::offline_queue::Rewrite::Pending
+    );
+}
+
+#[test]
+fn confirmed_start_rewrites_queued_messages() {
+    use v16::enums::AuthorizationStatus;
+
+    let mut sessions = OfflineSessions::new();
+    let call =
+        v16::offline_queue::next_call(&mut sessions.queue, &mut sessions.pending, at(5)).unwrap();
+    assert_eq!(
+        sessions.ids.on_start_transaction_conf(
+            &call.unique_id,
+            &start_conf(42, AuthorizationStatus::Accepted)
+        ),
+        Some(sessions.first)
+    );
+    assert!(sessions.ids.apply(&mut sessions.queue).is_empty());
+    sessions.queue.on_result(&call.unique_id);
+    assert_eq!(
+        queued_ids(&sessions.queue),
+        vec![Some(42), None, Some(42), Some(-2)]
+    );
+
+    let call =
+        v16::offline_queue::next_call(&mut sessions.queue, &mut sessions.pending, at(6)).unwrap();
+    assert!(matches!(
+        call.payload,
+        v16::call::Action::MeterValues(ref values) if values.transaction_id == Some(42)
+    ));
+}
+
+#[test]
+fn rejected_start_still_assigns_its_id() {
+    use v16::enums::AuthorizationStatus;
+    use v16::offline_queue::ProvisionalId;
+
+    let mut sessions = OfflineSessions::new();
+    sessions.answer_next(42, AuthorizationStatus::Accepted);
+    sessions.answer_next(0, AuthorizationStatus::Accepted);
+    // The stop goes out under the id of the rejected transaction.
+    sessions.answer_next(43, AuthorizationStatus::Invalid);
+    assert_eq!(queued_ids(&sessions.queue), vec![Some(42), Some(43)]);
+    assert_eq!(
+        sessions.ids.state(sessions.second),
+        Some(ProvisionalId::Assigned(43))
+    );
+}
+
+#[test]
+fn forgotten_ids_are_no_longer_tracked() {
+    use v16::enums::AuthorizationStatus;
+    use v16::offline_queue::Rewrite;
+
+    let mut sessions = OfflineSessions::new();
+    sessions.answer_next(42, AuthorizationStatus::Accepted);
+    sessions.ids.forget(42);
+    assert_eq!(sessions.ids.state(sessions.first), None);
+    assert_eq!(
+        sessions.ids.rewrite(&mut v16_call("y", stop(7))),
+        Rewrite::Unchanged
+    );
+}
+
+#[test]
+fn v16_discarded_start_orphans_its_messages() {
+    use v16::offline_queue::{ProvisionalId, TransactionIdMap, TransactionQueue, retry_policy};
+
+    let mut queue = TransactionQueue::new(retry_policy(1, 10));
+    let mut ids = TransactionIdMap::new();
+    let provisional = ids.start("s1");
+    queue.push(v16_call("s1", start(1)), at(0));
+    queue.push(v16_call("m1", meter_values(Some(provisional))), at(1));
+    queue.push(v16_call("t1", stop(provisional)), at(2));
+    queue.push(v16_call("s2", start(2)), at(3));
+
+    queue.set_online(true);
+    queue.next_message(at(4)).unwrap();
+    let Some(FailureOutcome::Discarded(discarded)) = queue.on_error("s1", at(5)) else {
+        panic!("expected discard");
+    };
+    assert_eq!(
+        ids.on_start_discarded(&discarded.unique_id),
+        Some(provisional)
+    );
+    assert_eq!(ids.state(provisional), Some(ProvisionalId::Abandoned));
+
+    let dropped: Vec<_> = ids
+        .apply(&mut queue)
+        .into_iter()
+        .map(|call| call.unique_id)
+        .collect();
+    assert_eq!(dropped, vec!["m1", "t1"]);
+    assert_eq!(queue.next_message(at(5)).unwrap().unique_id, "s2");
+}
+
+#[test]
+fn retry_at_saturates() {
+    let policy = RetryPolicy {
+        attempts: u32::MAX,
+        interval: 60,
+    };
+    assert_eq!(policy.retry_at(2, at(0)), at(120));
+    let policy = RetryPolicy {
+        attempts: u32::MAX,
+        interval: u32::MAX,
+    };
+    assert_eq!(policy.retry_at(u32::MAX, at(0)), DateTime::<Utc>::MAX_UTC);
+    assert_eq!(
+        policy.retry_at(1, DateTime::<Utc>::MAX_UTC),
+        DateTime::<Utc>::MAX_UTC
+    );
+}
+
+#[test]
+fn provisional_ids_wrap_around() {
+    use v16::offline_queue::{ProvisionalId, TransactionIdMap};
+
+    let mut ids: TransactionIdMap = serde_json::from_value(serde_json::json!({
+        "issued": i32::MAX - 1,
+        "starts": {},
+        "ids": { "-1": "Pending" },
+    }))
+    .unwrap();
+    assert_eq!(ids.start("a"), -i32::MAX);
+    // -1 is still in use.
+    assert_eq!(ids.start("b"), -2);
+    assert_eq!(ids.state(-1), Some(ProvisionalId::Pending));
+    assert_eq!(ids.state(-2), Some(ProvisionalId::Pending));
+}
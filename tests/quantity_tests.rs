@@ -0,0 +1,165 @@
+//! Unit-normalized quantities of 1.6 and 2.1 sampled values.
+
+use ocpp_rs::quantity::{Quantity, QuantityError, SiUnit, Unit, parse_decimal};
+use ocpp_rs::v16::data_types::SampledValue;
+use ocpp_rs::v16::enums::{Location, Measurand, ReadingContext};
+use ocpp_rs::v21::datatypes::SampledValueType;
+use ocpp_rs::v21::enumerations::{LocationEnumType, MeasurandEnumType, ReadingContextEnumType};
+
+fn v16(json: serde_json::Value) -> SampledValue {
+    serde_json::from_value(json).unwrap()
+}
+
+fn v21(json: serde_json::Value) -> SampledValueType {
+    serde_json::from_value(json).unwrap()
+}
+
+fn close(quantity: Quantity, value: f64, si_unit: SiUnit) {
+    assert_eq!(quantity.si_unit, si_unit);
+    assert!((quantity.value - value).abs() < 1e-9, "{quantity:?}");
+}
+
+#[test]
+fn v16_kilo_units_scale_to_si() {
+    close(
+        v16(serde_json::json!({ "value": " 12.5 ", "unit": "kWh" }))
+            .quantity()
+            .unwrap(),
+        12_500.0,
+        SiUnit::Wh,
+    );
+}
+
+#[test]
+fn v16_temperatures_convert_to_kelvin() {
+    close(
+        v16(serde_json::json!({ "value": "25", "measurand": "Temperature", "unit": "Celcius" }))
+            .quantity()
+            .unwrap(),
+        298.15,
+        SiUnit::K,
+    );
+    close(
+        v16(
+            serde_json::json!({ "value": "212", "measurand": "Temperature", "unit": "Fahrenheit" }),
+        )
+        .quantity()
+        .unwrap(),
+        373.15,
+        SiUnit::K,
+    );
+}
+
+#[test]
+fn v16_measurand_decides_a_missing_unit() {
+    close(
+        v16(serde_json::json!({ "value": "16", "measurand": "Current.Import" }))
+            .quantity()
+            .unwrap(),
+        16.0,
+        SiUnit::A,
+    );
+    close(
+        v16(serde_json::json!({ "value": "0.98", "measurand": "Power.Factor" }))
+            .quantity()
+            .unwrap(),
+        0.98,
+        SiUnit::One,
+    );
+}
+
+#[test]
+fn v16_reactive_energy_converts_only_to_reactive_units() {
+    let sample = v16(serde_json::json!({ "value": "1.5", "unit": "kvarh" }));
+    assert_eq!(sample.quantity().unwrap().to(Unit::Varh), Some(1500.0));
+    assert_eq!(sample.quantity().unwrap().to(Unit::Wh), None);
+}
+
+#[test]
+fn v16_spec_defaults() {
+    let sample = v16(serde_json::json!({ "value": "1.5" }));
+    assert_eq!(
+        sample.measurand_or_default(),
+        Measurand::EnergyActiveImportRegister
+    );
+    assert_eq!(sample.context_or_default(), ReadingContext::SamplePeriodic);
+    assert_eq!(sample.location_or_default(), Location::Outlet);
+}
+
+#[test]
+fn v16_signed_data_is_not_a_quantity() {
+    let signed = v16(serde_json::json!({ "value": "OCMF|{}|{}", "format": "SignedData" }));
+    assert!(signed.is_signed());
+    assert_eq!(signed.quantity(), Err(QuantityError::Signed));
+    // Signed payload without `format`.
+    let unflagged = v16(serde_json::json!({ "value": "OCMF|{\"FV\":\"1.0\"}|{\"SD\":\"30\"}" }));
+    assert!(unflagged.is_signed());
+}
+
+#[test]
+fn v16_decimal_parsing() {
+    assert_eq!(
+        v16(serde_json::json!({ "value": "n/a" })).quantity(),
+        Err(QuantityError::NotANumber("n/a".into()))
+    );
+    assert_eq!(parse_decimal("-3.25"), Ok(-3.25));
+}
+
+#[test]
+fn v21_multiplier_scales_the_unit() {
+    let sample = v21(serde_json::json!({
+        "value": 7.4,
+        "measurand": "Power.Active.Import",
+        "unitOfMeasure": { "unit": "kW", "multiplier": -1 },
+    }));
+    close(sample.quantity().unwrap(), 740.0, SiUnit::W);
+    assert!((sample.quantity().unwrap().to(Unit::Kw).unwrap() - 0.74).abs() < 1e-9);
+}
+
+#[test]
+fn v21_multiplier_without_unit_uses_the_measurand_default() {
+    close(
+        v21(serde_json::json!({ "value": 12, "unitOfMeasure": { "multiplier": 3 } }))
+            .quantity()
+            .unwrap(),
+        12_000.0,
+        SiUnit::Wh,
+    );
+}
+
+#[test]
+fn v21_kelvin_and_unknown_units() {
+    close(
+        v21(serde_json::json!({ "value": 300, "measurand": "Temperature", "unitOfMeasure": { "unit": "K" } }))
+            .quantity()
+            .unwrap(),
+        300.0,
+        SiUnit::K,
+    );
+    assert_eq!(
+        v21(serde_json::json!({ "value": 1, "unitOfMeasure": { "unit": "furlong" } })).quantity(),
+        Err(QuantityError::UnknownUnit("furlong".into()))
+    );
+}
+
+#[test]
+fn v21_spec_defaults() {
+    let bare = v21(serde_json::json!({ "value": 1 }));
+    assert_eq!(
+        bare.measurand_or_default(),
+        MeasurandEnumType::EnergyActiveImportRegister
+    );
+    assert_eq!(
+        bare.context_or_default(),
+        ReadingContextEnumType::SamplePeriodic
+    );
+    assert_eq!(bare.location_or_default(), LocationEnumType::Outlet);
+}
+
+#[test]
+fn v21_extreme_multipliers_do_not_stall() {
+    let huge = v21(serde_json::json!({ "value": 1, "unitOfMeasure": { "multiplier": i32::MAX } }));
+    assert_eq!(huge.quantity().unwrap().value, f64::INFINITY);
+    let tiny = v21(serde_json::json!({ "value": 1, "unitOfMeasure": { "multiplier": i32::MIN } }));
+    assert_eq!(tiny.quantity().unwrap().value, 0.0);
+}
@@ -0,0 +1,245 @@
+//! Station registration state machine.
+
+mod common;
+
+use common::at;
+use ocpp_rs::registration::{Registration, RegistrationConfig, RegistrationState};
+use ocpp_rs::v16;
+use ocpp_rs::v21;
+
+fn boot_conf(
+    status: v16::enums::RegistrationStatus,
+    interval: i32,
+) -> v16::call_result::BootNotification {
+    v16::call_result::BootNotification {
+        current_time: v16::data_types::DateTimeWrapper::new(at(0)),
+        interval,
+        status,
+    }
+}
+
+/// 1.6 registration that got a `Pending` boot response with `interval` at 00:02.
+fn v16_pending(interval: i32) -> Registration {
+    let mut registration = Registration::new(RegistrationConfig::default(), at(0));
+    registration.poll(at(0));
+    v16::registration::on_boot_conf(
+        &mut registration,
+        &boot_conf(v16::enums::RegistrationStatus::Pending, interval),
+        at(2),
+    );
+    registration
+}
+
+fn heartbeat() -> v16::call::Action {
+    v16::call::Action::Heartbeat(v16::call::Heartbeat {})
+}
+
+#[test]
+fn booting_sends_a_single_boot_notification() {
+    let mut registration = Registration::new(RegistrationConfig::default(), at(0));
+    assert_eq!(registration.state(), RegistrationState::Booting);
+    assert!(!v16::registration::may_send(&registration, &heartbeat()));
+    assert_eq!(registration.next_poll(), Some(at(0)));
+    assert!(registration.poll(at(0)));
+    assert!(!registration.poll(at(1)));
+    assert_eq!(registration.next_poll(), None);
+}
+
+#[test]
+fn v16_pending_refuses_csms_calls() {
+    use v16::call::{Action, RemoteStopTransaction};
+
+    let registration = v16_pending(30);
+    assert_eq!(registration.state(), RegistrationState::Pending);
+    assert_eq!(registration.next_poll(), Some(at(32)));
+    assert!(!v16::registration::accepts_call(
+        &registration,
+        &Action::RemoteStopTransaction(RemoteStopTransaction { transaction_id: 1 })
+    ));
+}
+
+#[test]
+fn v16_pending_allows_triggered_messages() {
+    use v16::enums::{MessageTrigger, TriggerMessageStatus};
+    use v16::registration::{may_send, on_trigger_message};
+
+    let mut registration = v16_pending(30);
+    assert_eq!(
+        on_trigger_message(&mut registration, &MessageTrigger::Heartbeat, at(3)),
+        TriggerMessageStatus::Accepted
+    );
+    assert!(may_send(&registration, &heartbeat()));
+    // A triggered boot goes out at once.
+    assert_eq!(
+        on_trigger_message(&mut registration, &MessageTrigger::BootNotification, at(4)),
+        TriggerMessageStatus::Accepted
+    );
+    assert!(registration.poll(at(4)));
+}
+
+#[test]
+fn v16_pending_without_interval_uses_the_retry_interval() {
+    let mut registration = v16_pending(0);
+    assert_eq!(registration.next_poll(), Some(at(62)));
+    assert!(!v16::registration::may_send(&registration, &heartbeat()));
+    assert!(!registration.poll(at(61)));
+    assert!(registration.poll(at(62)));
+}
+
+#[test]
+fn v16_accepted_takes_the_heartbeat_interval() {
+    let mut registration = v16_pending(30);
+    registration.poll(at(32));
+    v16::registration::on_boot_conf(
+        &mut registration,
+        &boot_conf(v16::enums::RegistrationStatus::Accepted, 300),
+        at(33),
+    );
+    assert!(registration.is_accepted());
+    assert_eq!(registration.heartbeat_interval(), Some(300));
+    assert_eq!(registration.next_poll(), None);
+    assert!(v16::registration::may_send(&registration, &heartbeat()));
+}
+
+#[test]
+fn failed_boot_is_retried() {
+    let config = RegistrationConfig { retry_interval: 20 };
+    let mut registration = Registration::new(config, at(0));
+    registration.poll(at(0));
+    registration.on_boot_failed(at(10));
+    assert_eq!(registration.next_poll(), Some(at(30)));
+    assert!(registration.poll(at(30)));
+}
+
+#[test]
+fn rejected_is_silent_until_the_interval() {
+    use v16::enums::{MessageTrigger, RegistrationStatus, TriggerMessageStatus};
+    use v16::registration::{on_boot_conf, on_trigger_message};
+
+    let mut registration = Registration::new(RegistrationConfig::default(), at(0));
+    registration.poll(at(0));
+    on_boot_conf(
+        &mut registration,
+        &boot_conf(RegistrationStatus::Rejected, 600),
+        at(31),
+    );
+    assert_eq!(registration.state(), RegistrationState::Rejected);
+    assert!(!registration.answers_csms());
+    assert_eq!(
+        on_trigger_message(&mut registration, &MessageTrigger::BootNotification, at(32)),
+        TriggerMessageStatus::Rejected
+    );
+    assert_eq!(
+        on_trigger_message(
+            &mut registration,
+            &MessageTrigger::StatusNotification,
+            at(32)
+        ),
+        TriggerMessageStatus::Rejected
+    );
+    assert!(!registration.may_send("StatusNotification"));
+    assert_eq!(registration.next_poll(), Some(at(631)));
+}
+
+fn boot_response(
+    status: &str,
+    interval: i32,
+) -> v21::messages::boot_notification::BootNotificationResponse {
+    serde_json::from_value(serde_json::json!({
+        "currentTime": "2025-03-14T12:00:00Z",
+        "interval": interval,
+        "status": status,
+    }))
+    .unwrap()
+}
+
+fn action(name: &str, json: serde_json::Value) -> v21::call::Action {
+    let call: v21::call::Call =
+        serde_json::from_value(serde_json::json!([2, "1", name, json])).unwrap();
+    call.payload
+}
+
+fn notify_report() -> v21::call::Action {
+    action(
+        "NotifyReport",
+        serde_json::json!({ "requestId": 1, "generatedAt": "2025-03-14T12:00:00Z", "seqNo": 0 }),
+    )
+}
+
+/// 2.1 registration that got a `Pending` boot response at 00:01.
+fn v21_pending() -> Registration {
+    let mut registration = Registration::new(RegistrationConfig::default(), at(0));
+    registration.poll(at(0));
+    v21::registration::on_boot_response(&mut registration, &boot_response("Pending", 10), at(1));
+    registration
+}
+
+#[test]
+fn v21_pending_reports_only_after_get_base_report() {
+    use v21::registration::{may_send, on_csms_call};
+
+    let mut registration = v21_pending();
+    assert!(!may_send(&registration, &notify_report()));
+    assert!(on_csms_call(
+        &mut registration,
+        &action(
+            "GetBaseReport",
+            serde_json::json!({ "requestId": 1, "reportBase": "FullInventory" })
+        )
+    ));
+    assert!(may_send(&registration, &notify_report()));
+}
+
+#[test]
+fn v21_pending_refuses_other_csms_calls() {
+    assert!(!v21::registration::on_csms_call(
+        &mut v21_pending(),
+        &action(
+            "RequestStopTransaction",
+            serde_json::json!({ "transactionId": "T1" })
+        )
+    ));
+}
+
+#[test]
+fn v21_custom_trigger_is_not_implemented() {
+    use v21::messages::trigger_message::{MessageTriggerEnumType, TriggerMessageStatusEnumType};
+
+    assert_eq!(
+        v21::registration::on_trigger_message(
+            &mut v21_pending(),
+            &MessageTriggerEnumType::CustomTrigger,
+            at(2)
+        ),
+        TriggerMessageStatusEnumType::NotImplemented
+    );
+}
+
+#[test]
+fn v21_boot_trigger_is_only_accepted_before_acceptance() {
+    use v21::messages::trigger_message::{MessageTriggerEnumType, TriggerMessageStatusEnumType};
+    use v21::registration::{on_boot_response, on_trigger_message};
+
+    let mut registration = v21_pending();
+    assert_eq!(
+        on_trigger_message(
+            &mut registration,
+            &MessageTriggerEnumType::BootNotification,
+            at(2)
+        ),
+        TriggerMessageStatusEnumType::Accepted
+    );
+    assert!(registration.poll(at(2)));
+
+    on_boot_response(&mut registration, &boot_response("Accepted", 120), at(3));
+    assert_eq!(registration.heartbeat_interval(), Some(120));
+    assert_eq!(
+        on_trigger_message(
+            &mut registration,
+            &MessageTriggerEnumType::BootNotification,
+            at(4)
+        ),
+        TriggerMessageStatusEnumType::Rejected
+    );
+    assert_eq!(registration.next_poll(), None);
+}
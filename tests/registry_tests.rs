@@ -0,0 +1,384 @@
+//! CSMS charger registry.
+
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::at;
+use ocpp_rs::registration::RegistrationState;
+use ocpp_rs::registry::{
+    ChargerRegistry, ConnectionState, ConnectorRef, Protocol, RegistryConfig, RegistryError,
+    RegistryEvent,
+};
+use ocpp_rs::v16;
+use ocpp_rs::v21;
+use ocpp_rs::v21::version::NegotiatedVersion;
+
+fn ingest_v16(registry: &mut ChargerRegistry, id: &str, frame: &str, second: i64) {
+    let message = v16::parse::deserialize_to_message(frame).unwrap();
+    registry.ingest_v16(id, message, at(second)).unwrap();
+}
+
+fn ingest_v21(registry: &mut ChargerRegistry, id: &str, frame: &str, second: i64) {
+    let message = v21::parse::deserialize_to_message(frame).unwrap();
+    registry.ingest_v21(id, message, at(second)).unwrap();
+}
+
+const V16_CONNECTOR: ConnectorRef = ConnectorRef {
+    evse_id: None,
+    connector_id: 1,
+};
+
+fn charger() -> String {
+    "CP-1".to_string()
+}
+
+/// Registry with `CP-1` connected over 1.6 and its `BootNotification` accepted.
+fn v16_booted() -> ChargerRegistry {
+    use v16::typed_call_result::TypedCallResult;
+
+    let mut registry = ChargerRegistry::default();
+    registry.connect(
+        "CP-1",
+        Protocol::from_subprotocol("ocpp1.6").unwrap(),
+        at(0),
+    );
+    ingest_v16(
+        &mut registry,
+        "CP-1",
+        r#"[2,"b1","BootNotification",{"chargePointVendor":"Acme","chargePointModel":"X1","chargeBoxSerialNumber":"SN-7"}]"#,
+        1,
+    );
+    let conf = v16::call_result::BootNotification {
+        current_time: v16::data_types::DateTimeWrapper::new(at(1)),
+        interval: 60,
+        status: v16::enums::RegistrationStatus::Accepted,
+    };
+    registry
+        .responded_v16(
+            "CP-1",
+            &TypedCallResult::BootNotification(v16::call_result::CallResult::new(
+                "b1".into(),
+                conf,
+            )),
+            at(1),
+        )
+        .unwrap();
+    registry
+}
+
+/// `StartTransaction` on connector 1 at second 4, confirmed with transaction id 42.
+fn v16_start(registry: &mut ChargerRegistry) {
+    use v16::typed_call_result::TypedCallResult;
+
+    ingest_v16(
+        registry,
+        "CP-1",
+        r#"[2,"t1","StartTransaction",{"connectorId":1,"idTag":"TAG","meterStart":0,"timestamp":"2025-03-14T12:00:04Z"}]"#,
+        4,
+    );
+    let conf = v16::call_result::StartTransaction {
+        transaction_id: 42,
+        id_tag_info: v16::data_types::IdTagInfo::default(),
+    };
+    registry
+        .responded_v16(
+            "CP-1",
+            &TypedCallResult::StartTransaction(v16::call_result::CallResult::new(
+                "t1".into(),
+                conf,
+            )),
+            at(4),
+        )
+        .unwrap();
+}
+
+/// Registry with `CS-1` connected over 2.1 and booted.
+fn v21_booted() -> ChargerRegistry {
+    let mut registry = ChargerRegistry::default();
+    registry.connect("CS-1", Protocol::Ocpp2(NegotiatedVersion::Ocpp21), at(0));
+    ingest_v21(
+        &mut registry,
+        "CS-1",
+        r#"[2,"b1","BootNotification",{"reason":"PowerUp","chargingStation":{"model":"M","vendorName":"V","firmwareVersion":"1.2"}}]"#,
+        1,
+    );
+    registry
+}
+
+const V21_STARTED: &str = r#"[2,"e1","TransactionEvent",{"eventType":"Started","timestamp":"2025-03-14T12:00:02Z","triggerReason":"Authorized","seqNo":0,"transactionInfo":{"transactionId":"T-1"},"evse":{"id":1,"connectorId":1},"idToken":{"idToken":"TOK","type":"ISO14443"}}]"#;
+
+#[test]
+fn v16_boot_notification_accepts_the_station() {
+    let mut registry = v16_booted();
+    let record = registry.charger("CP-1").unwrap();
+    let boot = record.boot.as_ref().unwrap();
+    assert_eq!(boot.vendor, "Acme");
+    assert_eq!(boot.serial_number.as_deref(), Some("SN-7"));
+    assert_eq!(record.registration, RegistrationState::Accepted);
+    assert_eq!(record.heartbeat_interval, Some(60));
+    assert_eq!(
+        registry.take_events(),
+        [
+            RegistryEvent::Connected { charger: charger() },
+            RegistryEvent::Booted { charger: charger() },
+            RegistryEvent::RegistrationChanged {
+                charger: charger(),
+                state: RegistrationState::Accepted,
+            },
+        ]
+    );
+    assert!(registry.take_events().is_empty());
+}
+
+#[test]
+fn v16_repeated_status_is_one_change() {
+    let mut registry = v16_booted();
+    registry.take_events();
+    let status = r#"[2,"s1","StatusNotification",{"connectorId":1,"errorCode":"NoError","status":"Preparing"}]"#;
+    ingest_v16(&mut registry, "CP-1", status, 2);
+    ingest_v16(&mut registry, "CP-1", status, 3);
+
+    let record = registry.charger("CP-1").unwrap();
+    assert_eq!(record.connectors[&V16_CONNECTOR].status, "Preparing");
+    assert_eq!(
+        registry.take_events(),
+        [RegistryEvent::ConnectorStatusChanged {
+            charger: charger(),
+            connector: V16_CONNECTOR,
+            status: "Preparing".into(),
+        }]
+    );
+}
+
+#[test]
+fn v16_transaction_starts_with_the_confirmation() {
+    let mut registry = v16_booted();
+    registry.take_events();
+    v16_start(&mut registry);
+
+    let tx = &registry.charger("CP-1").unwrap().transactions["42"];
+    assert_eq!(tx.connector, Some(V16_CONNECTOR));
+    assert_eq!(tx.id_token.as_deref(), Some("TAG"));
+    assert_eq!(tx.started_at, at(4));
+    assert_eq!(
+        registry.take_events(),
+        [RegistryEvent::TransactionStarted {
+            charger: charger(),
+            transaction_id: "42".into(),
+        }]
+    );
+}
+
+#[test]
+fn v16_stop_transaction_ends_it() {
+    let mut registry = v16_booted();
+    v16_start(&mut registry);
+    registry.take_events();
+    ingest_v16(
+        &mut registry,
+        "CP-1",
+        r#"[2,"t2","StopTransaction",{"meterStop":10,"timestamp":"2025-03-14T12:00:09Z","transactionId":42}]"#,
+        9,
+    );
+    assert!(registry.charger("CP-1").unwrap().transactions.is_empty());
+    assert_eq!(
+        registry.take_events(),
+        [RegistryEvent::TransactionEnded {
+            charger: charger(),
+            transaction_id: "42".into(),
+        }]
+    );
+}
+
+#[test]
+fn calls_to_the_wrong_protocol_or_an_unknown_station_fail() {
+    let mut registry = ChargerRegistry::default();
+    registry.connect("CS-1", Protocol::Ocpp2(NegotiatedVersion::Ocpp21), at(0));
+    assert!(matches!(
+        registry.send_v16(
+            "CS-1",
+            v16::call::Call::new(
+                "x".into(),
+                v16::call::Action::Heartbeat(v16::call::Heartbeat {})
+            )
+        ),
+        Err(RegistryError::ProtocolMismatch(_))
+    ));
+    assert!(matches!(
+        registry.ingest_v21(
+            "nobody",
+            v21::parse::deserialize_to_message(r#"[2,"h","Heartbeat",{}]"#).unwrap(),
+            at(0)
+        ),
+        Err(RegistryError::UnknownCharger(_))
+    ));
+}
+
+#[test]
+fn v21_boot_notification_is_recorded() {
+    let registry = v21_booted();
+    let boot = registry.charger("CS-1").unwrap().boot.clone().unwrap();
+    assert_eq!(boot.reason.as_deref(), Some("PowerUp"));
+    assert_eq!(boot.firmware_version.as_deref(), Some("1.2"));
+}
+
+#[test]
+fn v21_transaction_events_update_the_transaction() {
+    let mut registry = v21_booted();
+    ingest_v21(&mut registry, "CS-1", V21_STARTED, 2);
+    let frame = r#"[2,"e2","TransactionEvent",{"eventType":"Updated","timestamp":"2025-03-14T12:00:03Z","triggerReason":"ChargingStateChanged","seqNo":1,"transactionInfo":{"transactionId":"T-1","chargingState":"Charging"}}]"#;
+    ingest_v21(&mut registry, "CS-1", frame, 3);
+
+    let tx = registry.charger("CS-1").unwrap().transactions["T-1"].clone();
+    assert_eq!(tx.id_token.as_deref(), Some("TOK"));
+    assert_eq!(tx.charging_state.as_deref(), Some("Charging"));
+    assert_eq!(
+        tx.connector,
+        Some(ConnectorRef {
+            evse_id: Some(1),
+            connector_id: 1
+        })
+    );
+
+    let frame = r#"[2,"e3","TransactionEvent",{"eventType":"Ended","timestamp":"2025-03-14T12:00:05Z","triggerReason":"EVDeparted","seqNo":2,"transactionInfo":{"transactionId":"T-1"}}]"#;
+    ingest_v21(&mut registry, "CS-1", frame, 5);
+    assert!(registry.charger("CS-1").unwrap().transactions.is_empty());
+}
+
+#[test]
+fn v21_call_error_settles_a_pending_call() {
+    use v21::messages::heartbeat::HeartbeatRequest;
+
+    let mut registry = v21_booted();
+    let call = v21::call::Call::new(
+        "c1".into(),
+        v21::call::Action::Heartbeat(HeartbeatRequest { custom_data: None }),
+    );
+    registry.send_v21("CS-1", call).unwrap();
+    assert_eq!(registry.charger("CS-1").unwrap().pending_calls(), 1);
+    ingest_v21(&mut registry, "CS-1", r#"[4,"c1","NotSupported","",{}]"#, 4);
+    assert_eq!(registry.charger("CS-1").unwrap().pending_calls(), 0);
+}
+
+fn stale_after_20s() -> ChargerRegistry {
+    let mut registry = ChargerRegistry::new(RegistryConfig {
+        default_heartbeat_interval: 10,
+        missed_heartbeats: 2,
+    });
+    registry.connect("CP-1", Protocol::Ocpp16, at(0));
+    registry.take_events();
+    registry
+}
+
+#[test]
+fn silent_connections_go_stale() {
+    let mut registry = stale_after_20s();
+    assert_eq!(registry.next_poll(), Some(at(20)));
+
+    ingest_v16(&mut registry, "CP-1", r#"[2,"h1","Heartbeat",{}]"#, 5);
+    assert_eq!(
+        registry.charger("CP-1").unwrap().last_heartbeat,
+        Some(at(5))
+    );
+    registry.poll(at(24));
+    assert_eq!(
+        registry.charger("CP-1").unwrap().connection,
+        ConnectionState::Connected
+    );
+    registry.poll(at(25));
+    assert_eq!(
+        registry.charger("CP-1").unwrap().connection,
+        ConnectionState::Stale
+    );
+    assert_eq!(registry.next_poll(), None);
+    assert_eq!(
+        registry.take_events(),
+        [RegistryEvent::Stale { charger: charger() }]
+    );
+}
+
+#[test]
+fn stale_connections_revive_on_any_message() {
+    let mut registry = stale_after_20s();
+    registry.poll(at(20));
+    ingest_v16(&mut registry, "CP-1", r#"[2,"h2","Heartbeat",{}]"#, 30);
+    assert_eq!(
+        registry.charger("CP-1").unwrap().connection,
+        ConnectionState::Connected
+    );
+    assert_eq!(registry.next_poll(), Some(at(50)));
+}
+
+#[test]
+fn disconnect_is_reported_once() {
+    let mut registry = stale_after_20s();
+    assert!(registry.disconnect("CP-1"));
+    assert!(!registry.disconnect("CP-1"));
+    assert!(!registry.charger("CP-1").unwrap().is_connected());
+    assert_eq!(
+        registry.take_events(),
+        [RegistryEvent::Disconnected { charger: charger() }]
+    );
+}
+
+#[test]
+fn huge_heartbeat_settings_never_go_stale() {
+    let mut registry = ChargerRegistry::new(RegistryConfig {
+        default_heartbeat_interval: u32::MAX,
+        missed_heartbeats: u32::MAX,
+    });
+    registry.connect("CP-1", Protocol::Ocpp16, at(0));
+    assert_eq!(registry.next_poll(), Some(DateTime::<Utc>::MAX_UTC));
+    registry.poll(at(i64::from(u32::MAX)));
+    assert_eq!(
+        registry.charger("CP-1").unwrap().connection,
+        ConnectionState::Connected
+    );
+}
+
+#[test]
+fn disconnected_stations_are_refused() {
+    let mut registry = ChargerRegistry::default();
+    registry.connect("CP-1", Protocol::Ocpp16, at(0));
+    registry.disconnect("CP-1");
+
+    let heartbeat = || v16::parse::deserialize_to_message(r#"[2,"h1","Heartbeat",{}]"#).unwrap();
+    assert!(matches!(
+        registry.ingest_v16("CP-1", heartbeat(), at(1)),
+        Err(RegistryError::Disconnected(_))
+    ));
+    let call = || {
+        v16::call::Call::new(
+            "c1".into(),
+            v16::call::Action::Heartbeat(v16::call::Heartbeat {}),
+        )
+    };
+    assert!(matches!(
+        registry.send_v16("CP-1", call()),
+        Err(RegistryError::Disconnected(_))
+    ));
+    let record = registry.charger("CP-1").unwrap();
+    assert_eq!(record.connection, ConnectionState::Disconnected);
+    assert_eq!(record.last_heartbeat, None);
+    assert_eq!(record.pending_calls(), 0);
+}
+
+#[test]
+fn reconnected_stations_are_served_again() {
+    let mut registry = ChargerRegistry::default();
+    registry.connect("CP-1", Protocol::Ocpp16, at(0));
+    registry.disconnect("CP-1");
+    let call = || {
+        v16::call::Call::new(
+            "c1".into(),
+            v16::call::Action::Heartbeat(v16::call::Heartbeat {}),
+        )
+    };
+
+    registry.connect("CP-1", Protocol::Ocpp16, at(2));
+    ingest_v16(&mut registry, "CP-1", r#"[2,"h2","Heartbeat",{}]"#, 3);
+    registry.send_v16("CP-1", call()).unwrap();
+    let record = registry.charger("CP-1").unwrap();
+    assert_eq!(record.last_heartbeat, Some(at(3)));
+    assert_eq!(record.pending_calls(), 1);
+}
@@ -0,0 +1,217 @@
+//! OCMF decoding and signature verification of signed meter values.
+
+use chrono::{TimeZone, Utc};
+use ocpp_rs::quantity::SiUnit;
+use ocpp_rs::signed_meter::{
+    KeyEncoding, Ocmf, Pagination, SignatureVerifier, SignedMeterError, decode_public_key,
+    decode_public_key_as,
+};
+use ocpp_rs::v16::data_types::SampledValue;
+use ocpp_rs::v21::datatypes::SignedMeterValueType;
+
+/// Signed with a fixed P-256 key; `TT` contains the `|` separator on purpose.
+const OCMF: &str = r#"OCMF|{"FV":"1.0","GI":"ABL SBC-301","GS":"808829900001","GV":"1.4p3","PG":"T12","MV":"Phoenix Contact","MM":"EEM-350-D-MCB","MS":"BQ27400330016","MF":"1.0","IS":true,"IL":"VERIFIED","IF":["RFID_PLAIN","OCPP_RS_TLS"],"IT":"ISO14443","ID":"1F2D3A4F5506C7","TT":"0,25 EUR/kWh | 0,05 EUR/min","RD":[{"TM":"2018-07-24T13:22:04,000+0200 S","TX":"B","RV":2935.6,"RI":"01-00:B2.08.00*FF","RU":"kWh","RT":"AC","EF":"","ST":"G"},{"TM":"2018-07-24T13:26:04,000+0200 S","TX":"E","RV":2938.1,"RI":"01-00:B2.08.00*FF","RU":"kWh","RT":"AC","EF":"","ST":"G"}]}|{"SA":"ECDSA-secp256r1-SHA256","SD":"3045022100F0C1B05B522AEB11630C5EB81FB8D77197658236F2B8A1231DB2057D1FE792F202205E15FAB91B7A9A8D16600DF7C015CB053BF1BF0CCB1086A95E45FD792DAABAA7"}"#;
+
+const PUBLIC_KEY: &str = "3059301306072A8648CE3D020106082A8648CE3D03010703420004F1E8E4E109F94E55E4D190D809D6CBA6B71ACA0CEE0E2E78EA768FB465425E9B4172AB9CE7872935E6429D4D92E0D0BEDCB8E40CB77179AA0DD882F60865326C";
+
+/// Accepts whatever it is handed, recording it.
+struct Recording(core::cell::RefCell<Vec<(String, usize, Vec<u8>)>>);
+
+impl SignatureVerifier for Recording {
+    fn verify(
+        &self,
+        algorithm: &str,
+        public_key: &[u8],
+        _message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), SignedMeterError> {
+        self.0
+            .borrow_mut()
+            .push((algorithm.into(), public_key.len(), signature.to_vec()));
+        Ok(())
+    }
+}
+
+/// Fiscal page 3 with a three-byte signature, base64-wrapped.
+const WRAPPED: &str =
+    "T0NNRnx7IkZWIjoiMS4wIiwiUEciOiJGMyIsIlJEIjpbXX18eyJTRSI6ImJhc2U2NCIsIlNEIjoiQUFFQyJ9";
+
+fn signed_value(data: &str, encoding_method: &str) -> SignedMeterValueType {
+    SignedMeterValueType {
+        signed_meter_data: data.into(),
+        signing_method: None,
+        encoding_method: encoding_method.into(),
+        public_key: None,
+        custom_data: None,
+    }
+}
+
+#[test]
+fn decodes_ocmf_payload() {
+    let payload = Ocmf::decode(OCMF).unwrap().payload;
+    assert_eq!(payload.pagination(), Some(Pagination::Transaction(12)));
+    assert_eq!(payload.meter_serial.as_deref(), Some("BQ27400330016"));
+    assert_eq!(payload.identified, Some(true));
+    assert_eq!(payload.identification_flags, ["RFID_PLAIN", "OCPP_RS_TLS"]);
+    assert_eq!(
+        payload.identification_data.as_deref(),
+        Some("1F2D3A4F5506C7")
+    );
+    assert_eq!(
+        payload.tariff_text.as_deref(),
+        Some("0,25 EUR/kWh | 0,05 EUR/min")
+    );
+}
+
+#[test]
+fn decodes_ocmf_readings() {
+    let payload = Ocmf::decode(OCMF).unwrap().payload;
+    let [begin, end] = payload.readings.as_slice() else {
+        panic!("two readings expected");
+    };
+    assert_eq!(
+        begin.timestamp(),
+        Some(Utc.with_ymd_and_hms(2018, 7, 24, 11, 22, 4).unwrap())
+    );
+    assert_eq!(end.transaction.as_deref(), Some("E"));
+    let energy = end.quantity().unwrap();
+    assert_eq!(energy.si_unit, SiUnit::Wh);
+    assert!((energy.value - 2_938_100.0).abs() < 1e-6);
+    assert!(begin.is_good() && end.is_good());
+}
+
+#[test]
+fn signature_covers_the_raw_payload() {
+    let ocmf = Ocmf::decode(OCMF).unwrap();
+    assert!(ocmf.signed_message().starts_with(br#"{"FV":"1.0""#));
+    assert!(ocmf.signed_message().ends_with(b"}]}"));
+    assert_eq!(ocmf.signature.algorithm(), "ECDSA-secp256r1-SHA256");
+    assert_eq!(ocmf.signature.bytes().unwrap().len(), 71);
+}
+
+#[test]
+fn verifier_gets_the_decoded_key_and_signature() {
+    let recording = Recording(core::cell::RefCell::default());
+    Ocmf::decode(OCMF)
+        .unwrap()
+        .verify(&decode_public_key(PUBLIC_KEY).unwrap(), &recording)
+        .unwrap();
+    let calls = recording.0.into_inner();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].0, "ECDSA-secp256r1-SHA256");
+    assert_eq!(calls[0].1, 91);
+    assert_eq!(calls[0].2[..2], [0x30, 0x45]);
+}
+
+#[test]
+fn base64_wrapped_ocmf_decodes() {
+    let ocmf = Ocmf::decode(WRAPPED).unwrap();
+    assert_eq!(ocmf.payload.pagination(), Some(Pagination::Fiscal(3)));
+    assert_eq!(ocmf.signature.bytes().unwrap(), [0, 1, 2]);
+}
+
+#[test]
+fn v16_signed_sampled_value_decodes() {
+    let sample: SampledValue = serde_json::from_value(serde_json::json!({
+        "value": OCMF,
+        "format": "SignedData",
+    }))
+    .unwrap();
+    assert!(sample.is_signed());
+    assert_eq!(sample.ocmf().unwrap().payload.readings.len(), 2);
+}
+
+#[test]
+fn v21_signed_meter_value_decodes() {
+    let signed = signed_value(WRAPPED, "OCMF");
+    assert!(signed.ocmf().is_ok());
+    assert_eq!(
+        signed.public_key_bytes(),
+        Err(SignedMeterError::MissingPublicKey)
+    );
+}
+
+#[test]
+fn other_encodings_are_unsupported() {
+    assert_eq!(
+        signed_value(WRAPPED, "EDL").ocmf(),
+        Err(SignedMeterError::UnsupportedEncoding("EDL".into()))
+    );
+}
+
+#[test]
+fn malformed_data_is_rejected() {
+    assert_eq!(Ocmf::decode("hello"), Err(SignedMeterError::NotOcmf));
+    assert!(matches!(
+        Ocmf::decode("OCMF|{|{}"),
+        Err(SignedMeterError::Json(_))
+    ));
+}
+
+#[test]
+fn public_key_encodings() {
+    let hex = decode_public_key(PUBLIC_KEY).unwrap();
+    assert_eq!(hex.len(), 91);
+    assert_eq!(decode_public_key_as(PUBLIC_KEY, KeyEncoding::Hex), Ok(hex));
+    // Base64 of a SEC1 point that consists of hex digits only.
+    assert_eq!(decode_public_key("BAAA"), Ok(vec![4, 0, 0]));
+    assert_eq!(
+        decode_public_key_as("BAAA", KeyEncoding::Hex),
+        Ok(vec![0xBA, 0xAA])
+    );
+    assert_eq!(
+        decode_public_key_as("MFkw", KeyEncoding::Hex),
+        Err(SignedMeterError::Encoding)
+    );
+    assert_eq!(
+        decode_public_key_as("MFkw", KeyEncoding::Base64),
+        Ok(vec![0x30, 0x59, 0x30])
+    );
+}
+
+#[cfg(feature = "signed_meter_p256")]
+fn p256_signed() -> SignedMeterValueType {
+    SignedMeterValueType {
+        signing_method: Some("ECDSA-secp256r1-SHA256".into()),
+        public_key: Some(PUBLIC_KEY.into()),
+        ..signed_value(OCMF, "OCMF")
+    }
+}
+
+#[cfg(feature = "signed_meter_p256")]
+#[test]
+fn p256_verifies_fixture() {
+    use ocpp_rs::signed_meter::P256Verifier;
+
+    let ocmf = p256_signed().verify(&P256Verifier).unwrap();
+    assert_eq!(ocmf.payload.readings.len(), 2);
+}
+
+#[cfg(feature = "signed_meter_p256")]
+#[test]
+fn p256_rejects_tampering() {
+    use ocpp_rs::signed_meter::P256Verifier;
+
+    let tampered = SignedMeterValueType {
+        signed_meter_data: OCMF.replace("2938.1", "2948.1"),
+        ..p256_signed()
+    };
+    assert_eq!(
+        tampered.verify(&P256Verifier),
+        Err(SignedMeterError::BadSignature)
+    );
+}
+
+#[cfg(feature = "signed_meter_p256")]
+#[test]
+fn p256_refuses_other_algorithms() {
+    use ocpp_rs::signed_meter::P256Verifier;
+
+    let other_algorithm = Ocmf::decode(&OCMF.replace("secp256r1", "secp384r1")).unwrap();
+    assert_eq!(
+        other_algorithm.verify(&decode_public_key(PUBLIC_KEY).unwrap(), &P256Verifier),
+        Err(SignedMeterError::UnsupportedAlgorithm(
+            "ECDSA-secp384r1-SHA256".into()
+        ))
+    );
+}
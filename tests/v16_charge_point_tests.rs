@@ -0,0 +1,380 @@
+//! 1.6 charge point core.
+
+mod common;
+
+use common::at;
+use ocpp_rs::v16::call::{
+    Action, ChangeAvailability, RemoteStartTransaction, RemoteStopTransaction,
+};
+use ocpp_rs::v16::call_result;
+use ocpp_rs::v16::charge_point::{ChargePoint, ChargePointConfig, EnergyFlow, transition_allowed};
+use ocpp_rs::v16::charging_profiles::ConnectorState;
+use ocpp_rs::v16::data_types::IdTagInfo;
+use ocpp_rs::v16::enums::{
+    AuthorizationStatus, AvailabilityStatus, AvailabilityType, ChargePointErrorCode,
+    ChargePointStatus, Reason, RemoteStartStopStatus,
+};
+
+/// `(connector, status)` of the queued `StatusNotification`s.
+fn statuses(actions: &[Action]) -> Vec<(u32, ChargePointStatus)> {
+    actions
+        .iter()
+        .filter_map(|a| match a {
+            Action::StatusNotification(n) => Some((n.connector_id, n.status.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn conf(transaction_id: i32, status: AuthorizationStatus) -> call_result::StartTransaction {
+    call_result::StartTransaction {
+        transaction_id,
+        id_tag_info: IdTagInfo {
+            expiry_date: None,
+            parent_id_tag: None,
+            status,
+        },
+    }
+}
+
+/// Charge point whose connector 1 runs transaction 42 (meter at 1000 Wh), outputs drained.
+fn charging(connectors: u32) -> ChargePoint {
+    let mut cp = ChargePoint::new(connectors, ChargePointConfig::default(), at(0));
+    cp.set_meter(1, 1_000);
+    cp.plug_in(1, at(1));
+    cp.present_id_tag(1, "TAG", at(2));
+    cp.on_start_transaction_conf(1, &conf(42, AuthorizationStatus::Accepted), at(3));
+    cp.take_requests();
+    cp
+}
+
+fn inoperative(connector_id: u32) -> ChangeAvailability {
+    ChangeAvailability {
+        connector_id,
+        availability_type: AvailabilityType::Inoperative,
+    }
+}
+
+#[test]
+fn boot_reports_every_connector_available() {
+    let mut cp = ChargePoint::new(2, ChargePointConfig::default(), at(0));
+    assert_eq!(
+        statuses(&cp.take_requests()),
+        vec![
+            (0, ChargePointStatus::Available),
+            (1, ChargePointStatus::Available),
+            (2, ChargePointStatus::Available),
+        ]
+    );
+}
+
+#[test]
+fn authorized_tag_on_a_plugged_connector_starts_a_transaction() {
+    let mut cp = ChargePoint::new(1, ChargePointConfig::default(), at(0));
+    cp.set_meter(1, 1_000);
+    cp.plug_in(1, at(1));
+    cp.take_requests();
+    assert!(cp.present_id_tag(1, "TAG", at(2)));
+    let actions = cp.take_requests();
+    let Action::StartTransaction(start) = &actions[0] else {
+        panic!("{actions:?}")
+    };
+    assert_eq!((start.connector_id, start.meter_start), (1, 1_000));
+    assert_eq!(start.id_tag, "TAG");
+
+    cp.on_start_transaction_conf(1, &conf(42, AuthorizationStatus::Accepted), at(3));
+    assert_eq!(cp.transaction_id(1), Some(42));
+    assert_eq!(cp.transaction_connector(42), Some(1));
+}
+
+#[test]
+fn meter_values_carry_the_transaction_id() {
+    let mut cp = charging(1);
+    cp.meter_values(1, Vec::new());
+    let actions = cp.take_requests();
+    let Action::MeterValues(values) = &actions[0] else {
+        panic!("{actions:?}")
+    };
+    assert_eq!(values.transaction_id, Some(42));
+}
+
+#[test]
+fn only_the_starting_tag_stops_locally() {
+    let mut cp = charging(1);
+    cp.set_meter(1, 8_500);
+    assert!(!cp.present_id_tag(1, "OTHER", at(5)));
+    assert!(cp.present_id_tag(1, "TAG", at(6)));
+    let actions = cp.take_requests();
+    let Action::StopTransaction(stop) = &actions[0] else {
+        panic!("{actions:?}")
+    };
+    assert_eq!(stop.transaction_id, 42);
+    assert_eq!(stop.meter_stop, 8_500);
+    assert_eq!(stop.reason, Some(Reason::Local));
+    assert_eq!(stop.id_tag.as_deref(), Some("TAG"));
+}
+
+#[test]
+fn session_walks_through_allowed_statuses() {
+    let mut cp = ChargePoint::new(1, ChargePointConfig::default(), at(0));
+    cp.take_requests();
+    let mut seen = vec![ChargePointStatus::Available];
+    let mut record = |cp: &mut ChargePoint| {
+        seen.extend(statuses(&cp.take_requests()).into_iter().map(|(_, s)| s));
+    };
+
+    cp.plug_in(1, at(1));
+    record(&mut cp);
+    cp.present_id_tag(1, "TAG", at(2));
+    record(&mut cp);
+    cp.on_start_transaction_conf(1, &conf(42, AuthorizationStatus::Accepted), at(3));
+    cp.set_energy_flow(1, EnergyFlow::SuspendedEv, at(4));
+    record(&mut cp);
+    cp.present_id_tag(1, "TAG", at(6));
+    record(&mut cp);
+    cp.unplug(1, at(7));
+    record(&mut cp);
+    assert_eq!(
+        seen,
+        vec![
+            ChargePointStatus::Available,
+            ChargePointStatus::Preparing,
+            ChargePointStatus::Charging,
+            ChargePointStatus::SuspendedEV,
+            ChargePointStatus::Finishing,
+            ChargePointStatus::Available,
+        ]
+    );
+    assert!(seen.windows(2).all(|w| transition_allowed(&w[0], &w[1])));
+}
+
+#[test]
+fn stop_waits_for_the_start_conf() {
+    let mut cp = ChargePoint::new(1, ChargePointConfig::default(), at(0));
+    cp.present_id_tag(1, "TAG", at(1));
+    cp.plug_in(1, at(2));
+    cp.unplug(1, at(3));
+    cp.take_requests();
+    assert_eq!(
+        cp.connector(1).unwrap().status(),
+        &ChargePointStatus::Available
+    );
+
+    cp.on_start_transaction_conf(1, &conf(7, AuthorizationStatus::Accepted), at(4));
+    let actions = cp.take_requests();
+    let Action::StopTransaction(stop) = &actions[0] else {
+        panic!("{actions:?}")
+    };
+    assert_eq!(stop.transaction_id, 7);
+    assert_eq!(stop.reason, Some(Reason::EVDisconnected));
+}
+
+#[test]
+fn rejected_tag_is_deauthorized() {
+    let mut cp = ChargePoint::new(1, ChargePointConfig::default(), at(0));
+    cp.plug_in(1, at(1));
+    cp.present_id_tag(1, "BAD", at(2));
+    cp.on_start_transaction_conf(1, &conf(8, AuthorizationStatus::Invalid), at(3));
+    let actions = cp.take_requests();
+    assert!(actions.iter().any(|a| matches!(
+        a,
+        Action::StopTransaction(s) if s.reason == Some(Reason::DeAuthorized)
+    )));
+}
+
+#[test]
+fn rejected_tag_suspends_without_stop_transaction_on_invalid_id() {
+    let config = ChargePointConfig {
+        stop_transaction_on_invalid_id: false,
+        ..ChargePointConfig::default()
+    };
+    let mut cp = ChargePoint::new(1, config, at(0));
+    cp.plug_in(1, at(1));
+    cp.present_id_tag(1, "BAD", at(2));
+    cp.on_start_transaction_conf(1, &conf(9, AuthorizationStatus::Blocked), at(3));
+    assert_eq!(
+        cp.connector(1).unwrap().status(),
+        &ChargePointStatus::SuspendedEVSE
+    );
+}
+
+/// Single connector with a 10 s `MinimumStatusDuration` and a 30 s `ConnectionTimeOut`.
+fn debounced() -> ChargePoint {
+    let config = ChargePointConfig {
+        minimum_status_duration: 10,
+        connection_timeout: 30,
+        ..ChargePointConfig::default()
+    };
+    let mut cp = ChargePoint::new(1, config, at(0));
+    cp.take_requests();
+    cp
+}
+
+#[test]
+fn short_status_is_never_reported() {
+    let mut cp = debounced();
+    cp.plug_in(1, at(1));
+    cp.unplug(1, at(4));
+    cp.poll(at(20));
+    assert!(cp.take_requests().is_empty());
+}
+
+#[test]
+fn status_is_reported_after_the_minimum_duration() {
+    let mut cp = debounced();
+    cp.plug_in(1, at(30));
+    assert_eq!(cp.next_poll(), Some(at(40)));
+    cp.poll(at(39));
+    assert!(cp.take_requests().is_empty());
+    cp.poll(at(40));
+    assert_eq!(
+        statuses(&cp.take_requests()),
+        vec![(1, ChargePointStatus::Preparing)]
+    );
+}
+
+#[test]
+fn authorized_tag_without_cable_expires() {
+    let mut cp = debounced();
+    cp.present_id_tag(1, "TAG", at(100));
+    assert_eq!(cp.next_poll(), Some(at(110)));
+    cp.poll(at(110));
+    assert_eq!(cp.next_poll(), Some(at(130)));
+    cp.poll(at(130));
+    assert_eq!(
+        cp.connector(1).unwrap().status(),
+        &ChargePointStatus::Available
+    );
+    assert_eq!(
+        statuses(&cp.take_requests()),
+        vec![(1, ChargePointStatus::Preparing)]
+    );
+}
+
+#[test]
+fn change_availability_is_scheduled_during_a_transaction() {
+    let mut cp = charging(2);
+    assert_eq!(
+        cp.change_availability(&inoperative(2), at(3)).status,
+        AvailabilityStatus::Accepted
+    );
+    assert_eq!(
+        cp.change_availability(&inoperative(1), at(3)).status,
+        AvailabilityStatus::Scheduled
+    );
+    assert_eq!(
+        cp.change_availability(&inoperative(3), at(3)).status,
+        AvailabilityStatus::Rejected
+    );
+    assert_eq!(
+        statuses(&cp.take_requests()),
+        vec![(2, ChargePointStatus::Unavailable)]
+    );
+    assert!(!cp.present_id_tag(2, "TAG2", at(4)));
+}
+
+#[test]
+fn scheduled_availability_applies_after_the_transaction() {
+    let mut cp = charging(2);
+    cp.change_availability(&inoperative(1), at(3));
+    cp.stop_transaction(1, Reason::EmergencyStop, at(5));
+    let actions = cp.take_requests();
+    assert!(
+        matches!(&actions[0], Action::StopTransaction(s) if s.reason == Some(Reason::EmergencyStop))
+    );
+    assert_eq!(
+        statuses(&actions),
+        vec![(1, ChargePointStatus::Unavailable)]
+    );
+    assert_eq!(cp.connector(1).unwrap().scheduled_availability(), None);
+}
+
+#[test]
+fn disallowed_transition_is_reported_via_available() {
+    let mut cp = ChargePoint::new(1, ChargePointConfig::default(), at(0));
+    cp.take_requests();
+    cp.present_id_tag(1, "TAG", at(1));
+    assert_eq!(
+        cp.change_availability(&inoperative(1), at(2)).status,
+        AvailabilityStatus::Scheduled
+    );
+    // The tag times out: Preparing may not go to Unavailable directly.
+    cp.poll(at(61));
+    let seen: Vec<_> = statuses(&cp.take_requests())
+        .into_iter()
+        .map(|(_, s)| s)
+        .collect();
+    assert_eq!(
+        seen,
+        vec![
+            ChargePointStatus::Preparing,
+            ChargePointStatus::Available,
+            ChargePointStatus::Unavailable,
+        ]
+    );
+    assert!(seen.windows(2).all(|w| transition_allowed(&w[0], &w[1])));
+}
+
+#[test]
+fn faults_are_reported_and_cleared() {
+    let mut cp = ChargePoint::new(1, ChargePointConfig::default(), at(0));
+    cp.take_requests();
+    cp.fault(1, ChargePointErrorCode::GroundFailure, None, at(1));
+    let actions = cp.take_requests();
+    let Action::StatusNotification(notification) = &actions[0] else {
+        panic!("{actions:?}")
+    };
+    assert_eq!(notification.status, ChargePointStatus::Faulted);
+    assert_eq!(notification.error_code, ChargePointErrorCode::GroundFailure);
+
+    cp.clear_fault(1, at(6));
+    assert_eq!(
+        cp.connector(1).unwrap().status(),
+        &ChargePointStatus::Available
+    );
+}
+
+#[test]
+fn remote_start_without_connector_picks_the_first_available() {
+    let mut cp = ChargePoint::new(2, ChargePointConfig::default(), at(0));
+    cp.fault(1, ChargePointErrorCode::GroundFailure, None, at(1));
+    let response = cp.remote_start_transaction(
+        &RemoteStartTransaction {
+            id_tag: "APP".into(),
+            connector_id: None,
+            charging_profile: None,
+        },
+        at(2),
+    );
+    assert_eq!(response.status, RemoteStartStopStatus::Accepted);
+    assert_eq!(
+        cp.connector(2).unwrap().status(),
+        &ChargePointStatus::Preparing
+    );
+}
+
+#[test]
+fn remote_stop_matches_the_transaction_id() {
+    let mut cp = charging(1);
+    let stop = |transaction_id| RemoteStopTransaction { transaction_id };
+    assert_eq!(
+        cp.remote_stop_transaction(&stop(6), at(5)).status,
+        RemoteStartStopStatus::Rejected
+    );
+    assert!(cp.take_requests().is_empty());
+    assert_eq!(
+        cp.remote_stop_transaction(&stop(42), at(5)).status,
+        RemoteStartStopStatus::Accepted
+    );
+    assert!(matches!(
+        &cp.take_requests()[0],
+        Action::StopTransaction(s) if s.reason == Some(Reason::Remote)
+    ));
+}
+
+#[test]
+fn connector_exists_checks_the_range() {
+    let cp = ChargePoint::new(2, ChargePointConfig::default(), at(0));
+    assert!(cp.connector_exists(2));
+    assert!(!cp.connector_exists(3));
+}
@@ -0,0 +1,226 @@
+//! 1.6 charging profile store.
+
+use ocpp_rs::v16::call::{ClearChargingProfile, SetChargingProfile};
+use ocpp_rs::v16::charging_profiles::{ChargingProfileStore, ConnectorState, ProfileLimits};
+use ocpp_rs::v16::data_types::ChargingProfile;
+use ocpp_rs::v16::enums::{
+    ChargingProfilePurposeType, ChargingProfileStatus, ClearChargingProfileStatus,
+};
+
+struct ChargePoint {
+    transaction: Option<i32>,
+}
+
+impl ConnectorState for ChargePoint {
+    fn connector_exists(&self, connector_id: i32) -> bool {
+        (1..=2).contains(&connector_id)
+    }
+    fn transaction_id(&self, connector_id: i32) -> Option<i32> {
+        if connector_id == 1 {
+            self.transaction
+        } else {
+            None
+        }
+    }
+}
+
+fn profile(id: i32, stack_level: u32, purpose: &str) -> ChargingProfile {
+    serde_json::from_value(serde_json::json!({
+        "chargingProfileId": id,
+        "stackLevel": stack_level,
+        "chargingProfilePurpose": purpose,
+        "chargingProfileKind": "Relative",
+        "chargingSchedule": {
+            "chargingRateUnit": "A",
+            "chargingSchedulePeriod": [{ "startPeriod": 0, "limit": 16.0 }],
+        },
+    }))
+    .unwrap()
+}
+
+fn set(
+    store: &mut ChargingProfileStore,
+    connector_id: u32,
+    profile: ChargingProfile,
+    cp: &ChargePoint,
+) -> ChargingProfileStatus {
+    store
+        .set_charging_profile(
+            &SetChargingProfile {
+                connector_id,
+                cs_charging_profiles: profile,
+            },
+            cp,
+        )
+        .status
+}
+
+fn clear(
+    store: &mut ChargingProfileStore,
+    request: ClearChargingProfile,
+) -> ClearChargingProfileStatus {
+    store.clear_charging_profile(&request).status
+}
+
+const IDLE: ChargePoint = ChargePoint { transaction: None };
+
+#[test]
+fn same_stack_level_and_purpose_replaces() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    assert_eq!(
+        set(&mut store, 1, profile(1, 0, "TxDefaultProfile"), &IDLE),
+        ChargingProfileStatus::Accepted
+    );
+    assert_eq!(
+        set(&mut store, 1, profile(2, 0, "TxDefaultProfile"), &IDLE),
+        ChargingProfileStatus::Accepted
+    );
+    assert_eq!(store.len(), 1);
+    assert!(store.profile(1).is_none());
+}
+
+#[test]
+fn same_id_on_another_connector_replaces() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    set(&mut store, 1, profile(2, 0, "TxDefaultProfile"), &IDLE);
+    assert_eq!(
+        set(&mut store, 2, profile(2, 5, "TxDefaultProfile"), &IDLE),
+        ChargingProfileStatus::Accepted
+    );
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.profiles()[0].connector_id, 2);
+}
+
+#[test]
+fn charge_point_max_profile_only_on_connector_zero() {
+    use ChargingProfileStatus::{Accepted, Rejected};
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    assert_eq!(
+        set(&mut store, 1, profile(3, 0, "ChargePointMaxProfile"), &IDLE),
+        Rejected
+    );
+    assert_eq!(
+        set(&mut store, 0, profile(3, 0, "ChargePointMaxProfile"), &IDLE),
+        Accepted
+    );
+}
+
+#[test]
+fn unknown_connector_is_rejected() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    assert_eq!(
+        set(&mut store, 3, profile(5, 0, "TxDefaultProfile"), &IDLE),
+        ChargingProfileStatus::Rejected
+    );
+}
+
+#[test]
+fn installed_profile_limit() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::new(None, Some(2)));
+    set(&mut store, 1, profile(1, 0, "TxDefaultProfile"), &IDLE);
+    set(&mut store, 0, profile(2, 0, "ChargePointMaxProfile"), &IDLE);
+    assert_eq!(
+        set(&mut store, 1, profile(3, 1, "TxDefaultProfile"), &IDLE),
+        ChargingProfileStatus::Rejected
+    );
+    // Replacing does not count against the limit.
+    assert_eq!(
+        set(&mut store, 1, profile(4, 0, "TxDefaultProfile"), &IDLE),
+        ChargingProfileStatus::Accepted
+    );
+}
+
+#[test]
+fn schedule_period_limit() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::new(Some(1), None));
+    let mut long = profile(6, 0, "ChargePointMaxProfile");
+    let mut second = long.charging_schedule.charging_schedule_period[0].clone();
+    second.start_period = 60;
+    long.charging_schedule.charging_schedule_period.push(second);
+    assert_eq!(
+        set(&mut store, 0, long, &IDLE),
+        ChargingProfileStatus::Rejected
+    );
+}
+
+#[test]
+fn tx_profile_needs_the_matching_transaction() {
+    use ChargingProfileStatus::{Accepted, Rejected};
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    let mut tx = profile(1, 0, "TxProfile");
+    tx.transaction_id = Some(7);
+    assert_eq!(set(&mut store, 1, tx.clone(), &IDLE), Rejected);
+    let other = ChargePoint {
+        transaction: Some(8),
+    };
+    assert_eq!(set(&mut store, 1, tx.clone(), &other), Rejected);
+    let charging = ChargePoint {
+        transaction: Some(7),
+    };
+    assert_eq!(set(&mut store, 0, tx.clone(), &charging), Rejected);
+    assert_eq!(set(&mut store, 1, tx, &charging), Accepted);
+}
+
+#[test]
+fn tx_profiles_end_with_their_transaction() {
+    let charging = ChargePoint {
+        transaction: Some(7),
+    };
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    let mut tx = profile(1, 0, "TxProfile");
+    tx.transaction_id = Some(7);
+    set(&mut store, 1, tx, &charging);
+    set(&mut store, 1, profile(2, 0, "TxDefaultProfile"), &charging);
+    assert_eq!(store.end_transaction(1), 1);
+    assert_eq!(store.len(), 1);
+    assert!(store.profile(1).is_none());
+}
+
+#[test]
+fn clear_unknown_id() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    set(&mut store, 1, profile(1, 0, "TxDefaultProfile"), &IDLE);
+    assert_eq!(
+        clear(
+            &mut store,
+            ClearChargingProfile {
+                id: Some(42),
+                ..ClearChargingProfile::default()
+            }
+        ),
+        ClearChargingProfileStatus::Unknown
+    );
+    assert_eq!(store.len(), 1);
+}
+
+#[test]
+fn clear_by_purpose_and_stack_level() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    set(&mut store, 1, profile(2, 0, "TxDefaultProfile"), &IDLE);
+    set(&mut store, 2, profile(3, 1, "TxDefaultProfile"), &IDLE);
+    assert_eq!(
+        clear(
+            &mut store,
+            ClearChargingProfile {
+                charging_profile_purpose: Some(ChargingProfilePurposeType::TxDefaultProfile),
+                stack_level: Some(1),
+                ..ClearChargingProfile::default()
+            }
+        ),
+        ClearChargingProfileStatus::Accepted
+    );
+    assert!(store.profile(3).is_none());
+    assert!(store.profile(2).is_some());
+}
+
+#[test]
+fn clear_everything() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    set(&mut store, 1, profile(2, 0, "TxDefaultProfile"), &IDLE);
+    set(&mut store, 0, profile(3, 0, "ChargePointMaxProfile"), &IDLE);
+    assert_eq!(
+        clear(&mut store, ClearChargingProfile::default()),
+        ClearChargingProfileStatus::Accepted
+    );
+    assert!(store.is_empty());
+}
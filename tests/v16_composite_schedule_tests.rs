@@ -0,0 +1,295 @@
+//! 1.6 composite schedule calculation.
+
+use chrono::{DateTime, TimeZone, Utc};
+use ocpp_rs::v16::composite::{
+    ActiveTransaction, CompositeContext, InstalledProfile, composite_schedule, convert_limit,
+};
+use ocpp_rs::v16::data_types::{
+    ChargingProfile, ChargingSchedule, ChargingSchedulePeriod, DateTimeWrapper,
+};
+use ocpp_rs::v16::enums::{
+    ChargingProfileKindType, ChargingProfilePurposeType, ChargingRateUnitType, RecurrencyKind,
+};
+
+fn at(h: u32, m: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, h, m, 0).unwrap()
+}
+
+fn profile(
+    connector_id: i32,
+    stack_level: u32,
+    purpose: ChargingProfilePurposeType,
+    kind: ChargingProfileKindType,
+    start: Option<DateTime<Utc>>,
+    unit: ChargingRateUnitType,
+    periods: &[(i32, f32)],
+) -> InstalledProfile {
+    InstalledProfile {
+        connector_id,
+        profile: ChargingProfile {
+            charging_profile_id: connector_id * 10 + i32::try_from(stack_level).unwrap(),
+            transaction_id: None,
+            stack_level,
+            charging_profile_purpose: purpose,
+            charging_profile_kind: kind,
+            recurrency_kind: None,
+            valid_from: None,
+            valid_to: None,
+            charging_schedule: ChargingSchedule {
+                duration: None,
+                start_schedule: start.map(DateTimeWrapper::new),
+                charging_rate_unit: unit,
+                charging_schedule_period: periods
+                    .iter()
+                    .map(|&(start_period, limit)| ChargingSchedulePeriod {
+                        start_period,
+                        limit,
+                        number_phases: None,
+                    })
+                    .collect(),
+                min_charging_rate: None,
+            },
+        },
+    }
+}
+
+fn periods(schedule: &ChargingSchedule) -> Vec<(i32, f32)> {
+    schedule
+        .charging_schedule_period
+        .iter()
+        .map(|p| (p.start_period, p.limit))
+        .collect()
+}
+
+/// 16 A default from 10:00, 10 A at stack level 1 from 11:00 for an hour, an 11 kW
+/// `ChargePointMaxProfile` and a profile for connector 2.
+fn stacked() -> [InstalledProfile; 4] {
+    use ChargingProfileKindType::Absolute;
+    use ChargingProfilePurposeType::{ChargePointMaxProfile, TxDefaultProfile};
+    let start = at(10, 0);
+    let mut higher = profile(
+        1,
+        1,
+        TxDefaultProfile,
+        Absolute,
+        Some(at(11, 0)),
+        ChargingRateUnitType::A,
+        &[(0, 10.0)],
+    );
+    higher.profile.charging_schedule.duration = Some(3600);
+    [
+        profile(
+            0,
+            0,
+            TxDefaultProfile,
+            Absolute,
+            Some(start),
+            ChargingRateUnitType::A,
+            &[(0, 16.0)],
+        ),
+        higher,
+        profile(
+            0,
+            0,
+            ChargePointMaxProfile,
+            Absolute,
+            Some(start),
+            ChargingRateUnitType::W,
+            &[(0, 11_000.0)],
+        ),
+        // Not for this connector.
+        profile(
+            2,
+            5,
+            TxDefaultProfile,
+            Absolute,
+            Some(start),
+            ChargingRateUnitType::A,
+            &[(0, 6.0)],
+        ),
+    ]
+}
+
+#[test]
+fn higher_stack_level_applies_within_charge_point_max() {
+    let start = at(10, 0);
+    let schedule = composite_schedule(
+        &stacked(),
+        1,
+        start,
+        3 * 3600,
+        ChargingRateUnitType::A,
+        &CompositeContext::default(),
+    );
+    assert_eq!(schedule.duration, Some(3 * 3600));
+    assert_eq!(schedule.start_schedule, Some(DateTimeWrapper::new(start)));
+    assert_eq!(periods(&schedule), [(0, 15.9), (3600, 10.0), (7200, 15.9)]);
+}
+
+#[test]
+fn connector_zero_sees_only_the_charge_point_max() {
+    let whole = composite_schedule(
+        &stacked(),
+        0,
+        at(10, 0),
+        3600,
+        ChargingRateUnitType::W,
+        &CompositeContext::default(),
+    );
+    assert_eq!(periods(&whole), [(0, 11_000.0)]);
+}
+
+/// 20 A default plus relative `TxProfile`s for transactions 42 and 41 on connector 1.
+fn tx_profiles() -> [InstalledProfile; 3] {
+    use ChargingProfileKindType::{Absolute, Relative};
+    use ChargingProfilePurposeType::{TxDefaultProfile, TxProfile};
+    let mut tx = profile(
+        1,
+        0,
+        TxProfile,
+        Relative,
+        None,
+        ChargingRateUnitType::A,
+        &[(0, 8.0), (1800, 12.0)],
+    );
+    tx.profile.transaction_id = Some(42);
+    let mut other_tx = profile(
+        1,
+        9,
+        TxProfile,
+        Relative,
+        None,
+        ChargingRateUnitType::A,
+        &[(0, 1.0)],
+    );
+    other_tx.profile.transaction_id = Some(41);
+    [
+        profile(
+            0,
+            0,
+            TxDefaultProfile,
+            Absolute,
+            Some(at(0, 0)),
+            ChargingRateUnitType::A,
+            &[(0, 20.0)],
+        ),
+        tx,
+        other_tx,
+    ]
+}
+
+#[test]
+fn tx_profiles_are_ignored_without_a_transaction() {
+    let idle = composite_schedule(
+        &tx_profiles(),
+        1,
+        at(10, 0),
+        3600,
+        ChargingRateUnitType::A,
+        &CompositeContext::default(),
+    );
+    assert_eq!(periods(&idle), [(0, 20.0)]);
+}
+
+#[test]
+fn tx_profile_relative_to_transaction_start() {
+    let ctx = CompositeContext {
+        transaction: Some(ActiveTransaction {
+            transaction_id: Some(42),
+            started: at(9, 45),
+        }),
+        ..CompositeContext::default()
+    };
+    let charging = composite_schedule(
+        &tx_profiles(),
+        1,
+        at(10, 0),
+        3600,
+        ChargingRateUnitType::A,
+        &ctx,
+    );
+    assert_eq!(periods(&charging), [(0, 8.0), (900, 12.0)]);
+}
+
+#[test]
+fn daily_recurring_profile_wraps_midnight() {
+    let mut daily = profile(
+        0,
+        0,
+        ChargingProfilePurposeType::ChargePointMaxProfile,
+        ChargingProfileKindType::Recurring,
+        Some(Utc.with_ymd_and_hms(2023, 12, 1, 0, 0, 0).unwrap()),
+        ChargingRateUnitType::A,
+        &[(0, 6.0), (6 * 3600, 16.0), (22 * 3600, 8.0)],
+    );
+    daily.profile.recurrency_kind = Some(RecurrencyKind::Daily);
+    let schedule = composite_schedule(
+        &[daily],
+        1,
+        at(20, 0),
+        8 * 3600,
+        ChargingRateUnitType::W,
+        &CompositeContext::default(),
+    );
+    assert_eq!(
+        periods(&schedule),
+        [(0, 11_040.0), (2 * 3600, 5_520.0), (4 * 3600, 4_140.0)]
+    );
+}
+
+#[test]
+fn validity_and_phases_bound_the_limit() {
+    let mut limited = profile(
+        1,
+        0,
+        ChargingProfilePurposeType::TxDefaultProfile,
+        ChargingProfileKindType::Absolute,
+        Some(at(10, 0)),
+        ChargingRateUnitType::W,
+        &[(0, 3_680.0)],
+    );
+    limited.profile.valid_to = Some(DateTimeWrapper::new(at(10, 30)));
+    limited.profile.charging_schedule.charging_schedule_period[0].number_phases = Some(1);
+    let ctx = CompositeContext {
+        max_current: 20.0,
+        ..CompositeContext::default()
+    };
+    let schedule = composite_schedule(
+        &[limited],
+        1,
+        at(10, 0),
+        3600,
+        ChargingRateUnitType::A,
+        &ctx,
+    );
+    let got: Vec<_> = schedule
+        .charging_schedule_period
+        .iter()
+        .map(|p| (p.start_period, p.limit, p.number_phases))
+        .collect();
+    assert_eq!(got, [(0, 16.0, Some(1)), (1800, 20.0, Some(3))]);
+}
+
+#[test]
+fn limits_convert_between_units() {
+    assert_eq!(
+        convert_limit(
+            16.0,
+            &ChargingRateUnitType::A,
+            &ChargingRateUnitType::W,
+            3,
+            230.0
+        ),
+        11_040.0
+    );
+    assert_eq!(
+        convert_limit(
+            7_360.0,
+            &ChargingRateUnitType::W,
+            &ChargingRateUnitType::A,
+            2,
+            230.0
+        ),
+        16.0
+    );
+}
@@ -0,0 +1,100 @@
+//! 1.6 `ChargingSchedule` time queries.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ocpp_rs::v16::data_types::ChargingSchedule;
+
+fn t0() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap()
+}
+
+fn relative(duration: Option<i32>) -> ChargingSchedule {
+    serde_json::from_value(serde_json::json!({
+        "duration": duration,
+        "chargingRateUnit": "A",
+        "chargingSchedulePeriod": [
+            { "startPeriod": 0, "limit": 16.0 },
+            { "startPeriod": 600, "limit": 8.0, "numberPhases": 1 },
+            { "startPeriod": 1800, "limit": 32.0 },
+        ],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn relative_schedule_uses_the_given_start() {
+    let schedule = relative(Some(1200));
+    let start = t0();
+    let at = |secs| start + TimeDelta::seconds(secs);
+    assert_eq!(schedule.limit_at(start, at(-1)), None);
+    assert_eq!(schedule.limit_at(start, at(0)), Some(16.0));
+    assert_eq!(schedule.limit_at(start, at(600)), Some(8.0));
+    // The third period starts after `duration` and never applies.
+    assert_eq!(schedule.limit_at(start, at(1200)), None);
+    assert_eq!(schedule.effective_periods(start).count(), 2);
+    assert_eq!(schedule.next_change(start, at(-60)), Some(at(0)));
+    assert_eq!(schedule.next_change(start, at(0)), Some(at(600)));
+    assert_eq!(schedule.next_change(start, at(700)), Some(at(1200)));
+    assert_eq!(schedule.next_change(start, at(1200)), None);
+
+    // 16 A x 3 x 230 V for 10 min + 8 A x 1 x 230 V for 5 min.
+    let wh = schedule.max_energy(start, at(0), at(900), 230.0);
+    assert!((wh - (1840.0 + 153.333)).abs() < 0.01, "{wh}");
+    assert!(schedule.max_energy(start, at(1200), at(5000), 230.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn absolute_schedule_ignores_the_given_start() {
+    let mut schedule = relative(None);
+    schedule.start_schedule = Some(ocpp_rs::v16::data_types::DateTimeWrapper::new(t0()));
+    let other = t0() + TimeDelta::days(3);
+    assert_eq!(schedule.anchor(other), t0());
+    assert_eq!(schedule.end(other), None);
+    let last = schedule.effective_periods(other).last().unwrap();
+    assert_eq!(last.start, t0() + TimeDelta::seconds(1800));
+    assert_eq!(last.end, None);
+    assert_eq!(schedule.limit_at(other, other), Some(32.0));
+    assert_eq!(schedule.next_change(other, other), None);
+}
+
+#[test]
+fn unordered_and_overlapping_periods_are_sorted() {
+    let schedule: ChargingSchedule = serde_json::from_value(serde_json::json!({
+        "chargingRateUnit": "W",
+        "chargingSchedulePeriod": [
+            { "startPeriod": 600, "limit": 2000.0 },
+            { "startPeriod": 0, "limit": 1000.0 },
+            { "startPeriod": 600, "limit": 3000.0 },
+        ],
+    }))
+    .unwrap();
+    let start = t0();
+    let at = |secs| start + TimeDelta::seconds(secs);
+    let periods: Vec<_> = schedule
+        .effective_periods(start)
+        .map(|p| (p.start, p.end, p.period.limit))
+        .collect();
+    // Of the two periods starting at 600 s the later one counts.
+    assert_eq!(
+        periods,
+        [(at(0), Some(at(600)), 1000.0), (at(600), None, 3000.0)]
+    );
+    assert_eq!(schedule.limit_at(start, at(599)), Some(1000.0));
+    assert_eq!(schedule.next_change(start, at(0)), Some(at(600)));
+    let wh = schedule.max_energy(start, at(0), at(1200), 230.0);
+    assert!((wh - (1000.0 + 3000.0) / 6.0).abs() < 1e-9, "{wh}");
+}
+
+#[test]
+fn empty_schedule_has_no_limit() {
+    let schedule: ChargingSchedule = serde_json::from_value(serde_json::json!({
+        "duration": 600,
+        "chargingRateUnit": "A",
+        "chargingSchedulePeriod": [],
+    }))
+    .unwrap();
+    let start = t0();
+    assert_eq!(schedule.effective_periods(start).count(), 0);
+    assert_eq!(schedule.limit_at(start, start), None);
+    assert_eq!(schedule.next_change(start, start), None);
+    assert!(schedule.max_energy(start, start, start + TimeDelta::hours(1), 230.0) == 0.0);
+}
@@ -0,0 +1,440 @@
+//! 2.1 charging profile store.
+
+use chrono::{TimeZone, Utc};
+use ocpp_rs::v21::charging_profiles::{ChargingProfileStore, ProfileLimits, SOURCE_CSO};
+use ocpp_rs::v21::composite::{CompositeContext, composite_schedule};
+use ocpp_rs::v21::datatypes::{ChargingProfileType, DateTimeWrapper};
+use ocpp_rs::v21::enumerations::{
+    ChargingProfilePurposeEnumType, ChargingProfileStatusEnumType, ChargingRateUnitEnumType,
+};
+use ocpp_rs::v21::errata::REASON_INVALID_MESSAGE_SEQ;
+use ocpp_rs::v21::evse::EvseState;
+use ocpp_rs::v21::messages::clear_charging_profile::{
+    ClearChargingProfileRequest, ClearChargingProfileStatusEnumType, ClearChargingProfileType,
+};
+use ocpp_rs::v21::messages::get_charging_profiles::{
+    ChargingProfileCriterionType, GetChargingProfileStatusEnumType, GetChargingProfilesRequest,
+};
+use ocpp_rs::v21::messages::set_charging_profile::SetChargingProfileRequest;
+use ocpp_rs::v21::reason_codes;
+use ocpp_rs::v21::report::MessageLimits;
+
+struct Station {
+    transaction: Option<&'static str>,
+    awaiting_needs: bool,
+}
+
+impl EvseState for Station {
+    fn evse_exists(&self, evse_id: i32) -> bool {
+        (1..=2).contains(&evse_id)
+    }
+    fn transaction_id(&self, evse_id: i32) -> Option<&str> {
+        if evse_id == 1 { self.transaction } else { None }
+    }
+    fn awaiting_charging_needs(&self, _evse_id: i32) -> bool {
+        self.awaiting_needs
+    }
+}
+
+const IDLE: Station = Station {
+    transaction: None,
+    awaiting_needs: false,
+};
+
+fn profile(id: i32, stack_level: i32, purpose: &str, limit: f64) -> ChargingProfileType {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "stackLevel": stack_level,
+        "chargingProfilePurpose": purpose,
+        "chargingProfileKind": "Absolute",
+        "chargingSchedule": [{
+            "id": 1,
+            "startSchedule": "2024-01-01T00:00:00Z",
+            "chargingRateUnit": "A",
+            "chargingSchedulePeriod": [{ "startPeriod": 0, "limit": limit }],
+        }],
+    }))
+    .unwrap()
+}
+
+fn set(
+    store: &mut ChargingProfileStore,
+    evse_id: i32,
+    profile: ChargingProfileType,
+    station: &Station,
+) -> (ChargingProfileStatusEnumType, Option<String>) {
+    let response = store.set_charging_profile(
+        &SetChargingProfileRequest {
+            evse_id,
+            charging_profile: profile,
+            custom_data: None,
+        },
+        station,
+    );
+    (response.status, response.status_info.map(|i| i.reason_code))
+}
+
+fn get_request(
+    evse_id: Option<i32>,
+    purpose: Option<ChargingProfilePurposeEnumType>,
+) -> GetChargingProfilesRequest {
+    GetChargingProfilesRequest {
+        request_id: 9,
+        evse_id,
+        charging_profile: ChargingProfileCriterionType {
+            charging_profile_purpose: purpose,
+            stack_level: None,
+            charging_profile_id: None,
+            charging_limit_source: None,
+            custom_data: None,
+        },
+        custom_data: None,
+    }
+}
+
+#[test]
+fn same_stack_level_and_purpose_on_an_evse_is_a_duplicate() {
+    use ChargingProfileStatusEnumType::{Accepted, Rejected};
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    assert_eq!(
+        set(
+            &mut store,
+            1,
+            profile(1, 0, "TxDefaultProfile", 16.0),
+            &IDLE
+        ),
+        (Accepted, None)
+    );
+    assert_eq!(
+        set(
+            &mut store,
+            1,
+            profile(2, 0, "TxDefaultProfile", 10.0),
+            &IDLE
+        ),
+        (Rejected, Some(reason_codes::DUPLICATE_PROFILE.into()))
+    );
+    // Same stack level on another EVSE is fine.
+    assert_eq!(
+        set(
+            &mut store,
+            2,
+            profile(2, 0, "TxDefaultProfile", 10.0),
+            &IDLE
+        ),
+        (Accepted, None)
+    );
+}
+
+#[test]
+fn same_id_replaces_even_with_another_stack_level() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    set(
+        &mut store,
+        1,
+        profile(1, 0, "TxDefaultProfile", 16.0),
+        &IDLE,
+    );
+    assert_eq!(
+        set(
+            &mut store,
+            1,
+            profile(1, 3, "TxDefaultProfile", 12.0),
+            &IDLE
+        ),
+        (ChargingProfileStatusEnumType::Accepted, None)
+    );
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.profile(1).unwrap().profile.stack_level, 3);
+    assert_eq!(store.source(1), Some(SOURCE_CSO));
+}
+
+#[test]
+fn non_overlapping_validity_is_not_a_duplicate() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    let switch = Some(DateTimeWrapper::new(
+        Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap(),
+    ));
+    let mut earlier = profile(1, 3, "TxDefaultProfile", 12.0);
+    earlier.valid_to = switch;
+    let mut later = profile(3, 3, "TxDefaultProfile", 8.0);
+    later.valid_from = switch;
+    assert_eq!(
+        set(&mut store, 1, earlier, &IDLE).0,
+        ChargingProfileStatusEnumType::Accepted
+    );
+    assert_eq!(
+        set(&mut store, 1, later, &IDLE).0,
+        ChargingProfileStatusEnumType::Accepted
+    );
+}
+
+#[test]
+fn unknown_evse_is_rejected() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    assert_eq!(
+        set(&mut store, 5, profile(4, 0, "TxDefaultProfile", 6.0), &IDLE),
+        (
+            ChargingProfileStatusEnumType::Rejected,
+            Some(reason_codes::UNKNOWN_EVSE.into())
+        )
+    );
+}
+
+#[test]
+fn station_wide_purposes_only_on_evse_zero() {
+    use ChargingProfileStatusEnumType::Rejected;
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    assert_eq!(
+        set(
+            &mut store,
+            1,
+            profile(4, 0, "ChargingStationMaxProfile", 6.0),
+            &IDLE
+        ),
+        (Rejected, Some(reason_codes::INVALID_PROFILE.into()))
+    );
+    // External constraints are not set by the CSMS.
+    assert_eq!(
+        set(
+            &mut store,
+            0,
+            profile(4, 0, "ChargingStationExternalConstraints", 6.0),
+            &IDLE
+        ),
+        (Rejected, Some(reason_codes::INVALID_PROFILE.into()))
+    );
+}
+
+#[test]
+fn schedule_must_start_at_zero() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    let mut bad = profile(4, 0, "TxDefaultProfile", 6.0);
+    bad.charging_schedule[0].charging_schedule_period[0].start_period = 10;
+    assert_eq!(
+        set(&mut store, 1, bad, &IDLE),
+        (
+            ChargingProfileStatusEnumType::Rejected,
+            Some(reason_codes::INVALID_SCHEDULE.into())
+        )
+    );
+}
+
+#[test]
+fn tx_profiles_need_a_transaction() {
+    use ChargingProfileStatusEnumType::{Accepted, Rejected};
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    let mut tx = profile(10, 0, "TxProfile", 16.0);
+    tx.transaction_id = Some("tx-1".into());
+    assert_eq!(
+        set(&mut store, 1, tx.clone(), &IDLE),
+        (Rejected, Some(reason_codes::TX_NOT_FOUND.into()))
+    );
+    let charging = Station {
+        transaction: Some("tx-1"),
+        awaiting_needs: false,
+    };
+    let mut other = tx.clone();
+    other.transaction_id = Some("tx-2".into());
+    assert_eq!(
+        set(&mut store, 1, other, &charging),
+        (Rejected, Some(reason_codes::TX_NOT_FOUND.into()))
+    );
+    assert_eq!(
+        set(&mut store, 0, tx.clone(), &charging),
+        (Rejected, Some(reason_codes::INVALID_PROFILE.into()))
+    );
+    let negotiating = Station {
+        transaction: Some("tx-1"),
+        awaiting_needs: true,
+    };
+    assert_eq!(
+        set(&mut store, 1, tx.clone(), &negotiating),
+        (Rejected, Some(REASON_INVALID_MESSAGE_SEQ.into()))
+    );
+    assert_eq!(set(&mut store, 1, tx, &charging), (Accepted, None));
+    assert_eq!(store.end_transaction("tx-1"), 1);
+    assert!(store.is_empty());
+}
+
+#[test]
+fn too_many_schedule_periods() {
+    let mut store = ChargingProfileStore::new(ProfileLimits::new(Some(2), None));
+    let mut long = profile(1, 0, "TxDefaultProfile", 16.0);
+    let first = long.charging_schedule[0].charging_schedule_period[0].clone();
+    for start in [60, 120] {
+        let mut p = first.clone();
+        p.start_period = start;
+        long.charging_schedule[0].charging_schedule_period.push(p);
+    }
+    assert_eq!(
+        set(&mut store, 1, long, &IDLE),
+        (
+            ChargingProfileStatusEnumType::Rejected,
+            Some(reason_codes::TOO_MANY_ELEMENTS.into())
+        )
+    );
+}
+
+#[test]
+fn too_many_installed_profiles() {
+    use ChargingProfileStatusEnumType::{Accepted, Rejected};
+    let mut store = ChargingProfileStore::new(ProfileLimits::new(None, Some(2)));
+    set(
+        &mut store,
+        1,
+        profile(1, 0, "TxDefaultProfile", 16.0),
+        &IDLE,
+    );
+    set(
+        &mut store,
+        0,
+        profile(2, 0, "ChargingStationMaxProfile", 32.0),
+        &IDLE,
+    );
+    assert_eq!(
+        set(
+            &mut store,
+            2,
+            profile(3, 0, "TxDefaultProfile", 16.0),
+            &IDLE
+        ),
+        (Rejected, Some(reason_codes::TOO_MANY_ELEMENTS.into()))
+    );
+    // Replacing does not need room.
+    assert_eq!(
+        set(&mut store, 1, profile(1, 1, "TxDefaultProfile", 8.0), &IDLE).0,
+        Accepted
+    );
+
+    let schedule = composite_schedule(
+        store.profiles(),
+        1,
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        60,
+        ChargingRateUnitEnumType::A,
+        &CompositeContext::default(),
+    );
+    assert_eq!(schedule.charging_schedule_period[0].limit, Some(8.0));
+}
+
+/// Defaults 1 (EVSE 1, level 0), 2 (EVSE 1, level 1) and 3 (EVSE 2), station maximum 4 and
+/// external constraint 5 from `EMS`.
+fn populated() -> ChargingProfileStore {
+    let mut store = ChargingProfileStore::new(ProfileLimits::default());
+    for (evse, id, stack) in [(1, 1, 0), (1, 2, 1), (2, 3, 0)] {
+        set(
+            &mut store,
+            evse,
+            profile(id, stack, "TxDefaultProfile", 16.0),
+            &IDLE,
+        );
+    }
+    set(
+        &mut store,
+        0,
+        profile(4, 0, "ChargingStationMaxProfile", 32.0),
+        &IDLE,
+    );
+    store
+        .install_external(
+            0,
+            profile(5, 0, "ChargingStationExternalConstraints", 20.0),
+            "EMS",
+        )
+        .unwrap();
+    store
+}
+
+fn clear(
+    store: &mut ChargingProfileStore,
+    id: Option<i32>,
+    evse_id: Option<i32>,
+    stack_level: Option<i32>,
+) -> ClearChargingProfileStatusEnumType {
+    store
+        .clear_charging_profile(&ClearChargingProfileRequest {
+            charging_profile_id: id,
+            charging_profile_criteria: Some(ClearChargingProfileType {
+                evse_id,
+                charging_profile_purpose: None,
+                stack_level,
+                custom_data: None,
+            }),
+            custom_data: None,
+        })
+        .status
+}
+
+#[test]
+fn reports_group_by_evse_and_source() {
+    let (response, reports) = populated()
+        .get_charging_profiles(&get_request(None, None), &MessageLimits::new(Some(2), None))
+        .unwrap();
+    assert_eq!(response.status, GetChargingProfileStatusEnumType::Accepted);
+    let shape: Vec<_> = reports
+        .iter()
+        .map(|r| {
+            (
+                r.evse_id,
+                r.charging_limit_source.as_str(),
+                r.charging_profile.len(),
+                r.tbc,
+            )
+        })
+        .collect();
+    assert_eq!(
+        shape,
+        [
+            (0, "CSO", 1, Some(true)),
+            (0, "EMS", 1, Some(true)),
+            (1, "CSO", 2, Some(true)),
+            (2, "CSO", 1, None),
+        ]
+    );
+    assert!(reports.iter().all(|r| r.request_id == 9));
+}
+
+#[test]
+fn report_without_matches() {
+    let (none, empty) = populated()
+        .get_charging_profiles(
+            &get_request(Some(2), Some(ChargingProfilePurposeEnumType::TxProfile)),
+            &MessageLimits::default(),
+        )
+        .unwrap();
+    assert_eq!(none.status, GetChargingProfileStatusEnumType::NoProfiles);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn clear_unknown_id() {
+    let mut store = populated();
+    assert_eq!(
+        clear(&mut store, Some(99), None, None),
+        ClearChargingProfileStatusEnumType::Unknown
+    );
+    assert_eq!(store.len(), 5);
+}
+
+#[test]
+fn clear_by_evse_and_stack_level() {
+    let mut store = populated();
+    assert_eq!(
+        clear(&mut store, None, Some(1), Some(1)),
+        ClearChargingProfileStatusEnumType::Accepted
+    );
+    assert!(store.profile(2).is_none());
+    assert!(store.profile(1).is_some());
+}
+
+#[test]
+fn external_constraints_survive_a_clear_all() {
+    let mut store = populated();
+    assert_eq!(
+        clear(&mut store, None, None, None),
+        ClearChargingProfileStatusEnumType::Accepted
+    );
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.source(5), Some("EMS"));
+}
@@ -0,0 +1,463 @@
+//! 2.1 composite schedule calculation.
+
+use chrono::{DateTime, TimeZone, Utc};
+use ocpp_rs::v21::composite::{
+    ActiveTransaction, CompositeContext, InstalledProfile, composite_schedule,
+};
+use ocpp_rs::v21::datatypes::{
+    ChargingProfileType, ChargingSchedulePeriodType, ChargingScheduleType, DateTimeWrapper,
+};
+use ocpp_rs::v21::enumerations::{
+    ChargingProfileKindEnumType, ChargingProfilePurposeEnumType, ChargingRateUnitEnumType,
+    OperationModeEnumType, RecurrencyKindEnumType,
+};
+use ocpp_rs::v21::messages::get_composite_schedule::CompositeScheduleType;
+
+fn at(d: u32, h: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, d, h, 0, 0).unwrap()
+}
+
+fn period(start_period: i32, limit: f64) -> ChargingSchedulePeriodType {
+    serde_json::from_value(serde_json::json!({ "startPeriod": start_period, "limit": limit }))
+        .unwrap()
+}
+
+fn schedule(
+    id: i32,
+    unit: ChargingRateUnitEnumType,
+    start: Option<DateTime<Utc>>,
+    periods: Vec<ChargingSchedulePeriodType>,
+) -> ChargingScheduleType {
+    let mut s: ChargingScheduleType = serde_json::from_value(serde_json::json!({
+        "id": id,
+        "chargingRateUnit": "A",
+        "chargingSchedulePeriod": [],
+    }))
+    .unwrap();
+    s.charging_rate_unit = unit;
+    s.start_schedule = start.map(DateTimeWrapper::new);
+    s.charging_schedule_period = periods;
+    s
+}
+
+fn profile(
+    evse_id: i32,
+    id: i32,
+    stack_level: i32,
+    purpose: ChargingProfilePurposeEnumType,
+    schedules: Vec<ChargingScheduleType>,
+) -> InstalledProfile {
+    InstalledProfile {
+        evse_id,
+        profile: ChargingProfileType {
+            id,
+            stack_level,
+            charging_profile_purpose: purpose,
+            charging_profile_kind: ChargingProfileKindEnumType::Absolute,
+            recurrency_kind: None,
+            valid_from: None,
+            valid_to: None,
+            transaction_id: None,
+            max_offline_duration: None,
+            charging_schedule: schedules,
+            invalid_after_offline_duration: None,
+            dyn_update_interval: None,
+            dyn_update_time: None,
+            price_schedule_signature: None,
+            custom_data: None,
+        },
+    }
+}
+
+fn limits(schedule: &CompositeScheduleType) -> Vec<(i32, f64)> {
+    schedule
+        .charging_schedule_period
+        .iter()
+        .map(|p| (p.start_period, p.limit.unwrap()))
+        .collect()
+}
+
+/// Default profiles of 10 A on EVSE 0 (level 1), 12 A on EVSE 1 (level 1), and 14 A (id 4)
+/// and 6 A (id 3) on EVSE 2 (level 0).
+fn stacked_defaults() -> Vec<InstalledProfile> {
+    use ChargingProfilePurposeEnumType::TxDefaultProfile;
+    use ChargingRateUnitEnumType::A;
+    let start = at(1, 0);
+    let flat = |evse_id, id, stack_level, limit| {
+        profile(
+            evse_id,
+            id,
+            stack_level,
+            TxDefaultProfile,
+            vec![schedule(1, A, Some(start), vec![period(0, limit)])],
+        )
+    };
+    vec![
+        flat(0, 1, 1, 10.0),
+        flat(1, 2, 1, 12.0),
+        flat(2, 4, 0, 14.0),
+        flat(2, 3, 0, 6.0),
+    ]
+}
+
+#[test]
+fn evse_profile_wins_a_stack_level_tie_with_evse_zero() {
+    let evse1 = composite_schedule(
+        &stacked_defaults(),
+        1,
+        at(1, 0),
+        3600,
+        ChargingRateUnitEnumType::A,
+        &CompositeContext::default(),
+    );
+    assert_eq!(evse1.evse_id, 1);
+    assert_eq!(evse1.duration, 3600);
+    assert_eq!(limits(&evse1), [(0, 12.0)]);
+}
+
+#[test]
+fn higher_evse_zero_stack_level_wins() {
+    let profiles = stacked_defaults();
+    let ctx = CompositeContext::default();
+    // EVSE 2 has stack level 0 only, so EVSE 0's stack level 1 wins.
+    for evse_id in [2, 3] {
+        assert_eq!(
+            limits(&composite_schedule(
+                &profiles,
+                evse_id,
+                at(1, 0),
+                3600,
+                ChargingRateUnitEnumType::A,
+                &ctx
+            )),
+            [(0, 10.0)]
+        );
+    }
+}
+
+#[test]
+fn higher_id_decides_a_tie_on_one_evse() {
+    let only_evse2 = stacked_defaults().split_off(2);
+    assert_eq!(
+        limits(&composite_schedule(
+            &only_evse2,
+            2,
+            at(1, 0),
+            3600,
+            ChargingRateUnitEnumType::A,
+            &CompositeContext::default()
+        )),
+        [(0, 14.0)]
+    );
+}
+
+fn recurring(
+    id: i32,
+    kind: RecurrencyKindEnumType,
+    start: DateTime<Utc>,
+    duration: i32,
+    periods: Vec<ChargingSchedulePeriodType>,
+) -> InstalledProfile {
+    let mut recurring = profile(
+        0,
+        id,
+        0,
+        ChargingProfilePurposeEnumType::ChargingStationMaxProfile,
+        vec![schedule(
+            1,
+            ChargingRateUnitEnumType::A,
+            Some(start),
+            periods,
+        )],
+    );
+    recurring.profile.charging_profile_kind = ChargingProfileKindEnumType::Recurring;
+    recurring.profile.recurrency_kind = Some(kind);
+    recurring.profile.charging_schedule[0].duration = Some(duration);
+    recurring
+}
+
+#[test]
+fn daily_recurrence_across_midnight() {
+    let daily = recurring(
+        1,
+        RecurrencyKindEnumType::Daily,
+        at(1, 22),
+        8 * 3600,
+        vec![period(0, 8.0), period(4 * 3600, 16.0)],
+    );
+    let schedule_daily = composite_schedule(
+        &[daily],
+        1,
+        at(2, 20),
+        12 * 3600,
+        ChargingRateUnitEnumType::A,
+        &CompositeContext::default(),
+    );
+    assert_eq!(
+        limits(&schedule_daily),
+        [
+            (0, 32.0),
+            (2 * 3600, 8.0),
+            (6 * 3600, 16.0),
+            (10 * 3600, 32.0)
+        ]
+    );
+}
+
+/// Sunday 23:00 for two hours, every week.
+fn sunday_night() -> InstalledProfile {
+    recurring(
+        2,
+        RecurrencyKindEnumType::Weekly,
+        Utc.with_ymd_and_hms(2023, 12, 31, 23, 0, 0).unwrap(),
+        2 * 3600,
+        vec![period(0, 6.0)],
+    )
+}
+
+#[test]
+fn weekly_recurrence_on_its_day() {
+    let schedule_weekly = composite_schedule(
+        &[sunday_night()],
+        1,
+        at(7, 22),
+        4 * 3600,
+        ChargingRateUnitEnumType::A,
+        &CompositeContext::default(),
+    );
+    assert_eq!(
+        limits(&schedule_weekly),
+        [(0, 32.0), (3600, 6.0), (3 * 3600, 32.0)]
+    );
+}
+
+#[test]
+fn weekly_recurrence_is_idle_mid_week() {
+    assert_eq!(
+        limits(&composite_schedule(
+            &[sunday_night()],
+            1,
+            at(3, 22),
+            4 * 3600,
+            ChargingRateUnitEnumType::A,
+            &CompositeContext::default()
+        )),
+        [(0, 32.0)]
+    );
+}
+
+/// Station maximum 22 kW, external constraint 11 kW plus 5 kW local generation, and a 16 A
+/// `TxProfile` for `tx-1` on EVSE 1 with a 20 A setpoint.
+fn station_and_tx_profiles() -> Vec<InstalledProfile> {
+    use ChargingProfilePurposeEnumType::{
+        ChargingStationExternalConstraints, ChargingStationMaxProfile, LocalGeneration, TxProfile,
+    };
+    use ChargingRateUnitEnumType::{A, W};
+    let start = at(1, 12);
+    let mut tx_period = period(0, 16.0);
+    tx_period.setpoint = Some(20.0);
+    tx_period.discharge_limit = Some(-10.0);
+    tx_period.operation_mode = Some(OperationModeEnumType::CentralSetpoint);
+    let mut tx = profile(
+        1,
+        10,
+        0,
+        TxProfile,
+        vec![schedule(1, A, Some(start), vec![tx_period])],
+    );
+    tx.profile.transaction_id = Some("tx-1".into());
+    vec![
+        profile(
+            0,
+            1,
+            0,
+            ChargingStationMaxProfile,
+            vec![schedule(1, W, Some(start), vec![period(0, 22_000.0)])],
+        ),
+        profile(
+            0,
+            2,
+            0,
+            ChargingStationExternalConstraints,
+            vec![schedule(1, W, Some(start), vec![period(0, 11_000.0)])],
+        ),
+        profile(
+            0,
+            3,
+            0,
+            LocalGeneration,
+            vec![schedule(1, W, Some(start), vec![period(0, 5_000.0)])],
+        ),
+        tx,
+    ]
+}
+
+fn transaction(transaction_id: &str) -> CompositeContext {
+    CompositeContext {
+        transaction: Some(ActiveTransaction {
+            transaction_id: transaction_id.into(),
+            started: at(1, 12),
+            selected_schedule: None,
+        }),
+        ..CompositeContext::default()
+    }
+}
+
+#[test]
+fn local_generation_raises_external_constraints() {
+    let station = composite_schedule(
+        &station_and_tx_profiles(),
+        0,
+        at(1, 12),
+        3600,
+        ChargingRateUnitEnumType::W,
+        &CompositeContext::default(),
+    );
+    assert_eq!(station.evse_id, 0);
+    assert_eq!(limits(&station), [(0, 16_000.0)]);
+    assert_eq!(station.charging_schedule_period[0].operation_mode, None);
+}
+
+#[test]
+fn tx_profile_needs_its_transaction() {
+    let profiles = station_and_tx_profiles();
+    for ctx in [CompositeContext::default(), transaction("tx-2")] {
+        let evse = composite_schedule(
+            &profiles,
+            1,
+            at(1, 12),
+            3600,
+            ChargingRateUnitEnumType::W,
+            &ctx,
+        );
+        assert_eq!(limits(&evse), [(0, 16_000.0)]);
+    }
+}
+
+#[test]
+fn tx_profile_setpoint_is_clamped_to_the_limit() {
+    let evse = composite_schedule(
+        &station_and_tx_profiles(),
+        1,
+        at(1, 12),
+        3600,
+        ChargingRateUnitEnumType::A,
+        &transaction("tx-1"),
+    );
+    let p = &evse.charging_schedule_period[0];
+    assert_eq!(p.limit, Some(16.0));
+    assert_eq!(p.setpoint, Some(16.0));
+    assert_eq!(p.discharge_limit, Some(-10.0));
+    assert_eq!(
+        p.operation_mode,
+        Some(OperationModeEnumType::CentralSetpoint)
+    );
+    assert_eq!(p.number_phases, Some(3));
+}
+
+#[test]
+fn local_generation_without_limit_keeps_external_constraints() {
+    use ChargingProfilePurposeEnumType::{ChargingStationExternalConstraints, LocalGeneration};
+    use ChargingRateUnitEnumType::W;
+    let start = at(1, 12);
+    let no_limit: ChargingSchedulePeriodType =
+        serde_json::from_value(serde_json::json!({ "startPeriod": 0, "dischargeLimit": -1000.0 }))
+            .unwrap();
+    let profiles = [
+        profile(
+            0,
+            1,
+            0,
+            ChargingStationExternalConstraints,
+            vec![schedule(1, W, Some(start), vec![period(0, 10_000.0)])],
+        ),
+        profile(
+            0,
+            2,
+            0,
+            LocalGeneration,
+            vec![schedule(1, W, Some(start), vec![no_limit])],
+        ),
+    ];
+    let station = composite_schedule(&profiles, 0, start, 3600, W, &CompositeContext::default());
+    assert_eq!(limits(&station), [(0, 10_000.0)]);
+}
+
+/// Relative `TxProfile` with schedules 1 (6 A) and 2 (10 A, 20 A after 600 s) on EVSE 1, an
+/// 8 A default and a 30 A `PriorityCharging` profile, for a transaction started 300 s before
+/// 08:00.
+fn selectable() -> (Vec<InstalledProfile>, CompositeContext) {
+    use ChargingProfilePurposeEnumType::{PriorityCharging, TxDefaultProfile, TxProfile};
+    use ChargingRateUnitEnumType::A;
+    let start = at(1, 8);
+    let mut relative = profile(
+        1,
+        1,
+        0,
+        TxProfile,
+        vec![
+            schedule(1, A, None, vec![period(0, 6.0)]),
+            schedule(2, A, None, vec![period(0, 10.0), period(600, 20.0)]),
+        ],
+    );
+    relative.profile.charging_profile_kind = ChargingProfileKindEnumType::Relative;
+    let profiles = vec![
+        relative,
+        profile(
+            0,
+            2,
+            0,
+            TxDefaultProfile,
+            vec![schedule(1, A, Some(start), vec![period(0, 8.0)])],
+        ),
+        profile(
+            0,
+            3,
+            0,
+            PriorityCharging,
+            vec![schedule(1, A, Some(start), vec![period(0, 30.0)])],
+        ),
+    ];
+    let ctx = CompositeContext {
+        transaction: Some(ActiveTransaction {
+            transaction_id: "tx".into(),
+            started: start - chrono::TimeDelta::seconds(300),
+            selected_schedule: None,
+        }),
+        ..CompositeContext::default()
+    };
+    (profiles, ctx)
+}
+
+fn at_eight(profiles: &[InstalledProfile], ctx: &CompositeContext) -> Vec<(i32, f64)> {
+    limits(&composite_schedule(
+        profiles,
+        1,
+        at(1, 8),
+        3600,
+        ChargingRateUnitEnumType::A,
+        ctx,
+    ))
+}
+
+#[test]
+fn first_schedule_applies_without_a_selection() {
+    let (profiles, ctx) = selectable();
+    assert_eq!(at_eight(&profiles, &ctx), [(0, 6.0)]);
+}
+
+#[test]
+fn selected_schedule_runs_relative_to_the_transaction_start() {
+    let (profiles, mut ctx) = selectable();
+    if let Some(tx) = ctx.transaction.as_mut() {
+        tx.selected_schedule = Some(2);
+    }
+    assert_eq!(at_eight(&profiles, &ctx), [(0, 10.0), (300, 20.0)]);
+}
+
+#[test]
+fn priority_charging_overrides_the_tx_profile() {
+    let (profiles, mut ctx) = selectable();
+    ctx.priority_charging = true;
+    assert_eq!(at_eight(&profiles, &ctx), [(0, 30.0)]);
+}
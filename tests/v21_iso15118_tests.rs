@@ -0,0 +1,213 @@
+//! ISO 15118 schedule offer and EV schedule verification.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ocpp_rs::load_balancing::SiteLimit;
+use ocpp_rs::v21::datatypes::ChargingProfileType;
+use ocpp_rs::v21::iso15118::{
+    EvNeeds, OfferOptions, ParameterSet, ScheduleViolation, offer_profile, verify_ev_schedule,
+};
+use ocpp_rs::v21::messages::notify_ev_charging_needs::NotifyEVChargingNeedsRequest;
+use ocpp_rs::v21::messages::notify_ev_charging_schedule::NotifyEVChargingScheduleRequest;
+
+fn t0() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 6, 1, 18, 0, 0).unwrap()
+}
+
+fn needs(json: serde_json::Value) -> NotifyEVChargingNeedsRequest {
+    serde_json::from_value(json).unwrap()
+}
+
+fn ev_schedule(periods: serde_json::Value) -> NotifyEVChargingScheduleRequest {
+    serde_json::from_value(serde_json::json!({
+        "timeBase": "2024-06-01T18:00:00Z",
+        "evseId": 1,
+        "chargingSchedule": {
+            "id": 7,
+            "chargingRateUnit": "W",
+            "chargingSchedulePeriod": periods,
+        },
+    }))
+    .unwrap()
+}
+
+#[test]
+fn iso2_ac_offer_follows_budget() {
+    let request = needs(serde_json::json!({
+        "evseId": 1,
+        "chargingNeeds": {
+            "requestedEnergyTransfer": "AC_three_phase",
+            "departureTime": "2024-06-01T21:00:00Z",
+            "acChargingParameters": {
+                "energyAmount": 40000.0,
+                "evMinCurrent": 6.0,
+                "evMaxCurrent": 16.0,
+                "evMaxVoltage": 400.0,
+            },
+        },
+    }));
+    let ev = EvNeeds::from_request(&request, 3, 230.0).unwrap();
+    assert_eq!(ev.parameter_set, ParameterSet::Iso2);
+    assert_eq!(ev.max_power, 11_040.0);
+    assert_eq!(ev.min_power, 4140.0);
+
+    // 3000 W for the first hour (below the EV minimum), then 20 kW.
+    let budget = SiteLimit::Series(vec![(t0(), 3000.0), (t0() + TimeDelta::hours(1), 20_000.0)]);
+    let offer = offer_profile(&ev, &budget, t0(), &OfferOptions::new(5, "tx-1"));
+    let schedule = &offer.profile.charging_schedule[0];
+    assert_eq!(schedule.duration, Some(3 * 3600));
+    let limits: Vec<_> = schedule
+        .charging_schedule_period
+        .iter()
+        .map(|p| (p.start_period, p.limit))
+        .collect();
+    assert_eq!(limits, [(0, Some(0.0)), (3600, Some(11_040.0))]);
+    assert_eq!(offer.profile.transaction_id.as_deref(), Some("tx-1"));
+    assert!((offer.deliverable_energy - 22_080.0).abs() < 1e-6);
+    assert!((offer.shortfall - 17_920.0).abs() < 1e-6);
+    assert!(
+        EvNeeds::from_request(
+            &needs(serde_json::json!({
+                "evseId": 1,
+                "chargingNeeds": { "requestedEnergyTransfer": "DC" },
+            })),
+            3,
+            230.0
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn iso20_offer_respects_schedule_tuples_and_discharge() {
+    let request = needs(serde_json::json!({
+        "evseId": 1,
+        "maxScheduleTuples": 2,
+        "chargingNeeds": {
+            "requestedEnergyTransfer": "DC_BPT",
+            "v2xChargingParameters": {
+                "maxChargePower": 50000.0,
+                "maxDischargePower": 10000.0,
+                "evTargetEnergyRequest": 30000.0,
+            },
+            "dcChargingParameters": { "evMaxCurrent": 10.0, "evMaxVoltage": 400.0 },
+        },
+    }));
+    let ev = EvNeeds::from_request(&request, 3, 230.0).unwrap();
+    assert_eq!(ev.parameter_set, ParameterSet::Iso20);
+    assert_eq!(ev.max_power, 50_000.0);
+    assert_eq!(ev.max_discharge_power, Some(10_000.0));
+    let budget = SiteLimit::Series(vec![
+        (t0(), 40_000.0),
+        (t0() + TimeDelta::hours(1), 30_000.0),
+        (t0() + TimeDelta::hours(2), 35_000.0),
+    ]);
+    let mut options = OfferOptions::new(1, "tx-2");
+    options.default_duration = 3 * 3600;
+    options.allow_discharge = true;
+    let offer = offer_profile(&ev, &budget, t0(), &options);
+    let periods = &offer.profile.charging_schedule[0].charging_schedule_period;
+    let limits: Vec<_> = periods
+        .iter()
+        .map(|p| (p.start_period, p.limit, p.discharge_limit))
+        .collect();
+    // The last two budget steps fold into one at the lower value.
+    assert_eq!(
+        limits,
+        [
+            (0, Some(40_000.0), Some(-10_000.0)),
+            (3600, Some(30_000.0), Some(-10_000.0)),
+        ]
+    );
+    assert!(offer.shortfall.abs() < f64::EPSILON);
+}
+
+/// Offer of 11 kW for the first hour and 7 kW for the second, ending at departure.
+fn offered() -> ChargingProfileType {
+    offer_profile(
+        &EvNeeds {
+            parameter_set: ParameterSet::Iso2,
+            max_power: 11_000.0,
+            min_power: 0.0,
+            energy_request: None,
+            min_energy_request: None,
+            max_discharge_power: None,
+            departure: Some(t0() + TimeDelta::hours(2)),
+            max_schedule_tuples: None,
+        },
+        &SiteLimit::Series(vec![(t0(), 11_000.0), (t0() + TimeDelta::hours(1), 7000.0)]),
+        t0(),
+        &OfferOptions::new(1, "tx-3"),
+    )
+    .profile
+}
+
+fn fits() -> NotifyEVChargingScheduleRequest {
+    ev_schedule(serde_json::json!([
+        { "startPeriod": 0, "limit": 11000.0 },
+        { "startPeriod": 3600, "limit": 5000.0 },
+        { "startPeriod": 7200, "limit": 0.0 },
+    ]))
+}
+
+#[test]
+fn schedule_within_the_offer_passes() {
+    assert!(verify_ev_schedule(&offered(), &fits(), None, 3, 230.0).is_empty());
+}
+
+#[test]
+fn verify_reports_precise_violations() {
+    let greedy = ev_schedule(serde_json::json!([
+        { "startPeriod": 0, "limit": 9000.0 },
+        { "startPeriod": 1800, "limit": 8000.0 },
+        { "startPeriod": 7200, "limit": 1000.0 },
+        { "startPeriod": 7500, "limit": -500.0 },
+    ]));
+    assert_eq!(
+        verify_ev_schedule(&offered(), &greedy, Some(3), 3, 230.0),
+        [
+            ScheduleViolation::TooManyPeriods { max: 3, actual: 4 },
+            // Fine until the offer drops to 7000 W an hour in.
+            ScheduleViolation::ExceedsLimit {
+                start_period: 1800,
+                at: t0() + TimeDelta::hours(1),
+                offered: 7000.0,
+                requested: 8000.0,
+            },
+            ScheduleViolation::OutsideOffer {
+                start_period: 7200,
+                at: t0() + TimeDelta::hours(2),
+            },
+            ScheduleViolation::OutsideOffer {
+                start_period: 7500,
+                at: t0() + TimeDelta::seconds(7500),
+            },
+        ]
+    );
+}
+
+#[test]
+fn discharge_beyond_the_offer_is_flagged() {
+    let mut discharging = ev_schedule(serde_json::json!([
+        { "startPeriod": 0, "limit": -2000.0 },
+    ]));
+    discharging.charging_schedule.duration = Some(600);
+    assert_eq!(
+        verify_ev_schedule(&offered(), &discharging, None, 3, 230.0),
+        [ScheduleViolation::ExceedsDischargeLimit {
+            start_period: 0,
+            at: t0(),
+            offered: 0.0,
+            requested: -2000.0,
+        }]
+    );
+}
+
+#[test]
+fn unknown_selected_schedule_is_flagged() {
+    let mut unknown = fits();
+    unknown.selected_charging_schedule_id = Some(99);
+    assert_eq!(
+        verify_ev_schedule(&offered(), &unknown, None, 3, 230.0),
+        [ScheduleViolation::UnknownSchedule { schedule_id: 99 }]
+    );
+}
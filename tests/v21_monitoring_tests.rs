@@ -0,0 +1,422 @@
+//! Station-side variable monitoring engine.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ocpp_rs::v21::datatypes::{ComponentType, DateTimeWrapper, EVSEType, VariableType};
+use ocpp_rs::v21::enumerations::{EventNotificationEnumType, MonitorEnumType};
+use ocpp_rs::v21::messages::clear_variable_monitoring::{
+    ClearMonitoringStatusEnumType, ClearVariableMonitoringRequest,
+};
+use ocpp_rs::v21::messages::notify_event::EventTriggerEnumType;
+use ocpp_rs::v21::messages::set_monitoring_base::{
+    MonitoringBaseEnumType, SetMonitoringBaseRequest,
+};
+use ocpp_rs::v21::messages::set_monitoring_level::SetMonitoringLevelRequest;
+use ocpp_rs::v21::messages::set_variable_monitoring::{
+    SetMonitoringDataType, SetMonitoringStatusEnumType, SetVariableMonitoringRequest,
+};
+use ocpp_rs::v21::monitoring::{
+    MAX_PERIODIC_INTERVAL, Monitor, MonitorOrigin, MonitoredVariables, MonitoringEngine,
+    VariableLookup, build_notify_events,
+};
+use ocpp_rs::v21::report::MessageLimits;
+
+struct Station;
+
+impl MonitoredVariables for Station {
+    fn lookup(&self, component: &ComponentType, variable: &VariableType) -> VariableLookup {
+        if component.name != "EVSE" {
+            VariableLookup::UnknownComponent
+        } else if !["Power", "AvailabilityState"].contains(&variable.name.as_str()) {
+            VariableLookup::UnknownVariable
+        } else {
+            VariableLookup::Found
+        }
+    }
+}
+
+fn at(secs: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 7).unwrap() + TimeDelta::seconds(secs)
+}
+
+fn evse(name: &str) -> ComponentType {
+    ComponentType {
+        evse: Some(EVSEType {
+            id: 1,
+            connector_id: None,
+            custom_data: None,
+        }),
+        name: name.into(),
+        instance: None,
+        custom_data: None,
+    }
+}
+
+fn variable(name: &str) -> VariableType {
+    VariableType {
+        name: name.into(),
+        instance: None,
+        custom_data: None,
+    }
+}
+
+fn data(
+    id: Option<i32>,
+    var: &str,
+    type_: MonitorEnumType,
+    value: f64,
+    severity: i32,
+) -> SetMonitoringDataType {
+    SetMonitoringDataType {
+        id,
+        periodic_event_stream: None,
+        transaction: None,
+        value,
+        type_,
+        severity,
+        component: evse("EVSE"),
+        variable: variable(var),
+        custom_data: None,
+    }
+}
+
+fn set(
+    engine: &mut MonitoringEngine,
+    items: Vec<SetMonitoringDataType>,
+) -> Vec<(SetMonitoringStatusEnumType, Option<i32>)> {
+    engine
+        .set_variable_monitoring(
+            &SetVariableMonitoringRequest {
+                set_monitoring_data: items,
+                custom_data: None,
+            },
+            &Station,
+            at(0),
+        )
+        .set_monitoring_result
+        .into_iter()
+        .map(|r| (r.status, r.id))
+        .collect()
+}
+
+#[test]
+fn set_variable_monitoring_statuses() {
+    let mut engine = MonitoringEngine::new();
+    let results = set(
+        &mut engine,
+        vec![
+            data(None, "Power", MonitorEnumType::UpperThreshold, 20_000.0, 4),
+            data(None, "Power", MonitorEnumType::UpperThreshold, 21_000.0, 4),
+            data(None, "Nope", MonitorEnumType::Delta, 1.0, 4),
+            data(None, "Power", MonitorEnumType::Periodic, 0.0, 4),
+            data(None, "Power", MonitorEnumType::Delta, 100.0, 10),
+            data(Some(99), "Power", MonitorEnumType::Delta, 100.0, 4),
+            data(
+                Some(1),
+                "Power",
+                MonitorEnumType::UpperThreshold,
+                18_000.0,
+                3,
+            ),
+        ],
+    );
+    use SetMonitoringStatusEnumType as S;
+    assert_eq!(
+        results,
+        vec![
+            (S::Accepted, Some(1)),
+            (S::Duplicate, None),
+            (S::UnknownVariable, None),
+            (S::Rejected, None),
+            (S::Rejected, None),
+            (S::Rejected, Some(99)),
+            (S::Accepted, Some(1)),
+        ]
+    );
+    let monitor = engine.monitor(1).unwrap();
+    assert_eq!(monitor.value, 18_000.0);
+    assert_eq!(monitor.origin, MonitorOrigin::Custom);
+    assert_eq!(engine.monitoring_data().len(), 1);
+
+    let mut foreign = data(None, "Power", MonitorEnumType::Delta, 1.0, 4);
+    foreign.component = evse("Connector");
+    assert_eq!(set(&mut engine, vec![foreign])[0].0, S::UnknownComponent);
+}
+
+#[test]
+fn thresholds_raise_and_clear_with_cause() {
+    let mut engine = MonitoringEngine::new();
+    set(
+        &mut engine,
+        vec![data(
+            None,
+            "Power",
+            MonitorEnumType::UpperThreshold,
+            20_000.0,
+            4,
+        )],
+    );
+    let (c, v) = (evse("EVSE"), variable("Power"));
+    assert!(engine.update_value(&c, &v, "19000", at(1)).is_empty());
+    let raised = engine.update_value(&c, &v, "21000", at(2));
+    assert_eq!(raised.len(), 1);
+    assert_eq!(raised[0].trigger, EventTriggerEnumType::Alerting);
+    assert_eq!(raised[0].actual_value, "21000");
+    assert_eq!(raised[0].variable_monitoring_id, Some(1));
+    assert_eq!(
+        raised[0].event_notification_type,
+        EventNotificationEnumType::CustomMonitor
+    );
+    assert_eq!(raised[0].cleared, None);
+    assert!(engine.update_value(&c, &v, "22000", at(3)).is_empty());
+
+    let cleared = engine.update_value(&c, &v, "15000", at(4));
+    assert_eq!(cleared.len(), 1);
+    assert_eq!(cleared[0].cleared, Some(true));
+    assert_eq!(cleared[0].cause, Some(raised[0].event_id));
+    assert_ne!(cleared[0].event_id, raised[0].event_id);
+}
+
+#[test]
+fn delta_and_target_delta() {
+    let mut engine = MonitoringEngine::new();
+    let (c, power, state) = (
+        evse("EVSE"),
+        variable("Power"),
+        variable("AvailabilityState"),
+    );
+    engine.update_value(&c, &power, "1000", at(0));
+    set(
+        &mut engine,
+        vec![
+            data(None, "Power", MonitorEnumType::Delta, 500.0, 5),
+            data(None, "AvailabilityState", MonitorEnumType::Delta, 0.0, 5),
+            data(None, "Power", MonitorEnumType::TargetDeltaRelative, 0.1, 5),
+        ],
+    );
+    assert!(engine.update_value(&c, &power, "1400", at(1)).is_empty());
+    let delta = engine.update_value(&c, &power, "1600", at(2));
+    assert_eq!(delta.len(), 1);
+    assert_eq!(delta[0].trigger, EventTriggerEnumType::Delta);
+    // Reference moved to 1600.
+    assert!(engine.update_value(&c, &power, "1900", at(3)).is_empty());
+
+    assert!(
+        engine
+            .update_value(&c, &state, "Available", at(4))
+            .is_empty()
+    );
+    assert_eq!(engine.update_value(&c, &state, "Occupied", at(5)).len(), 1);
+
+    let off_target = engine.update_target(&c, &power, "1500", at(6));
+    assert_eq!(off_target.len(), 1);
+    assert_eq!(off_target[0].trigger, EventTriggerEnumType::Alerting);
+    let back = engine.update_value(&c, &power, "1550", at(7));
+    assert!(back.iter().any(|e| e.cleared == Some(true)));
+}
+
+#[test]
+fn periodic_and_clock_aligned() {
+    let mut engine = MonitoringEngine::new();
+    let (c, v) = (evse("EVSE"), variable("Power"));
+    set(
+        &mut engine,
+        vec![
+            data(None, "Power", MonitorEnumType::Periodic, 60.0, 6),
+            data(
+                None,
+                "Power",
+                MonitorEnumType::PeriodicClockAligned,
+                900.0,
+                6,
+            ),
+        ],
+    );
+    // No known value yet: reschedule silently.
+    assert!(engine.tick(at(60)).is_empty());
+    engine.update_value(&c, &v, "7000", at(61));
+    assert!(engine.tick(at(100)).is_empty());
+    let periodic = engine.tick(at(120));
+    assert_eq!(periodic.len(), 1);
+    assert_eq!(periodic[0].trigger, EventTriggerEnumType::Periodic);
+    assert_eq!(periodic[0].variable_monitoring_id, Some(1));
+
+    // Set at 10:00:07 -> aligned to 10:15:00 (893 s later).
+    let aligned = engine.tick(at(893));
+    assert!(aligned.iter().any(|e| e.variable_monitoring_id == Some(2)));
+    assert_eq!(
+        aligned[0].timestamp,
+        DateTimeWrapper::new(Utc.with_ymd_and_hms(2024, 1, 1, 10, 15, 0).unwrap())
+    );
+}
+
+#[test]
+fn periodic_interval_above_the_bound_is_rejected() {
+    use SetMonitoringStatusEnumType as S;
+    let mut engine = MonitoringEngine::new();
+    let results = set(
+        &mut engine,
+        vec![
+            data(None, "Power", MonitorEnumType::Periodic, 1e18, 4),
+            data(
+                None,
+                "Power",
+                MonitorEnumType::PeriodicClockAligned,
+                1e18,
+                4,
+            ),
+            data(
+                None,
+                "Power",
+                MonitorEnumType::Periodic,
+                MAX_PERIODIC_INTERVAL,
+                4,
+            ),
+        ],
+    );
+    assert_eq!(
+        results,
+        vec![
+            (S::Rejected, None),
+            (S::Rejected, None),
+            (S::Accepted, Some(1)),
+        ]
+    );
+}
+
+#[test]
+fn installed_long_periodic_monitor_near_the_end_of_time() {
+    let mut engine = MonitoringEngine::new();
+    let (c, v) = (evse("EVSE"), variable("Power"));
+    let end = DateTime::<Utc>::MAX_UTC;
+    for (id, type_) in [
+        (1, MonitorEnumType::Periodic),
+        (2, MonitorEnumType::PeriodicClockAligned),
+    ] {
+        engine.install(
+            Monitor {
+                id,
+                component: c.clone(),
+                variable: v.clone(),
+                type_,
+                value: 1e18,
+                severity: 6,
+                transaction: false,
+                origin: MonitorOrigin::HardWired,
+            },
+            end - TimeDelta::seconds(10),
+        );
+    }
+    engine.update_value(&c, &v, "7000", end - TimeDelta::seconds(5));
+    assert!(engine.tick(end - TimeDelta::seconds(1)).is_empty());
+    assert_eq!(engine.tick(end).len(), 2);
+}
+
+/// Hard-wired monitor 100 (severity 2), preconfigured 101 (severity 8) and custom 102
+/// (severity 5), all upper thresholds on EVSE Power.
+fn three_thresholds() -> MonitoringEngine {
+    let mut engine = MonitoringEngine::new();
+    let threshold = |id, value, severity, origin| Monitor {
+        id,
+        component: evse("EVSE"),
+        variable: variable("Power"),
+        type_: MonitorEnumType::UpperThreshold,
+        value,
+        severity,
+        transaction: false,
+        origin,
+    };
+    engine.install(threshold(100, 30_000.0, 2, MonitorOrigin::HardWired), at(0));
+    engine.install(
+        threshold(
+            101,
+            25_000.0,
+            8,
+            MonitorOrigin::Preconfigured {
+                factory_default: false,
+            },
+        ),
+        at(0),
+    );
+    set(
+        &mut engine,
+        vec![data(
+            None,
+            "Power",
+            MonitorEnumType::UpperThreshold,
+            20_000.0,
+            5,
+        )],
+    );
+    assert_eq!(engine.monitors().count(), 3);
+    engine
+}
+
+#[test]
+fn monitoring_level_hides_less_severe_events() {
+    let mut engine = three_thresholds();
+    let level = engine.set_monitoring_level(&SetMonitoringLevelRequest {
+        severity: 5,
+        custom_data: None,
+    });
+    assert_eq!(
+        level.status,
+        ocpp_rs::v21::enumerations::GenericStatusEnumType::Accepted
+    );
+    let events = engine.update_value(&evse("EVSE"), &variable("Power"), "35000", at(1));
+    let ids: Vec<_> = events
+        .iter()
+        .filter_map(|e| e.variable_monitoring_id)
+        .collect();
+    assert_eq!(ids, [100, 102]);
+}
+
+#[test]
+fn clear_refuses_hard_wired_monitors() {
+    let mut engine = three_thresholds();
+    let cleared = engine.clear_variable_monitoring(&ClearVariableMonitoringRequest {
+        id: vec![100, 102, 7],
+        custom_data: None,
+    });
+    let statuses: Vec<_> = cleared
+        .clear_monitoring_result
+        .into_iter()
+        .map(|r| r.status)
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ClearMonitoringStatusEnumType::Rejected,
+            ClearMonitoringStatusEnumType::Accepted,
+            ClearMonitoringStatusEnumType::NotFound,
+        ]
+    );
+}
+
+#[test]
+fn factory_default_base_drops_custom_monitors() {
+    let mut engine = three_thresholds();
+    engine.set_monitoring_base(&SetMonitoringBaseRequest {
+        monitoring_base: MonitoringBaseEnumType::FactoryDefault,
+        custom_data: None,
+    });
+    let remaining: Vec<_> = engine.monitors().map(|m| m.id).collect();
+    assert_eq!(remaining, [100, 101]);
+    assert!(!engine.is_active(engine.monitor(101).unwrap()));
+}
+
+#[test]
+fn notify_events_are_chunked_by_message_limits() {
+    let mut engine = three_thresholds();
+    let events = engine.update_value(&evse("EVSE"), &variable("Power"), "35000", at(1));
+    assert_eq!(events.len(), 3);
+    let requests = build_notify_events(
+        DateTimeWrapper::new(at(2)),
+        events,
+        &MessageLimits::new(Some(2), None),
+    )
+    .unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].tbc, Some(true));
+    assert_eq!(requests[1].seq_no, 1);
+    assert_eq!(requests[1].tbc, None);
+}
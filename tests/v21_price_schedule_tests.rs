@@ -0,0 +1,246 @@
+//! ISO 15118-20 absolute price schedule evaluation and tariff conversion.
+
+use chrono::{DateTime, FixedOffset, TimeDelta, TimeZone, Utc};
+use ocpp_rs::v21::datatypes::{AbsolutePriceScheduleType, RationalNumberType, TariffType};
+use ocpp_rs::v21::price_schedule::{POWER_PRICE_ALGORITHM, PriceScheduleOptions};
+use ocpp_rs::v21::tariff::{CostClamp, Timeline};
+
+fn at(minute: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 14, 17, 0, 0).unwrap() + TimeDelta::minutes(minute)
+}
+
+fn close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+}
+
+fn r(value: i32, exponent: i32) -> RationalNumberType {
+    RationalNumberType::new(value, exponent)
+}
+
+#[test]
+fn rational_numbers_from_f64() {
+    assert_eq!(RationalNumberType::from_f64(0.25), Some(r(25, -2)));
+    assert_eq!(RationalNumberType::from_f64(1500.0), Some(r(15, 2)));
+    assert_eq!(RationalNumberType::from_f64(-0.1), Some(r(-1, -1)));
+    assert_eq!(RationalNumberType::from_f64(f64::NAN), None);
+    close(r(1234, -3).to_f64(), 1.234);
+}
+
+#[test]
+fn rational_number_arithmetic_is_checked() {
+    assert_eq!(r(25, -2).checked_add(&r(5, -1)), Some(r(75, -2)));
+    assert_eq!(r(25, -2).checked_mul(&r(4, 0)), Some(r(1, 0)));
+    assert_eq!(r(i32::MAX, 0).checked_mul(&r(3, 0)), None);
+}
+
+#[test]
+fn rational_numbers_with_extreme_exponents() {
+    assert_eq!(r(1, i32::MAX).to_f64(), f64::INFINITY);
+    assert_eq!(r(1, i32::MIN).to_f64(), 0.0);
+    assert_eq!(r(0, i32::MAX).to_f64(), 0.0);
+}
+
+/// Two one-hour stacks (power-ranged in the first), 19 % included VAT on energy, a 2.00
+/// minimum cost and overstay billed after ten minutes.
+fn power_schedule() -> AbsolutePriceScheduleType {
+    serde_json::from_value(serde_json::json!({
+        "timeAnchor": "2025-03-14T17:00:00.000Z",
+        "priceScheduleId": 1,
+        "currency": "EUR",
+        "language": "en",
+        "priceAlgorithm": POWER_PRICE_ALGORITHM,
+        "minimumCost": { "value": 2, "exponent": 0 },
+        "priceRuleStacks": [
+            { "duration": 3600, "priceRule": [
+                { "powerRangeStart": { "value": 0, "exponent": 0 },
+                  "energyFee": { "value": 30, "exponent": -2 } },
+                { "powerRangeStart": { "value": 11, "exponent": 3 },
+                  "energyFee": { "value": 5, "exponent": -1 },
+                  "parkingFee": { "value": 6, "exponent": -1 }, "parkingFeePeriod": 3600 },
+            ]},
+            { "duration": 3600, "priceRule": [
+                { "powerRangeStart": { "value": 0, "exponent": 0 },
+                  "energyFee": { "value": 2, "exponent": -1 } },
+            ]},
+        ],
+        "taxRules": [{
+            "taxRuleId": 7, "taxRuleName": "VAT", "taxIncludedInPrice": true,
+            "appliesToEnergyFee": true, "appliesToParkingFee": false,
+            "appliesToOverstayFee": false, "appliesToMinimumMaximumCost": false,
+            "taxRate": { "value": 19, "exponent": 0 },
+        }],
+        "overstayRuleList": {
+            "overstayPowerThreshold": { "value": 1, "exponent": 0 },
+            "overstayRule": [
+                { "startTime": 0, "overstayFeePeriod": 60, "overstayFee": { "value": 0, "exponent": 0 } },
+                { "startTime": 600, "overstayFeePeriod": 60, "overstayFee": { "value": 1, "exponent": -1 } },
+            ],
+        },
+    }))
+    .unwrap()
+}
+
+/// 22 kW for half an hour, 3 kW across the stack change, then half an hour idle.
+fn session() -> Timeline {
+    let mut timeline = Timeline::new(FixedOffset::east_opt(0).unwrap());
+    timeline.charging(at(0), at(30), 11_000.0); // 22 kW
+    timeline.charging(at(30), at(90), 3_000.0); // 3 kW, across the stack change
+    timeline.idle(at(90), at(120));
+    timeline
+}
+
+/// Energy cost of [`session`] including tax.
+const GROSS_ENERGY: f64 = 5.5 + 0.45 + 0.30;
+
+#[test]
+fn price_lines_follow_power_ranges_and_stacks() {
+    let price = power_schedule().price(&session());
+    let lines: Vec<_> = price.lines.iter().map(|l| (l.stack, l.rule)).collect();
+    assert_eq!(lines, [(0, 1), (0, 0), (1, 0)]);
+    close(price.lines[0].parking_fee, 0.30);
+    close(price.parking_fee, 0.30);
+    assert_eq!(price.clamped, None);
+    close(price.uncovered, 0.0);
+}
+
+#[test]
+fn included_tax_is_taken_out_of_the_energy_fee() {
+    let price = power_schedule().price(&session());
+    close(price.energy_fee, GROSS_ENERGY / 1.19);
+    assert_eq!(price.taxes.len(), 1);
+    assert_eq!(price.taxes[0].tax_rule_id, 7);
+    close(price.incl_tax, GROSS_ENERGY + 0.30 + 2.0);
+}
+
+#[test]
+fn overstay_is_free_for_ten_minutes() {
+    let price = power_schedule().price(&session());
+    // Ten minutes of overstay are free, then 0.10 per minute.
+    close(price.overstay[1].seconds, 1200.0);
+    close(price.overstay_fee, 2.0);
+}
+
+#[test]
+fn short_session_is_raised_to_the_minimum_cost() {
+    let mut short = Timeline::new(FixedOffset::east_opt(0).unwrap());
+    short.charging(at(0), at(10), 1_000.0);
+    let price = power_schedule().price(&short);
+    assert_eq!(price.clamped, Some(CostClamp::MinCost));
+    close(price.excl_tax, 2.0);
+}
+
+/// Energy priced by power and time of day, VAT, idle fee after ten minutes and a fixed fee.
+fn tariff() -> TariffType {
+    serde_json::from_value(serde_json::json!({
+        "tariffId": "T1",
+        "currency": "EUR",
+        "energy": {
+            "prices": [
+                { "priceKwh": 0.50, "conditions": { "minPower": 22000.0 } },
+                { "priceKwh": 0.40, "conditions": { "startTimeOfDay": "08:00", "endTimeOfDay": "20:00" } },
+                { "priceKwh": 0.25 },
+            ],
+            "taxRates": [{ "type": "VAT", "tax": 19.0 }],
+        },
+        "idleTime": { "prices": [{ "priceMinute": 0.10, "conditions": { "minIdleTime": 600 } }] },
+        "fixedFee": { "prices": [{ "priceFixed": 1.0 }] },
+    }))
+    .unwrap()
+}
+
+/// [`tariff`] converted for six hours from 17:00.
+fn converted() -> AbsolutePriceScheduleType {
+    let mut options = PriceScheduleOptions::new(at(0), 3);
+    options.duration = 6 * 3600;
+    tariff().to_absolute_price_schedule(&options)
+}
+
+#[test]
+fn conversion_stacks_follow_the_time_of_day() {
+    let schedule = converted();
+    assert_eq!(schedule.price_algorithm, POWER_PRICE_ALGORITHM);
+    let stacks: Vec<_> = schedule
+        .price_rule_stacks
+        .iter()
+        .map(|s| {
+            let rules: Vec<_> = s
+                .price_rule
+                .iter()
+                .map(|r| (r.power_range_start.to_f64(), r.energy_fee.to_f64()))
+                .collect();
+            (s.duration, rules)
+        })
+        .collect();
+    assert_eq!(
+        stacks,
+        [
+            (3 * 3600, vec![(0.0, 0.40), (22_000.0, 0.50)]),
+            (3 * 3600, vec![(0.0, 0.25), (22_000.0, 0.50)]),
+        ]
+    );
+}
+
+#[test]
+fn conversion_keeps_idle_fees_and_taxes() {
+    let schedule = converted();
+    let overstay = schedule.overstay_rule_list.as_ref().unwrap();
+    let rules: Vec<_> = overstay
+        .overstay_rule
+        .iter()
+        .map(|r| (r.start_time, r.overstay_fee.to_f64()))
+        .collect();
+    assert_eq!(rules, [(0, 0.0), (600, 0.10)]);
+    let tax = &schedule.tax_rules.as_ref().unwrap()[0];
+    assert!(tax.applies_to_energy_fee && !tax.applies_to_parking_fee);
+}
+
+#[test]
+fn converted_schedule_prices_like_the_tariff() {
+    // A 19:30–20:30 session.
+    let mut timeline = Timeline::new(FixedOffset::east_opt(0).unwrap());
+    timeline.charging(at(150), at(210), 6_000.0);
+    let cost = tariff().cost(&timeline);
+    let price = converted().price(&timeline);
+    close(cost.excl_tax, 1.95 + 1.0);
+    close(price.excl_tax, cost.excl_tax);
+    close(price.incl_tax, cost.incl_tax);
+}
+
+#[test]
+fn overstay_rule_change_after_float_accumulated_idle_time() {
+    let schedule: AbsolutePriceScheduleType = serde_json::from_value(serde_json::json!({
+        "timeAnchor": "2025-03-14T17:00:00.000Z",
+        "priceScheduleId": 1,
+        "currency": "EUR",
+        "language": "en",
+        "priceAlgorithm": POWER_PRICE_ALGORITHM,
+        "priceRuleStacks": [
+            { "duration": 3600, "priceRule": [
+                { "powerRangeStart": { "value": 0, "exponent": 0 },
+                  "energyFee": { "value": 0, "exponent": 0 } },
+            ]},
+        ],
+        "overstayRuleList": {
+            "overstayTimeThreshold": 0,
+            "overstayRule": [
+                { "startTime": 0, "overstayFeePeriod": 1, "overstayFee": { "value": 0, "exponent": 0 } },
+                { "startTime": 1, "overstayFeePeriod": 1, "overstayFee": { "value": 1, "exponent": 0 } },
+            ],
+        },
+    }))
+    .unwrap();
+    let ms = |n: i64| at(0) + TimeDelta::milliseconds(n);
+    let mut timeline = Timeline::new(FixedOffset::east_opt(0).unwrap());
+    // Ten 100 ms periods, then one more second.
+    for i in 0..10 {
+        timeline.idle(ms(100 * i), ms(100 * (i + 1)));
+    }
+    timeline.idle(ms(1000), ms(2000));
+
+    let price = schedule.price(&timeline);
+    let overstay: Vec<_> = price.overstay.iter().map(|l| l.rule).collect();
+    assert_eq!(overstay, [0, 1]);
+    close(price.overstay[0].seconds, 1.0);
+    close(price.overstay[1].seconds, 1.0);
+    close(price.overstay_fee, 1.0);
+}
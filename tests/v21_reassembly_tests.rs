@@ -0,0 +1,390 @@
+//! CSMS-side reassembly of `tbc`-streamed reports.
+
+mod common;
+
+use chrono::TimeDelta;
+use common::at;
+use ocpp_rs::v21::datatypes::{ComponentType, DateTimeWrapper, EVSEType, VariableType};
+use ocpp_rs::v21::enumerations::AttributeEnumType;
+use ocpp_rs::v21::messages::notify_customer_information::NotifyCustomerInformationRequest;
+use ocpp_rs::v21::messages::notify_display_messages::NotifyDisplayMessagesRequest;
+use ocpp_rs::v21::messages::notify_monitoring_report::NotifyMonitoringReportRequest;
+use ocpp_rs::v21::messages::notify_report::{
+    NotifyReportRequest, ReportDataType, VariableAttributeType,
+};
+use ocpp_rs::v21::reassembly::{
+    CompletedReport, FragmentOutcome, Inventory, MAX_FRAGMENTS, MonitoringInventory, Reassembler,
+};
+
+fn item(component: &str, evse: Option<i32>, variable: &str, value: &str) -> ReportDataType {
+    ReportDataType {
+        component: ComponentType {
+            evse: evse.map(|id| EVSEType {
+                id,
+                connector_id: None,
+                custom_data: None,
+            }),
+            name: component.into(),
+            instance: None,
+            custom_data: None,
+        },
+        variable: VariableType {
+            name: variable.into(),
+            instance: None,
+            custom_data: None,
+        },
+        variable_attribute: vec![VariableAttributeType {
+            type_: Some(AttributeEnumType::Actual),
+            value: Some(value.into()),
+            mutability: None,
+            persistent: None,
+            constant: None,
+            custom_data: None,
+        }],
+        variable_characteristics: None,
+        custom_data: None,
+    }
+}
+
+fn report(
+    request_id: i32,
+    seq_no: i32,
+    tbc: bool,
+    data: Vec<ReportDataType>,
+) -> NotifyReportRequest {
+    NotifyReportRequest {
+        request_id,
+        generated_at: DateTimeWrapper::new(at(0)),
+        report_data: Some(data),
+        tbc: tbc.then_some(true),
+        seq_no,
+        custom_data: None,
+    }
+}
+
+/// Fragments 0, 1 and 2 (final) of report 1.
+fn three_fragments() -> [NotifyReportRequest; 3] {
+    [
+        report(
+            1,
+            0,
+            true,
+            vec![
+                item("OCPPCommCtrlr", None, "HeartbeatInterval", "300"),
+                item("EVSE", Some(1), "Power", "11000"),
+            ],
+        ),
+        report(1, 1, true, vec![item("EVSE", Some(1), "Available", "true")]),
+        report(1, 2, false, vec![item("EVSE", Some(2), "Power", "22000")]),
+    ]
+}
+
+fn completed() -> CompletedReport<ReportDataType> {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    let [first, middle, last] = three_fragments();
+    r.push(last, at(0));
+    r.push(first, at(1));
+    let FragmentOutcome::Complete(done) = r.push(middle, at(3)) else {
+        panic!("expected complete report");
+    };
+    done
+}
+
+#[test]
+fn out_of_order_fragments_report_what_is_missing() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    let [first, _, last] = three_fragments();
+    assert_eq!(
+        r.push(last, at(0)),
+        FragmentOutcome::Pending {
+            missing: vec![0, 1]
+        }
+    );
+    assert_eq!(
+        r.push(first, at(1)),
+        FragmentOutcome::Pending { missing: vec![1] }
+    );
+    assert_eq!(r.missing(1), Some(vec![1]));
+}
+
+#[test]
+fn duplicate_fragments_are_flagged() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    let [first, _, last] = three_fragments();
+    r.push(last.clone(), at(0));
+    r.push(first.clone(), at(1));
+    assert_eq!(r.push(first, at(2)), FragmentOutcome::Duplicate);
+    assert_eq!(r.push(last, at(2)), FragmentOutcome::Duplicate);
+}
+
+#[test]
+fn second_final_fragment_is_invalid() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    let [_, _, last] = three_fragments();
+    r.push(last, at(0));
+    assert_eq!(
+        r.push(report(1, 1, false, vec![]), at(2)),
+        FragmentOutcome::Invalid
+    );
+    assert_eq!(r.missing(1), Some(vec![0, 1]));
+}
+
+#[test]
+fn completed_report_keeps_seq_no_order() {
+    let done = completed();
+    assert_eq!(done.fragments, 3);
+    let names: Vec<_> = done
+        .items
+        .iter()
+        .map(|d| d.variable.name.as_str())
+        .collect();
+    assert_eq!(names, ["HeartbeatInterval", "Power", "Available", "Power"]);
+}
+
+#[test]
+fn completed_report_is_no_longer_pending() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    for fragment in three_fragments() {
+        r.push(fragment, at(0));
+    }
+    assert!(!r.is_pending(1));
+    assert_eq!(r.pending_len(), 0);
+}
+
+#[test]
+fn report_groups_into_inventory() {
+    let inventory = Inventory::from(completed());
+    assert_eq!(inventory.components.len(), 3);
+    assert_eq!(inventory.variable_count(), 4);
+    assert_eq!(inventory.components_named("evse").count(), 2);
+    let evse1 = inventory
+        .component(&item("EVSE", Some(1), "", "").component)
+        .unwrap();
+    assert_eq!(
+        evse1
+            .variable("power", None)
+            .unwrap()
+            .value(&AttributeEnumType::Actual),
+        Some("11000")
+    );
+}
+
+#[test]
+fn single_fragment_completes_at_once() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    assert!(matches!(
+        r.push(report(5, 0, false, vec![]), at(0)),
+        FragmentOutcome::Complete(c) if c.items.is_empty() && c.fragments == 1
+    ));
+    assert_eq!(r.pending_len(), 0);
+}
+
+#[test]
+fn negative_seq_no_is_rejected() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    assert_eq!(
+        r.push(report(6, -1, true, vec![]), at(0)),
+        FragmentOutcome::Invalid
+    );
+    assert_eq!(r.pending_len(), 0);
+}
+
+#[test]
+fn fragment_past_the_final_is_rejected() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    r.push(report(7, 1, false, vec![]), at(0));
+    assert_eq!(
+        r.push(report(7, 3, true, vec![]), at(0)),
+        FragmentOutcome::Invalid
+    );
+    assert_eq!(r.missing(7), Some(vec![0]));
+}
+
+#[test]
+fn seq_no_above_the_cap_is_rejected() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    assert_eq!(
+        r.push(report(1, i32::MAX, false, vec![]), at(0)),
+        FragmentOutcome::Invalid
+    );
+    assert_eq!(
+        r.push(report(1, MAX_FRAGMENTS, true, vec![]), at(0)),
+        FragmentOutcome::Invalid
+    );
+    assert_eq!(r.pending_len(), 0);
+
+    let FragmentOutcome::Pending { missing } =
+        r.push(report(1, MAX_FRAGMENTS - 1, false, vec![]), at(0))
+    else {
+        panic!("expected pending report");
+    };
+    assert_eq!(missing.len(), usize::try_from(MAX_FRAGMENTS - 1).unwrap());
+}
+
+#[test]
+fn final_fragment_below_a_received_seq_no_is_rejected() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    r.push(report(1, 5, true, vec![]), at(0));
+    assert_eq!(
+        r.push(report(1, 3, false, vec![]), at(0)),
+        FragmentOutcome::Invalid
+    );
+    assert_eq!(r.missing(1), Some(vec![0, 1, 2, 3, 4]));
+}
+
+#[test]
+fn retransmitted_fragments_of_a_completed_report_are_ignored() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    r.push(report(1, 0, true, vec![]), at(0));
+    assert!(matches!(
+        r.push(report(1, 1, false, vec![]), at(5)),
+        FragmentOutcome::Complete(_)
+    ));
+    assert_eq!(
+        r.push(report(1, 1, false, vec![]), at(10)),
+        FragmentOutcome::AlreadyComplete
+    );
+    assert_eq!(
+        r.push(report(1, 0, true, vec![]), at(35)),
+        FragmentOutcome::AlreadyComplete
+    );
+    assert_eq!(r.pending_len(), 0);
+}
+
+#[test]
+fn completed_request_ids_are_forgotten_after_the_timeout() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    r.push(report(1, 0, false, vec![]), at(0));
+    r.push(report(2, 0, false, vec![]), at(0));
+    assert!(r.expire(at(31)).is_empty());
+
+    assert!(matches!(
+        r.push(report(1, 0, false, vec![]), at(31)),
+        FragmentOutcome::Complete(_)
+    ));
+    assert!(matches!(
+        r.push(report(2, 0, true, vec![]), at(36)),
+        FragmentOutcome::Pending { .. }
+    ));
+}
+
+#[test]
+fn stalled_reports_expire_with_gaps() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    r.push(report(1, 0, true, vec![item("A", None, "X", "1")]), at(0));
+    r.push(report(1, 2, true, vec![item("A", None, "Y", "2")]), at(10));
+    r.push(report(2, 0, true, vec![]), at(35));
+
+    assert!(r.expire(at(40)).is_empty());
+    let expired = r.expire(at(41));
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].request_id, 1);
+    assert_eq!(expired[0].missing, vec![1]);
+    assert!(!expired[0].final_received);
+    assert_eq!(expired[0].items.len(), 2);
+    assert!(r.is_pending(2));
+}
+
+fn monitoring_fragment(
+    seq_no: i32,
+    tbc: bool,
+    evse: i32,
+    id: i32,
+    value: f64,
+) -> NotifyMonitoringReportRequest {
+    serde_json::from_value(serde_json::json!({
+        "requestId": 3,
+        "seqNo": seq_no,
+        "tbc": tbc,
+        "generatedAt": "2024-01-01T00:00:00Z",
+        "monitor": [{
+            "component": {"name": "EVSE", "evse": {"id": evse}},
+            "variable": {"name": "Power"},
+            "variableMonitoring": [{
+                "id": id, "transaction": false, "value": value,
+                "type": "UpperThreshold", "severity": 4, "eventNotificationType": "CustomMonitor"
+            }]
+        }]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn monitoring_report_completes_in_seq_no_order() {
+    let mut monitors = Reassembler::new(TimeDelta::seconds(30));
+    monitors.push(monitoring_fragment(1, false, 1, 11, 20_000.0), at(0));
+    let FragmentOutcome::Complete(done) =
+        monitors.push(monitoring_fragment(0, true, 1, 10, 20_000.0), at(0))
+    else {
+        panic!("expected complete report");
+    };
+    let ids: Vec<_> = done
+        .items
+        .iter()
+        .map(|m| m.variable_monitoring[0].id)
+        .collect();
+    assert_eq!(ids, [10, 11]);
+}
+
+#[test]
+fn customer_information_chunks_join_into_text() {
+    let mut info = Reassembler::<NotifyCustomerInformationRequest>::new(TimeDelta::seconds(30));
+    let chunk = |seq_no: i32, tbc: bool, data: &str| NotifyCustomerInformationRequest {
+        data: data.into(),
+        tbc: Some(tbc),
+        seq_no,
+        generated_at: DateTimeWrapper::new(at(0)),
+        request_id: 9,
+        custom_data: None,
+    };
+    info.push(chunk(1, false, "world"), at(0));
+    let FragmentOutcome::Complete(text) = info.push(chunk(0, true, "hello "), at(0)) else {
+        panic!("expected complete report");
+    };
+    assert_eq!(text.text(), "hello world");
+}
+
+#[test]
+fn display_messages_complete_without_seq_no() {
+    let mut display = Reassembler::<NotifyDisplayMessagesRequest>::new(TimeDelta::seconds(30));
+    let messages = |tbc: bool, id: i32| {
+        serde_json::from_value::<NotifyDisplayMessagesRequest>(serde_json::json!({
+            "requestId": 4,
+            "tbc": tbc,
+            "messageInfo": [{
+                "id": id, "priority": "NormalCycle",
+                "message": {"format": "UTF8", "content": "hi"}
+            }]
+        }))
+        .unwrap()
+    };
+    assert_eq!(
+        display.push(messages(true, 1), at(0)),
+        FragmentOutcome::Pending { missing: vec![] }
+    );
+    let FragmentOutcome::Complete(done) = display.push(messages(false, 2), at(0)) else {
+        panic!("expected complete report");
+    };
+    assert_eq!(done.items.iter().map(|m| m.id).collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn monitoring_report_groups_into_inventory() {
+    let mut r = Reassembler::new(TimeDelta::seconds(30));
+    r.push(monitoring_fragment(0, true, 1, 10, 20_000.0), at(0));
+    r.push(monitoring_fragment(1, true, 2, 11, 11_000.0), at(0));
+    let FragmentOutcome::Complete(done) =
+        r.push(monitoring_fragment(2, false, 1, 10, 22_000.0), at(0))
+    else {
+        panic!("expected complete report");
+    };
+
+    let inventory = MonitoringInventory::from(done);
+    assert_eq!(inventory.components_named("evse").count(), 2);
+    assert_eq!(inventory.monitor_count(), 2);
+    let (component, variable, monitor) = inventory.monitor(10).unwrap();
+    assert_eq!(component.evse.as_ref().map(|e| e.id), Some(1));
+    assert_eq!(variable.name, "Power");
+    assert!((monitor.value - 22_000.0).abs() < f64::EPSILON);
+    assert!(inventory.monitor(12).is_none());
+}
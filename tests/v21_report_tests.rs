@@ -0,0 +1,155 @@
+//! `NotifyReport` generation: filters, criteria and chunking.
+
+use chrono::{TimeZone, Utc};
+use ocpp_rs::errors::Error;
+use ocpp_rs::v21::call::{Action, Call};
+use ocpp_rs::v21::datatypes::{
+    ComponentType, ComponentVariableType, DateTimeWrapper, EVSEType, VariableType,
+};
+use ocpp_rs::v21::enumerations::AttributeEnumType;
+use ocpp_rs::v21::messages::get_report::{ComponentCriterionEnumType, GetReportRequest};
+use ocpp_rs::v21::messages::notify_report::{ReportDataType, VariableAttributeType};
+use ocpp_rs::v21::parse::{self, Message};
+use ocpp_rs::v21::report::{MessageLimits, build_notify_reports, filter_report_data};
+
+fn item(component: &str, evse: Option<i32>, variable: &str, value: &str) -> ReportDataType {
+    ReportDataType {
+        component: ComponentType {
+            evse: evse.map(|id| EVSEType {
+                id,
+                connector_id: None,
+                custom_data: None,
+            }),
+            name: component.into(),
+            instance: None,
+            custom_data: None,
+        },
+        variable: VariableType {
+            name: variable.into(),
+            instance: None,
+            custom_data: None,
+        },
+        variable_attribute: vec![VariableAttributeType {
+            type_: Some(AttributeEnumType::Actual),
+            value: Some(value.into()),
+            mutability: None,
+            persistent: None,
+            constant: None,
+            custom_data: None,
+        }],
+        variable_characteristics: None,
+        custom_data: None,
+    }
+}
+
+fn inventory() -> Vec<ReportDataType> {
+    vec![
+        item("OCPPCommCtrlr", None, "HeartbeatInterval", "300"),
+        item("EVSE", Some(1), "Available", "true"),
+        item("EVSE", Some(1), "Power", "11000"),
+        item("EVSE", Some(2), "Available", "false"),
+        item("EVSE", Some(2), "Problem", "true"),
+        item("EVSE", Some(2), "Power", "22000"),
+    ]
+}
+
+fn at() -> DateTimeWrapper {
+    DateTimeWrapper::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+}
+
+fn wire_len(req: &ocpp_rs::v21::messages::notify_report::NotifyReportRequest) -> usize {
+    let call = Call::new("0".repeat(36), Action::NotifyReport(req.clone()));
+    parse::serialize_message(&Message::Call(call))
+        .unwrap()
+        .len()
+}
+
+#[test]
+fn chunks_by_items_with_seq_and_tbc() {
+    let reports =
+        build_notify_reports(7, at(), inventory(), &MessageLimits::new(Some(4), None)).unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].seq_no, 0);
+    assert_eq!(reports[0].tbc, Some(true));
+    assert_eq!(reports[0].report_data.as_ref().unwrap().len(), 4);
+    assert_eq!(reports[1].seq_no, 1);
+    assert_eq!(reports[1].tbc, None);
+    assert_eq!(reports[1].report_data.as_ref().unwrap().len(), 2);
+    assert!(reports.iter().all(|r| r.request_id == 7));
+}
+
+#[test]
+fn chunks_by_bytes_measured_on_wire_frame() {
+    let unlimited = build_notify_reports(1, at(), inventory(), &MessageLimits::default()).unwrap();
+    assert_eq!(unlimited.len(), 1);
+    let full = wire_len(&unlimited[0]);
+
+    let max = full / 2;
+    let reports =
+        build_notify_reports(1, at(), inventory(), &MessageLimits::new(None, Some(max))).unwrap();
+    assert!(reports.len() >= 2);
+    for r in &reports {
+        assert!(wire_len(r) <= max, "{} > {max}", wire_len(r));
+    }
+    let total: usize = reports
+        .iter()
+        .map(|r| r.report_data.as_ref().unwrap().len())
+        .sum();
+    assert_eq!(total, 6);
+
+    let err = build_notify_reports(1, at(), inventory(), &MessageLimits::new(None, Some(150)))
+        .unwrap_err();
+    assert!(matches!(err, Error::ConstraintViolation(_)));
+    assert!(
+        build_notify_reports(1, at(), Vec::new(), &MessageLimits::default())
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn get_report_filters_and_criteria() {
+    let evse_filter = GetReportRequest {
+        component_variable: Some(vec![ComponentVariableType {
+            component: ComponentType {
+                evse: None,
+                name: "evse".into(),
+                instance: None,
+                custom_data: None,
+            },
+            variable: Some(VariableType {
+                name: "Power".into(),
+                instance: None,
+                custom_data: None,
+            }),
+            custom_data: None,
+        }]),
+        request_id: 1,
+        component_criteria: None,
+        custom_data: None,
+    };
+    let power = filter_report_data(inventory(), &evse_filter);
+    assert_eq!(power.len(), 2);
+    assert!(power.iter().all(|d| d.variable.name == "Power"));
+
+    let problems = GetReportRequest {
+        component_variable: None,
+        request_id: 2,
+        component_criteria: Some(vec![ComponentCriterionEnumType::Problem]),
+        custom_data: None,
+    };
+    let problem = filter_report_data(inventory(), &problems);
+    assert_eq!(problem.len(), 3);
+    assert!(
+        problem
+            .iter()
+            .all(|d| d.component.evse.as_ref().unwrap().id == 2)
+    );
+
+    let available = GetReportRequest {
+        component_criteria: Some(vec![ComponentCriterionEnumType::Available]),
+        ..problems
+    };
+    // EVSE 2 reports Available=false; the comm controller has no Available variable.
+    assert_eq!(filter_report_data(inventory(), &available).len(), 3);
+}
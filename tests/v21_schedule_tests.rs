@@ -0,0 +1,103 @@
+//! 2.1 `ChargingScheduleType` time queries.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use ocpp_rs::v21::datatypes::ChargingScheduleType;
+
+fn t0() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap()
+}
+
+fn schedule(unit: &str) -> ChargingScheduleType {
+    serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "startSchedule": "2024-03-01T08:00:00Z",
+        "duration": 3600,
+        "chargingRateUnit": unit,
+        "chargingSchedulePeriod": [
+            { "startPeriod": 0, "limit": 10.0, "limitL2": 20.0, "limitL3": 30.0 },
+            { "startPeriod": 1800, "setpoint": 5.0 },
+        ],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn limit_next_change_and_energy() {
+    let s = schedule("A");
+    let relative_start = t0() - TimeDelta::hours(5);
+    let at = |secs| t0() + TimeDelta::seconds(secs);
+    assert_eq!(s.limit_at(relative_start, at(10)), Some(10.0));
+    // A period without a limit.
+    assert_eq!(s.limit_at(relative_start, at(1800)), None);
+    assert!(s.period_at(relative_start, at(1800)).is_some());
+    assert_eq!(s.limit_at(relative_start, at(3600)), None);
+    assert_eq!(s.next_change(relative_start, at(10)), Some(at(1800)));
+    assert_eq!(s.next_change(relative_start, at(1800)), Some(at(3600)));
+
+    let periods: Vec<_> = s
+        .effective_periods(relative_start)
+        .map(|p| (p.start, p.end))
+        .collect();
+    assert_eq!(
+        periods,
+        [(at(0), Some(at(1800))), (at(1800), Some(at(3600)))]
+    );
+
+    // (10 + 20 + 30) A x 230 V for half an hour; the second period adds nothing.
+    let wh = s.max_energy(relative_start, at(-600), at(7200), 230.0);
+    assert!((wh - 6900.0).abs() < 1e-9, "{wh}");
+    let watts = schedule("W").max_energy(relative_start, at(900), at(1800), 230.0);
+    assert!((watts - 2.5).abs() < 1e-9, "{watts}");
+}
+
+#[test]
+fn unordered_periods_and_empty_schedule() {
+    let s: ChargingScheduleType = serde_json::from_value(serde_json::json!({
+        "id": 2,
+        "duration": 1200,
+        "chargingRateUnit": "W",
+        "chargingSchedulePeriod": [
+            { "startPeriod": 600, "limit": 2000.0 },
+            { "startPeriod": 0, "limit": 1000.0 },
+        ],
+    }))
+    .unwrap();
+    let at = |secs| t0() + TimeDelta::seconds(secs);
+    assert_eq!(s.limit_at(t0(), at(300)), Some(1000.0));
+    assert_eq!(s.limit_at(t0(), at(900)), Some(2000.0));
+    assert_eq!(s.next_change(t0(), at(0)), Some(at(600)));
+    assert_eq!(s.next_change(t0(), at(600)), Some(at(1200)));
+    let wh = s.max_energy(t0(), at(0), at(3600), 230.0);
+    assert!((wh - 500.0).abs() < 1e-9, "{wh}");
+
+    let mut empty = s;
+    empty.charging_schedule_period.clear();
+    assert_eq!(empty.effective_periods(t0()).count(), 0);
+    assert_eq!(empty.limit_at(t0(), at(0)), None);
+    assert_eq!(empty.next_change(t0(), at(0)), None);
+    assert!(empty.max_energy(t0(), at(0), at(3600), 230.0) == 0.0);
+}
+
+#[test]
+fn amps_and_watts_schedules_agree_after_conversion() {
+    let amps: ChargingScheduleType = serde_json::from_value(serde_json::json!({
+        "id": 3,
+        "chargingRateUnit": "A",
+        "chargingSchedulePeriod": [{ "startPeriod": 0, "limit": 16.0, "numberPhases": 1 }],
+    }))
+    .unwrap();
+    let watts: ChargingScheduleType = serde_json::from_value(serde_json::json!({
+        "id": 4,
+        "chargingRateUnit": "W",
+        "chargingSchedulePeriod": [{ "startPeriod": 0, "limit": 3680.0, "numberPhases": 1 }],
+    }))
+    .unwrap();
+    let hour = t0() + TimeDelta::hours(1);
+    // The limits themselves stay in their own unit.
+    assert_eq!(amps.limit_at(t0(), t0()), Some(16.0));
+    assert_eq!(watts.limit_at(t0(), t0()), Some(3680.0));
+    let from_amps = amps.max_energy(t0(), t0(), hour, 230.0);
+    let from_watts = watts.max_energy(t0(), t0(), hour, 230.0);
+    assert!((from_amps - 3680.0).abs() < 1e-9, "{from_amps}");
+    assert!((from_amps - from_watts).abs() < 1e-9);
+}
@@ -0,0 +1,167 @@
+//! 2.1 tariff cost engine.
+
+use chrono::{DateTime, FixedOffset, TimeDelta, TimeZone, Utc};
+use ocpp_rs::v21::datatypes::TariffType;
+use ocpp_rs::v21::tariff::{CostClamp, CostDimension, Timeline};
+
+fn at(minute: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 14, 17, 0, 0).unwrap() + TimeDelta::minutes(minute)
+}
+
+fn tariff(json: serde_json::Value) -> TariffType {
+    serde_json::from_value(json).unwrap()
+}
+
+fn close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+}
+
+/// Peak/off-peak energy with VAT, idle fee after ten minutes, taxed fixed fee and a max cost.
+fn peak_tariff() -> TariffType {
+    tariff(serde_json::json!({
+        "tariffId": "T1",
+        "currency": "EUR",
+        "energy": {
+            "prices": [
+                { "priceKwh": 0.40, "conditions": { "startTimeOfDay": "08:00", "endTimeOfDay": "20:00" } },
+                { "priceKwh": 0.25 },
+            ],
+            "taxRates": [{ "type": "VAT", "tax": 19.0 }],
+        },
+        "idleTime": { "prices": [{ "priceMinute": 0.10, "conditions": { "minIdleTime": 600 } }] },
+        "fixedFee": { "prices": [{ "priceFixed": 1.0 }], "taxRates": [{ "type": "VAT", "tax": 19.0 }] },
+        "maxCost": { "inclTax": 100.0 },
+    }))
+}
+
+/// 10 kW from 19:30 to 20:30 local time, then half an hour idle.
+fn evening() -> Timeline {
+    let mut timeline = Timeline::new(FixedOffset::east_opt(2 * 3600).unwrap());
+    timeline.charging(at(30), at(90), 10_000.0);
+    timeline.idle(at(90), at(120));
+    timeline
+}
+
+#[test]
+fn energy_price_switches_at_the_time_of_day() {
+    let cost = peak_tariff().cost(&evening());
+    // Half the energy before the 20:00 switch.
+    let energy: Vec<_> = cost
+        .lines
+        .iter()
+        .filter(|l| l.dimension == CostDimension::Energy)
+        .map(|l| (l.element, l.quantity))
+        .collect();
+    assert_eq!(energy.len(), 2);
+    close(energy[0].1, 5.0);
+    close(energy[1].1, 5.0);
+    close(cost.dimension_excl_tax(CostDimension::Energy), 3.25);
+}
+
+#[test]
+fn first_idle_minutes_are_free() {
+    let cost = peak_tariff().cost(&evening());
+    close(cost.dimension_excl_tax(CostDimension::IdleTime), 2.0);
+}
+
+#[test]
+fn fixed_fee_and_taxes_add_up() {
+    let cost = peak_tariff().cost(&evening());
+    assert_eq!(cost.currency, "EUR");
+    assert_eq!(cost.clamped, None);
+    close(cost.dimension_excl_tax(CostDimension::FixedFee), 1.0);
+    close(cost.excl_tax, 6.25);
+    close(cost.incl_tax, 6.25 + (3.25 + 1.0) * 0.19);
+    close(cost.cost_updated("tx-1".into()).total_cost, cost.incl_tax);
+}
+
+/// Energy cheaper after 2 kWh with stacked taxes, reservation fees and a 5.00 minimum cost.
+fn threshold_tariff() -> TariffType {
+    tariff(serde_json::json!({
+        "tariffId": "T2",
+        "currency": "EUR",
+        "energy": {
+            "prices": [
+                { "priceKwh": 0.30, "conditions": { "maxEnergy": 2000.0 } },
+                { "priceKwh": 0.20 },
+            ],
+            "taxRates": [
+                { "type": "federal", "tax": 10.0, "stack": 0 },
+                { "type": "city", "tax": 5.0, "stack": 1 },
+            ],
+        },
+        "reservationTime": { "prices": [{ "priceMinute": 0.05 }] },
+        "reservationFixed": {
+            "prices": [{ "priceFixed": 2.0, "conditions": { "paymentBrand": "VISA" } }],
+        },
+        "minCost": { "exclTax": 5.0 },
+    }))
+}
+
+/// Twenty minutes reserved, then 3 kW for an hour.
+fn reserved_session() -> Timeline {
+    let mut timeline = Timeline::new(FixedOffset::east_opt(0).unwrap());
+    timeline.reservation = Some((at(-20), at(0)));
+    timeline.charging(at(0), at(60), 3000.0);
+    timeline
+}
+
+#[test]
+fn energy_price_changes_past_the_threshold() {
+    let cost = threshold_tariff().cost(&reserved_session());
+    close(
+        cost.dimension_excl_tax(CostDimension::Energy),
+        2.0 * 0.30 + 0.20,
+    );
+}
+
+#[test]
+fn reservation_fees_follow_their_conditions() {
+    let cost = threshold_tariff().cost(&reserved_session());
+    close(cost.dimension_excl_tax(CostDimension::ReservationTime), 1.0);
+    // No payment brand known: the VISA-only fee does not apply.
+    close(
+        cost.dimension_excl_tax(CostDimension::ReservationFixed),
+        0.0,
+    );
+}
+
+#[test]
+fn stacked_taxes_compound() {
+    let cost = threshold_tariff().cost(&reserved_session());
+    let taxes: Vec<f64> = cost
+        .lines
+        .iter()
+        .filter(|l| l.dimension == CostDimension::Energy)
+        .flat_map(|l| l.taxes.iter().map(|t| t.amount))
+        .collect();
+    // 0.60 → 0.06 federal, then 5 % of 0.66; 0.20 → 0.02, then 5 % of 0.22.
+    close(taxes.iter().sum(), 0.06 + 0.033 + 0.02 + 0.011);
+}
+
+#[test]
+fn minimum_cost_scales_the_totals() {
+    let cost = threshold_tariff().cost(&reserved_session());
+    assert_eq!(cost.clamped, Some(CostClamp::MinCost));
+    close(cost.excl_tax, 5.0);
+    close(cost.incl_tax, (0.924 + 1.0) * 5.0 / 1.8);
+}
+
+#[test]
+fn near_zero_energy_period_does_not_overflow() {
+    let tariff = tariff(serde_json::json!({
+        "tariffId": "T3",
+        "currency": "EUR",
+        "energy": {
+            "prices": [
+                { "priceKwh": 0.30, "conditions": { "maxEnergy": 50000.0 } },
+                { "priceKwh": 0.20 },
+            ],
+        },
+    }));
+    let mut timeline = Timeline::new(FixedOffset::east_opt(0).unwrap());
+    timeline.charging(at(0), at(60), 1e-9);
+
+    let cost = tariff.cost(&timeline);
+    close(cost.dimension_excl_tax(CostDimension::Energy), 1e-12 * 0.30);
+}
@@ -0,0 +1,329 @@
+//! 2.1 station tariff store.
+
+use chrono::{DateTime, TimeZone, Utc};
+use ocpp_rs::v21::datatypes::TariffType;
+use ocpp_rs::v21::evse::EvseState;
+use ocpp_rs::v21::messages::authorize::AuthorizeResponse;
+use ocpp_rs::v21::messages::clear_tariffs::{ClearTariffsRequest, TariffClearStatusEnumType};
+use ocpp_rs::v21::messages::get_tariffs::{
+    GetTariffsRequest, TariffGetStatusEnumType, TariffKindEnumType,
+};
+use ocpp_rs::v21::messages::set_default_tariff::{
+    SetDefaultTariffRequest, TariffSetStatusEnumType,
+};
+use ocpp_rs::v21::reason_codes;
+use ocpp_rs::v21::tariff::CostDimension;
+use ocpp_rs::v21::tariffs::{TariffIssue, TariffLimits, TariffStore, check_tariff};
+
+struct Station;
+
+impl EvseState for Station {
+    fn evse_exists(&self, evse_id: i32) -> bool {
+        (1..=2).contains(&evse_id)
+    }
+    fn transaction_id(&self, _evse_id: i32) -> Option<&str> {
+        None
+    }
+    fn awaiting_charging_needs(&self, _evse_id: i32) -> bool {
+        false
+    }
+}
+
+fn at(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap()
+}
+
+fn tariff(json: serde_json::Value) -> TariffType {
+    serde_json::from_value(json).unwrap()
+}
+
+fn simple(id: &str, price: f64) -> TariffType {
+    tariff(serde_json::json!({
+        "tariffId": id,
+        "currency": "EUR",
+        "energy": { "prices": [{ "priceKwh": price }] },
+    }))
+}
+
+fn set(store: &mut TariffStore, evse_id: i32, tariff: TariffType) -> TariffSetStatusEnumType {
+    store
+        .set_default_tariff(
+            &SetDefaultTariffRequest {
+                evse_id,
+                tariff,
+                custom_data: None,
+            },
+            &Station,
+        )
+        .status
+}
+
+#[test]
+fn defaults_by_evse_and_valid_from() {
+    let mut store = TariffStore::default();
+    assert_eq!(
+        set(&mut store, 0, simple("station", 0.30)),
+        TariffSetStatusEnumType::Accepted
+    );
+    assert_eq!(
+        set(&mut store, 1, simple("evse1", 0.35)),
+        TariffSetStatusEnumType::Accepted
+    );
+    let mut later = simple("evse1-april", 0.40);
+    later.valid_from = Some(ocpp_rs::v21::datatypes::DateTimeWrapper::new(at(20)));
+    assert_eq!(set(&mut store, 1, later), TariffSetStatusEnumType::Accepted);
+
+    let id = |t: Option<&TariffType>| t.map(|t| t.tariff_id.clone());
+    assert_eq!(id(store.tariff_for(1, None, at(10))), Some("evse1".into()));
+    assert_eq!(
+        id(store.tariff_for(1, None, at(25))),
+        Some("evse1-april".into())
+    );
+    assert_eq!(
+        id(store.tariff_for(2, None, at(10))),
+        Some("station".into())
+    );
+}
+
+#[test]
+fn default_tariff_on_an_unknown_evse_is_rejected() {
+    let mut store = TariffStore::default();
+    let response = store.set_default_tariff(
+        &SetDefaultTariffRequest {
+            evse_id: 9,
+            tariff: simple("x", 0.1),
+            custom_data: None,
+        },
+        &Station,
+    );
+    assert_eq!(response.status, TariffSetStatusEnumType::Rejected);
+    assert_eq!(
+        response.status_info.unwrap().reason_code,
+        reason_codes::UNKNOWN_EVSE
+    );
+}
+
+fn limited_store() -> TariffStore {
+    TariffStore::new(TariffLimits {
+        max_elements: Some(2),
+        conditions_supported: false,
+        currency: None,
+    })
+}
+
+#[test]
+fn same_tariff_id_needs_the_same_content() {
+    let mut store = limited_store();
+    assert_eq!(
+        set(&mut store, 1, simple("T1", 0.30)),
+        TariffSetStatusEnumType::Accepted
+    );
+    // Same tariff on another EVSE is fine, different content under the same id is not.
+    assert_eq!(
+        set(&mut store, 2, simple("T1", 0.30)),
+        TariffSetStatusEnumType::Accepted
+    );
+    assert_eq!(
+        set(&mut store, 2, simple("T1", 0.31)),
+        TariffSetStatusEnumType::DuplicateTariffId
+    );
+}
+
+#[test]
+fn currency_must_match_installed_tariffs() {
+    let mut store = limited_store();
+    set(&mut store, 1, simple("T1", 0.30));
+    let mut dollars = simple("T2", 0.30);
+    dollars.currency = "USD".into();
+    assert_eq!(
+        set(&mut store, 2, dollars),
+        TariffSetStatusEnumType::Rejected
+    );
+}
+
+#[test]
+fn too_many_price_elements() {
+    let many = tariff(serde_json::json!({
+        "tariffId": "T3",
+        "currency": "EUR",
+        "energy": { "prices": [{ "priceKwh": 0.3 }] },
+        "chargingTime": { "prices": [{ "priceMinute": 0.1 }, { "priceMinute": 0.2 }] },
+    }));
+    assert_eq!(
+        set(&mut limited_store(), 2, many),
+        TariffSetStatusEnumType::TooManyElements
+    );
+}
+
+#[test]
+fn conditions_may_be_unsupported() {
+    let conditional = tariff(serde_json::json!({
+        "tariffId": "T4",
+        "currency": "EUR",
+        "energy": { "prices": [{ "priceKwh": 0.3, "conditions": { "maxEnergy": 1000.0 } }] },
+    }));
+    assert_eq!(
+        set(&mut limited_store(), 2, conditional),
+        TariffSetStatusEnumType::ConditionNotSupported
+    );
+}
+
+#[test]
+fn check_reports_invalid_and_shadowed_elements() {
+    let checked = tariff(serde_json::json!({
+        "tariffId": "T",
+        "currency": "EUR",
+        "energy": { "prices": [
+            { "priceKwh": 0.3 },
+            { "priceKwh": 0.2, "conditions": { "minEnergy": 5000.0, "maxEnergy": 1000.0 } },
+        ] },
+        "fixedFee": { "prices": [{ "priceFixed": 1.0, "conditions": { "startTimeOfDay": "25:00" } }] },
+    }));
+    let issues = check_tariff(&checked, &TariffLimits::default());
+    assert_eq!(
+        issues,
+        vec![
+            TariffIssue::InvalidCondition {
+                dimension: CostDimension::Energy,
+                element: 1,
+            },
+            TariffIssue::ShadowedElement {
+                dimension: CostDimension::Energy,
+                element: 1,
+            },
+            TariffIssue::InvalidCondition {
+                dimension: CostDimension::FixedFee,
+                element: 0,
+            },
+        ]
+    );
+    assert!(!issues[1].is_fatal());
+
+    let empty = tariff(serde_json::json!({ "tariffId": "E", "currency": "EUR" }));
+    assert_eq!(
+        check_tariff(&empty, &TariffLimits::default()),
+        vec![TariffIssue::NoPriceElement]
+    );
+}
+
+/// Defaults `station` (EVSE 0) and `evse1`, and driver tariff `driver` for `TOKEN`.
+fn with_driver_tariff() -> TariffStore {
+    let mut store = TariffStore::default();
+    set(&mut store, 0, simple("station", 0.30));
+    set(&mut store, 1, simple("evse1", 0.35));
+    let authorize: AuthorizeResponse = serde_json::from_value(serde_json::json!({
+        "idTokenInfo": { "status": "Accepted" },
+        "tariff": { "tariffId": "driver", "currency": "EUR",
+                    "energy": { "prices": [{ "priceKwh": 0.20 }] } },
+    }))
+    .unwrap();
+    store.apply_authorize_response("TOKEN", &authorize).unwrap();
+    store
+}
+
+fn get(
+    store: &TariffStore,
+    evse_id: i32,
+) -> ocpp_rs::v21::messages::get_tariffs::GetTariffsResponse {
+    store.get_tariffs(
+        &GetTariffsRequest {
+            evse_id,
+            custom_data: None,
+        },
+        &Station,
+    )
+}
+
+fn clear(
+    store: &mut TariffStore,
+    tariff_ids: Option<Vec<String>>,
+) -> Vec<TariffClearStatusEnumType> {
+    store
+        .clear_tariffs(&ClearTariffsRequest {
+            tariff_ids,
+            evse_id: None,
+            custom_data: None,
+        })
+        .clear_tariffs_result
+        .into_iter()
+        .map(|r| r.status)
+        .collect()
+}
+
+#[test]
+fn driver_tariff_wins_for_its_token() {
+    let store = with_driver_tariff();
+    assert_eq!(
+        store
+            .tariff_for(1, Some("TOKEN"), at(10))
+            .unwrap()
+            .tariff_id,
+        "driver"
+    );
+    assert_eq!(
+        store
+            .tariff_for(1, Some("OTHER"), at(10))
+            .unwrap()
+            .tariff_id,
+        "evse1"
+    );
+}
+
+#[test]
+fn driver_tariff_cannot_reuse_a_default_tariff_id() {
+    let mut store = with_driver_tariff();
+    assert_eq!(
+        store.set_driver_tariff("OTHER", simple("evse1", 0.10)),
+        Err(vec![TariffIssue::DuplicateTariffId])
+    );
+}
+
+#[test]
+fn get_tariffs_lists_every_assignment() {
+    let all = get(&with_driver_tariff(), 0);
+    assert_eq!(all.status, TariffGetStatusEnumType::Accepted);
+    let assignments = all.tariff_assignments.unwrap();
+    assert_eq!(assignments.len(), 3);
+    assert_eq!(assignments[1].evse_ids, Some(vec![1]));
+    assert_eq!(assignments[2].tariff_kind, TariffKindEnumType::DriverTariff);
+    assert_eq!(assignments[2].id_tokens, Some(vec!["TOKEN".into()]));
+}
+
+#[test]
+fn get_tariffs_for_one_evse() {
+    let ids: Vec<_> = get(&with_driver_tariff(), 2)
+        .tariff_assignments
+        .unwrap()
+        .into_iter()
+        .map(|a| a.tariff_id)
+        .collect();
+    assert_eq!(ids, vec!["station"]);
+}
+
+#[test]
+fn clear_by_id_falls_back_to_the_station_default() {
+    let mut store = with_driver_tariff();
+    assert_eq!(
+        clear(&mut store, Some(vec!["evse1".into(), "missing".into()])),
+        vec![
+            TariffClearStatusEnumType::Accepted,
+            TariffClearStatusEnumType::NoTariff
+        ]
+    );
+    assert_eq!(
+        store.tariff_for(1, None, at(10)).unwrap().tariff_id,
+        "station"
+    );
+}
+
+#[test]
+fn clear_everything() {
+    let mut store = with_driver_tariff();
+    assert_eq!(clear(&mut store, None).len(), 3);
+    assert!(store.tariffs().is_empty());
+    assert_eq!(
+        clear(&mut store, None),
+        [TariffClearStatusEnumType::NoTariff]
+    );
+    assert_eq!(get(&store, 0).status, TariffGetStatusEnumType::NoTariff);
+}
@@ -0,0 +1,530 @@
+//! 2.1 `TransactionEvent` manager and validator.
+
+mod common;
+
+use common::at;
+use ocpp_rs::v21::datatypes::IdTokenType;
+use ocpp_rs::v21::enumerations::{
+    MeasurandEnumType, ReadingContextEnumType, RequestStartStopStatusEnumType,
+};
+use ocpp_rs::v21::messages::request_start_transaction::RequestStartTransactionRequest;
+use ocpp_rs::v21::messages::request_stop_transaction::RequestStopTransactionRequest;
+use ocpp_rs::v21::messages::transaction_event::{
+    ChargingStateEnumType, ReasonEnumType, TransactionEventEnumType, TransactionEventRequest,
+    TransactionEventResponse, TriggerReasonEnumType,
+};
+use ocpp_rs::v21::reason_codes;
+use ocpp_rs::v21::transactions::{
+    TransactionConfig, TransactionEventIssue, TransactionEventValidator, TransactionManager,
+    TxPoint,
+};
+
+fn token(id: &str) -> IdTokenType {
+    IdTokenType {
+        additional_info: None,
+        id_token: id.into(),
+        type_: "ISO14443".into(),
+        custom_data: None,
+    }
+}
+
+fn kinds(
+    events: &[TransactionEventRequest],
+) -> Vec<(TransactionEventEnumType, TriggerReasonEnumType)> {
+    events
+        .iter()
+        .map(|e| (e.event_type.clone(), e.trigger_reason.clone()))
+        .collect()
+}
+
+/// Plug in, authorize, charge on three phases and stop by presenting the token again, with a
+/// periodic meter value in between. Returns the manager (EV still plugged) and the events.
+fn charging_session() -> (TransactionManager, Vec<TransactionEventRequest>) {
+    let config = TransactionConfig {
+        tx_updated_interval: 60,
+        ..TransactionConfig::default()
+    };
+    let mut manager = TransactionManager::new(2, config, "CS1-");
+    manager.set_reading(1, MeasurandEnumType::EnergyActiveImportRegister, 1_000.0);
+
+    manager.ev_connected(1, 1, at(0));
+    manager.authorize(1, token("TAG"), at(5));
+    manager.set_phases(1, 3);
+    manager.set_charging_state(1, ChargingStateEnumType::Charging, at(10));
+    assert_eq!(manager.next_poll(), Some(at(60)));
+    manager.set_reading(1, MeasurandEnumType::EnergyActiveImportRegister, 2_000.0);
+    manager.poll(at(60));
+    manager.set_reading(1, MeasurandEnumType::EnergyActiveImportRegister, 3_500.0);
+    manager.authorize(1, token("TAG"), at(100));
+    let events = manager.take_requests();
+    (manager, events)
+}
+
+#[test]
+fn session_emits_one_numbered_event_stream() {
+    let (manager, events) = charging_session();
+    assert_eq!(
+        kinds(&events),
+        vec![
+            (
+                TransactionEventEnumType::Started,
+                TriggerReasonEnumType::CablePluggedIn
+            ),
+            (
+                TransactionEventEnumType::Updated,
+                TriggerReasonEnumType::Authorized
+            ),
+            (
+                TransactionEventEnumType::Updated,
+                TriggerReasonEnumType::ChargingStateChanged
+            ),
+            (
+                TransactionEventEnumType::Updated,
+                TriggerReasonEnumType::MeterValuePeriodic
+            ),
+            (
+                TransactionEventEnumType::Ended,
+                TriggerReasonEnumType::StopAuthorized
+            ),
+        ]
+    );
+    let seq: Vec<i32> = events.iter().map(|e| e.seq_no).collect();
+    assert_eq!(seq, vec![0, 1, 2, 3, 4]);
+    assert!(
+        events
+            .iter()
+            .all(|e| e.transaction_info.transaction_id == "CS1-1")
+    );
+    assert!(manager.transaction(1).is_none());
+}
+
+#[test]
+fn started_event_carries_evse_and_begin_reading() {
+    let (_, events) = charging_session();
+    let started = &events[0];
+    assert_eq!(
+        started.evse.as_ref().map(|e| (e.id, e.connector_id)),
+        Some((1, Some(1)))
+    );
+    assert_eq!(
+        started.transaction_info.charging_state,
+        Some(ChargingStateEnumType::EVConnected)
+    );
+    let begin = &started.meter_value.as_ref().unwrap()[0].sampled_value[0];
+    assert_eq!(begin.value, 1_000.0);
+    assert_eq!(
+        begin.context,
+        Some(ReadingContextEnumType::TransactionBegin)
+    );
+}
+
+#[test]
+fn updated_events_report_only_changes() {
+    let (_, events) = charging_session();
+    assert_eq!(events[1].id_token.as_ref().unwrap().id_token, "TAG");
+    assert_eq!(events[1].transaction_info.charging_state, None);
+    assert_eq!(
+        events[2].transaction_info.charging_state,
+        Some(ChargingStateEnumType::Charging)
+    );
+    assert_eq!(events[2].number_of_phases_used, Some(3));
+    assert_eq!(events[3].number_of_phases_used, None);
+}
+
+#[test]
+fn periodic_event_carries_the_sampled_reading() {
+    let (_, events) = charging_session();
+    let periodic = &events[3].meter_value.as_ref().unwrap()[0].sampled_value[0];
+    assert_eq!(
+        periodic.context,
+        Some(ReadingContextEnumType::SamplePeriodic)
+    );
+    assert_eq!(periodic.value, 2_000.0);
+}
+
+#[test]
+fn ended_event_carries_reason_charging_time_and_end_reading() {
+    let (_, events) = charging_session();
+    let ended = &events[4];
+    assert_eq!(
+        ended.transaction_info.stopped_reason,
+        Some(ReasonEnumType::Local)
+    );
+    assert_eq!(ended.transaction_info.time_spent_charging, Some(90));
+    let end = &ended.meter_value.as_ref().unwrap()[0].sampled_value[0];
+    assert_eq!(end.context, Some(ReadingContextEnumType::TransactionEnd));
+    assert_eq!(end.value, 3_500.0);
+}
+
+#[test]
+fn session_passes_the_validator() {
+    let (_, events) = charging_session();
+    let mut validator = TransactionEventValidator::new();
+    for event in &events {
+        assert_eq!(validator.check(event), vec![]);
+    }
+    assert_eq!(validator.open_transactions().count(), 0);
+}
+
+#[test]
+fn unplugging_after_the_stop_is_no_event() {
+    let (mut manager, _) = charging_session();
+    manager.ev_disconnected(1, at(110));
+    assert!(manager.take_requests().is_empty());
+}
+
+#[test]
+fn authorized_start_point_and_connection_timeout() {
+    let config = TransactionConfig {
+        tx_start_point: TxPoint::parse_list("Authorized").unwrap(),
+        tx_stop_point: TxPoint::parse_list("EVConnected, Authorized").unwrap(),
+        ev_connection_timeout: 30,
+        ..TransactionConfig::default()
+    };
+    let mut manager = TransactionManager::new(1, config, "T");
+    manager.ev_connected(1, 1, at(0));
+    manager.ev_disconnected(1, at(1));
+    assert!(manager.take_requests().is_empty());
+
+    manager.authorize(1, token("TAG"), at(10));
+    assert_eq!(manager.next_poll(), Some(at(40)));
+    manager.poll(at(40));
+    let events = manager.take_requests();
+    assert_eq!(
+        kinds(&events),
+        vec![
+            (
+                TransactionEventEnumType::Started,
+                TriggerReasonEnumType::Authorized
+            ),
+            (
+                TransactionEventEnumType::Ended,
+                TriggerReasonEnumType::EVConnectTimeout
+            ),
+        ]
+    );
+    assert_eq!(events[0].id_token.as_ref().unwrap().id_token, "TAG");
+    assert_eq!(
+        events[1].transaction_info.stopped_reason,
+        Some(ReasonEnumType::Timeout)
+    );
+}
+
+#[test]
+fn tx_point_list_rejects_unknown_points() {
+    assert_eq!(TxPoint::parse_list("EVConnected,Nope"), None);
+}
+
+fn remote_start(evse_id: Option<i32>, remote_start_id: i32) -> RequestStartTransactionRequest {
+    RequestStartTransactionRequest {
+        evse_id,
+        group_id_token: None,
+        id_token: token("APP"),
+        remote_start_id,
+        charging_profile: None,
+        custom_data: None,
+    }
+}
+
+fn remote_stop(transaction_id: &str) -> RequestStopTransactionRequest {
+    RequestStopTransactionRequest {
+        transaction_id: transaction_id.into(),
+        custom_data: None,
+    }
+}
+
+/// Manager starting transactions on authorization, with one remotely started transaction.
+fn remotely_started() -> TransactionManager {
+    let config = TransactionConfig {
+        tx_start_point: vec![TxPoint::Authorized],
+        ..TransactionConfig::default()
+    };
+    let mut manager = TransactionManager::new(1, config, "R");
+    manager.request_start_transaction(&remote_start(None, 77), at(0));
+    manager
+}
+
+#[test]
+fn remote_start_on_a_free_evse() {
+    let config = TransactionConfig {
+        tx_start_point: vec![TxPoint::Authorized],
+        ..TransactionConfig::default()
+    };
+    let mut manager = TransactionManager::new(1, config, "R");
+    let response = manager.request_start_transaction(&remote_start(None, 77), at(0));
+    assert_eq!(response.status, RequestStartStopStatusEnumType::Accepted);
+    assert_eq!(response.transaction_id.as_deref(), Some("R1"));
+    let events = manager.take_requests();
+    assert_eq!(events[0].trigger_reason, TriggerReasonEnumType::RemoteStart);
+    assert_eq!(events[0].transaction_info.remote_start_id, Some(77));
+}
+
+#[test]
+fn remote_stop_of_an_unknown_transaction_is_rejected() {
+    let mut manager = remotely_started();
+    let unknown = manager.request_stop_transaction(&remote_stop("nope"), at(1));
+    assert_eq!(unknown.status, RequestStartStopStatusEnumType::Rejected);
+    assert_eq!(
+        unknown.status_info.unwrap().reason_code,
+        reason_codes::TX_NOT_FOUND
+    );
+}
+
+#[test]
+fn remote_stop_ends_with_reason_remote() {
+    let mut manager = remotely_started();
+    manager.take_requests();
+    let stopped = manager.request_stop_transaction(&remote_stop("R1"), at(2));
+    assert_eq!(stopped.status, RequestStartStopStatusEnumType::Accepted);
+    let events = manager.take_requests();
+    assert_eq!(events[0].trigger_reason, TriggerReasonEnumType::RemoteStop);
+    assert_eq!(
+        events[0].transaction_info.stopped_reason,
+        Some(ReasonEnumType::Remote)
+    );
+}
+
+/// Offline transaction started by token `BAD`, with the `Invalid` response to its first event.
+fn rejected_token(
+    stop_tx_on_invalid_id: bool,
+) -> (
+    TransactionManager,
+    TransactionEventRequest,
+    TransactionEventResponse,
+) {
+    let config = TransactionConfig {
+        tx_start_point: vec![TxPoint::Authorized],
+        stop_tx_on_invalid_id,
+        ..TransactionConfig::default()
+    };
+    let mut manager = TransactionManager::new(1, config, "R");
+    manager.ev_connected(1, 1, at(3));
+    manager.authorize(1, token("BAD"), at(4));
+    manager.set_offline(true);
+    manager.set_charging_state(1, ChargingStateEnumType::Charging, at(5));
+    let started = manager.take_requests().remove(0);
+    let rejected = serde_json::from_value(serde_json::json!({
+        "idTokenInfo": { "status": "Invalid" },
+    }))
+    .unwrap();
+    (manager, started, rejected)
+}
+
+#[test]
+fn rejected_token_suspends_without_stop_tx_on_invalid_id() {
+    let (mut manager, started, rejected) = rejected_token(false);
+    manager.on_transaction_event_response(&started, &rejected, at(6));
+    let events = manager.take_requests();
+    assert_eq!(
+        events[0].trigger_reason,
+        TriggerReasonEnumType::Deauthorized
+    );
+    assert_eq!(events[0].event_type, TransactionEventEnumType::Updated);
+    assert_eq!(events[0].offline, Some(true));
+    assert_eq!(
+        events[0].transaction_info.charging_state,
+        Some(ChargingStateEnumType::SuspendedEVSE)
+    );
+}
+
+#[test]
+fn rejected_token_ends_with_stop_tx_on_invalid_id() {
+    let (mut manager, started, rejected) = rejected_token(true);
+    manager.on_transaction_event_response(&started, &rejected, at(6));
+    let events = manager.take_requests();
+    assert_eq!(events[0].event_type, TransactionEventEnumType::Ended);
+    assert_eq!(
+        events[0].transaction_info.stopped_reason,
+        Some(ReasonEnumType::DeAuthorized)
+    );
+}
+
+#[test]
+fn remote_start_authorizes_a_transaction_started_by_plugging_in() {
+    let mut manager = TransactionManager::new(1, TransactionConfig::default(), "R");
+    manager.ev_connected(1, 1, at(0));
+    manager.take_requests();
+
+    let response = manager.request_start_transaction(&remote_start(Some(1), 78), at(5));
+    assert_eq!(response.status, RequestStartStopStatusEnumType::Accepted);
+    assert_eq!(response.transaction_id.as_deref(), Some("R1"));
+    let events = manager.take_requests();
+    assert_eq!(
+        kinds(&events),
+        vec![(
+            TransactionEventEnumType::Updated,
+            TriggerReasonEnumType::RemoteStart
+        )]
+    );
+    assert_eq!(
+        events[0].id_token.as_ref().map(|t| t.id_token.as_str()),
+        Some("APP")
+    );
+    assert_eq!(events[0].transaction_info.remote_start_id, Some(78));
+}
+
+#[test]
+fn remote_start_on_an_authorized_evse_is_rejected() {
+    let mut manager = TransactionManager::new(1, TransactionConfig::default(), "R");
+    manager.ev_connected(1, 1, at(0));
+    manager.authorize(1, token("TAG"), at(1));
+    let response = manager.request_start_transaction(&remote_start(None, 79), at(5));
+    assert_eq!(response.status, RequestStartStopStatusEnumType::Rejected);
+    assert_eq!(response.transaction_id, None);
+}
+
+/// Events of transaction V1 (plug, authorize, charge, unplug) and the start of V2.
+fn two_sessions() -> (TransactionManager, Vec<TransactionEventRequest>) {
+    let mut manager = TransactionManager::new(1, TransactionConfig::default(), "V");
+    manager.ev_connected(1, 1, at(0));
+    manager.authorize(1, token("A"), at(1));
+    manager.set_charging_state(1, ChargingStateEnumType::Charging, at(2));
+    manager.ev_disconnected(1, at(3));
+    manager.ev_connected(1, 1, at(4));
+    let events = manager.take_requests();
+    assert_eq!(events.len(), 5);
+    (manager, events)
+}
+
+#[test]
+fn validator_flags_gaps_and_late_events() {
+    let (_, events) = two_sessions();
+    let mut validator = TransactionEventValidator::new();
+    assert!(validator.check(&events[0]).is_empty());
+    // events[1] lost.
+    assert_eq!(
+        validator.check(&events[2]),
+        vec![TransactionEventIssue::SeqNoGap {
+            transaction_id: "V1".into(),
+            expected: 1,
+            found: 2,
+        }]
+    );
+    assert_eq!(
+        validator.check(&events[1]),
+        vec![
+            TransactionEventIssue::SeqNoRepeated {
+                transaction_id: "V1".into(),
+                seq_no: 1,
+            },
+            TransactionEventIssue::TimestampRegression {
+                transaction_id: "V1".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn validator_flags_events_after_ended() {
+    let (_, events) = two_sessions();
+    let mut validator = TransactionEventValidator::new();
+    for event in &events[..4] {
+        assert!(validator.check(event).is_empty());
+    }
+    assert_eq!(
+        validator.check(&events[3]),
+        vec![
+            TransactionEventIssue::EventAfterEnded {
+                transaction_id: "V1".into(),
+            },
+            TransactionEventIssue::SeqNoRepeated {
+                transaction_id: "V1".into(),
+                seq_no: 3,
+            },
+        ]
+    );
+    assert!(validator.check(&events[4]).is_empty());
+    assert_eq!(
+        validator.open_transactions().collect::<Vec<_>>(),
+        vec!["V2"]
+    );
+}
+
+#[test]
+fn validator_flags_a_missing_ended() {
+    let (mut manager, events) = two_sessions();
+    let mut validator = TransactionEventValidator::new();
+    for event in &events {
+        validator.check(event);
+    }
+    // V2 never ends; V3 starts on the same EVSE.
+    manager.ev_disconnected(1, at(5));
+    manager.take_requests();
+    manager.ev_connected(1, 1, at(6));
+    let started = manager.take_requests().remove(0);
+    assert_eq!(
+        validator.check(&started),
+        vec![TransactionEventIssue::MissingEnded {
+            transaction_id: "V2".into(),
+        }]
+    );
+    assert_eq!(
+        validator.open_transactions().collect::<Vec<_>>(),
+        vec!["V3"]
+    );
+}
+
+#[test]
+fn validator_flags_a_missing_started() {
+    let (_, events) = two_sessions();
+    let mut validator = TransactionEventValidator::new();
+    let mut orphan = events[1].clone();
+    orphan.transaction_info.transaction_id = "V9".into();
+    assert_eq!(
+        validator.check(&orphan),
+        vec![
+            TransactionEventIssue::MissingStarted {
+                transaction_id: "V9".into(),
+            },
+            TransactionEventIssue::SeqNoGap {
+                transaction_id: "V9".into(),
+                expected: 0,
+                found: 1,
+            },
+        ]
+    );
+    validator.forget("V9");
+    assert_eq!(validator.open_transactions().count(), 0);
+}
+
+#[test]
+fn validator_checks_first_and_last_seq_no() {
+    let mut manager = TransactionManager::new(1, TransactionConfig::default(), "V");
+    manager.ev_connected(1, 1, at(0));
+    let mut started = manager.take_requests().remove(0);
+
+    // The first event of a transaction is expected with seqNo 0.
+    started.seq_no = 5;
+    let mut validator = TransactionEventValidator::new();
+    assert_eq!(
+        validator.check(&started),
+        vec![TransactionEventIssue::SeqNoGap {
+            transaction_id: "V1".into(),
+            expected: 0,
+            found: 5,
+        }]
+    );
+
+    // seqNo i32::MAX does not overflow; nothing can follow it.
+    let mut validator = TransactionEventValidator::new();
+    started.seq_no = 0;
+    assert!(validator.check(&started).is_empty());
+    let mut last = started.clone();
+    last.event_type = TransactionEventEnumType::Updated;
+    last.seq_no = i32::MAX;
+    assert_eq!(
+        validator.check(&last),
+        vec![TransactionEventIssue::SeqNoGap {
+            transaction_id: "V1".into(),
+            expected: 1,
+            found: i32::MAX,
+        }]
+    );
+    assert_eq!(
+        validator.check(&last),
+        vec![TransactionEventIssue::SeqNoRepeated {
+            transaction_id: "V1".into(),
+            seq_no: i32::MAX,
+        }]
+    );
+}
@@ -168,18 +168,136 @@ def gen_matrix() -> str:
     return "\n".join(lines)
 
 
+SNAKE_OVERRIDES = {"CHAdeMOCtrlr": "chademo_ctrlr"}
+
+
+def snake(name: str) -> str:
+    if name in SNAKE_OVERRIDES:
+        return SNAKE_OVERRIDES[name]
+    out = ""
+    for i, ch in enumerate(name):
+        prev = name[i - 1] if i else ""
+        nxt = name[i + 1] if i + 1 < len(name) else ""
+        if ch.isupper() and i and (
+            prev.islower() or prev.isdigit() or (prev.isupper() and nxt.islower())
+        ):
+            out += "_"
+        out += ch.lower() if ch.isalpha() else ch
+    return out
+
+
+def rust_type(variable: str, dtype: str, unit: str) -> str:
+    d = dtype.lower()
+    if d == "boolean":
+        return "bool"
+    if d == "integer":
+        return "Duration" if unit == "s" else "i32"
+    if d == "decimal":
+        return "f64"
+    if d == "datetime":
+        return "DateTimeWrapper"
+    if d in ("memberlist", "sequencelist"):
+        return "Vec<MeasurandEnumType>" if variable.endswith("Measurands") else "Vec<String>"
+    return "String"
+
+
+def gen_controllers() -> str:
+    rows = read_semi(CSV_DIR / "dm_components_vars.csv")
+    meta = {
+        r[0].lower(): (r[1] if len(r) > 1 else "", r[2] if len(r) > 2 else "")
+        for r in read_semi(CSV_DIR / "variables.csv")[1:]
+        if r and r[0]
+    }
+    controllers: dict[str, list[tuple[str, str, str, str, bool]]] = {}
+    for row in rows[1:]:
+        if len(row) < 4 or not row[0].endswith("Ctrlr"):
+            continue
+        comp, var = row[0], row[1]
+        instance = row[2] if len(row) > 2 else ""
+        if "<" in instance or "," in instance:
+            continue
+        required = (row[3] if len(row) > 3 else "").lower() in ("yes", "y", "true", "required")
+        dtype = row[4] if len(row) > 4 else ""
+        unit = row[5] if len(row) > 5 else ""
+        controllers.setdefault(comp, []).append((var, instance, dtype, unit, required))
+
+    lines = [
+        "//! AUTO-GENERATED by `tools/gen_device_model.py` — do not edit by hand.",
+        "//!",
+        "//! Typed [`ControllerVariable`] handles for the standard controllers, one module per",
+        "//! controller. Types follow the catalog data type: `integer` with unit `s` is",
+        "//! [`Duration`], `*Measurands` lists are `Vec<MeasurandEnumType>`.",
+        "",
+    ]
+    for comp in sorted(controllers):
+        consts = []
+        seen: set[str] = set()
+        for var, instance, dtype, unit, required in controllers[comp]:
+            const = snake(var).upper()
+            if instance:
+                const += "_" + snake(instance).upper()
+            if const in seen:
+                continue
+            seen.add(const)
+            dtype = dtype or meta.get(var.lower(), ("", ""))[0]
+            unit = unit or meta.get(var.lower(), ("", ""))[1]
+            ty = rust_type(var, dtype, unit)
+            desc = ", ".join(
+                x for x in (f"`{dtype or 'string'}`", unit, "required" if required else "") if x
+            )
+            inst = f"[{instance}]" if instance else ""
+            inst_arg = f'Some("{instance}")' if instance else "None"
+            consts.append(f"    /// `{var}{inst}` ({desc}).")
+            consts.append(
+                f"    pub const {const}: ControllerVariable<{ty}> = "
+                f'ControllerVariable::new("{comp}", "{var}", {inst_arg});'
+            )
+        body = "\n".join(consts)
+        imports = ["    use super::super::accessor::ControllerVariable;"]
+        for needle, path in (
+            ("DateTimeWrapper", "crate::v21::datatypes::DateTimeWrapper"),
+            ("MeasurandEnumType", "crate::v21::enumerations::MeasurandEnumType"),
+            ("String>", "alloc::string::String"),
+            ("Vec<", "alloc::vec::Vec"),
+            ("Duration>", "core::time::Duration"),
+        ):
+            if needle in body:
+                imports.append(f"    use {path};")
+        lines.append(f"/// `{comp}` variables.")
+        lines.append(f"pub mod {snake(comp)} {{")
+        lines.extend(imports)
+        lines.append("")
+        lines.append(body)
+        lines.append("}")
+        lines.append("")
+    return "\n".join(lines)
+
+
 def gen_mod() -> str:
     return """\
 //! OCPP 2.1 standardized device-model catalogs (feature `device_model_catalog`).
 //!
 //! Lookup tables of common component and variable names. Matching is case-insensitive.
+//! [`store::DeviceModel`] is a runtime store seeded from these tables;
+//! [`check::VariableCatalog`] checks `GetVariables` / `SetVariables` items against them;
+//! [`controllers`] holds typed [`accessor::ControllerVariable`] handles for the standard
+//! controllers.
 
+pub mod accessor;
+pub mod check;
 pub mod components;
+pub mod controllers;
 pub mod matrix;
+pub mod store;
 pub mod variables;
 
+pub use accessor::{ControllerVariable, VariableValue};
+pub use check::{CatalogIssue, CatalogMismatch, VariableCatalog};
 pub use components::{is_standard_component, STANDARD_COMPONENTS};
 pub use matrix::{rows_for_component, ComponentVariableRow, COMPONENT_VARIABLE_MATRIX};
+pub use store::{
+    ComponentEntry, ComponentKey, DeviceModel, VariableAttribute, VariableEntry, VariableKey,
+};
 pub use variables::{is_standard_variable, VariableMeta, STANDARD_VARIABLES, VARIABLE_META};
 """
 
@@ -211,6 +329,7 @@ def main() -> int:
     ok = write_or_check(OUT_DIR / "components.rs", gen_components(), args.check) and ok
     ok = write_or_check(OUT_DIR / "variables.rs", gen_variables(), args.check) and ok
     ok = write_or_check(OUT_DIR / "matrix.rs", gen_matrix(), args.check) and ok
+    ok = write_or_check(OUT_DIR / "controllers.rs", gen_controllers(), args.check) and ok
     return 0 if ok else 1
 
 